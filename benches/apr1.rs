@@ -16,9 +16,9 @@ fn criterion_benchmark(c: &mut Criterion) {
     #[allow(deprecated)]
     let base = apr1::hash("password").unwrap();
 
-    c.bench_function("hash", |b| b.iter(|| black_box(hash())));
+    c.bench_function("hash", |b| b.iter(hash));
     c.bench_function("verify", |b| {
-        b.iter(|| black_box(verify(black_box("password"), black_box(&base))))
+        b.iter(|| verify(black_box("password"), black_box(&base)))
     });
 }
 
@@ -0,0 +1,965 @@
+use std::{fmt, ops::RangeInclusive, str::FromStr};
+
+use crate::{
+    AlgorithmParams, IntoHashSetup, crypt,
+    error::{Error, Result},
+    hash::Hash,
+};
+
+/// Identifier of a hashing algorithm.
+///
+/// Each [`Hash`](crate::Hash) value belongs to exactly one algorithm, which
+/// can be obtained with [`Hash::algorithm`](crate::Hash::algorithm). The
+/// set of variants depends on the enabled features.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Algorithm {
+    /// [`crypt::aix`](crate::crypt::aix) algorithm
+    #[cfg(feature = "aix")]
+    Aix,
+    /// [`crypt::apr1`](crate::crypt::apr1) algorithm
+    #[cfg(feature = "apr1")]
+    Apr1,
+    /// [`crypt::balloon`](crate::crypt::balloon) algorithm
+    #[cfg(feature = "balloon")]
+    Balloon,
+    /// [`crypt::bcrypt`](crate::crypt::bcrypt) algorithm
+    #[cfg(feature = "bcrypt")]
+    Bcrypt,
+    /// [`crypt::bcrypt_sha256`](crate::crypt::bcrypt_sha256) algorithm
+    #[cfg(feature = "bcrypt_sha256")]
+    BcryptSha256,
+    /// [`crypt::bigcrypt`](crate::crypt::bigcrypt) algorithm
+    #[cfg(feature = "bigcrypt")]
+    Bigcrypt,
+    /// [`crypt::bsdi`](crate::crypt::bsdi) algorithm
+    #[cfg(feature = "bsdi")]
+    Bsdi,
+    /// [`crypt::cisco::type8`](crate::crypt::cisco::type8) algorithm
+    #[cfg(feature = "cisco")]
+    CiscoType8,
+    /// [`crypt::cisco::type9`](crate::crypt::cisco::type9) algorithm
+    #[cfg(feature = "cisco")]
+    CiscoType9,
+    /// Algorithm of a [`Scheme`](crate::Scheme) registered by the
+    /// application, with its name
+    Custom(&'static str),
+    /// [`crypt::django`](crate::crypt::django) algorithm
+    #[cfg(feature = "django")]
+    Django,
+    /// [`crypt::grub`](crate::crypt::grub) algorithm
+    #[cfg(feature = "grub")]
+    Grub,
+    /// [`crypt::ldap`](crate::crypt::ldap) algorithm
+    #[cfg(feature = "ldap")]
+    Ldap,
+    /// [`crypt::md5`](crate::crypt::md5) algorithm
+    #[cfg(feature = "md5")]
+    Md5,
+    /// [`crypt::mediawiki`](crate::crypt::mediawiki) algorithm
+    #[cfg(feature = "mediawiki")]
+    Mediawiki,
+    /// [`crypt::mssql`](crate::crypt::mssql) algorithm
+    #[cfg(feature = "mssql")]
+    Mssql,
+    /// [`crypt::mysql323`](crate::crypt::mysql323) algorithm
+    #[cfg(feature = "mysql323")]
+    Mysql323,
+    /// [`crypt::mysql41`](crate::crypt::mysql41) algorithm
+    #[cfg(feature = "mysql41")]
+    Mysql41,
+    /// [`crypt::oracle11g`](crate::crypt::oracle11g) algorithm
+    #[cfg(feature = "oracle11g")]
+    Oracle11g,
+    /// [`crypt::pbkdf2`](crate::crypt::pbkdf2) algorithm
+    #[cfg(feature = "pbkdf2")]
+    Pbkdf2,
+    /// [`crypt::pepper`](crate::crypt::pepper) algorithm
+    #[cfg(feature = "pepper")]
+    Peppered,
+    /// [`crypt::scram`](crate::crypt::scram) algorithm
+    #[cfg(feature = "scram")]
+    Scram,
+    /// [`crypt::scrypt`](crate::crypt::scrypt) algorithm
+    #[cfg(feature = "scrypt")]
+    Scrypt,
+    /// [`crypt::sha1`](crate::crypt::sha1) algorithm
+    #[cfg(feature = "sha1")]
+    Sha1,
+    /// [`crypt::sha256`](crate::crypt::sha256) algorithm
+    #[cfg(feature = "sha2")]
+    Sha256,
+    /// [`crypt::sha512`](crate::crypt::sha512) algorithm
+    #[cfg(feature = "sha2")]
+    Sha512,
+    /// [`crypt::unix`](crate::crypt::unix) algorithm
+    #[cfg(feature = "unix")]
+    Unix,
+    /// [`crypt::wrapped`](crate::crypt::wrapped) algorithm
+    #[cfg(feature = "wrapped")]
+    Wrapped,
+}
+
+// Built-in algorithms of the build, in the order of their declaration.
+const ALL: &[Algorithm] = &[
+    #[cfg(feature = "aix")]
+    Algorithm::Aix,
+    #[cfg(feature = "apr1")]
+    Algorithm::Apr1,
+    #[cfg(feature = "balloon")]
+    Algorithm::Balloon,
+    #[cfg(feature = "bcrypt")]
+    Algorithm::Bcrypt,
+    #[cfg(feature = "bcrypt_sha256")]
+    Algorithm::BcryptSha256,
+    #[cfg(feature = "bigcrypt")]
+    Algorithm::Bigcrypt,
+    #[cfg(feature = "bsdi")]
+    Algorithm::Bsdi,
+    #[cfg(feature = "cisco")]
+    Algorithm::CiscoType8,
+    #[cfg(feature = "cisco")]
+    Algorithm::CiscoType9,
+    #[cfg(feature = "django")]
+    Algorithm::Django,
+    #[cfg(feature = "grub")]
+    Algorithm::Grub,
+    #[cfg(feature = "ldap")]
+    Algorithm::Ldap,
+    #[cfg(feature = "md5")]
+    Algorithm::Md5,
+    #[cfg(feature = "mediawiki")]
+    Algorithm::Mediawiki,
+    #[cfg(feature = "mssql")]
+    Algorithm::Mssql,
+    #[cfg(feature = "mysql323")]
+    Algorithm::Mysql323,
+    #[cfg(feature = "mysql41")]
+    Algorithm::Mysql41,
+    #[cfg(feature = "oracle11g")]
+    Algorithm::Oracle11g,
+    #[cfg(feature = "pbkdf2")]
+    Algorithm::Pbkdf2,
+    #[cfg(feature = "pepper")]
+    Algorithm::Peppered,
+    #[cfg(feature = "scram")]
+    Algorithm::Scram,
+    #[cfg(feature = "scrypt")]
+    Algorithm::Scrypt,
+    #[cfg(feature = "sha1")]
+    Algorithm::Sha1,
+    #[cfg(feature = "sha2")]
+    Algorithm::Sha256,
+    #[cfg(feature = "sha2")]
+    Algorithm::Sha512,
+    #[cfg(feature = "unix")]
+    Algorithm::Unix,
+    #[cfg(feature = "wrapped")]
+    Algorithm::Wrapped,
+];
+
+// Hash with a DES-based algorithm, which takes only a salt, given either as
+// a string or as raw bytes.
+#[allow(dead_code)]
+fn des_hash_with<B: AsRef<[u8]>>(
+    params: AlgorithmParams<'_>,
+    pass: B,
+    hash: fn(B) -> Result<Hash>,
+    hash_with: fn(&str, B) -> Result<Hash>,
+    hash_with_bytes: fn(&[u8], B) -> Result<Hash>,
+) -> Result<Hash> {
+    let hs = params.into_hash_setup(|_| Err(Error::InvalidHashString))?;
+    match (hs.salt, hs.salt_bytes) {
+        (Some(salt), _) => hash_with(salt, pass),
+        (None, Some(salt)) => hash_with_bytes(salt, pass),
+        (None, None) => hash(pass),
+    }
+}
+
+// Get the salt of a basic setup for an algorithm which takes no rounds, and
+// either no salt or only a salt string, reporting any other parameter as an
+// error instead of ignoring it.
+fn setting_salt<'a>(name: &str, params: AlgorithmParams<'a>, salted: bool) -> Result<&'a str> {
+    let hs = params.into_hash_setup(|_| Err(Error::InvalidHashString))?;
+    if hs.rounds.is_some() {
+        return Err(Error::InvalidRounds);
+    }
+    if hs.salt_bytes.is_some() || (!salted && hs.salt.is_some()) {
+        return Err(Error::UnsupportedScheme {
+            prefix: name.to_string(),
+        });
+    }
+    Ok(hs.salt.unwrap_or(""))
+}
+
+/// Parameters of an algorithm, as reported by [`Algorithm::params`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ParamInfo {
+    /// Range of the salt length, or `None` if the algorithm doesn't take a
+    /// salt. Like for a [`Salt`](crate::Salt), the length is in bytes for
+    /// the algorithms whose salt is a byte string, and in characters for
+    /// the others.
+    pub salt_len: Option<RangeInclusive<usize>>,
+    /// Length of a generated salt.
+    pub default_salt_len: Option<usize>,
+    /// Range of the number of rounds or cost, as given in a
+    /// [`HashSetup`](crate::HashSetup), or `None` if it's fixed.
+    pub rounds: Option<RangeInclusive<u32>>,
+    /// Number of rounds or cost used if none is given.
+    pub default_rounds: Option<u32>,
+    /// Length of the checksum of a hash made with the default parameters,
+    /// in bytes, or `None` if it depends on the password or the input.
+    pub output_len: Option<usize>,
+    /// Whether the algorithm is [deprecated](Algorithm::is_deprecated).
+    pub deprecated: bool,
+}
+
+impl Algorithm {
+    /// Iterate over the built-in algorithms compiled into the build.
+    ///
+    /// The algorithms are those of the enabled cargo features, in
+    /// alphabetical order; schemes registered with
+    /// [`register_scheme`](crate::register_scheme) aren't included. To check
+    /// for a single algorithm, parse its name: the names of algorithms left
+    /// out of the build give [`Error::UnsupportedScheme`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crypt3_rs::{Algorithm, error::Error};
+    ///
+    /// for algorithm in Algorithm::all().filter(Algorithm::is_recommended) {
+    ///     println!("{algorithm}");
+    /// }
+    /// assert!(Algorithm::all().any(|algorithm| algorithm == Algorithm::Bcrypt));
+    /// assert!(matches!(
+    ///     "argon2".parse::<Algorithm>(),
+    ///     Err(Error::UnsupportedScheme { .. })
+    /// ));
+    /// ```
+    pub fn all() -> impl Iterator<Item = Algorithm> {
+        ALL.iter().copied()
+    }
+
+    /// Check whether the algorithm's hashing functions are deprecated.
+    ///
+    /// Deprecated algorithms are supported for verifying existing hashes,
+    /// but shouldn't be used for new ones; a password with a deprecated hash
+    /// should be rehashed after the next successful login, or reset.
+    pub fn is_deprecated(&self) -> bool {
+        match self {
+            #[cfg(feature = "apr1")]
+            Self::Apr1 => true,
+            #[cfg(feature = "bigcrypt")]
+            Self::Bigcrypt => true,
+            #[cfg(feature = "bsdi")]
+            Self::Bsdi => true,
+            #[cfg(feature = "md5")]
+            Self::Md5 => true,
+            #[cfg(feature = "mediawiki")]
+            Self::Mediawiki => true,
+            #[cfg(feature = "mssql")]
+            Self::Mssql => true,
+            #[cfg(feature = "mysql323")]
+            Self::Mysql323 => true,
+            #[cfg(feature = "mysql41")]
+            Self::Mysql41 => true,
+            #[cfg(feature = "oracle11g")]
+            Self::Oracle11g => true,
+            #[cfg(feature = "sha1")]
+            Self::Sha1 => true,
+            #[cfg(feature = "sha2")]
+            Self::Sha256 => true,
+            #[cfg(feature = "unix")]
+            Self::Unix => true,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    /// Check whether the algorithm is recommended for new passwords.
+    ///
+    /// The recommended algorithms are well studied, widely supported, and
+    /// have a tunable work factor: bcrypt, Passlib bcrypt-SHA256 and PBKDF2,
+    /// scrypt, and SHA-512.
+    pub fn is_recommended(&self) -> bool {
+        match self {
+            #[cfg(feature = "bcrypt")]
+            Self::Bcrypt => true,
+            #[cfg(feature = "bcrypt_sha256")]
+            Self::BcryptSha256 => true,
+            #[cfg(feature = "pbkdf2")]
+            Self::Pbkdf2 => true,
+            #[cfg(feature = "scrypt")]
+            Self::Scrypt => true,
+            #[cfg(feature = "sha2")]
+            Self::Sha512 => true,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    /// Get the parameters of the algorithm.
+    ///
+    /// The ranges and defaults are those described in the documentation of
+    /// the algorithm's module; for algorithms with several variants, like
+    /// PBKDF2, they are those of the default variant. Rounds which are
+    /// coerced into the range, like those of SHA-512, are reported with the
+    /// range they are coerced into.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crypt3_rs::Algorithm;
+    ///
+    /// let params = Algorithm::Bcrypt.params();
+    /// assert_eq!(params.rounds, Some(4..=31));
+    /// assert_eq!(params.salt_len, Some(16..=16));
+    /// assert_eq!(params.output_len, Some(23));
+    /// ```
+    pub fn params(&self) -> ParamInfo {
+        let (salt_len, default_salt_len) = crate::salt::salt_len(*self).unzip();
+        #[allow(unreachable_patterns)]
+        let default_rounds = match self {
+            #[cfg(feature = "aix")]
+            Self::Aix => Some(crypt::aix::DEFAULT_COST),
+            #[cfg(feature = "balloon")]
+            Self::Balloon => Some(crypt::balloon::DEFAULT_TIME_COST),
+            #[cfg(feature = "bcrypt")]
+            Self::Bcrypt => Some(crypt::bcrypt::DEFAULT_COST),
+            #[cfg(feature = "bcrypt_sha256")]
+            Self::BcryptSha256 => Some(crypt::bcrypt::DEFAULT_COST),
+            #[cfg(feature = "bsdi")]
+            Self::Bsdi => Some(crypt::bsdi::DEFAULT_ROUNDS),
+            #[cfg(feature = "django")]
+            Self::Django => Some(crypt::django::DEFAULT_ROUNDS),
+            #[cfg(feature = "grub")]
+            Self::Grub => Some(crypt::grub::DEFAULT_ROUNDS),
+            #[cfg(feature = "pbkdf2")]
+            Self::Pbkdf2 => Some(crypt::pbkdf2::DEFAULT_ALGORITHM.default_rounds()),
+            #[cfg(feature = "scram")]
+            Self::Scram => Some(crypt::scram::DEFAULT_ROUNDS),
+            #[cfg(feature = "scrypt")]
+            Self::Scrypt => Some(u32::from(crypt::scrypt::DEFAULT_LOG_N)),
+            #[cfg(feature = "sha1")]
+            Self::Sha1 => Some(crypt::sha1::DEFAULT_ROUNDS),
+            #[cfg(feature = "sha2")]
+            Self::Sha256 | Self::Sha512 => Some(crypt::sha512::DEFAULT_ROUNDS),
+            _ => None,
+        };
+        #[allow(unreachable_patterns)]
+        let output_len = match self {
+            #[cfg(feature = "aix")]
+            Self::Aix => Some(64),
+            #[cfg(feature = "apr1")]
+            Self::Apr1 => Some(16),
+            #[cfg(feature = "balloon")]
+            Self::Balloon => Some(32),
+            #[cfg(feature = "bcrypt")]
+            Self::Bcrypt => Some(23),
+            #[cfg(feature = "bcrypt_sha256")]
+            Self::BcryptSha256 => Some(23),
+            #[cfg(feature = "bsdi")]
+            Self::Bsdi => Some(8),
+            #[cfg(feature = "cisco")]
+            Self::CiscoType8 | Self::CiscoType9 => Some(32),
+            #[cfg(feature = "django")]
+            Self::Django => Some(32),
+            #[cfg(feature = "grub")]
+            Self::Grub => Some(64),
+            #[cfg(feature = "ldap")]
+            Self::Ldap => Some(20),
+            #[cfg(feature = "md5")]
+            Self::Md5 => Some(16),
+            #[cfg(feature = "mediawiki")]
+            Self::Mediawiki => Some(16),
+            #[cfg(feature = "mssql")]
+            Self::Mssql => Some(64),
+            #[cfg(feature = "mysql323")]
+            Self::Mysql323 => Some(8),
+            #[cfg(feature = "mysql41")]
+            Self::Mysql41 => Some(20),
+            #[cfg(feature = "oracle11g")]
+            Self::Oracle11g => Some(20),
+            #[cfg(feature = "pbkdf2")]
+            Self::Pbkdf2 => Some(32),
+            #[cfg(feature = "scram")]
+            Self::Scram => Some(32),
+            #[cfg(feature = "scrypt")]
+            Self::Scrypt => Some(32),
+            #[cfg(feature = "sha1")]
+            Self::Sha1 => Some(20),
+            #[cfg(feature = "sha2")]
+            Self::Sha256 => Some(32),
+            #[cfg(feature = "sha2")]
+            Self::Sha512 => Some(64),
+            #[cfg(feature = "unix")]
+            Self::Unix => Some(8),
+            _ => None,
+        };
+        ParamInfo {
+            salt_len,
+            default_salt_len,
+            rounds: crate::salt::rounds_range(*self),
+            default_rounds,
+            output_len,
+            deprecated: self.is_deprecated(),
+        }
+    }
+
+    /// Get the name of the algorithm.
+    ///
+    /// Where Passlib has a scheme for the same hash format, its name is used,
+    /// e.g. `sha512_crypt` or `bcrypt`; otherwise, the name is the one of the
+    /// algorithm's module. The name is also the [`Display`](fmt::Display)
+    /// representation of the algorithm, and is accepted by its
+    /// [`FromStr`] implementation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "aix")]
+            Self::Aix => "aix",
+            #[cfg(feature = "apr1")]
+            Self::Apr1 => "apr_md5_crypt",
+            #[cfg(feature = "balloon")]
+            Self::Balloon => "balloon",
+            #[cfg(feature = "bcrypt")]
+            Self::Bcrypt => "bcrypt",
+            #[cfg(feature = "bcrypt_sha256")]
+            Self::BcryptSha256 => "bcrypt_sha256",
+            #[cfg(feature = "bigcrypt")]
+            Self::Bigcrypt => "bigcrypt",
+            #[cfg(feature = "bsdi")]
+            Self::Bsdi => "bsdi_crypt",
+            #[cfg(feature = "cisco")]
+            Self::CiscoType8 => "cisco_type8",
+            #[cfg(feature = "cisco")]
+            Self::CiscoType9 => "cisco_type9",
+            Self::Custom(name) => name,
+            #[cfg(feature = "django")]
+            Self::Django => "django",
+            #[cfg(feature = "grub")]
+            Self::Grub => "grub_pbkdf2_sha512",
+            #[cfg(feature = "ldap")]
+            Self::Ldap => "ldap",
+            #[cfg(feature = "md5")]
+            Self::Md5 => "md5_crypt",
+            #[cfg(feature = "mediawiki")]
+            Self::Mediawiki => "mediawiki",
+            #[cfg(feature = "mssql")]
+            Self::Mssql => "mssql",
+            #[cfg(feature = "mysql323")]
+            Self::Mysql323 => "mysql323",
+            #[cfg(feature = "mysql41")]
+            Self::Mysql41 => "mysql41",
+            #[cfg(feature = "oracle11g")]
+            Self::Oracle11g => "oracle11",
+            #[cfg(feature = "pbkdf2")]
+            Self::Pbkdf2 => "pbkdf2",
+            #[cfg(feature = "pepper")]
+            Self::Peppered => "pepper",
+            #[cfg(feature = "scram")]
+            Self::Scram => "scram",
+            #[cfg(feature = "scrypt")]
+            Self::Scrypt => "scrypt",
+            #[cfg(feature = "sha1")]
+            Self::Sha1 => "sha1_crypt",
+            #[cfg(feature = "sha2")]
+            Self::Sha256 => "sha256_crypt",
+            #[cfg(feature = "sha2")]
+            Self::Sha512 => "sha512_crypt",
+            #[cfg(feature = "unix")]
+            Self::Unix => "des_crypt",
+            #[cfg(feature = "wrapped")]
+            Self::Wrapped => "wrapped",
+        }
+    }
+
+    /// Hash a password with the algorithm, using the default parameters.
+    ///
+    /// This is the same as calling the `hash` function of the algorithm's
+    /// module, which makes it possible to select the algorithm at runtime.
+    /// Algorithms which can't hash a password without further input, like
+    /// wrapped legacy digests or peppered hashes, return
+    /// [`UnsupportedScheme`](Error::UnsupportedScheme) with the name of the
+    /// algorithm.
+    pub fn hash<B: AsRef<[u8]>>(&self, pass: B) -> Result<Hash> {
+        crate::policy::check_password_len(pass.as_ref())?;
+        #[allow(deprecated)]
+        match self {
+            #[cfg(feature = "aix")]
+            Self::Aix => crypt::aix::hash(pass),
+            #[cfg(feature = "apr1")]
+            Self::Apr1 => crypt::apr1::hash(pass),
+            #[cfg(feature = "balloon")]
+            Self::Balloon => crypt::balloon::hash(pass),
+            #[cfg(feature = "bcrypt")]
+            Self::Bcrypt => crypt::bcrypt::hash(pass),
+            #[cfg(feature = "bcrypt_sha256")]
+            Self::BcryptSha256 => crypt::bcrypt_sha256::hash(pass),
+            #[cfg(feature = "bigcrypt")]
+            Self::Bigcrypt => crypt::bigcrypt::hash(pass),
+            #[cfg(feature = "bsdi")]
+            Self::Bsdi => crypt::bsdi::hash(pass),
+            #[cfg(feature = "cisco")]
+            Self::CiscoType8 => crypt::cisco::type8::hash(pass),
+            #[cfg(feature = "cisco")]
+            Self::CiscoType9 => crypt::cisco::type9::hash(pass),
+            Self::Custom(name) => {
+                let scheme = crate::scheme::by_name(name).ok_or(Error::UnsupportedScheme {
+                    prefix: name.to_string(),
+                })?;
+                Hash::custom(&scheme, "", pass.as_ref())
+            }
+            #[cfg(feature = "django")]
+            Self::Django => crypt::django::hash(pass),
+            #[cfg(feature = "grub")]
+            Self::Grub => crypt::grub::hash(pass),
+            #[cfg(feature = "ldap")]
+            Self::Ldap => crypt::ldap::hash(pass),
+            #[cfg(feature = "md5")]
+            Self::Md5 => crypt::md5::hash(pass),
+            #[cfg(feature = "mediawiki")]
+            Self::Mediawiki => crypt::mediawiki::hash(pass),
+            #[cfg(feature = "mssql")]
+            Self::Mssql => crypt::mssql::hash(pass),
+            #[cfg(feature = "mysql323")]
+            Self::Mysql323 => crypt::mysql323::hash(pass),
+            #[cfg(feature = "mysql41")]
+            Self::Mysql41 => crypt::mysql41::hash(pass),
+            #[cfg(feature = "oracle11g")]
+            Self::Oracle11g => crypt::oracle11g::hash(pass),
+            #[cfg(feature = "pbkdf2")]
+            Self::Pbkdf2 => crypt::pbkdf2::hash(pass),
+            #[cfg(feature = "pepper")]
+            Self::Peppered => Err(Error::UnsupportedScheme {
+                prefix: self.name().to_string(),
+            }),
+            #[cfg(feature = "scram")]
+            Self::Scram => crypt::scram::hash(pass),
+            #[cfg(feature = "scrypt")]
+            Self::Scrypt => crypt::scrypt::hash(pass),
+            #[cfg(feature = "sha1")]
+            Self::Sha1 => crypt::sha1::hash(pass),
+            #[cfg(feature = "sha2")]
+            Self::Sha256 => crypt::sha256::hash(pass),
+            #[cfg(feature = "sha2")]
+            Self::Sha512 => crypt::sha512::hash(pass),
+            #[cfg(feature = "unix")]
+            Self::Unix => crypt::unix::hash(pass),
+            #[cfg(feature = "wrapped")]
+            Self::Wrapped => Err(Error::UnsupportedScheme {
+                prefix: self.name().to_string(),
+            }),
+        }
+    }
+
+    /// Hash a password with the algorithm, using custom parameters.
+    ///
+    /// The parameters are passed to the `hash_with` function of the
+    /// algorithm's module; see its documentation for the interpretation of the
+    /// salt and the rounds. The DES-based algorithms, which only accept a
+    /// salt, ignore the rounds. Algorithms without parameters, like the
+    /// MySQL hashes, fail with [`InvalidRounds`](Error::InvalidRounds) if
+    /// rounds are given, and with [`UnsupportedScheme`](Error::UnsupportedScheme)
+    /// if a salt is given. For a [custom scheme](crate::Scheme), the salt of
+    /// the setup is passed as the setting, and rounds or a raw byte salt
+    /// result in the same errors. Wrapped and peppered hashes can't be made
+    /// from a setup, and fail like in [`hash`](Algorithm::hash). Parameters
+    /// specific to another algorithm result in an error. A salt given as raw
+    /// bytes is handled as described for [`HashSetup`](crate::HashSetup).
+    pub fn hash_with<'a, P, B>(&self, params: P, pass: B) -> Result<Hash>
+    where
+        P: Into<AlgorithmParams<'a>>,
+        B: AsRef<[u8]>,
+    {
+        crate::policy::check_password_len(pass.as_ref())?;
+        let params = params.into();
+        #[allow(deprecated)]
+        match self {
+            #[cfg(feature = "aix")]
+            Self::Aix => crypt::aix::hash_with(params, pass),
+            #[cfg(feature = "apr1")]
+            Self::Apr1 => crypt::apr1::hash_with(params, pass),
+            #[cfg(feature = "balloon")]
+            Self::Balloon => crypt::balloon::hash_with(params, pass),
+            #[cfg(feature = "bcrypt")]
+            Self::Bcrypt => crypt::bcrypt::hash_with(params, pass),
+            #[cfg(feature = "bcrypt_sha256")]
+            Self::BcryptSha256 => crypt::bcrypt_sha256::hash_with(params, pass),
+            #[cfg(feature = "bigcrypt")]
+            Self::Bigcrypt => des_hash_with(
+                params,
+                pass,
+                crypt::bigcrypt::hash,
+                crypt::bigcrypt::hash_with,
+                crypt::bigcrypt::hash_with_bytes,
+            ),
+            #[cfg(feature = "bsdi")]
+            Self::Bsdi => crypt::bsdi::hash_with(params, pass),
+            #[cfg(feature = "cisco")]
+            Self::CiscoType8 => crypt::cisco::type8::hash_with(params, pass),
+            #[cfg(feature = "cisco")]
+            Self::CiscoType9 => crypt::cisco::type9::hash_with(params, pass),
+            Self::Custom(name) => {
+                let scheme = crate::scheme::by_name(name).ok_or(Error::UnsupportedScheme {
+                    prefix: name.to_string(),
+                })?;
+                let setting = setting_salt(name, params, true)?;
+                Hash::custom(&scheme, setting, pass.as_ref())
+            }
+            #[cfg(feature = "django")]
+            Self::Django => crypt::django::hash_with(params, pass),
+            #[cfg(feature = "grub")]
+            Self::Grub => crypt::grub::hash_with(params, pass),
+            #[cfg(feature = "ldap")]
+            Self::Ldap => crypt::ldap::hash_with(params, pass),
+            #[cfg(feature = "md5")]
+            Self::Md5 => crypt::md5::hash_with(params, pass),
+            #[cfg(feature = "mediawiki")]
+            Self::Mediawiki => crypt::mediawiki::hash_with(params, pass),
+            #[cfg(feature = "mssql")]
+            Self::Mssql => crypt::mssql::hash_with(params, pass),
+            #[cfg(feature = "mysql323")]
+            Self::Mysql323 => {
+                setting_salt(self.name(), params, false)?;
+                crypt::mysql323::hash(pass)
+            }
+            #[cfg(feature = "mysql41")]
+            Self::Mysql41 => {
+                setting_salt(self.name(), params, false)?;
+                crypt::mysql41::hash(pass)
+            }
+            #[cfg(feature = "oracle11g")]
+            Self::Oracle11g => crypt::oracle11g::hash_with(params, pass),
+            #[cfg(feature = "pbkdf2")]
+            Self::Pbkdf2 => crypt::pbkdf2::hash_with(params, pass),
+            #[cfg(feature = "pepper")]
+            Self::Peppered => Err(Error::UnsupportedScheme {
+                prefix: self.name().to_string(),
+            }),
+            #[cfg(feature = "scram")]
+            Self::Scram => crypt::scram::hash_with(params, pass),
+            #[cfg(feature = "scrypt")]
+            Self::Scrypt => crypt::scrypt::hash_with(params, pass),
+            #[cfg(feature = "sha1")]
+            Self::Sha1 => crypt::sha1::hash_with(params, pass),
+            #[cfg(feature = "sha2")]
+            Self::Sha256 => crypt::sha256::hash_with(params, pass),
+            #[cfg(feature = "sha2")]
+            Self::Sha512 => crypt::sha512::hash_with(params, pass),
+            #[cfg(feature = "unix")]
+            Self::Unix => des_hash_with(
+                params,
+                pass,
+                crypt::unix::hash,
+                crypt::unix::hash_with,
+                crypt::unix::hash_with_bytes,
+            ),
+            #[cfg(feature = "wrapped")]
+            Self::Wrapped => Err(Error::UnsupportedScheme {
+                prefix: self.name().to_string(),
+            }),
+        }
+    }
+
+    /// Spend the time of a verification, and fail.
+    ///
+    /// The password is hashed with the default parameters of the algorithm,
+    /// and the result is discarded. Calling this function when a login names
+    /// an account which doesn't exist makes the failure take as long as for
+    /// an existing account whose hash uses the algorithm, so that the
+    /// response time doesn't tell which accounts exist. Use
+    /// [`unix::dummy_verify`](crate::unix::dummy_verify) to match hashes
+    /// with other parameters.
+    ///
+    /// The result is always `false`.
+    pub fn dummy_verify<B: AsRef<[u8]>>(&self, pass: B) -> bool {
+        let _ = std::hint::black_box(self.hash(pass));
+        false
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Recognize an algorithm by name.
+///
+/// Besides the names returned by [`Algorithm::name`], the names of the
+/// algorithms' modules, the related Passlib schemes, and the __crypt__(3)
+/// prefixes with or without their __`$`__ delimiters, like `$6$` or `2b`,
+/// are accepted. Names are case-insensitive, and dashes may be used instead
+/// of underscores. An unknown name, or the name of an algorithm which isn't
+/// enabled, results in an `UnsupportedScheme` error.
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().to_ascii_lowercase().replace('-', "_");
+        let name = name
+            .strip_prefix('$')
+            .and_then(|name| name.strip_suffix('$'))
+            .unwrap_or(&name);
+        match name {
+            #[cfg(feature = "aix")]
+            "aix" => Ok(Self::Aix),
+            #[cfg(feature = "apr1")]
+            "apr_md5_crypt" | "apr1" | "apr_md5" => Ok(Self::Apr1),
+            #[cfg(feature = "balloon")]
+            "balloon" => Ok(Self::Balloon),
+            #[cfg(feature = "bcrypt")]
+            "bcrypt" | "2" | "2a" | "2b" | "2x" | "2y" => Ok(Self::Bcrypt),
+            #[cfg(feature = "bcrypt_sha256")]
+            "bcrypt_sha256" => Ok(Self::BcryptSha256),
+            #[cfg(feature = "bigcrypt")]
+            "bigcrypt" => Ok(Self::Bigcrypt),
+            #[cfg(feature = "bsdi")]
+            "bsdi_crypt" | "bsdi" | "_" => Ok(Self::Bsdi),
+            #[cfg(feature = "cisco")]
+            "cisco_type8" => Ok(Self::CiscoType8),
+            #[cfg(feature = "cisco")]
+            "cisco_type9" => Ok(Self::CiscoType9),
+            #[cfg(feature = "django")]
+            "django" | "django_pbkdf2_sha1" | "django_pbkdf2_sha256" => Ok(Self::Django),
+            #[cfg(feature = "grub")]
+            "grub_pbkdf2_sha512" | "grub" => Ok(Self::Grub),
+            #[cfg(feature = "ldap")]
+            "ldap" => Ok(Self::Ldap),
+            #[cfg(feature = "md5")]
+            "md5_crypt" | "1" | "md5" => Ok(Self::Md5),
+            #[cfg(feature = "mediawiki")]
+            "mediawiki" => Ok(Self::Mediawiki),
+            #[cfg(feature = "mssql")]
+            "mssql" | "mssql2000" | "mssql2005" => Ok(Self::Mssql),
+            #[cfg(feature = "mysql323")]
+            "mysql323" => Ok(Self::Mysql323),
+            #[cfg(feature = "mysql41")]
+            "mysql41" => Ok(Self::Mysql41),
+            #[cfg(feature = "oracle11g")]
+            "oracle11" | "oracle11g" => Ok(Self::Oracle11g),
+            #[cfg(feature = "pbkdf2")]
+            "pbkdf2" | "pbkdf2_sha1" | "pbkdf2_sha256" | "pbkdf2_sha512" => Ok(Self::Pbkdf2),
+            #[cfg(feature = "pepper")]
+            "pepper" | "peppered" => Ok(Self::Peppered),
+            #[cfg(feature = "scram")]
+            "scram" | "scram_sha_256" => Ok(Self::Scram),
+            #[cfg(feature = "scrypt")]
+            "scrypt" => Ok(Self::Scrypt),
+            #[cfg(feature = "sha1")]
+            "sha1_crypt" | "sha1" => Ok(Self::Sha1),
+            #[cfg(feature = "sha2")]
+            "sha256_crypt" | "5" | "sha256" => Ok(Self::Sha256),
+            #[cfg(feature = "sha2")]
+            "sha512_crypt" | "6" | "sha512" => Ok(Self::Sha512),
+            #[cfg(feature = "unix")]
+            "des_crypt" | "des" | "unix" => Ok(Self::Unix),
+            #[cfg(feature = "wrapped")]
+            "wrapped" => Ok(Self::Wrapped),
+            _ => crate::scheme::by_name(s.trim())
+                .map(|scheme| Self::Custom(scheme.name()))
+                .ok_or(Error::UnsupportedScheme {
+                    prefix: name.to_string(),
+                }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Algorithm;
+    use crate::{AlgorithmParams, HashSetup};
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn hash_with() {
+        assert_eq!(
+            Algorithm::Sha512
+                .hash_with(
+                    HashSetup::default().salt("saltsalt").rounds(1000),
+                    "password"
+                )
+                .unwrap(),
+            "$6$rounds=1000$saltsalt$Z/J9iYO1iE9xnr8JPQL57ZWsVRtVjrUv3CiWc/wKWseqXgSqn3HF\
+             YJ/Ng7YXa8XlLj.wpdAwHOJJzuGFqBBRa0"
+        );
+        let h = Algorithm::Sha256.hash("password").unwrap();
+        assert_eq!(h.algorithm(), Algorithm::Sha256);
+        assert!(h.verify("password"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "sha1", feature = "sha2"))]
+    fn hash_with_buf() {
+        use crate::HashSetupBuf;
+
+        let setup = HashSetupBuf::from(HashSetup::default().salt("saltsalt").rounds(1000));
+        assert_eq!(setup.salt.as_deref(), Some("saltsalt"));
+        let h = Algorithm::Sha512.hash_with(&setup, "password").unwrap();
+        assert!(h.starts_with("$6$rounds=1000$saltsalt$Z/J9iYO1iE9xnr8J"));
+        let h = crate::crypt::sha1::hash_with(&setup, "password").unwrap();
+        assert!(h.starts_with("$sha1$1000$saltsalt$"));
+        let setup = setup.salt_bytes(b"\xff".to_vec());
+        assert_eq!(setup.as_setup().salt_bytes, Some(&b"\xff"[..]));
+    }
+
+    #[test]
+    #[cfg(all(feature = "bcrypt", feature = "bcrypt_sha256", feature = "sha2"))]
+    fn params() {
+        use crate::crypt::bcrypt::{BcryptSetup, BcryptVariant};
+
+        let setup = || {
+            BcryptSetup::default()
+                .salt("bvIG6Nmid91Mu9RcmmWZfO")
+                .cost(5)
+                .variant(BcryptVariant::V2y)
+        };
+        assert_eq!(
+            Algorithm::Bcrypt.hash_with(setup(), "password").unwrap(),
+            "$2y$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe"
+        );
+        assert!(
+            Algorithm::BcryptSha256
+                .hash_with(setup(), "password")
+                .is_ok()
+        );
+        assert!(Algorithm::Sha512.hash_with(setup(), "password").is_err());
+        assert!(
+            Algorithm::Bcrypt
+                .hash_with(
+                    AlgorithmParams::from(HashSetup::default().rounds(4)),
+                    "password"
+                )
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn all() {
+        for algorithm in Algorithm::all() {
+            assert_eq!(algorithm.name().parse::<Algorithm>().unwrap(), algorithm);
+        }
+        assert!(!Algorithm::all().any(|algorithm| matches!(algorithm, Algorithm::Custom(_))));
+        #[cfg(feature = "sha2")]
+        assert!(Algorithm::all().any(|algorithm| algorithm == Algorithm::Sha512));
+    }
+
+    #[test]
+    #[cfg(all(feature = "bcrypt", feature = "sha2", feature = "mysql41"))]
+    fn param_info() {
+        let params = Algorithm::Sha512.params();
+        assert_eq!(params.salt_len, Some(0..=16));
+        assert_eq!(params.default_salt_len, Some(16));
+        assert_eq!(params.rounds, Some(1000..=999_999_999));
+        assert_eq!(params.default_rounds, Some(5000));
+        assert_eq!(params.output_len, Some(64));
+        assert!(!params.deprecated);
+        assert_eq!(Algorithm::Bcrypt.params().default_rounds, Some(10));
+
+        let params = Algorithm::Mysql41.params();
+        assert_eq!(params.salt_len, None);
+        assert_eq!(params.rounds, None);
+        assert_eq!(params.output_len, Some(20));
+        assert!(params.deprecated);
+    }
+
+    #[test]
+    #[cfg(feature = "unix")]
+    fn salt_only() {
+        assert_eq!(
+            Algorithm::Unix
+                .hash_with(HashSetup::default().salt("aZ").rounds(1000), "test")
+                .unwrap(),
+            "aZGJuE6EXrjEE"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "mysql41")]
+    fn no_params() {
+        use crate::error::Error;
+
+        assert_eq!(
+            Algorithm::Mysql41
+                .hash_with(HashSetup::default(), "password")
+                .unwrap(),
+            "*2470C0C06DEE42FD1618BB99005ADCA2EC9D1E19"
+        );
+        assert!(matches!(
+            Algorithm::Mysql41.hash_with(HashSetup::default().rounds(1000), "password"),
+            Err(Error::InvalidRounds)
+        ));
+        assert!(matches!(
+            Algorithm::Mysql41.hash_with(HashSetup::default().salt("aZ"), "password"),
+            Err(Error::UnsupportedScheme { prefix }) if prefix == "mysql41"
+        ));
+    }
+
+    #[test]
+    #[cfg(all(feature = "pepper", feature = "wrapped"))]
+    fn needs_input() {
+        use crate::error::Error;
+
+        for algorithm in [Algorithm::Peppered, Algorithm::Wrapped] {
+            assert!(matches!(
+                algorithm.hash("password"),
+                Err(Error::UnsupportedScheme { prefix }) if prefix == algorithm.name()
+            ));
+            assert!(matches!(
+                algorithm.hash_with(HashSetup::default(), "password"),
+                Err(Error::UnsupportedScheme { prefix }) if prefix == algorithm.name()
+            ));
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "bcrypt", feature = "bcrypt_sha256", feature = "sha2"))]
+    fn names() {
+        assert_eq!(Algorithm::Sha512.to_string(), "sha512_crypt");
+        assert_eq!(
+            "sha512_crypt".parse::<Algorithm>().unwrap(),
+            Algorithm::Sha512
+        );
+        assert_eq!(
+            "SHA512-Crypt".parse::<Algorithm>().unwrap(),
+            Algorithm::Sha512
+        );
+        assert_eq!("$6$".parse::<Algorithm>().unwrap(), Algorithm::Sha512);
+        assert_eq!("2b".parse::<Algorithm>().unwrap(), Algorithm::Bcrypt);
+        assert_eq!(
+            "$bcrypt-sha256$".parse::<Algorithm>().unwrap(),
+            Algorithm::BcryptSha256
+        );
+        assert_eq!(
+            Algorithm::Bcrypt.to_string().parse::<Algorithm>().unwrap(),
+            Algorithm::Bcrypt
+        );
+        assert!(matches!(
+            "argon2".parse::<Algorithm>(),
+            Err(crate::error::Error::UnsupportedScheme { prefix }) if prefix == "argon2"
+        ));
+    }
+
+    #[test]
+    #[cfg(all(feature = "bcrypt", feature = "md5", feature = "sha2"))]
+    fn deprecated() {
+        assert!(Algorithm::Md5.is_deprecated());
+        assert!(!Algorithm::Md5.is_recommended());
+        assert!(Algorithm::Sha256.is_deprecated());
+        assert!(!Algorithm::Sha512.is_deprecated());
+        assert!(Algorithm::Sha512.is_recommended());
+        #[cfg(feature = "sha1")]
+        assert!(Algorithm::Sha1.is_deprecated());
+
+        let h = crate::Hash::try_from("$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0").unwrap();
+        assert!(h.is_deprecated());
+        assert!(!h.is_recommended());
+        let h =
+            crate::Hash::try_from("$2y$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe")
+                .unwrap();
+        assert!(!h.is_deprecated());
+        assert!(h.is_recommended());
+    }
+}
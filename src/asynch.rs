@@ -0,0 +1,251 @@
+//! Asynchronous hashing and verification.
+//!
+//! Hashing a password with a sound algorithm takes tens to hundreds of
+//! milliseconds of CPU time, during which an asynchronous executor can't
+//! run other tasks on the same thread. The functions here run the work on a
+//! pool of threads bundled with the crate, one per CPU, and return a
+//! [`Task`] which completes with the result. It works with any executor.
+//!
+//! The pool is started on first use. If the operating system refuses to
+//! start some of its threads, the pool runs with the others; if it refuses
+//! all of them, the work is done on the calling thread instead.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::asynch;
+//!
+//! async fn register(pass: String) -> crypt3_rs::error::Result<String> {
+//!     Ok(asynch::hash(pass).await?.into())
+//! }
+//!
+//! async fn login(pass: String, stored: String) -> bool {
+//!     asynch::verify(pass, stored).await
+//! }
+//! ```
+
+use std::{
+    future::Future,
+    num::NonZero,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Arc, Mutex, OnceLock, PoisonError, mpsc},
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+use crate::{Hash, default_crypter, error::Result};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+static POOL: OnceLock<Option<mpsc::Sender<Job>>> = OnceLock::new();
+
+// Start the threads of the pool, and return the sender of their jobs, or
+// `None` if no thread could be started.
+fn start_pool() -> Option<mpsc::Sender<Job>> {
+    let (sender, receiver) = mpsc::channel::<Job>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    let threads = thread::available_parallelism().map_or(1, NonZero::get);
+    let mut started = 0;
+    for i in 0..threads {
+        let receiver = Arc::clone(&receiver);
+        let spawned = thread::Builder::new()
+            .name(format!("crypt3-{i}"))
+            .spawn(move || {
+                loop {
+                    let job = receiver
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => break,
+                    }
+                }
+            });
+        if spawned.is_ok() {
+            started += 1;
+        }
+    }
+    (started > 0).then_some(sender)
+}
+
+struct Slot<T> {
+    result: Option<thread::Result<T>>,
+    waker: Option<Waker>,
+}
+
+/// The result of a function run on the thread pool of the crate.
+///
+/// The function runs whether or not the task is polled. If it panics, the
+/// panic is resumed when the task is polled.
+#[must_use = "the result is lost unless the task is awaited"]
+pub struct Task<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+impl<T> Future for Task<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut slot = self.slot.lock().unwrap_or_else(PoisonError::into_inner);
+        match slot.result.take() {
+            Some(result) => {
+                drop(slot);
+                Poll::Ready(result.unwrap_or_else(|payload| panic::resume_unwind(payload)))
+            }
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Run a function on the thread pool of the crate.
+///
+/// This is for the operations which have no function of their own here,
+/// like hashing with a particular algorithm. If the pool has no threads, the
+/// function runs on the calling thread before `spawn` returns.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::{Algorithm, HashSetup, asynch};
+///
+/// async fn register(pass: String) -> crypt3_rs::error::Result<crypt3_rs::Hash> {
+///     asynch::spawn(move || Algorithm::Sha512.hash_with(HashSetup::default().rounds(10000), pass))
+///         .await
+/// }
+/// ```
+pub fn spawn<T, F>(f: F) -> Task<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    spawn_on(POOL.get_or_init(start_pool).as_ref(), f)
+}
+
+// Run a function on the threads receiving from `pool`, or on the calling
+// thread if there are none.
+fn spawn_on<T, F>(pool: Option<&mpsc::Sender<Job>>, f: F) -> Task<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let slot = Arc::new(Mutex::new(Slot {
+        result: None,
+        waker: None,
+    }));
+    let task = Task {
+        slot: Arc::clone(&slot),
+    };
+    let job = move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(f));
+        let mut slot = slot.lock().unwrap_or_else(PoisonError::into_inner);
+        slot.result = Some(result);
+        let waker = slot.waker.take();
+        drop(slot);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    };
+    let job: Job = Box::new(job);
+    let unsent = match pool {
+        Some(sender) => sender.send(job).err().map(|mpsc::SendError(job)| job),
+        None => Some(job),
+    };
+    if let Some(job) = unsent {
+        job();
+    }
+    task
+}
+
+/// Hash a password with the [default crypter](crate::default_crypter), like
+/// [`hash`](crate::hash).
+pub fn hash<B>(pass: B) -> Task<Result<Hash>>
+where
+    B: AsRef<[u8]> + Send + 'static,
+{
+    spawn(move || crate::hash(pass))
+}
+
+/// Verify a password with the [default crypter](crate::default_crypter), like
+/// [`Crypter::verify`](crate::Crypter::verify).
+///
+/// If there's no default crypter, the password doesn't verify.
+pub fn verify<B, H>(pass: B, hash: H) -> Task<bool>
+where
+    B: AsRef<[u8]> + Send + 'static,
+    H: Into<String>,
+{
+    let hash = hash.into();
+    spawn(move || default_crypter().is_ok_and(|crypter| crypter.verify(pass, &hash)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        pin::pin,
+        sync::Arc,
+        task::{Context, Poll, Wake, Waker},
+        thread::{self, Thread},
+    };
+
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn hash_verify() {
+        use crate::{Algorithm, HashSetup};
+
+        let h = block_on(super::spawn(|| {
+            Algorithm::Sha512.hash_with(HashSetup::default().rounds(1000), "password")
+        }))
+        .unwrap();
+        assert!(block_on(super::verify("password", h.as_str())));
+        assert!(!block_on(super::verify("passwort", h.as_str())));
+
+        let h = block_on(super::hash("password")).unwrap();
+        assert!(block_on(super::verify("password", h)));
+    }
+
+    #[test]
+    fn concurrent() {
+        let tasks: Vec<_> = (0..16).map(|i| super::spawn(move || i * 2)).collect();
+        let results: Vec<_> = tasks.into_iter().map(block_on).collect();
+        assert_eq!(results, (0..16).map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn inline() {
+        let caller = thread::current().id();
+        let task = super::spawn_on(None, move || thread::current().id() == caller);
+        assert!(block_on(task));
+    }
+
+    #[test]
+    #[should_panic(expected = "boom")]
+    fn panic() {
+        block_on(super::spawn(|| panic!("boom")))
+    }
+}
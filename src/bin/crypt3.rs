@@ -0,0 +1,546 @@
+//! Command-line interface to the hashing algorithms of `crypt3_rs`.
+//!
+//! `crypt3 hash` prints the hash of a password, `crypt3 verify` checks a
+//! password against a hash, `crypt3 identify` names the algorithm of a hash,
+//! `crypt3 list` lists the algorithms of the build, and `crypt3 chpasswd`
+//! hashes passwords in bulk, like __chpasswd__(8). Passwords are never
+//! taken from the command line, where other users could see them; they're
+//! read from the terminal without echo, or from the standard input. For
+//! automation, like with `docker login`, `--password-stdin`,
+//! `--password-fd N`, and `--password-env VAR` take the password from the
+//! whole standard input, a file descriptor, or an environment variable.
+
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command, value_parser};
+use crypt3_rs::{
+    Algorithm, Crypter, Hash, HashSetup, default_crypter,
+    error::{Error, Result},
+    htpasswd::HtpasswdFile,
+    prompt,
+    shadow::ShadowEntry,
+    unix,
+};
+
+fn algorithm_arg() -> Arg {
+    Arg::new("algorithm")
+        .short('a')
+        .long("algorithm")
+        .value_name("NAME")
+        .help("Algorithm, as listed by `crypt3 list` [default: bcrypt]")
+}
+
+fn rounds_arg() -> Arg {
+    Arg::new("rounds")
+        .short('r')
+        .long("rounds")
+        .value_name("N")
+        .value_parser(value_parser!(u32))
+        .help("Number of rounds or cost")
+}
+
+fn password_args() -> [Arg; 3] {
+    [
+        Arg::new("password_stdin")
+            .long("password-stdin")
+            .action(ArgAction::SetTrue)
+            .help("Read the password from the standard input, even a terminal"),
+        Arg::new("password_fd")
+            .long("password-fd")
+            .value_name("N")
+            .value_parser(value_parser!(u32))
+            .help("Read the password from file descriptor N (Unix)"),
+        Arg::new("password_env")
+            .long("password-env")
+            .value_name("VAR")
+            .help("Take the password from environment variable VAR"),
+    ]
+}
+
+fn password_group() -> ArgGroup {
+    ArgGroup::new("password").args(["password_stdin", "password_fd", "password_env"])
+}
+
+fn cli() -> Command {
+    Command::new("crypt3")
+        .about("Hash and verify passwords in crypt(3) and related formats")
+        .version(env!("CARGO_PKG_VERSION"))
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("hash")
+                .about("Hash a password")
+                .arg(algorithm_arg())
+                .arg(rounds_arg())
+                .arg(
+                    Arg::new("salt")
+                        .short('s')
+                        .long("salt")
+                        .value_name("SALT")
+                        .help("Salt, instead of a random one"),
+                )
+                .args(password_args())
+                .group(password_group()),
+        )
+        .subcommand(
+            Command::new("verify")
+                .about("Verify a password; exits with 1 if it doesn't match")
+                .arg(
+                    Arg::new("hash")
+                        .required(true)
+                        .help("Hash to verify against"),
+                )
+                .args(password_args())
+                .group(password_group()),
+        )
+        .subcommand(
+            Command::new("identify")
+                .about("Print the algorithm of a hash")
+                .arg(Arg::new("hash").required(true).help("Hash to identify")),
+        )
+        .subcommand(Command::new("list").about("List the supported algorithms"))
+        .subcommand(
+            Command::new("chpasswd")
+                .about("Hash user:password lines read from the standard input")
+                .long_about(
+                    "Hash user:password lines read from the standard input, and print \
+                     user:hash lines, or update the entries of a shadow or htpasswd file.",
+                )
+                .arg(algorithm_arg())
+                .arg(rounds_arg())
+                .arg(
+                    Arg::new("shadow")
+                        .long("shadow")
+                        .value_name("FILE")
+                        .help("Update the users of a shadow file"),
+                )
+                .arg(
+                    Arg::new("htpasswd")
+                        .long("htpasswd")
+                        .value_name("FILE")
+                        .help("Update or add the users of an htpasswd file"),
+                )
+                .group(ArgGroup::new("file").args(["shadow", "htpasswd"])),
+        )
+}
+
+// Read a password: from the source selected by the arguments, from the
+// terminal without echo, asking for confirmation if it's a new one, or from
+// the first line of the standard input if it's redirected.
+fn read_password(matches: &ArgMatches, new: bool) -> Result<String> {
+    if let Some(var) = matches.get_one::<String>("password_env") {
+        return env::var(var).map_err(|err| Error::Io(io::Error::other(format!("{var}: {err}"))));
+    }
+    if let Some(fd) = matches.get_one::<u32>("password_fd") {
+        let file = fs::File::open(format!("/dev/fd/{fd}")).map_err(Error::Io)?;
+        return read_secret(file);
+    }
+    let stdin = io::stdin();
+    if matches.get_flag("password_stdin") {
+        return read_secret(stdin.lock());
+    }
+    if stdin.is_terminal() {
+        return match new {
+            true => prompt::prompt_new_password("Password: "),
+            false => prompt::prompt_password("Password: "),
+        };
+    }
+    let mut line = String::new();
+    stdin.lock().read_line(&mut line).map_err(Error::Io)?;
+    let len = line.trim_end_matches(['\n', '\r']).len();
+    line.truncate(len);
+    Ok(line)
+}
+
+// Read a password from the whole of a stream, without the line break at its
+// end, if any, like `docker login --password-stdin`.
+fn read_secret<R: Read>(mut reader: R) -> Result<String> {
+    let mut secret = String::new();
+    reader.read_to_string(&mut secret).map_err(Error::Io)?;
+    if secret.ends_with('\n') {
+        secret.pop();
+        if secret.ends_with('\r') {
+            secret.pop();
+        }
+    }
+    Ok(secret)
+}
+
+// Whether the algorithm can hash a password on its own. Wrapped and
+// peppered hashes need further input, and are rejected like by a crypter.
+fn can_hash(algorithm: Algorithm) -> bool {
+    Crypter::builder().algorithm(algorithm).build().is_ok()
+}
+
+// Get the algorithm selected by the arguments, warning if it's deprecated.
+fn algorithm(matches: &ArgMatches) -> Result<Algorithm> {
+    let algorithm = match matches.get_one::<String>("algorithm") {
+        Some(name) => name.parse()?,
+        None => default_crypter()?.algorithm(),
+    };
+    if !can_hash(algorithm) {
+        return Err(Error::UnsupportedScheme {
+            prefix: algorithm.name().to_string(),
+        });
+    }
+    if algorithm.is_deprecated() {
+        eprintln!("crypt3: warning: {algorithm} is deprecated");
+    }
+    Ok(algorithm)
+}
+
+// Find an argument which the algorithm has no use for: a salt for an
+// unsalted algorithm, or rounds for one with a fixed cost.
+fn unused_arg(algorithm: Algorithm, matches: &ArgMatches) -> Option<&'static str> {
+    let params = algorithm.params();
+    let salt = matches.try_get_one::<String>("salt").ok().flatten();
+    if params.salt_len.is_none() && salt.is_some() {
+        return Some("--salt");
+    }
+    if params.rounds.is_none() && matches.get_one::<u32>("rounds").is_some() {
+        return Some("--rounds");
+    }
+    None
+}
+
+fn setup(matches: &ArgMatches) -> HashSetup<'_> {
+    let mut setup = HashSetup::default();
+    if let Some(salt) = matches.try_get_one::<String>("salt").ok().flatten() {
+        setup = setup.salt(salt);
+    }
+    if let Some(&rounds) = matches.get_one::<u32>("rounds") {
+        setup = setup.rounds(rounds);
+    }
+    setup
+}
+
+fn hash(matches: &ArgMatches) -> Result<ExitCode> {
+    let algorithm = algorithm(matches)?;
+    if let Some(arg) = unused_arg(algorithm, matches) {
+        eprintln!("crypt3: {algorithm} doesn't take {arg}");
+        return Ok(ExitCode::from(2));
+    }
+    let setup = setup(matches);
+    let pass = read_password(matches, true)?;
+    println!("{}", algorithm.hash_with(setup, pass)?);
+    Ok(ExitCode::SUCCESS)
+}
+
+fn verify(matches: &ArgMatches) -> Result<ExitCode> {
+    let hash = matches.get_one::<String>("hash").expect("required");
+    let pass = read_password(matches, false)?;
+    if unix::verify_result(pass, hash)? {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        eprintln!("crypt3: password doesn't match");
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+// Describe a hash: the name of its algorithm, marked if it's deprecated.
+fn identify(hash: &str) -> Result<String> {
+    let algorithm = Hash::try_from(hash)?.algorithm();
+    Ok(describe(algorithm))
+}
+
+fn describe(algorithm: Algorithm) -> String {
+    if algorithm.is_deprecated() {
+        format!("{algorithm} (deprecated)")
+    } else {
+        algorithm.to_string()
+    }
+}
+
+// Split `user:password` lines, skipping empty ones. An error comes with the
+// number of the offending line.
+fn parse_pairs(text: &str) -> std::result::Result<Vec<(&str, &str)>, (usize, Error)> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| {
+            line.split_once(':').ok_or((
+                i + 1,
+                Error::InvalidEntry {
+                    expected: 2,
+                    found: 1,
+                },
+            ))
+        })
+        .collect()
+}
+
+// Replace the hashes of the users of a shadow file, recording the date of
+// the change. Other lines are copied as they are. Returns the new contents,
+// and the users without an entry.
+fn update_shadow<'a>(
+    text: &str,
+    hashes: &[(&'a str, Hash)],
+    today: i64,
+) -> Result<(String, Vec<&'a str>)> {
+    let mut out = String::with_capacity(text.len());
+    let mut missing: Vec<&str> = hashes.iter().map(|&(user, _)| user).collect();
+    for line in text.split_inclusive('\n') {
+        let body = line.trim_end_matches(['\n', '\r']);
+        if body.trim().is_empty() {
+            out.push_str(line);
+            continue;
+        }
+        let mut entry: ShadowEntry = body.parse()?;
+        match hashes.iter().rev().find(|(user, _)| *user == entry.name) {
+            Some((_, hash)) => {
+                entry.hash = hash.to_string();
+                entry.last_change = Some(today);
+                missing.retain(|user| *user != entry.name);
+                out.push_str(&entry.to_string());
+                out.push_str(&line[body.len()..]);
+            }
+            None => out.push_str(line),
+        }
+    }
+    Ok((out, missing))
+}
+
+// Replace a file atomically: the text is written to a temporary file in the
+// same directory, with the permissions and, on Unix, the owner of the file,
+// which is synced and renamed over the file.
+fn write_file(path: &Path, text: &str) -> io::Result<()> {
+    let name = path.file_name().ok_or(io::ErrorKind::InvalidInput)?;
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(name);
+    tmp_name.push(format!(".{}.tmp", std::process::id()));
+    let tmp = path.with_file_name(tmp_name);
+    let result = write_tmp(path, &tmp, text.as_bytes()).and_then(|()| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+fn write_tmp(path: &Path, tmp: &Path, data: &[u8]) -> io::Result<()> {
+    let meta = fs::metadata(path)?;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(tmp)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        std::os::unix::fs::fchown(&file, Some(meta.uid()), Some(meta.gid()))?;
+    }
+    file.set_permissions(meta.permissions())?;
+    file.write_all(data)?;
+    file.sync_all()
+}
+
+fn chpasswd(matches: &ArgMatches) -> Result<ExitCode> {
+    let algorithm = algorithm(matches)?;
+    if let Some(arg) = unused_arg(algorithm, matches) {
+        eprintln!("crypt3: {algorithm} doesn't take {arg}");
+        return Ok(ExitCode::from(2));
+    }
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input).map_err(Error::Io)?;
+    let pairs = match parse_pairs(&input) {
+        Ok(pairs) => pairs,
+        Err((line, err)) => {
+            eprintln!("crypt3: line {line}: {err}");
+            return Ok(ExitCode::from(2));
+        }
+    };
+    let hashes = pairs
+        .into_iter()
+        .map(|(user, pass)| Ok((user, algorithm.hash_with(setup(matches), pass)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    if let Some(path) = matches.get_one::<String>("shadow") {
+        let text = fs::read_to_string(path).map_err(Error::Io)?;
+        let today = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs() / 86400);
+        let (text, missing) = update_shadow(&text, &hashes, today as i64)?;
+        write_file(Path::new(path), &text).map_err(Error::Io)?;
+        for user in &missing {
+            eprintln!("crypt3: user '{user}' not found in {path}");
+        }
+        return Ok(match missing.is_empty() {
+            true => ExitCode::SUCCESS,
+            false => ExitCode::FAILURE,
+        });
+    }
+    if let Some(path) = matches.get_one::<String>("htpasswd") {
+        let mut file = match Path::new(path).exists() {
+            true => HtpasswdFile::load(path)?,
+            false => HtpasswdFile::new(),
+        };
+        for (user, hash) in &hashes {
+            file.set_hash(user, hash)?;
+        }
+        file.save(path)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    for (user, hash) in &hashes {
+        println!("{user}:{hash}");
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run(matches: &ArgMatches) -> Result<ExitCode> {
+    match matches.subcommand() {
+        Some(("hash", matches)) => hash(matches),
+        Some(("verify", matches)) => verify(matches),
+        Some(("identify", matches)) => {
+            let hash = matches.get_one::<String>("hash").expect("required");
+            println!("{}", identify(hash)?);
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("list", _)) => {
+            for algorithm in Algorithm::all().filter(|&algorithm| can_hash(algorithm)) {
+                println!("{}", describe(algorithm));
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("chpasswd", matches)) => chpasswd(matches),
+        _ => unreachable!("subcommand required"),
+    }
+}
+
+fn main() -> ExitCode {
+    let matches = cli().get_matches();
+    run(&matches).unwrap_or_else(|err| {
+        eprintln!("crypt3: {err}");
+        ExitCode::from(2)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        can_hash, cli, identify, parse_pairs, read_password, read_secret, unused_arg,
+        update_shadow, write_file,
+    };
+
+    #[test]
+    fn args() {
+        cli().debug_assert();
+        let matches = cli()
+            .try_get_matches_from(["crypt3", "hash", "-a", "sha512_crypt", "-r", "10000"])
+            .unwrap();
+        let (_, hash) = matches.subcommand().unwrap();
+        assert_eq!(hash.get_one::<u32>("rounds"), Some(&10000));
+        assert!(
+            cli()
+                .try_get_matches_from(["crypt3", "hash", "-r", "many"])
+                .is_err()
+        );
+        assert!(cli().try_get_matches_from(["crypt3", "verify"]).is_err());
+    }
+
+    #[test]
+    fn password_sources() {
+        assert_eq!(read_secret(&b"secret\r\n"[..]).unwrap(), "secret");
+        assert_eq!(read_secret(&b"pass\nword\n\n"[..]).unwrap(), "pass\nword\n");
+        assert_eq!(read_secret(&b" secret "[..]).unwrap(), " secret ");
+
+        let matches = cli()
+            .try_get_matches_from([
+                "crypt3",
+                "verify",
+                "--password-env",
+                "CRYPT3_TEST_UNSET",
+                "$1$",
+            ])
+            .unwrap();
+        let (_, verify) = matches.subcommand().unwrap();
+        assert!(read_password(verify, false).is_err());
+        assert!(
+            cli()
+                .try_get_matches_from(["crypt3", "hash", "--password-stdin", "--password-fd", "3"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn chpasswd() {
+        use crypt3_rs::Algorithm;
+
+        let pairs = parse_pairs("alice:pass:word\n\nbob:secret\n").unwrap();
+        assert_eq!(pairs, [("alice", "pass:word"), ("bob", "secret")]);
+        assert!(matches!(parse_pairs("alice:x\nbob\n"), Err((2, _))));
+
+        let hashes = [
+            ("alice", Algorithm::Sha512.hash("pass:word").unwrap()),
+            ("carol", Algorithm::Sha512.hash("secret").unwrap()),
+        ];
+        let text = "root:*:19000:0:99999:7:::\nalice:!:19000:0:99999:7:::\r\n\n\
+                    bob:*:019000:0:99999:007:::";
+        let (text, missing) = update_shadow(text, &hashes, 20000).unwrap();
+        assert_eq!(missing, ["carol"]);
+        let alice: crypt3_rs::shadow::ShadowEntry = text.lines().nth(1).unwrap().parse().unwrap();
+        assert_eq!(alice.last_change, Some(20000));
+        assert!(alice.verify("pass:word"));
+        assert!(text.starts_with("root:*:19000:0:99999:7:::\nalice:$6$"));
+        assert!(text.ends_with(":0:99999:7:::\r\n\nbob:*:019000:0:99999:007:::"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn replace_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("crypt3_cli_{}", std::process::id()));
+        std::fs::create_dir(&dir).unwrap();
+        let path = dir.join("shadow");
+        std::fs::write(&path, "old\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+        write_file(&path, "new\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new\n");
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(write_file(&path, "new\n").is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "md5", feature = "mysql41", feature = "sha2"))]
+    fn unused_args() {
+        use crypt3_rs::Algorithm;
+
+        let args = |args: &[&str]| {
+            let matches = cli()
+                .try_get_matches_from(["crypt3", "hash"].iter().chain(args))
+                .unwrap();
+            matches.subcommand_matches("hash").unwrap().clone()
+        };
+        let matches = args(&["-r", "1000"]);
+        assert_eq!(unused_arg(Algorithm::Md5, &matches), Some("--rounds"));
+        assert_eq!(unused_arg(Algorithm::Sha512, &matches), None);
+        let matches = args(&["-s", "saltsalt"]);
+        assert_eq!(unused_arg(Algorithm::Mysql41, &matches), Some("--salt"));
+        assert_eq!(unused_arg(Algorithm::Md5, &matches), None);
+        assert!(can_hash(Algorithm::Md5));
+        #[cfg(feature = "pepper")]
+        assert!(!can_hash(Algorithm::Peppered));
+        #[cfg(feature = "wrapped")]
+        assert!(!can_hash(Algorithm::Wrapped));
+    }
+
+    #[test]
+    #[cfg(feature = "bcrypt")]
+    fn identify_hash() {
+        assert_eq!(
+            identify("$2b$10$saltsaltsaltsaltsaltsOF8gOe7Q2djsDdlP9U528dfcxNNK.BXC").unwrap(),
+            "bcrypt"
+        );
+        assert!(identify("$x$").is_err());
+    }
+}
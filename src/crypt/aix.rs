@@ -0,0 +1,394 @@
+//! AIX `/etc/security/passwd` hashes.
+//!
+//! Besides the traditional DES-based hash, AIX can store passwords using
+//! one of the loadable password algorithms configured in
+//! `/etc/security/pwdalg.cfg`. The __{ssha1}__, __{ssha256}__, and
+//! __{ssha512}__ algorithms are PBKDF2 with HMAC-SHA1, HMAC-SHA256, and
+//! HMAC-SHA512, respectively, with a configurable number of iterations. With
+//! a sufficient number of iterations, they are considered secure.
+//!
+//! The __{smd5}__ algorithm is the MD5-based crypt, without the algorithm
+//! magic in the digested data. It's as weak as [MD5 crypt](super::md5), and
+//! is supported so that older entries can be verified.
+//!
+//! The scheme names are lowercase, which distinguishes these hashes from
+//! the similar looking [LDAP](super::ldap) ones.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::aix;
+//!
+//! let h = "{ssha256}06$2715084824104660$1s/s4RZWEcvZ5VuWPXWGUfwSoG07eVSVce8F6ANJ.g4";
+//! assert!(aix::verify("hashcat", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: 0 to 24 characters for the PBKDF2 algorithms, default is
+//!   16. For __{smd5}__, 0 to 8 characters, default is 8. Longer salts are
+//!   truncated to the maximum length.
+//!
+//! * __Cost__: 4 to 31, the base-2 logarithm of the number of PBKDF2
+//!   iterations. Default is 10. The cost is fixed for __{smd5}__.
+//!
+//! # Hash Format
+//!
+//! The format of the hash is
+//! __`{`__*`{scheme}`*__`}`__*`{cost}`*__`$`__*`{salt}`*__`$`__*`{checksum}`*,
+//! where:
+//!
+//! * *`{scheme}`* is the lowercase scheme name, one of **ssha512**,
+//!   **ssha256**, **ssha1**, or **smd5**.
+//!
+//! * *`{cost}`* is a two-digit decimal cost value. It's absent, together
+//!   with the following __`$`__, from __{smd5}__ hashes.
+//!
+//! * *`{salt}`* consists of characters from `[./0-9A-Za-z]`. The characters
+//!   are used as the salt without decoding.
+//!
+//! * *`{checksum}`* is the encoding of the derived key with the same
+//!   alphabet. Unlike the other crypt encodings, each group of three bytes is
+//!   read in big-endian order, and written starting with the least
+//!   significant six bits.
+
+use std::fmt;
+
+use pbkdf2::pbkdf2_hmac;
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+
+use crate::{
+    AlgorithmParams, HashSetup, consteq,
+    encode::aix_hash64_encode,
+    error::{Error, Result},
+    hash::{Hash, HashV},
+    parse::{self, HashIterator},
+    random,
+};
+
+use super::md5::do_md5_crypt;
+
+const DEFAULT_SCHEME: AixScheme = AixScheme::Ssha512;
+
+/// Minimum cost.
+pub const MIN_COST: u32 = 4;
+
+/// Maximum cost.
+pub const MAX_COST: u32 = 31;
+
+/// Default cost.
+pub const DEFAULT_COST: u32 = 10;
+
+/// Maximum salt length for the PBKDF2 algorithms.
+pub const MAX_SALT_LEN: usize = 24;
+
+/// Default salt length for the PBKDF2 algorithms.
+pub const DEFAULT_SALT_LEN: usize = 16;
+
+/// Maximum salt length for __{smd5}__.
+pub const MAX_SMD5_SALT_LEN: usize = 8;
+
+/// Identifiers of the supported AIX password algorithms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AixScheme {
+    /// PBKDF2 with HMAC-SHA512.
+    Ssha512,
+    /// PBKDF2 with HMAC-SHA256.
+    Ssha256,
+    /// PBKDF2 with HMAC-SHA1.
+    Ssha1,
+    /// MD5 crypt without the magic.
+    Smd5,
+}
+
+impl AixScheme {
+    fn from_prefix(hash: &str) -> Option<(Self, &str)> {
+        let (scheme, rest) = hash.strip_prefix('{')?.split_once('}')?;
+        let scheme = match scheme {
+            "ssha512" => AixScheme::Ssha512,
+            "ssha256" => AixScheme::Ssha256,
+            "ssha1" => AixScheme::Ssha1,
+            "smd5" => AixScheme::Smd5,
+            _ => return None,
+        };
+        Some((scheme, rest))
+    }
+
+    fn max_salt_len(&self) -> usize {
+        match self {
+            AixScheme::Smd5 => MAX_SMD5_SALT_LEN,
+            _ => MAX_SALT_LEN,
+        }
+    }
+
+    fn default_salt_len(&self) -> usize {
+        match self {
+            AixScheme::Smd5 => MAX_SMD5_SALT_LEN,
+            _ => DEFAULT_SALT_LEN,
+        }
+    }
+}
+
+impl fmt::Display for AixScheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scheme = match self {
+            AixScheme::Ssha512 => "ssha512",
+            AixScheme::Ssha256 => "ssha256",
+            AixScheme::Ssha1 => "ssha1",
+            AixScheme::Smd5 => "smd5",
+        };
+        write!(f, "{{{scheme}}}")
+    }
+}
+
+/// Setup struct for AIX hashes.
+///
+/// In addition to custom salt and cost values, an AIX hash can use different
+/// algorithms.
+#[derive(Default)]
+pub struct AixSetup<'a> {
+    /// Custom salt.
+    pub salt: Option<&'a str>,
+    /// Custom cost.
+    pub cost: Option<u32>,
+    /// Password algorithm.
+    pub scheme: Option<AixScheme>,
+}
+
+impl<'a> AixSetup<'a> {
+    /// Configure custom salt for AIX hash
+    pub fn salt(mut self, salt: &'a str) -> Self {
+        self.salt = Some(salt);
+        self
+    }
+    /// Configure custom cost for AIX hash
+    pub fn cost(mut self, cost: u32) -> Self {
+        self.cost = Some(cost);
+        self
+    }
+    /// Configure password algorithm for AIX hash
+    pub fn scheme(mut self, scheme: AixScheme) -> Self {
+        self.scheme = Some(scheme);
+        self
+    }
+}
+
+/// A trait for converting a type into an `AixSetup` struct.
+pub trait IntoAixSetup<'a> {
+    /// The conversion function.
+    fn into_aix_setup(self) -> Result<AixSetup<'a>>;
+}
+
+impl<'a> IntoAixSetup<'a> for &'a str {
+    fn into_aix_setup(self) -> Result<AixSetup<'a>> {
+        let (scheme, rest) = AixScheme::from_prefix(self).ok_or(Error::InvalidHashString)?;
+        let mut hs = parse::HashSlice::new(rest);
+        let cost = if scheme == AixScheme::Smd5 {
+            None
+        } else {
+            let cost_str = hs.take_until(b'$').ok_or(Error::InvalidHashString)?;
+            if cost_str.len() != 2 {
+                return Err(Error::InvalidHashString);
+            }
+            Some(cost_str.parse().map_err(Error::InvalidNumber)?)
+        };
+        let salt = hs.take_until(b'$').ok_or(Error::InvalidHashString)?;
+        Ok(AixSetup {
+            salt: Some(salt),
+            cost,
+            scheme: Some(scheme),
+        })
+    }
+}
+
+impl<'a> IntoAixSetup<'a> for HashSetup<'a> {
+    fn into_aix_setup(self) -> Result<AixSetup<'a>> {
+        Ok(AixSetup {
+            salt: self.salt_str()?,
+            cost: self.rounds,
+            scheme: Some(DEFAULT_SCHEME),
+        })
+    }
+}
+
+impl<'a> IntoAixSetup<'a> for AixSetup<'a> {
+    fn into_aix_setup(self) -> Result<AixSetup<'a>> {
+        Ok(self)
+    }
+}
+
+impl<'a> IntoAixSetup<'a> for AlgorithmParams<'a> {
+    fn into_aix_setup(self) -> Result<AixSetup<'a>> {
+        #[allow(unreachable_patterns)]
+        match self {
+            AlgorithmParams::Basic(hs) => hs.into_aix_setup(),
+            AlgorithmParams::Aix(setup) => Ok(setup),
+            _ => Err(Error::InvalidHashString),
+        }
+    }
+}
+
+#[inline]
+pub(crate) fn is_aix_hash(hash: &str) -> bool {
+    AixScheme::from_prefix(hash).is_some()
+}
+
+fn do_aix_crypt(pass: &[u8], salt: &str, cost: u32, scheme: AixScheme) -> Result<String> {
+    if !salt
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || b == b'.' || b == b'/')
+    {
+        return Err(Error::EncodingError);
+    }
+    let rounds = 1u32 << cost;
+    let checksum = match scheme {
+        AixScheme::Ssha512 => {
+            let mut output = [0u8; 64];
+            pbkdf2_hmac::<Sha512>(pass, salt.as_bytes(), rounds, &mut output);
+            aix_hash64_encode(&output)
+        }
+        AixScheme::Ssha256 => {
+            let mut output = [0u8; 32];
+            pbkdf2_hmac::<Sha256>(pass, salt.as_bytes(), rounds, &mut output);
+            aix_hash64_encode(&output)
+        }
+        AixScheme::Ssha1 => {
+            let mut output = [0u8; 20];
+            pbkdf2_hmac::<Sha1>(pass, salt.as_bytes(), rounds, &mut output);
+            aix_hash64_encode(&output)
+        }
+        AixScheme::Smd5 => {
+            let hash = do_md5_crypt(pass, salt, "")?;
+            return Ok(format!("{scheme}{hash}"));
+        }
+    };
+    Ok(format!("{scheme}{cost:02}${salt}${checksum}"))
+}
+
+/// Hash a password with a randomly generated salt, default cost,
+/// and default algorithm (__{ssha512}__).
+///
+/// An error is returned if the system random number generator cannot
+/// be opened.
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    let saltstr = random::gen_salt_str(DEFAULT_SALT_LEN);
+    let hash = do_aix_crypt(pass.as_ref(), &saltstr, DEFAULT_COST, DEFAULT_SCHEME)?;
+    Ok(Hash::Aix(HashV(hash)))
+}
+
+/// Hash a password with user-provided parameters.
+///
+/// If the `param` argument is a `&str`, it must be in the final hash
+/// format. The algorithm, cost, and salt are parsed out of that value.
+/// If a `HashSetup` is used, its rounds value is used as the cost, and the
+/// algorithm is set to default. If the salt is too long, it is truncated to
+/// maximum length. If it contains an invalid character, an error is returned.
+/// An out-of-range cost value will also result in an error.
+pub fn hash_with<'a, IAS, B>(param: IAS, pass: B) -> Result<Hash>
+where
+    IAS: IntoAixSetup<'a>,
+    B: AsRef<[u8]>,
+{
+    let aset = param.into_aix_setup()?;
+    let scheme = aset.scheme.unwrap_or(DEFAULT_SCHEME);
+    let cost = if let Some(c) = aset.cost {
+        if !(MIN_COST..=MAX_COST).contains(&c) {
+            return Err(Error::BadRounds {
+                value: c,
+                allowed: MIN_COST..=MAX_COST,
+            });
+        }
+        c
+    } else {
+        DEFAULT_COST
+    };
+
+    let max_salt_len = scheme.max_salt_len();
+    let salt = match aset.salt {
+        None => &random::gen_salt_str(scheme.default_salt_len()),
+        Some(salt) => (salt.len() <= max_salt_len)
+            .then_some(salt)
+            .or_else(|| parse::HashSlice::new(salt).take(max_salt_len))
+            .ok_or(Error::InvalidHashString)?,
+    };
+
+    let hash = do_aix_crypt(pass.as_ref(), salt, cost, scheme)?;
+    Ok(Hash::Aix(HashV(hash)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    consteq(hash, hash_with(hash, pass))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AixScheme, AixSetup, HashSetup};
+
+    #[test]
+    fn custom() {
+        assert_eq!(
+            super::hash_with(
+                "{ssha256}06$2715084824104660$1s/s4RZWEcvZ5VuWPXWGUfwSoG07eVSVce8F6ANJ.g4",
+                "hashcat"
+            )
+            .unwrap(),
+            "{ssha256}06$2715084824104660$1s/s4RZWEcvZ5VuWPXWGUfwSoG07eVSVce8F6ANJ.g4"
+        );
+        assert_eq!(
+            super::hash_with(
+                HashSetup::default().salt("saltsaltsaltsalt").rounds(6),
+                "password"
+            )
+            .unwrap(),
+            "{ssha512}06$saltsaltsaltsalt$jnPDnc3o6HvirNDeaSIbjgOWFj2tgUMnbnCcg.sQIQTWGTzv5gX\
+             rvb2.6mAjaG7GweqmOHjo0iQlbuItE7sx.."
+        );
+        assert_eq!(
+            super::hash_with(
+                AixSetup::default()
+                    .salt("0123456789abcdef")
+                    .cost(4)
+                    .scheme(AixScheme::Ssha1),
+                "password"
+            )
+            .unwrap(),
+            "{ssha1}04$0123456789abcdef$A0Ps/TxMDjvMQlzVLiW1GXFn.IE"
+        );
+        assert!(!super::verify(
+            "hashcae",
+            "{ssha256}06$2715084824104660$1s/s4RZWEcvZ5VuWPXWGUfwSoG07eVSVce8F6ANJ.g4"
+        ));
+    }
+
+    #[test]
+    fn smd5() {
+        assert!(super::verify(
+            "hashcat",
+            "{smd5}17800721$WkGka7tXcrfpUQS6WOQyw/"
+        ));
+        assert_eq!(
+            super::hash_with(
+                AixSetup::default().salt("saltsalt").scheme(AixScheme::Smd5),
+                "password"
+            )
+            .unwrap(),
+            "{smd5}saltsalt$aHbe55gE5UMLEiG0a8/NJ1"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "value: BadRounds")]
+    fn bad_cost() {
+        let _ = super::hash_with(
+            "{ssha256}32$2715084824104660$1s/s4RZWEcvZ5VuWPXWGUfwSoG07eVSVce8F6ANJ.g4",
+            "hashcat",
+        )
+        .unwrap();
+    }
+}
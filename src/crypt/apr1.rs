@@ -19,7 +19,8 @@
 //!
 //! # Parameters
 //!
-//! * __Password length__: unlimited.
+//! * __Password length__: up to the [process-wide
+//!   maximum](crate::set_max_password_len), 4096 bytes by default.
 //!
 //! * __Salt length__: 0 to 8 characters. Default is 8.
 //!
@@ -49,7 +50,7 @@ const APR1_MAGIC: &str = "$apr1$";
 const MAGIC_LEN: usize = APR1_MAGIC.len();
 
 // magic + (0..8 salt) + '$' + checksum
-pub(crate) const HASH_LENGTH_MIN: usize = MAGIC_LEN + 0 + 1 + 22;
+pub(crate) const HASH_LENGTH_MIN: usize = MAGIC_LEN + 1 + 22;
 pub(crate) const HASH_LENGTH_MAX: usize = MAGIC_LEN + 8 + 1 + 22;
 pub(crate) const HASH_LENGTH: RangeInclusive<usize> = HASH_LENGTH_MIN..=HASH_LENGTH_MAX;
 
@@ -67,15 +68,16 @@ pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
     Ok(Hash::Apr1(HashV(hash)))
 }
 
-fn parse_md5_hash(hash: &str) -> Result<HashSetup> {
+fn parse_md5_hash(hash: &str) -> Result<HashSetup<'_>> {
     let mut hs = parse::HashSlice::new(hash);
-    if hs.take(MAGIC_LEN).unwrap_or("X") != APR1_MAGIC {
+    if hs.take(MAGIC_LEN) != Some(APR1_MAGIC) {
         return Err(Error::InvalidHashString);
     }
     let salt = hs.take_until(b'$').ok_or(Error::InvalidHashString)?;
     Ok(HashSetup {
         salt: Some(salt),
         rounds: None,
+        salt_bytes: None,
     })
 }
 
@@ -92,7 +94,7 @@ where
     B: AsRef<[u8]>,
 {
     let hs = IHS::into_hash_setup(param, parse_md5_hash)?;
-    let salt = match hs.salt {
+    let salt = match hs.salt_str()? {
         None => &random::gen_salt_str(MAX_SALT_LEN),
         Some(salt) => (salt.len() <= MAX_SALT_LEN)
             .then_some(salt)
@@ -126,7 +128,8 @@ mod tests {
             super::hash_with(
                 HashSetup {
                     salt: Some("63JlJ2NH"),
-                    rounds: None
+                    rounds: None,
+                    salt_bytes: None,
                 },
                 "password"
             )
@@ -0,0 +1,315 @@
+//! Balloon hashing.
+//!
+//! Balloon hashing, by Boneh, Corrigan-Gibbs, and Schechter, is a
+//! memory-hard password hashing function built solely on a standard
+//! cryptographic hash. It fills a buffer of hash-sized blocks derived from
+//! the password and salt, and then repeatedly mixes each block with its
+//! predecessor and with pseudorandomly chosen other blocks, which makes the
+//! computation costly in both time and memory. This implementation uses
+//! SHA-256, and produces the same output as the reference algorithm.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::balloon;
+//!
+//! let h = "$balloon$v=1$s=16,t=1,p=1$ZXhhbXBsZXNhbHQ$\
+//!          GJdj7r0AtOnWhMJMJzPwgVgq3aVTFdwlghFjcxUihjU";
+//! assert!(balloon::verify("hunter42", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: any number of bytes. Default is 16 random bytes.
+//!
+//! * __Space cost__: 1 to 2<sup>24</sup> blocks of 32 bytes. Default is 16384,
+//!   i.e., 512 KiB of memory.
+//!
+//! * __Time cost__: 1 to 2<sup>32</sup>-1 mixing rounds. Default is 3.
+//!
+//! # Hash Format
+//!
+//! The format of the hash is
+//! __`$balloon$v=1$s=`__*`{space}`*__`,t=`__*`{time}`*__`,p=1$`__*`{salt}`*__`$`__*`{checksum}`*,
+//! where:
+//!
+//! * *`{space}`* and *`{time}`* are the space and time costs, encoded as
+//!   decimal numbers. The parallelism parameter is always 1.
+//!
+//! * *`{salt}`* is the standard Base64 encoding of the salt, without padding.
+//!   A custom salt must be given in the same encoding.
+//!
+//! * *`{checksum}`* is the 32-byte output, in the same encoding as the salt.
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    AlgorithmParams, HashSetup, consteq,
+    encode::{base64_nopad_decode, base64_nopad_encode},
+    error::{Error, Result},
+    hash::{Hash, HashV},
+    phc::PhcString,
+    random,
+};
+
+const BALLOON_MAGIC: &str = "$balloon$v=1$";
+const DELTA: u64 = 3;
+const BLOCK_LEN: usize = 32;
+
+/// Maximum space cost, in blocks.
+pub const MAX_SPACE_COST: u32 = 1 << 24;
+
+/// Default space cost, in blocks.
+pub const DEFAULT_SPACE_COST: u32 = 16384;
+
+/// Default time cost.
+pub const DEFAULT_TIME_COST: u32 = 3;
+
+/// Default salt length, in bytes.
+pub const DEFAULT_SALT_LEN: usize = 16;
+
+/// Setup struct for Balloon hashes.
+///
+/// Instead of the number of rounds, Balloon hashing has separate space and
+/// time costs.
+#[derive(Default)]
+pub struct BalloonSetup<'a> {
+    /// Custom salt.
+    pub salt: Option<&'a str>,
+    /// Custom space cost.
+    pub space_cost: Option<u32>,
+    /// Custom time cost.
+    pub time_cost: Option<u32>,
+}
+
+impl<'a> BalloonSetup<'a> {
+    /// Configure custom salt for Balloon hash
+    pub fn salt(mut self, salt: &'a str) -> Self {
+        self.salt = Some(salt);
+        self
+    }
+    /// Configure custom space cost for Balloon hash
+    pub fn space_cost(mut self, space_cost: u32) -> Self {
+        self.space_cost = Some(space_cost);
+        self
+    }
+    /// Configure custom time cost for Balloon hash
+    pub fn time_cost(mut self, time_cost: u32) -> Self {
+        self.time_cost = Some(time_cost);
+        self
+    }
+}
+
+/// A trait for converting a type into a `BalloonSetup` struct.
+pub trait IntoBalloonSetup<'a> {
+    /// The conversion function.
+    fn into_balloon_setup(self) -> Result<BalloonSetup<'a>>;
+}
+
+impl<'a> IntoBalloonSetup<'a> for &'a str {
+    fn into_balloon_setup(self) -> Result<BalloonSetup<'a>> {
+        let phc = PhcString::parse(self)?;
+        if phc.id != "balloon" || phc.version != Some(1) {
+            return Err(Error::InvalidHashString);
+        }
+        let cost = |name: &str| phc.param_u32(name)?.ok_or(Error::InvalidHashString);
+        let space_cost = cost("s")?;
+        let time_cost = cost("t")?;
+        if cost("p")? != 1 {
+            return Err(Error::InvalidHashString);
+        }
+
+        let salt = phc.salt.ok_or(Error::InvalidHashString)?;
+        Ok(BalloonSetup {
+            salt: Some(salt),
+            space_cost: Some(space_cost),
+            time_cost: Some(time_cost),
+        })
+    }
+}
+
+impl<'a> IntoBalloonSetup<'a> for HashSetup<'a> {
+    fn into_balloon_setup(self) -> Result<BalloonSetup<'a>> {
+        Ok(BalloonSetup {
+            salt: self.salt_str()?,
+            space_cost: None,
+            time_cost: self.rounds,
+        })
+    }
+}
+
+impl<'a> IntoBalloonSetup<'a> for BalloonSetup<'a> {
+    fn into_balloon_setup(self) -> Result<BalloonSetup<'a>> {
+        Ok(self)
+    }
+}
+
+impl<'a> IntoBalloonSetup<'a> for AlgorithmParams<'a> {
+    fn into_balloon_setup(self) -> Result<BalloonSetup<'a>> {
+        #[allow(unreachable_patterns)]
+        match self {
+            AlgorithmParams::Basic(hs) => hs.into_balloon_setup(),
+            AlgorithmParams::Balloon(setup) => Ok(setup),
+            _ => Err(Error::InvalidHashString),
+        }
+    }
+}
+
+fn balloon(pass: &[u8], salt: &[u8], space_cost: usize, time_cost: u32) -> [u8; BLOCK_LEN] {
+    let mut cnt = 0u64;
+    let mut hash = |parts: &[&[u8]]| {
+        let mut dgst = Sha256::new();
+        dgst.update(cnt.to_le_bytes());
+        cnt += 1;
+        parts.iter().for_each(|part| dgst.update(part));
+        <[u8; BLOCK_LEN]>::from(dgst.finalize())
+    };
+
+    let mut buf = vec![[0u8; BLOCK_LEN]; space_cost];
+    buf[0] = hash(&[pass, salt]);
+    for m in 1..space_cost {
+        buf[m] = hash(&[&buf[m - 1]]);
+    }
+
+    for t in 0..time_cost as u64 {
+        for m in 0..space_cost {
+            let prev = buf[(m + space_cost - 1) % space_cost];
+            buf[m] = hash(&[&prev, &buf[m]]);
+            for i in 0..DELTA {
+                let mut dgst = Sha256::new();
+                dgst.update(t.to_le_bytes());
+                dgst.update((m as u64).to_le_bytes());
+                dgst.update(i.to_le_bytes());
+                let other = hash(&[salt, &dgst.finalize()])
+                    .iter()
+                    .rev()
+                    .fold(0u64, |acc, &b| ((acc << 8) | b as u64) % space_cost as u64);
+                buf[m] = hash(&[&buf[m], &buf[other as usize]]);
+            }
+        }
+    }
+
+    let output = buf[space_cost - 1];
+    buf.iter_mut().for_each(|block| block.fill(0u8));
+    output
+}
+
+fn do_balloon_crypt(pass: &[u8], salt: &[u8], space_cost: u32, time_cost: u32) -> Result<String> {
+    let output = balloon(pass, salt, space_cost as usize, time_cost);
+    Ok(format!(
+        "{BALLOON_MAGIC}s={space_cost},t={time_cost},p=1${}${}",
+        base64_nopad_encode(salt),
+        base64_nopad_encode(&output)
+    ))
+}
+
+/// Hash a password with a randomly generated salt and the default
+/// space and time costs.
+///
+/// An error is returned if the system random number generator cannot
+/// be opened.
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    let mut salt_buf = [0u8; DEFAULT_SALT_LEN];
+    random::gen_salt_bytes(&mut salt_buf);
+
+    let hash = do_balloon_crypt(
+        pass.as_ref(),
+        &salt_buf,
+        DEFAULT_SPACE_COST,
+        DEFAULT_TIME_COST,
+    )?;
+    Ok(Hash::Balloon(HashV(hash)))
+}
+
+/// Hash a password with user-provided parameters.
+///
+/// If the `param` argument is a `&str`, it must be in the final hash
+/// format. The costs and the salt are parsed out of that value. If a
+/// `HashSetup` is used, its rounds value is used as the time cost, and the
+/// space cost is set to default. The salt must be Base64 encoded; if it
+/// isn't, an error is returned. An out-of-range cost value will also result
+/// in an error.
+pub fn hash_with<'a, IBS, B>(param: IBS, pass: B) -> Result<Hash>
+where
+    IBS: IntoBalloonSetup<'a>,
+    B: AsRef<[u8]>,
+{
+    let bs = param.into_balloon_setup()?;
+    let space_cost = bs.space_cost.unwrap_or(DEFAULT_SPACE_COST);
+    let time_cost = bs.time_cost.unwrap_or(DEFAULT_TIME_COST);
+    if !(1..=MAX_SPACE_COST).contains(&space_cost) {
+        return Err(Error::BadRounds {
+            value: space_cost,
+            allowed: 1..=MAX_SPACE_COST,
+        });
+    }
+    if time_cost < 1 {
+        return Err(Error::BadRounds {
+            value: time_cost,
+            allowed: 1..=u32::MAX,
+        });
+    }
+
+    let salt = match bs.salt {
+        Some(salt) => base64_nopad_decode(salt)?,
+        None => {
+            let mut salt_buf = vec![0u8; DEFAULT_SALT_LEN];
+            random::gen_salt_bytes(&mut salt_buf);
+            salt_buf
+        }
+    };
+
+    let hash = do_balloon_crypt(pass.as_ref(), &salt, space_cost, time_cost)?;
+    Ok(Hash::Balloon(HashV(hash)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    consteq(hash, hash_with(hash, pass))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BalloonSetup, HashSetup};
+
+    #[test]
+    fn custom() {
+        assert_eq!(
+            super::hash_with(
+                "$balloon$v=1$s=1024,t=3,p=1$ZXhhbXBsZXNhbHQ$\
+                 cWBD3/d3tEqnuI3LqxLAeKvs+snSicW1GVlnqmNEDfs",
+                "hunter42"
+            )
+            .unwrap(),
+            "$balloon$v=1$s=1024,t=3,p=1$ZXhhbXBsZXNhbHQ$\
+             cWBD3/d3tEqnuI3LqxLAeKvs+snSicW1GVlnqmNEDfs"
+        );
+        assert_eq!(
+            super::hash_with(
+                BalloonSetup::default()
+                    .salt("c2FsdA")
+                    .space_cost(3)
+                    .time_cost(3),
+                ""
+            )
+            .unwrap(),
+            "$balloon$v=1$s=3,t=3,p=1$c2FsdA$XwL4IG+c0hJIXGvfhVJ7aYlWcBrQhSEG+UuU7pRXc3g"
+        );
+        assert!(!super::verify(
+            "hunter43",
+            "$balloon$v=1$s=16,t=1,p=1$ZXhhbXBsZXNhbHQ$\
+             GJdj7r0AtOnWhMJMJzPwgVgq3aVTFdwlghFjcxUihjU"
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "value: BadRounds")]
+    fn zero_time_cost() {
+        let _ =
+            super::hash_with(HashSetup::default().salt("c2FsdA").rounds(0), "password").unwrap();
+    }
+}
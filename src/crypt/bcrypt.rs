@@ -38,7 +38,8 @@
 //! # Parameters
 //!
 //! * __Password length__: up to 72 characters. Longer passwords are
-//!   truncated to the maximum length.
+//!   truncated to the maximum length, unless another
+//!   [`BcryptPasswordPolicy`] is chosen.
 //!
 //! * __Salt length__: 16 random bytes, encoded as 22 Base64 characters.
 //!
@@ -52,7 +53,9 @@
 //!
 //! * _`{variant}`_ is one of **2a**, **2b**, or **2y**. The default is **2b**.
 //!   The actual computation is the same for all three variants; the choice
-//!   exists in order to retain compatibility with other software. See
+//!   exists in order to retain compatibility with other software. The legacy
+//!   **2** and **2x** variants are also recognized, and computed with their
+//!   original flaws, so that old hashes can be verified. See
 //!   [`BcryptVariant`](enum.BcryptVariant.html) for details.
 //!
 //! * _`{cost}`_ is a two-digit decimal cost value between 4 and 31. Values
@@ -63,16 +66,18 @@
 //!
 //! * _`{checksum}`_ is a 31-character Base64 encoding of the computed hash.
 
+use std::borrow::Cow;
 use std::cmp::min;
 use std::default::Default;
 use std::{fmt, iter};
 
 use blowfish::Blowfish;
 use byteorder::{BE, ByteOrder};
+use sha2::{Digest, Sha512};
 
 use crate::{
-    HashSetup, consteq,
-    encode::{bcrypt_hash64_decode, bcrypt_hash64_encode},
+    AlgorithmParams, HashSetup, consteq,
+    encode::{base64_encode, bcrypt_hash64_decode, bcrypt_hash64_encode},
     error::{Error, Result},
     hash::{Hash, HashV},
     parse::{self, HashIterator},
@@ -80,12 +85,14 @@ use crate::{
 };
 
 const MAX_PASS_LEN: usize = 72;
-const DEFAULT_VARIANT: BcryptVariant = BcryptVariant::V2b;
-const ENC_SALT_LEN: usize = 22;
+pub(crate) const DEFAULT_VARIANT: BcryptVariant = BcryptVariant::V2b;
+pub(crate) const ENC_SALT_LEN: usize = 22;
 const MAGIC_LEN: usize = 4;
 
 // magic + cost + salt + `$` + checksum
 pub(crate) const HASH_LENGTH: usize = MAGIC_LEN + 2 + 22 + 1 + 31;
+// the magic of the `$2$` variant is one character shorter
+pub(crate) const LEGACY_HASH_LENGTH: usize = HASH_LENGTH - 1;
 
 /// Minimum cost.
 pub const MIN_COST: u32 = 4;
@@ -129,30 +136,87 @@ pub const DEFAULT_COST: u32 = 10;
 /// This crate has a single bcrypt algorithm implementation which is equivalent
 /// to the **2b** variant. It accepts **2a** and **2y** on input, and can
 /// generate both on output, but doesn't treat them specially in any way.
+///
+/// The legacy **2** and **2x** variants are computed with their original
+/// flaws: **2** omits the terminating NUL from the key, and **2x** emulates the
+/// sign extension of high-bit-set characters. For passwords consisting of
+/// ASCII characters, **2x** hashes are the same as **2b** ones. They are meant
+/// for verifying old hashes only.
+#[non_exhaustive]
 pub enum BcryptVariant {
+    /// Original OpenBSD variant, hashed repeated strings identically.
+    V2,
     /// Second OpenBSD variant, fixed repeated string hashing.
     V2a,
     /// Third OpenBSD variant, fixed a wraparound bug.
     V2b,
+    /// Openwall identifier for hashes produced with the sign extension bug.
+    V2x,
     /// Openwall variant, fixed a sign extension bug.
     V2y,
 }
 
+impl BcryptVariant {
+    pub(crate) fn from_ident(ident: &str) -> Option<Self> {
+        match ident {
+            "2a" => Some(BcryptVariant::V2a),
+            "2b" => Some(BcryptVariant::V2b),
+            "2y" => Some(BcryptVariant::V2y),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for BcryptVariant {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let variant = match self {
+            BcryptVariant::V2 => "2",
             BcryptVariant::V2a => "2a",
             BcryptVariant::V2b => "2b",
+            BcryptVariant::V2x => "2x",
             BcryptVariant::V2y => "2y",
         };
         write!(f, "{variant}")
     }
 }
 
+/// Handling of passwords longer than 72 bytes.
+///
+/// Bcrypt uses at most 72 bytes of the password, and traditionally ignores
+/// the rest, so that passwords which share a long prefix hash the same. The
+/// policy is not recorded in the hash: a password hashed with a policy other
+/// than the default must be verified with [`verify_with`] and the same
+/// policy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BcryptPasswordPolicy {
+    /// Silently truncate the password to 72 bytes. This is compatible with
+    /// all other implementations.
+    #[default]
+    Truncate,
+    /// Reject longer passwords with [`Error::PasswordTooLong`].
+    Error,
+    /// Hash every password with SHA-512, and pass the Base64 encoding of the
+    /// digest to bcrypt. The first 72 characters of the encoding carry 432
+    /// bits of the digest, so the whole password affects the hash.
+    PreHash,
+}
+
+impl BcryptPasswordPolicy {
+    fn apply(self, pass: &[u8]) -> Result<Cow<'_, [u8]>> {
+        match self {
+            BcryptPasswordPolicy::Error if pass.len() > MAX_PASS_LEN => Err(Error::PasswordTooLong),
+            BcryptPasswordPolicy::PreHash => Ok(Cow::Owned(
+                base64_encode(&Sha512::digest(pass)).into_bytes(),
+            )),
+            _ => Ok(Cow::Borrowed(pass)),
+        }
+    }
+}
+
 /// Setup struct for bcrypt.
 ///
 /// In addition to custom salt and cost values, a bcrypt hash can use different
-/// algorithm variant identifiers.
+/// algorithm variant identifiers, and a policy for long passwords.
 pub struct BcryptSetup<'a> {
     /// Custom salt.
     pub salt: Option<&'a str>,
@@ -160,6 +224,9 @@ pub struct BcryptSetup<'a> {
     pub cost: Option<u32>,
     /// Algorithm variant.
     pub variant: Option<BcryptVariant>,
+    /// Handling of passwords longer than 72 bytes. It's ignored by
+    /// [`bcrypt_sha256`](super::bcrypt_sha256), which always pre-hashes.
+    pub password_policy: Option<BcryptPasswordPolicy>,
 }
 
 impl<'a> BcryptSetup<'a> {
@@ -178,6 +245,11 @@ impl<'a> BcryptSetup<'a> {
         self.variant = Some(variant);
         self
     }
+    /// Configure handling of passwords longer than 72 bytes
+    pub fn password_policy(mut self, policy: BcryptPasswordPolicy) -> Self {
+        self.password_policy = Some(policy);
+        self
+    }
 }
 
 /// A trait for converting a type into a `BcryptSetup` struct.
@@ -189,11 +261,15 @@ pub trait IntoBcryptSetup<'a> {
 impl<'a> IntoBcryptSetup<'a> for &'a str {
     fn into_bcrypt_setup(self) -> Result<BcryptSetup<'a>> {
         let mut hs = parse::HashSlice::new(self);
-        let variant = match hs.take(MAGIC_LEN).unwrap_or("X") {
-            "$2a$" => BcryptVariant::V2a,
-            "$2b$" => BcryptVariant::V2b,
-            "$2y$" => BcryptVariant::V2y,
-            _ => return Err(Error::InvalidHashString),
+        if hs.take(1) != Some("$") {
+            return Err(Error::InvalidHashString);
+        }
+        let variant = match hs.take_until(b'$') {
+            Some("2") => BcryptVariant::V2,
+            Some("2x") => BcryptVariant::V2x,
+            ident => ident
+                .and_then(BcryptVariant::from_ident)
+                .ok_or(Error::InvalidHashString)?,
         };
 
         let cost_str = hs.take_until(b'$').ok_or(Error::InvalidHashString)?;
@@ -201,7 +277,7 @@ impl<'a> IntoBcryptSetup<'a> for &'a str {
             return Err(Error::InvalidHashString);
         }
 
-        let cost: u32 = cost_str.parse().map_err(|_e| Error::InvalidRounds)?;
+        let cost: u32 = cost_str.parse().map_err(Error::InvalidNumber)?;
         if cost < 10 && !cost_str.starts_with('0') {
             return Err(Error::InvalidHashString);
         }
@@ -211,6 +287,7 @@ impl<'a> IntoBcryptSetup<'a> for &'a str {
             salt: Some(salt),
             cost: Some(cost),
             variant: Some(variant),
+            password_policy: None,
         })
     }
 }
@@ -218,25 +295,56 @@ impl<'a> IntoBcryptSetup<'a> for &'a str {
 impl<'a> IntoBcryptSetup<'a> for HashSetup<'a> {
     fn into_bcrypt_setup(self) -> Result<BcryptSetup<'a>> {
         Ok(BcryptSetup {
-            salt: self.salt,
+            salt: self.salt_str()?,
             cost: self.rounds,
             variant: Some(DEFAULT_VARIANT),
+            password_policy: None,
         })
     }
 }
 
+impl<'a> IntoBcryptSetup<'a> for &'a String {
+    fn into_bcrypt_setup(self) -> Result<BcryptSetup<'a>> {
+        self.as_str().into_bcrypt_setup()
+    }
+}
+
+impl<'a> IntoBcryptSetup<'a> for (&'a str, u32) {
+    fn into_bcrypt_setup(self) -> Result<BcryptSetup<'a>> {
+        HashSetup::from(self).into_bcrypt_setup()
+    }
+}
+
+impl<'a> IntoBcryptSetup<'a> for Option<&'a str> {
+    fn into_bcrypt_setup(self) -> Result<BcryptSetup<'a>> {
+        HashSetup::from(self).into_bcrypt_setup()
+    }
+}
+
 impl<'a> IntoBcryptSetup<'a> for BcryptSetup<'a> {
     fn into_bcrypt_setup(self) -> Result<BcryptSetup<'a>> {
         Ok(self)
     }
 }
 
+impl<'a> IntoBcryptSetup<'a> for AlgorithmParams<'a> {
+    fn into_bcrypt_setup(self) -> Result<BcryptSetup<'a>> {
+        #[allow(unreachable_patterns)]
+        match self {
+            AlgorithmParams::Basic(hs) => hs.into_bcrypt_setup(),
+            AlgorithmParams::Bcrypt(setup) => Ok(setup),
+            _ => Err(Error::InvalidHashString),
+        }
+    }
+}
+
 impl<'a> Default for BcryptSetup<'a> {
     fn default() -> Self {
         BcryptSetup {
             salt: None,
             cost: Some(DEFAULT_COST),
             variant: Some(DEFAULT_VARIANT),
+            password_policy: None,
         }
     }
 }
@@ -268,18 +376,59 @@ fn bcrypt(cost: u32, salt: &[u8], password: &[u8], output: &mut [u8]) {
     }
 }
 
-fn do_bcrypt(pass: &[u8], salt: &[u8], cost: u32, variant: BcryptVariant) -> Result<String> {
+// Expand the key into the 18 words XORed into the P-array, sign-extending each
+// character as the buggy crypt_blowfish versions did. The crate's key schedule
+// consumes the key cyclically in the same way, so the expanded words can be
+// passed on as a 72-byte key.
+fn sign_extended_key(key: &[u8]) -> Vec<u8> {
+    let mut stream = key.iter().cycle();
+    let mut expanded = Vec::with_capacity(MAX_PASS_LEN);
+    for _ in 0..MAX_PASS_LEN / 4 {
+        let mut word = 0u32;
+        for &b in stream.by_ref().take(4) {
+            word = (word << 8) | b as i8 as i32 as u32;
+        }
+        expanded.extend_from_slice(&word.to_be_bytes());
+    }
+    expanded
+}
+
+pub(crate) fn bcrypt_output(
+    pass: &[u8],
+    salt: &[u8],
+    cost: u32,
+    variant: &BcryptVariant,
+) -> Result<[u8; 24]> {
+    let key_len = match variant {
+        BcryptVariant::V2 if pass.is_empty() => return Err(Error::InsufficientLength),
+        BcryptVariant::V2 => min(pass.len(), MAX_PASS_LEN),
+        _ => min(pass.len() + 1, MAX_PASS_LEN),
+    };
     let mut upd_pass: Vec<_> = pass
         .iter()
         .copied()
         .chain(iter::repeat(0u8))
-        .take(min(pass.len() + 1, MAX_PASS_LEN))
+        .take(key_len)
         .collect();
+    if let BcryptVariant::V2x = variant {
+        let expanded = sign_extended_key(&upd_pass);
+        upd_pass.fill(0u8);
+        upd_pass = expanded;
+    }
 
     let mut output = [0u8; 24];
     bcrypt(cost, salt, &upd_pass[..], &mut output);
     upd_pass.fill(0u8);
+    Ok(output)
+}
 
+pub(crate) fn do_bcrypt(
+    pass: &[u8],
+    salt: &[u8],
+    cost: u32,
+    variant: BcryptVariant,
+) -> Result<String> {
+    let output = bcrypt_output(pass, salt, cost, &variant)?;
     Ok(format!(
         "${variant}${cost:02}${}{}",
         bcrypt_hash64_encode(salt),
@@ -316,7 +465,10 @@ where
     let bs = param.into_bcrypt_setup()?;
     let cost = if let Some(c) = bs.cost {
         if !(MIN_COST..=MAX_COST).contains(&c) {
-            return Err(Error::InvalidRounds);
+            return Err(Error::BadRounds {
+                value: c,
+                allowed: MIN_COST..=MAX_COST,
+            });
         }
         c
     } else {
@@ -324,13 +476,17 @@ where
     };
 
     let variant = bs.variant.unwrap_or(DEFAULT_VARIANT);
+    let pass = bs
+        .password_policy
+        .unwrap_or_default()
+        .apply(pass.as_ref())?;
     let mut salt_buf = [0u8; 16];
     match bs.salt {
         Some(salt) => bcrypt_hash64_decode(salt, &mut salt_buf)?,
         None => random::gen_salt_bytes(&mut salt_buf),
     }
 
-    let hash = do_bcrypt(pass.as_ref(), &salt_buf, cost, variant)?;
+    let hash = do_bcrypt(&pass, &salt_buf, cost, variant)?;
     Ok(Hash::Bcrypt(HashV(hash)))
 }
 
@@ -340,9 +496,21 @@ pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
     consteq(hash, hash_with(hash, pass))
 }
 
+/// Verify that the hash corresponds to a password hashed with the given
+/// policy for long passwords.
+///
+/// With [`BcryptPasswordPolicy::Error`], a password longer than 72 bytes
+/// never matches.
+pub fn verify_with<B: AsRef<[u8]>>(pass: B, hash: &str, policy: BcryptPasswordPolicy) -> bool {
+    let Ok(setup) = hash.into_bcrypt_setup() else {
+        return false;
+    };
+    consteq(hash, hash_with(setup.password_policy(policy), pass))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{BcryptSetup, BcryptVariant};
+    use super::{BcryptPasswordPolicy, BcryptSetup, BcryptVariant};
 
     #[test]
     fn variant() {
@@ -352,11 +520,104 @@ mod tests {
                 BcryptSetup {
                     salt: Some("bvIG6Nmid91Mu9RcmmWZfO"),
                     cost: Some(5),
-                    variant: Some(BcryptVariant::V2y)
+                    variant: Some(BcryptVariant::V2y),
+                    password_policy: None,
                 },
                 "password"
             )
             .unwrap()
         );
     }
+    #[test]
+    fn legacy_2x() {
+        assert!(super::verify(
+            "password",
+            "$2x$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe"
+        ));
+        assert!(super::verify(
+            b"\xa3",
+            "$2x$05$/OK.fbVrR/bpIqNJ5ianF.CE5elHaaO4EbggVDjb8P19RukzXSM3e"
+        ));
+        assert!(super::verify(
+            b"\xff\xff\xa3",
+            "$2b$05$/OK.fbVrR/bpIqNJ5ianF.CE5elHaaO4EbggVDjb8P19RukzXSM3e"
+        ));
+        assert!(super::verify(
+            b"\xd1\x91",
+            "$2x$05$6bNw2HLQYeqHYyBfLMsv/OiwqTymGIGzFsA4hOTWebfehXHNprcAS"
+        ));
+        assert!(!super::verify(
+            b"\xa3",
+            "$2b$05$/OK.fbVrR/bpIqNJ5ianF.CE5elHaaO4EbggVDjb8P19RukzXSM3e"
+        ));
+    }
+
+    #[test]
+    fn legacy_2() {
+        let h = super::hash_with(
+            BcryptSetup::default()
+                .salt("bvIG6Nmid91Mu9RcmmWZfO")
+                .cost(5)
+                .variant(BcryptVariant::V2),
+            b"password\0",
+        )
+        .unwrap();
+        assert_eq!(
+            h,
+            "$2$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe"
+        );
+        assert!(!super::verify("password", &h));
+        assert!(matches!(
+            crate::Hash::try_from(h.as_str()).unwrap(),
+            crate::Hash::Bcrypt(_)
+        ));
+    }
+
+    #[test]
+    fn password_policy() {
+        let long = "password".repeat(10);
+        let setup = || {
+            BcryptSetup::default()
+                .salt("bvIG6Nmid91Mu9RcmmWZfO")
+                .cost(5)
+        };
+        let h = super::hash_with(setup(), &long).unwrap();
+        assert!(super::verify(&long[..72], &h));
+        assert!(super::verify_with(
+            &long,
+            &h,
+            BcryptPasswordPolicy::Truncate
+        ));
+        assert!(!super::verify_with(&long, &h, BcryptPasswordPolicy::Error));
+        assert!(super::verify_with(
+            &long[..72],
+            &h,
+            BcryptPasswordPolicy::Error
+        ));
+        assert!(matches!(
+            super::hash_with(setup().password_policy(BcryptPasswordPolicy::Error), &long),
+            Err(crate::error::Error::PasswordTooLong)
+        ));
+
+        let h = super::hash_with(
+            setup().password_policy(BcryptPasswordPolicy::PreHash),
+            &long,
+        )
+        .unwrap();
+        assert_eq!(
+            h,
+            super::hash_with(
+                setup(),
+                "AjbXhfoBU9Jo/MnBaKqBHwjA7i77jpWfs7BuSJKBLnkgX44zXqWgwMWTuyeqRUoBdltXH37tiscmdDOBrhlc7g=="
+            )
+            .unwrap()
+        );
+        assert!(super::verify_with(&long, &h, BcryptPasswordPolicy::PreHash));
+        assert!(!super::verify_with(
+            &long[..72],
+            &h,
+            BcryptPasswordPolicy::PreHash
+        ));
+        assert!(!super::verify(&long, &h));
+    }
 }
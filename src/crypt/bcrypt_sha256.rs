@@ -0,0 +1,274 @@
+//! Passlib bcrypt-SHA256 hash.
+//!
+//! This scheme was introduced by the Python Passlib library to work around
+//! the 72-byte password limit of [bcrypt](super::bcrypt). The password is
+//! first run through HMAC-SHA256 keyed with the salt, and the Base64 encoded
+//! result is used as the bcrypt password. Its security is equivalent to that
+//! of bcrypt, without the truncation of long passwords.
+//!
+//! Only the second version of the format, which is what current Passlib
+//! releases produce, is supported.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::{crypt::bcrypt_sha256, HashSetup};
+//!
+//! let h = bcrypt_sha256::hash_with(
+//!     HashSetup::default().salt("n79VH.0Q2TMWmt3Oqt9uku").rounds(5),
+//!     "password",
+//! ).unwrap();
+//! assert!(bcrypt_sha256::verify("password", &h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: 16 random bytes, encoded as 22 Base64 characters.
+//!
+//! * __Cost__: logarithmic value between 4 and 31, inclusive. The default
+//!   is 10.
+//!
+//! # Hash Format
+//!
+//! The format of the hash is
+//! **`$bcrypt-sha256$v=2,t=`**_`{variant}`_**`,r=`**_`{cost}`_**`$`**_`{salt}`_**`$`**_`{checksum}`_,
+//! where:
+//!
+//! * _`{variant}`_ is the bcrypt variant identifier, usually **2b**.
+//!
+//! * _`{cost}`_ is the decimal cost value, without leading zeroes.
+//!
+//! * _`{salt}`_ is a 22-character Base64 encoding of the 16 bytes of salt.
+//!
+//! * _`{checksum}`_ is a 31-character Base64 encoding of the computed hash.
+
+use std::ops::RangeInclusive;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::bcrypt::{
+    BcryptSetup, BcryptVariant, DEFAULT_COST, DEFAULT_VARIANT, ENC_SALT_LEN, MAX_COST, MIN_COST,
+    do_bcrypt,
+};
+use crate::{
+    AlgorithmParams, HashSetup, consteq,
+    encode::{base64_encode, bcrypt_hash64_decode, bcrypt_hash64_encode},
+    error::{Error, Result},
+    hash::{Hash, HashV},
+    parse::{self, HashIterator},
+    random,
+};
+
+const BCRYPT_SHA256_MAGIC: &str = "$bcrypt-sha256$";
+
+// magic + `v=2,t=` + variant + `,r=` + (4..31 cost) + `$` + salt + `$` + checksum
+pub(crate) const HASH_LENGTH_MIN: usize =
+    BCRYPT_SHA256_MAGIC.len() + 6 + 2 + 3 + 1 + 1 + 22 + 1 + 31;
+pub(crate) const HASH_LENGTH_MAX: usize =
+    BCRYPT_SHA256_MAGIC.len() + 6 + 2 + 3 + 2 + 1 + 22 + 1 + 31;
+pub(crate) const HASH_LENGTH: RangeInclusive<usize> = HASH_LENGTH_MIN..=HASH_LENGTH_MAX;
+
+/// A trait for converting a type into a `BcryptSetup` struct for bcrypt-SHA256.
+///
+/// This is separate from [`IntoBcryptSetup`](super::bcrypt::IntoBcryptSetup)
+/// because a `&str` parameter is parsed in the bcrypt-SHA256 hash format.
+pub trait IntoBcryptSha256Setup<'a> {
+    /// The conversion function.
+    fn into_bcrypt_sha256_setup(self) -> Result<BcryptSetup<'a>>;
+}
+
+impl<'a> IntoBcryptSha256Setup<'a> for &'a str {
+    fn into_bcrypt_sha256_setup(self) -> Result<BcryptSetup<'a>> {
+        let mut hs = parse::HashSlice::new(self);
+        if hs.take(BCRYPT_SHA256_MAGIC.len()) != Some(BCRYPT_SHA256_MAGIC) {
+            return Err(Error::InvalidHashString);
+        }
+
+        let mut ps = parse::HashSlice::new(hs.take_until(b'$').ok_or(Error::InvalidHashString)?);
+        if ps.take_until(b',') != Some("v=2") {
+            return Err(Error::InvalidHashString);
+        }
+        let variant = ps
+            .take_until(b',')
+            .and_then(|t| t.strip_prefix("t="))
+            .and_then(BcryptVariant::from_ident)
+            .ok_or(Error::InvalidHashString)?;
+        let cost = ps
+            .take_until(b',')
+            .and_then(|r| r.strip_prefix("r="))
+            .ok_or(Error::InvalidHashString)?;
+        if cost.starts_with('0') {
+            return Err(Error::InvalidHashString);
+        }
+        let cost = cost.parse::<u32>().map_err(Error::InvalidNumber)?;
+
+        let salt = hs.take_until(b'$').ok_or(Error::InvalidHashString)?;
+        if salt.len() != ENC_SALT_LEN {
+            return Err(Error::InvalidHashString);
+        }
+        Ok(BcryptSetup {
+            salt: Some(salt),
+            cost: Some(cost),
+            variant: Some(variant),
+            password_policy: None,
+        })
+    }
+}
+
+impl<'a> IntoBcryptSha256Setup<'a> for HashSetup<'a> {
+    fn into_bcrypt_sha256_setup(self) -> Result<BcryptSetup<'a>> {
+        Ok(BcryptSetup {
+            salt: self.salt_str()?,
+            cost: self.rounds,
+            variant: Some(DEFAULT_VARIANT),
+            password_policy: None,
+        })
+    }
+}
+
+impl<'a> IntoBcryptSha256Setup<'a> for BcryptSetup<'a> {
+    fn into_bcrypt_sha256_setup(self) -> Result<BcryptSetup<'a>> {
+        Ok(self)
+    }
+}
+
+impl<'a> IntoBcryptSha256Setup<'a> for AlgorithmParams<'a> {
+    fn into_bcrypt_sha256_setup(self) -> Result<BcryptSetup<'a>> {
+        #[allow(unreachable_patterns)]
+        match self {
+            AlgorithmParams::Basic(hs) => hs.into_bcrypt_sha256_setup(),
+            AlgorithmParams::Bcrypt(setup) => Ok(setup),
+            _ => Err(Error::InvalidHashString),
+        }
+    }
+}
+
+fn do_bcrypt_sha256_crypt(
+    pass: &[u8],
+    salt: &[u8],
+    cost: u32,
+    variant: BcryptVariant,
+) -> Result<String> {
+    let salt_str = bcrypt_hash64_encode(salt);
+    let mut hmac = Hmac::<Sha256>::new_from_slice(salt_str.as_bytes())
+        .map_err(|_| Error::InsufficientLength)?;
+    hmac.update(pass);
+    let key = base64_encode(&hmac.finalize().into_bytes());
+
+    let ident = format!("v=2,t={variant},r={cost}");
+    let bcrypt_hash = do_bcrypt(key.as_bytes(), salt, cost, variant)?;
+    let checksum = &bcrypt_hash[bcrypt_hash.len() - 31..];
+    Ok(format!(
+        "{BCRYPT_SHA256_MAGIC}{ident}${salt_str}${checksum}"
+    ))
+}
+
+/// Hash a password with a randomly generated salt, default cost,
+/// and default variant.
+///
+/// An error is returned if the system random number generator cannot
+/// be opened.
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    let mut salt_buf = [0u8; 16];
+    random::gen_salt_bytes(&mut salt_buf);
+
+    let hash = do_bcrypt_sha256_crypt(pass.as_ref(), &salt_buf, DEFAULT_COST, DEFAULT_VARIANT)?;
+    Ok(Hash::BcryptSha256(HashV(hash)))
+}
+
+/// Hash a password with user-provided parameters.
+///
+/// If the `param` argument is a `&str`, it must be in the final hash
+/// format. The cost, variant, and salt are parsed out of that value.
+/// A `HashSetup` or a `BcryptSetup` can also be used, in which case the
+/// `rounds` or `cost` field sets the bcrypt cost.
+pub fn hash_with<'a, IBS, B>(param: IBS, pass: B) -> Result<Hash>
+where
+    IBS: IntoBcryptSha256Setup<'a>,
+    B: AsRef<[u8]>,
+{
+    let bs = param.into_bcrypt_sha256_setup()?;
+    let cost = if let Some(c) = bs.cost {
+        if !(MIN_COST..=MAX_COST).contains(&c) {
+            return Err(Error::BadRounds {
+                value: c,
+                allowed: MIN_COST..=MAX_COST,
+            });
+        }
+        c
+    } else {
+        DEFAULT_COST
+    };
+
+    let variant = bs.variant.unwrap_or(DEFAULT_VARIANT);
+    let mut salt_buf = [0u8; 16];
+    match bs.salt {
+        Some(salt) => bcrypt_hash64_decode(salt, &mut salt_buf)?,
+        None => random::gen_salt_bytes(&mut salt_buf),
+    }
+
+    let hash = do_bcrypt_sha256_crypt(pass.as_ref(), &salt_buf, cost, variant)?;
+    Ok(Hash::BcryptSha256(HashV(hash)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    consteq(hash, hash_with(hash, pass))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BcryptSetup, BcryptVariant, HashSetup};
+
+    #[test]
+    fn custom() {
+        assert_eq!(
+            super::hash_with(
+                "$bcrypt-sha256$v=2,t=2b,r=12$n79VH.0Q2TMWmt3Oqt9uku$Kq4Noyk3094Y2QlB8NdRT8SvGiI4ft2",
+                "password"
+            )
+            .unwrap(),
+            "$bcrypt-sha256$v=2,t=2b,r=12$n79VH.0Q2TMWmt3Oqt9uku$Kq4Noyk3094Y2QlB8NdRT8SvGiI4ft2"
+        );
+        assert_eq!(
+            super::hash_with(
+                HashSetup::default()
+                    .salt("n79VH.0Q2TMWmt3Oqt9uku")
+                    .rounds(5),
+                "password"
+            )
+            .unwrap(),
+            "$bcrypt-sha256$v=2,t=2b,r=5$n79VH.0Q2TMWmt3Oqt9uku$oyU6hjHR7FUYIEdu8RXDI0A.W3hqSO6"
+        );
+        assert!(
+            super::hash_with(
+                BcryptSetup::default()
+                    .salt("n79VH.0Q2TMWmt3Oqt9uku")
+                    .cost(5)
+                    .variant(BcryptVariant::V2a),
+                "password"
+            )
+            .unwrap()
+            .starts_with("$bcrypt-sha256$v=2,t=2a,r=5$n79VH.0Q2TMWmt3Oqt9uku$")
+        );
+    }
+
+    #[test]
+    fn long_password() {
+        let pass = [b'x'; 100];
+        let h = super::hash_with(
+            HashSetup::default()
+                .salt("n79VH.0Q2TMWmt3Oqt9uku")
+                .rounds(4),
+            pass,
+        )
+        .unwrap();
+        assert!(super::verify(pass, &h));
+        assert!(!super::verify(&pass[..72], &h));
+    }
+}
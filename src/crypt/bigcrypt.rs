@@ -0,0 +1,143 @@
+//! Digital Unix/HP-UX extended DES-based hash.
+//!
+//! Bigcrypt lifts the eight character password limit of the traditional
+//! Unix __crypt__(3) by hashing the password in 8-character segments and
+//! concatenating the results. It was used on HP-UX "trusted systems" and
+//! Digital Unix (Tru64). Every segment is as weak as DES crypt, so the
+//! algorithm should only be used for backward compatibility.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::bigcrypt;
+//!
+//! let h = "f8.SVpL2fvwjkAnxn8/rgTkwvrif6bjYB5c";
+//! assert!(bigcrypt::verify("This is very long passwd", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited, processed in 8-character segments of
+//!   7-bit characters.
+//!
+//! * __Salt length__: 2 characters (12 bits).
+//!
+//! * __Rounds__: 25 per segment (fixed).
+//!
+//! # Hash Format
+//!
+//! The format of the hash is *`{salt}`*_`{checksum}`_, where:
+//!
+//! * *`{salt}`* is a 2-character Base64 encoding of the salt.
+//!
+//! * *`{checksum}`* is a concatenation of 11-character Base64 encoded
+//!   segment checksums, one for every 8 characters of the password. Each
+//!   segment after the first is salted with the first two characters of
+//!   the preceding segment checksum.
+//!
+//! A hash of a password no longer than eight characters is identical to
+//! its [DES crypt](super::unix) hash, and is recognized as such by
+//! [`Hash::try_from`](crate::Hash).
+
+use crate::{
+    consteq,
+    error::Result,
+    hash::{Hash, HashV},
+    internal::des::bigcrypt,
+    random,
+};
+
+/// Salt length.
+pub const SALT_LEN: usize = 2;
+
+const SEGMENT_LEN: usize = 11;
+
+// salt + at least two checksum segments
+#[inline]
+pub(crate) fn is_hash_length(len: usize) -> bool {
+    len > SALT_LEN + SEGMENT_LEN && (len - SALT_LEN).is_multiple_of(SEGMENT_LEN)
+}
+
+/// Hash a password with a randomly generated salt.
+///
+/// An error is returned if the system random number generator cannot
+/// be opened.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    let saltstr = random::gen_salt_str(SALT_LEN);
+    Ok(Hash::Bigcrypt(HashV(bigcrypt(
+        pass.as_ref(),
+        saltstr.as_bytes(),
+    )?)))
+}
+
+/// Hash a password with a user-provided salt.
+///
+/// Only the first two characters of `salt` are used, so a complete hash
+/// can be passed in its place. Characters outside the alphabet are mapped
+/// to zero, like in the BSD implementations, and the salt is copied into
+/// the hash as it is. An error is returned if the salt is too short.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash_with<B: AsRef<[u8]>>(salt: &str, pass: B) -> Result<Hash> {
+    Ok(Hash::Bigcrypt(HashV(bigcrypt(
+        pass.as_ref(),
+        salt.as_bytes(),
+    )?)))
+}
+
+/// Hash a password with a user-provided salt, given as raw bytes.
+///
+/// Only the first two bytes of `salt` are used. They may have any value,
+/// and are decoded like by [`hash_with`]. The salt is copied into the hash
+/// if it's printable ASCII, and written in the standard encoding otherwise.
+/// An error is returned if the salt is too short.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash_with_bytes<B: AsRef<[u8]>>(salt: &[u8], pass: B) -> Result<Hash> {
+    Ok(Hash::Bigcrypt(HashV(bigcrypt(pass.as_ref(), salt)?)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    consteq(
+        hash,
+        bigcrypt(pass.as_ref(), hash.as_bytes()).map(|s| Hash::Bigcrypt(HashV(s))),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[allow(deprecated)]
+    fn custom() {
+        assert_eq!(
+            super::hash_with("qi", "passphrase").unwrap(),
+            "qiyh4XPJGsOZ2MEAyLkfWqeQ"
+        );
+        assert_eq!(
+            super::hash_with("f8", "This is very long passwd").unwrap(),
+            "f8.SVpL2fvwjkAnxn8/rgTkwvrif6bjYB5c"
+        );
+        assert!(super::verify("passphrase", "qiyh4XPJGsOZ2MEAyLkfWqeQ"));
+        assert!(!super::verify("passphrasf", "qiyh4XPJGsOZ2MEAyLkfWqeQ"));
+        assert!(!super::verify("passphrase", "qiyh4XPJGsOZ"));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn historical_salt() {
+        let h = super::hash_with("..", "passphrase").unwrap();
+        let h = format!("!!{}", &h[2..]);
+        assert_eq!(super::hash_with("!!", "passphrase").unwrap(), h);
+        assert!(super::verify("passphrase", &h));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn short_is_unix() {
+        assert_eq!(super::hash_with("aZ", "test").unwrap(), "aZGJuE6EXrjEE");
+    }
+}
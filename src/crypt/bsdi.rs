@@ -52,8 +52,8 @@ use crate::{
     random,
 };
 
-const MIN_ROUNDS: u32 = 1;
-const MAX_ROUNDS: u32 = (1 << 24) - 1;
+pub(crate) const MIN_ROUNDS: u32 = 1;
+pub(crate) const MAX_ROUNDS: u32 = (1 << 24) - 1;
 
 // `_` + rounds + salt + checksum
 pub(crate) const HASH_LENGTH: usize = 1 + 4 + 4 + 11;
@@ -75,13 +75,13 @@ const ROUNDS_LEN: usize = 4;
 #[inline]
 pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
     let saltstr = random::gen_salt_str(SALT_LEN);
-    let hash = bsdi_crypt(pass.as_ref(), &saltstr, DEFAULT_ROUNDS)?;
+    let hash = bsdi_crypt(pass.as_ref(), saltstr.as_bytes(), DEFAULT_ROUNDS)?;
     Ok(Hash::Bsdi(HashV(hash)))
 }
 
-fn parse_bsdi_hash(hash: &str) -> Result<HashSetup> {
+fn parse_bsdi_hash(hash: &str) -> Result<HashSetup<'_>> {
     let mut hs = parse::HashSlice::new(hash);
-    if hs.take(1).unwrap_or("X") != "_" {
+    if hs.take(1) != Some("_") {
         return Err(Error::InvalidHashString);
     }
 
@@ -94,6 +94,7 @@ fn parse_bsdi_hash(hash: &str) -> Result<HashSetup> {
     Ok(HashSetup {
         salt: Some(salt),
         rounds: Some(rounds),
+        salt_bytes: None,
     })
 }
 
@@ -101,8 +102,12 @@ fn parse_bsdi_hash(hash: &str) -> Result<HashSetup> {
 ///
 /// If the `param` argument is a `&str`, it must be in the final hash
 /// format. The number of rounds and the salt are parsed out of that value.
-/// An error is returned if the salt is too short or contains an invalid
-/// character. An out-of-range rounds value will also result in an error.
+/// Salt characters outside the alphabet are mapped to zero, like in the BSD
+/// implementations, which makes it possible to check historical hashes with
+/// such salts, and the salt is copied into the hash as it is. A salt given
+/// as raw bytes may contain any byte, and is written into the hash in the
+/// standard encoding if it isn't printable ASCII. An error is returned if
+/// the salt is too short, or if the rounds value is out of range.
 #[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
 pub fn hash_with<'a, IHS, B>(param: IHS, pass: B) -> Result<Hash>
 where
@@ -112,18 +117,22 @@ where
     let hs = IHS::into_hash_setup(param, parse_bsdi_hash)?;
     let rounds = if let Some(r) = hs.rounds {
         if !(MIN_ROUNDS..=MAX_ROUNDS).contains(&r) {
-            return Err(Error::InvalidRounds);
+            return Err(Error::BadRounds {
+                value: r,
+                allowed: MIN_ROUNDS..=MAX_ROUNDS,
+            });
         }
         r
     } else {
         DEFAULT_ROUNDS
     };
 
-    let hash = match hs.salt {
-        Some(salt) => bsdi_crypt(pass.as_ref(), salt, rounds),
-        None => {
+    let hash = match (hs.salt, hs.salt_bytes) {
+        (Some(salt), _) => bsdi_crypt(pass.as_ref(), salt.as_bytes(), rounds),
+        (None, Some(salt)) => bsdi_crypt(pass.as_ref(), salt, rounds),
+        (None, None) => {
             let saltstr = random::gen_salt_str(SALT_LEN);
-            bsdi_crypt(pass.as_ref(), &saltstr, rounds)
+            bsdi_crypt(pass.as_ref(), saltstr.as_bytes(), rounds)
         }
     }?;
     Ok(Hash::Bsdi(HashV(hash)))
@@ -147,7 +156,8 @@ mod tests {
             super::hash_with(
                 HashSetup {
                     salt: Some("K0Ay"),
-                    rounds: None
+                    rounds: None,
+                    salt_bytes: None,
                 },
                 "password"
             )
@@ -162,12 +172,22 @@ mod tests {
 
     #[test]
     #[allow(deprecated)]
-    #[should_panic(expected = "value: InvalidRounds")]
+    fn historical_salt() {
+        let h = super::hash_with("_Gl/.....aosctsbJ1k", "password").unwrap();
+        let h = format!("_Gl/.!!!!{}", &h[9..]);
+        assert_eq!(super::hash_with(h.as_str(), "password").unwrap(), h);
+        assert!(super::verify("password", &h));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    #[should_panic(expected = "value: BadRounds")]
     fn bad_rounds() {
         let _ = super::hash_with(
             HashSetup {
                 salt: Some("K0Ay"),
                 rounds: Some(0),
+                salt_bytes: None,
             },
             "password",
         )
@@ -0,0 +1,15 @@
+//! Cisco IOS password hashes.
+//!
+//! Cisco network devices store secrets in their configurations using a
+//! numbered type scheme. The modules in this family implement the types which
+//! can be found in current IOS and IOS XE configurations:
+//!
+//! * [Type 7](type7): reversible obfuscation, not a hash.
+//!
+//! * [Type 8](type8): PBKDF2 with HMAC-SHA256.
+//!
+//! * [Type 9](type9): scrypt.
+
+pub mod type7;
+pub mod type8;
+pub mod type9;
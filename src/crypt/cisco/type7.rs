@@ -0,0 +1,108 @@
+//! Cisco Type 7 password obfuscation.
+//!
+//! Type 7 is __not__ a hash: it's a reversible encoding which XORs the
+//! password with a fixed, publicly known key, starting from a position given
+//! by a two-digit seed. Anyone with access to the configuration can recover
+//! the original password, so it offers no protection beyond hiding secrets
+//! from a casual glance. It's supported because such values are ubiquitous
+//! in existing configurations; use [Type 9](super::type9) for new secrets.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::cisco::type7;
+//!
+//! assert_eq!(type7::decode("0822455D0A16").unwrap(), b"cisco");
+//! assert_eq!(type7::encode_with(8, "cisco").unwrap(), "0822455D0A16");
+//! ```
+//!
+//! # Format
+//!
+//! The format of the encoded value is *`{seed}`*_`{data}`_, where:
+//!
+//! * *`{seed}`* is the starting position in the key, encoded as a two-digit
+//!   decimal number.
+//!
+//! * *`{data}`* is the uppercase hexadecimal encoding of the obfuscated
+//!   password, two digits per byte.
+
+use crate::{
+    encode::hex_decode,
+    error::{Error, Result},
+    random,
+};
+
+const TYPE7_KEY: &[u8] = b"dsfd;kfoA,.iyewrkldJKDHSUBsgvca69834ncxv9873254k;fg87";
+
+/// Largest seed value produced by IOS.
+pub const MAX_SEED: u8 = 15;
+
+fn xor_with_key(seed: usize, data: &[u8]) -> Vec<u8> {
+    data.iter()
+        .zip(TYPE7_KEY.iter().cycle().skip(seed))
+        .map(|(d, k)| d ^ k)
+        .collect()
+}
+
+/// Obfuscate a password with a randomly chosen seed.
+pub fn encode<B: AsRef<[u8]>>(pass: B) -> Result<String> {
+    let mut seed = [0u8; 1];
+    random::gen_salt_bytes(&mut seed);
+    encode_with(seed[0] % (MAX_SEED + 1), pass)
+}
+
+/// Obfuscate a password with a user-provided seed.
+///
+/// An error is returned if the seed is larger than the length of the key.
+/// Values above [`MAX_SEED`] are accepted, but may not be understood by all
+/// devices.
+pub fn encode_with<B: AsRef<[u8]>>(seed: u8, pass: B) -> Result<String> {
+    if seed as usize >= TYPE7_KEY.len() {
+        return Err(Error::BadRounds {
+            value: seed.into(),
+            allowed: 0..=TYPE7_KEY.len() as u32 - 1,
+        });
+    }
+    let data = xor_with_key(seed as usize, pass.as_ref());
+    Ok(format!(
+        "{seed:02}{}",
+        data.iter().map(|b| format!("{b:02X}")).collect::<String>()
+    ))
+}
+
+/// Recover the password from an obfuscated value.
+///
+/// An error is returned if the value isn't in the Type 7 format.
+pub fn decode(enc: &str) -> Result<Vec<u8>> {
+    let seed = enc
+        .get(..2)
+        .filter(|seed| seed.bytes().all(|b| b.is_ascii_digit()))
+        .ok_or(Error::InvalidHashString)?
+        .parse::<usize>()
+        .map_err(|_e| Error::InvalidHashString)?;
+    if seed >= TYPE7_KEY.len() {
+        return Err(Error::BadRounds {
+            value: seed as u32,
+            allowed: 0..=TYPE7_KEY.len() as u32 - 1,
+        });
+    }
+    Ok(xor_with_key(seed, &hex_decode(&enc[2..])?))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn custom() {
+        assert_eq!(super::decode("0822455D0A16").unwrap(), b"cisco");
+        assert_eq!(super::decode("021605481811003348").unwrap(), b"password");
+        assert_eq!(super::encode_with(15, "a").unwrap(), "1513");
+        let enc = super::encode("password").unwrap();
+        assert_eq!(super::decode(&enc).unwrap(), b"password");
+    }
+
+    #[test]
+    #[should_panic(expected = "value: EncodingError")]
+    fn bad_hex() {
+        let _ = super::decode("08ZZ").unwrap();
+    }
+}
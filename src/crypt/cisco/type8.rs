@@ -0,0 +1,152 @@
+//! Cisco Type 8 (PBKDF2-SHA256) hash.
+//!
+//! Type 8 secrets are PBKDF2 derived keys with HMAC-SHA256 and a fixed
+//! number of iterations. They're the recommended password type on IOS
+//! releases which don't support [scrypt](https://www.tarsnap.com/scrypt.html)
+//! based secrets, and are considered reasonably secure.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::cisco::type8;
+//!
+//! let h = "$8$TnGX/fE4KGHOVU$pEhnEvxrvaynpi8j4f.EMHr6M.FzU8xnZnBr/tJdFWk";
+//! assert!(type8::verify("hashcat", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: unlimited, may not contain a `$`. Default is 14
+//!   random characters, as used by IOS.
+//!
+//! * __Rounds__: 20000 (fixed).
+//!
+//! # Hash Format
+//!
+//! The format of the hash is
+//! __`$8$`__*`{salt}`*__$__*`{checksum}`*, where:
+//!
+//! * *`{salt}`* is the salt string.
+//!
+//! * *`{checksum}`* is a 43-character Base64 encoding of the derived key.
+//!   Cisco's Base64 uses the same alphabet as the traditional Unix hashes,
+//!   but encodes the bytes in the standard (big-endian) bit order.
+
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha256;
+
+use crate::{
+    HashSetup, IntoHashSetup, consteq,
+    encode::crypt_hash64_encode,
+    error::{Error, Result},
+    hash::{Hash, HashV},
+    parse::{self, HashIterator},
+    random,
+};
+
+const TYPE8_MAGIC: &str = "$8$";
+const ROUNDS: u32 = 20000;
+
+/// Default salt length.
+pub const DEFAULT_SALT_LEN: usize = 14;
+
+// magic + salt + `$` + checksum
+pub(crate) const HASH_LENGTH: usize = TYPE8_MAGIC.len() + DEFAULT_SALT_LEN + 1 + 43;
+
+fn do_type8_crypt(pass: &[u8], salt: &str) -> Result<String> {
+    if salt.contains('$') {
+        return Err(Error::EncodingError);
+    }
+    let mut output = [0u8; 32];
+    pbkdf2_hmac::<Sha256>(pass, salt.as_bytes(), ROUNDS, &mut output);
+    Ok(format!(
+        "{TYPE8_MAGIC}{salt}${}",
+        crypt_hash64_encode(&output)
+    ))
+}
+
+/// Hash a password with a randomly generated salt.
+///
+/// An error is returned if the system random number generator cannot
+/// be opened.
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    let saltstr = random::gen_salt_str(DEFAULT_SALT_LEN);
+    let hash = do_type8_crypt(pass.as_ref(), &saltstr)?;
+    Ok(Hash::CiscoType8(HashV(hash)))
+}
+
+fn parse_type8_hash(hash: &str) -> Result<HashSetup<'_>> {
+    let mut hs = parse::HashSlice::new(hash);
+    if hs.take(TYPE8_MAGIC.len()) != Some(TYPE8_MAGIC) {
+        return Err(Error::InvalidHashString);
+    }
+    let salt = hs.take_until(b'$').ok_or(Error::InvalidHashString)?;
+
+    Ok(HashSetup {
+        salt: Some(salt),
+        rounds: None,
+        salt_bytes: None,
+    })
+}
+
+/// Hash a password with user-provided parameters.
+///
+/// If the `param` argument is a `&str`, it must be in the final hash
+/// format, and the salt is parsed out of that value. The number of rounds
+/// is fixed, so the `rounds` field of a `HashSetup` is ignored. An error is
+/// returned if the salt contains a `$`.
+pub fn hash_with<'a, IHS, B>(param: IHS, pass: B) -> Result<Hash>
+where
+    IHS: IntoHashSetup<'a>,
+    B: AsRef<[u8]>,
+{
+    let hs = IHS::into_hash_setup(param, parse_type8_hash)?;
+    let hash = match hs.salt_str()? {
+        Some(salt) => do_type8_crypt(pass.as_ref(), salt),
+        None => {
+            let saltstr = random::gen_salt_str(DEFAULT_SALT_LEN);
+            do_type8_crypt(pass.as_ref(), &saltstr)
+        }
+    }?;
+    Ok(Hash::CiscoType8(HashV(hash)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    consteq(hash, hash_with(hash, pass))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HashSetup;
+
+    #[test]
+    fn custom() {
+        assert_eq!(
+            super::hash_with(
+                "$8$TnGX/fE4KGHOVU$pEhnEvxrvaynpi8j4f.EMHr6M.FzU8xnZnBr/tJdFWk",
+                "hashcat"
+            )
+            .unwrap(),
+            "$8$TnGX/fE4KGHOVU$pEhnEvxrvaynpi8j4f.EMHr6M.FzU8xnZnBr/tJdFWk"
+        );
+        assert_eq!(
+            super::hash_with(HashSetup::default().salt("TnGX/fE4KGHOVU"), "hashcat").unwrap(),
+            "$8$TnGX/fE4KGHOVU$pEhnEvxrvaynpi8j4f.EMHr6M.FzU8xnZnBr/tJdFWk"
+        );
+        assert!(!super::verify(
+            "hashcas",
+            "$8$TnGX/fE4KGHOVU$pEhnEvxrvaynpi8j4f.EMHr6M.FzU8xnZnBr/tJdFWk"
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "value: EncodingError")]
+    fn bad_salt_chars() {
+        let _ = super::hash_with(HashSetup::default().salt("a$b"), "password").unwrap();
+    }
+}
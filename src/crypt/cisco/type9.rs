@@ -0,0 +1,144 @@
+//! Cisco Type 9 (scrypt) hash.
+//!
+//! Type 9 secrets are derived with the memory-hard scrypt function, using
+//! fixed cost parameters. They're the strongest password type supported
+//! by IOS and IOS XE, and are recommended for new secrets.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::cisco::type9;
+//!
+//! let h = "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6";
+//! assert!(type9::verify("hashcat", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: unlimited, may not contain a `$`. Default is 14
+//!   random characters, as used by IOS.
+//!
+//! * __Cost__: N = 16384, r = 1, p = 1 (fixed).
+//!
+//! # Hash Format
+//!
+//! The format of the hash is
+//! __`$9$`__*`{salt}`*__$__*`{checksum}`*, where:
+//!
+//! * *`{salt}`* is the salt string.
+//!
+//! * *`{checksum}`* is a 43-character Base64 encoding of the derived key,
+//!   in the same encoding as [Type 8](super::type8).
+
+use scrypt::{Params, scrypt};
+
+use crate::{
+    HashSetup, IntoHashSetup, consteq,
+    encode::crypt_hash64_encode,
+    error::{Error, Result},
+    hash::{Hash, HashV},
+    parse::{self, HashIterator},
+    random,
+};
+
+const TYPE9_MAGIC: &str = "$9$";
+const LOG_N: u8 = 14;
+
+/// Default salt length.
+pub const DEFAULT_SALT_LEN: usize = 14;
+
+// magic + salt + `$` + checksum
+pub(crate) const HASH_LENGTH: usize = TYPE9_MAGIC.len() + DEFAULT_SALT_LEN + 1 + 43;
+
+fn do_type9_crypt(pass: &[u8], salt: &str) -> Result<String> {
+    if salt.contains('$') {
+        return Err(Error::EncodingError);
+    }
+    let params = Params::new(LOG_N, 1, 1, 32).map_err(|_e| Error::InvalidRounds)?;
+    let mut output = [0u8; 32];
+    scrypt(pass, salt.as_bytes(), &params, &mut output).map_err(|_e| Error::InsufficientLength)?;
+    Ok(format!(
+        "{TYPE9_MAGIC}{salt}${}",
+        crypt_hash64_encode(&output)
+    ))
+}
+
+/// Hash a password with a randomly generated salt.
+///
+/// An error is returned if the system random number generator cannot
+/// be opened.
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    let saltstr = random::gen_salt_str(DEFAULT_SALT_LEN);
+    let hash = do_type9_crypt(pass.as_ref(), &saltstr)?;
+    Ok(Hash::CiscoType9(HashV(hash)))
+}
+
+fn parse_type9_hash(hash: &str) -> Result<HashSetup<'_>> {
+    let mut hs = parse::HashSlice::new(hash);
+    if hs.take(TYPE9_MAGIC.len()) != Some(TYPE9_MAGIC) {
+        return Err(Error::InvalidHashString);
+    }
+    let salt = hs.take_until(b'$').ok_or(Error::InvalidHashString)?;
+
+    Ok(HashSetup {
+        salt: Some(salt),
+        rounds: None,
+        salt_bytes: None,
+    })
+}
+
+/// Hash a password with user-provided parameters.
+///
+/// If the `param` argument is a `&str`, it must be in the final hash
+/// format, and the salt is parsed out of that value. The cost parameters
+/// are fixed, so the `rounds` field of a `HashSetup` is ignored. An error is
+/// returned if the salt contains a `$`.
+pub fn hash_with<'a, IHS, B>(param: IHS, pass: B) -> Result<Hash>
+where
+    IHS: IntoHashSetup<'a>,
+    B: AsRef<[u8]>,
+{
+    let hs = IHS::into_hash_setup(param, parse_type9_hash)?;
+    let hash = match hs.salt_str()? {
+        Some(salt) => do_type9_crypt(pass.as_ref(), salt),
+        None => {
+            let saltstr = random::gen_salt_str(DEFAULT_SALT_LEN);
+            do_type9_crypt(pass.as_ref(), &saltstr)
+        }
+    }?;
+    Ok(Hash::CiscoType9(HashV(hash)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    consteq(hash, hash_with(hash, pass))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HashSetup;
+
+    #[test]
+    fn custom() {
+        assert_eq!(
+            super::hash_with(
+                "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+                "hashcat"
+            )
+            .unwrap(),
+            "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6"
+        );
+        assert_eq!(
+            super::hash_with(HashSetup::default().salt("2MJBozw/9R3UsU"), "hashcat").unwrap(),
+            "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6"
+        );
+        assert!(!super::verify(
+            "hashcas",
+            "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6"
+        ));
+    }
+}
@@ -0,0 +1,208 @@
+//! CRAM-MD5 secrets and APOP digests.
+//!
+//! In CRAM-MD5 authentication, the server sends a challenge, and the client
+//! replies with its user name and the HMAC-MD5 of the challenge keyed with
+//! the password. The server doesn't need the plaintext password to check the
+//! reply: it's enough to store the MD5 states after the inner and outer HMAC
+//! key blocks, called the CRAM-MD5 context. This module produces the
+//! contexts in the format used by Dovecot's __`{CRAM-MD5}`__ scheme, and
+//! verifies replies against them.
+//!
+//! The context is password-equivalent for CRAM-MD5, and a single round of
+//! MD5 is very weak; the scheme should be used only where the mechanism is
+//! required.
+//!
+//! The module also verifies POP3 APOP digests, which are the MD5 digest of
+//! the server's timestamp banner followed by the password, and therefore
+//! need the plaintext password.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::cram_md5;
+//!
+//! let h = "{CRAM-MD5}d06d4e1b26fccaa4b0b61801132340a3\
+//!          54b21152711fb604ca3e035e7015116b";
+//! assert!(cram_md5::verify_response(
+//!     h,
+//!     "<1896.697170952@postoffice.reston.mci.net>",
+//!     "b913a602c7eda7a495b4e6e7334d3890"
+//! ));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited. A password longer than 64 bytes is
+//!   replaced with its MD5 digest, as HMAC requires.
+//!
+//! * __Salt__: none.
+//!
+//! * __Rounds__: 1 (fixed).
+//!
+//! # Hash Format
+//!
+//! The format of the hash is __`{CRAM-MD5}`__*`{context}`*, where
+//! *`{context}`* is the 64-character lowercase hexadecimal encoding of the
+//! outer and the inner MD5 states, in that order. Each state consists of four
+//! 32-bit words written in little-endian byte order. The prefix is optional
+//! when verifying.
+
+use md5::{Digest, Md5};
+
+use crate::{
+    ct::ct_eq_ignore_ascii_case,
+    encode::{hex_decode, hex_encode},
+    internal::md5::{BLOCK_LEN, STATE_INIT, compress, finalize},
+};
+
+const CRAM_MD5_PREFIX: &str = "{CRAM-MD5}";
+const CONTEXT_LEN: usize = 32;
+
+fn do_cram_md5_context(pass: &[u8]) -> [u8; CONTEXT_LEN] {
+    let mut key = [0u8; BLOCK_LEN];
+    if pass.len() > BLOCK_LEN {
+        key[..16].copy_from_slice(&Md5::digest(pass));
+    } else {
+        key[..pass.len()].copy_from_slice(pass);
+    }
+
+    let mut context = [0u8; CONTEXT_LEN];
+    for (half, pad) in context.chunks_exact_mut(16).zip([0x5cu8, 0x36]) {
+        let block = key.map(|b| b ^ pad);
+        let mut state = STATE_INIT;
+        compress(&mut state, &block);
+        for (chunk, word) in half.chunks_exact_mut(4).zip(state) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+    }
+    key.fill(0u8);
+    context
+}
+
+fn parse_context(hash: &str) -> Option<([u32; 4], [u32; 4])> {
+    let context = hex_decode(hash.strip_prefix(CRAM_MD5_PREFIX).unwrap_or(hash)).ok()?;
+    if context.len() != CONTEXT_LEN {
+        return None;
+    }
+    let mut words = context
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()));
+    let mut state = || [(); 4].map(|_| words.next().unwrap());
+    let outer = state();
+    let inner = state();
+    Some((outer, inner))
+}
+
+/// Compute the CRAM-MD5 context of a password.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> String {
+    format!(
+        "{CRAM_MD5_PREFIX}{}",
+        hex_encode(&do_cram_md5_context(pass.as_ref()))
+    )
+}
+
+/// Verify that the context corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    let context = hex_encode(&do_cram_md5_context(pass.as_ref()));
+    ct_eq_ignore_ascii_case(hash.strip_prefix(CRAM_MD5_PREFIX).unwrap_or(hash), context)
+}
+
+/// Verify a client's reply to a CRAM-MD5 challenge.
+///
+/// The client sends its user name, a space, and the hexadecimal HMAC-MD5
+/// digest of the challenge; `response` is the digest part of that reply.
+/// It's compared case-insensitively. A malformed context never verifies.
+pub fn verify_response(hash: &str, challenge: &str, response: &str) -> bool {
+    let Some((outer, inner)) = parse_context(hash) else {
+        return false;
+    };
+    let inner_digest = finalize(inner, BLOCK_LEN, challenge.as_bytes());
+    let digest = finalize(outer, BLOCK_LEN, &inner_digest);
+    ct_eq_ignore_ascii_case(response, hex_encode(&digest))
+}
+
+/// Compute the APOP digest of a password for the given timestamp.
+///
+/// The timestamp is the angle-bracketed string from the server's greeting,
+/// including the brackets.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn apop<B: AsRef<[u8]>>(pass: B, timestamp: &str) -> String {
+    do_apop(pass.as_ref(), timestamp)
+}
+
+fn do_apop(pass: &[u8], timestamp: &str) -> String {
+    let mut dgst = Md5::new();
+    dgst.update(timestamp.as_bytes());
+    dgst.update(pass);
+    hex_encode(&dgst.finalize())
+}
+
+/// Verify that the APOP digest corresponds to a password and a timestamp.
+///
+/// The digest is compared case-insensitively.
+#[inline]
+pub fn verify_apop<B: AsRef<[u8]>>(pass: B, timestamp: &str, digest: &str) -> bool {
+    ct_eq_ignore_ascii_case(digest, do_apop(pass.as_ref(), timestamp))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[allow(deprecated)]
+    fn context() {
+        assert_eq!(
+            super::hash("password"),
+            "{CRAM-MD5}9186d855e11eba527a7a52ca82b313e180d62234f0acc9051b527243d41e2740"
+        );
+        assert_eq!(
+            super::hash("x".repeat(70)),
+            "{CRAM-MD5}8103bb3a2a4b96a9ca9cce4efecc8d22ebe7163dff7401afb5aa16d4edcf9f1f"
+        );
+        assert!(super::verify(
+            "tanstaaftanstaaf",
+            "D06D4E1B26FCCAA4B0B61801132340A354B21152711FB604CA3E035E7015116B"
+        ));
+        assert!(!super::verify(
+            "tanstaaftanstaaF",
+            "{CRAM-MD5}d06d4e1b26fccaa4b0b61801132340a354b21152711fb604ca3e035e7015116b"
+        ));
+    }
+
+    #[test]
+    fn response() {
+        let h = "{CRAM-MD5}d06d4e1b26fccaa4b0b61801132340a354b21152711fb604ca3e035e7015116b";
+        assert!(super::verify_response(
+            h,
+            "<1896.697170952@postoffice.reston.mci.net>",
+            "B913A602C7EDA7A495B4E6E7334D3890"
+        ));
+        assert!(!super::verify_response(
+            h,
+            "<1896.697170953@postoffice.reston.mci.net>",
+            "b913a602c7eda7a495b4e6e7334d3890"
+        ));
+        assert!(!super::verify_response(
+            "{CRAM-MD5}d06d4e1b",
+            "<1896.697170952@postoffice.reston.mci.net>",
+            "b913a602c7eda7a495b4e6e7334d3890"
+        ));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn apop() {
+        assert_eq!(
+            super::apop("tanstaaf", "<1896.697170952@dbc.mtview.ca.us>"),
+            "c4c9334bac560ecc979e58001b3e22fb"
+        );
+        assert!(!super::verify_apop(
+            "tanstaaf",
+            "<1896.697170953@dbc.mtview.ca.us>",
+            "c4c9334bac560ecc979e58001b3e22fb"
+        ));
+    }
+}
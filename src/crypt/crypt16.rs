@@ -0,0 +1,142 @@
+//! Ultrix/OSF-1 double DES-based hash.
+//!
+//! Crypt16 was used by Ultrix and some other historical systems to support
+//! passwords of up to sixteen characters. The first eight characters are
+//! hashed with 20 rounds of DES, the next eight with 5 rounds, and the two
+//! results are concatenated. It is extremely weak by today's standards and
+//! should be used for backward compatibility only.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::crypt16;
+//!
+//! let h = "/.FcK3mad6JwYt8LVmDqz9Lc";
+//! assert!(crypt16::verify("LOLOAQICI82QB4IP", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: effectively sixteen 7-bit characters; anything
+//!   longer is ignored.
+//!
+//! * __Salt length__: 2 characters (12 bits).
+//!
+//! * __Rounds__: 20 and 5 for the two halves (fixed).
+//!
+//! # Hash Format
+//!
+//! The format of the hash is *`{salt}`*_`{checksum}`_, where:
+//!
+//! * *`{salt}`* is a 2-character Base64 encoding of the salt.
+//!
+//! * *`{checksum}`* is a 22-character Base64 encoding of the checksums of
+//!   the two password halves.
+//!
+//! A crypt16 hash can't be told apart from a two-segment [bigcrypt](super::bigcrypt)
+//! hash, which is what [`Hash::try_from`](crate::Hash) assumes for strings of
+//! this length. For this reason, crypt16 has no [`Hash`](crate::Hash) variant
+//! or [`Algorithm`](crate::Algorithm), and the functions in this module return
+//! plain strings. A crypt16 hash must be verified with [`verify`]; passing it
+//! to [`Hash::verify`](crate::Hash::verify) or [`unix::verify`](crate::unix::verify)
+//! checks it as a bigcrypt hash, which fails.
+
+use crate::{
+    ct::ct_eq,
+    encode::decode_legacy_val,
+    error::Result,
+    internal::des::{crypt16, crypt16_val},
+    random,
+};
+
+/// Salt length.
+pub const SALT_LEN: usize = 2;
+
+/// Hash a password with a randomly generated salt.
+///
+/// An error is returned if the system random number generator cannot
+/// be opened.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<String> {
+    let saltstr = random::gen_salt_str(SALT_LEN);
+    crypt16(pass.as_ref(), &saltstr)
+}
+
+/// Hash a password with a user-provided salt.
+///
+/// Only the first two characters of `salt` are used, so a complete hash
+/// can be passed in its place. An error is returned if the salt is too
+/// short or contains an invalid character.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash_with<B: AsRef<[u8]>>(salt: &str, pass: B) -> Result<String> {
+    crypt16(pass.as_ref(), salt)
+}
+
+/// Hash a password with a user-provided salt, given as raw bytes.
+///
+/// Only the first two bytes of `salt` are used. They may have any value;
+/// bytes outside the alphabet are mapped to zero, like in the BSD
+/// implementations, which makes it possible to check historical hashes with
+/// such salts. The salt is written into the hash in the standard encoding.
+/// An error is returned if the salt is too short.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash_with_bytes<B: AsRef<[u8]>>(salt: &[u8], pass: B) -> Result<String> {
+    let salt_val = decode_legacy_val(salt, SALT_LEN)?;
+    Ok(crypt16_val(pass.as_ref(), salt_val))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    crypt16(pass.as_ref(), hash).is_ok_and(|calc| ct_eq(hash, &calc))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[allow(deprecated)]
+    fn custom() {
+        assert_eq!(
+            super::hash_with("/.", "LOLOAQICI82QB4IP").unwrap(),
+            "/.FcK3mad6JwYt8LVmDqz9Lc"
+        );
+        assert_eq!(
+            super::hash_with("/.", "LOLOAQICI").unwrap(),
+            "/.FcK3mad6JwYSaRHJoTPzY2"
+        );
+        assert_eq!(
+            super::hash_with("/.", "LOLOAQIC").unwrap(),
+            "/.FcK3mad6JwYelhbtlysKy6"
+        );
+        assert_eq!(
+            super::hash_with("/.", "L").unwrap(),
+            "/.CIu/PzYCkl6elhbtlysKy6"
+        );
+        assert!(super::verify("passphrase", "qi8H8R7OM4xMUNMPuRAZxlY."));
+        assert!(!super::verify("passphrasf", "qi8H8R7OM4xMUNMPuRAZxlY."));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    #[cfg(feature = "bigcrypt")]
+    fn round_trip() {
+        use crate::Hash;
+
+        let h = super::hash_with("/.", "LOLOAQICI82QB4IP").unwrap();
+        let parsed = Hash::try_from(h.as_str()).unwrap();
+        assert!(matches!(parsed, Hash::Bigcrypt(_)));
+        assert_eq!(String::from(parsed), h);
+        assert!(super::verify("LOLOAQICI82QB4IP", &h));
+        assert!(!crate::unix::verify("LOLOAQICI82QB4IP", &h));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    #[should_panic(expected = "value: EncodingError")]
+    fn bad_salt_chars() {
+        let _ = super::hash_with("!!", "test").unwrap();
+    }
+}
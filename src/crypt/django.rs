@@ -0,0 +1,406 @@
+//! Django PBKDF2 based hashes.
+//!
+//! The Django web framework stores user passwords as PBKDF2 derived keys
+//! in a `$`-separated format which doesn't follow the Unix modular crypt
+//! conventions. PBKDF2 with HMAC-SHA256 is the framework default and is
+//! considered secure with a sufficient number of iterations; the HMAC-SHA1
+//! variant is still accepted by Django for backward compatibility.
+//!
+//! Very old Django releases stored a single salted SHA-1 or MD5 digest of
+//! the password. Those hashes can still be verified, but they are trivially
+//! brute-forced and shouldn't be produced for new passwords.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::django;
+//!
+//! let h = "pbkdf2_sha256$36000$7Vv7jlObSUcH$CUzgrdCVDegjpJ8Sx3fAFNDxi8T4HZCCDoKpm4ic/48=";
+//! assert!(django::verify("password", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: unlimited, may not contain a `$`. Default is 22
+//!   random characters.
+//!
+//! * __Rounds__: 1 to 2<sup>32</sup>-1. Default is 1000000, which is
+//!   the value used by recent Django releases. The legacy digests have
+//!   a single round (fixed).
+//!
+//! # Hash Format
+//!
+//! The format of the hash is
+//! *`{algorithm}`*__$__*`{rounds}`*__$__*`{salt}`*__$__*`{checksum}`*, where:
+//!
+//! * *`{algorithm}`* is one of **pbkdf2_sha256** or **pbkdf2_sha1**.
+//!
+//! * *`{rounds}`* is the number of PBKDF2 iterations, encoded as a decimal
+//!   number.
+//!
+//! * *`{salt}`* is the salt string.
+//!
+//! * *`{checksum}`* is the standard (padded) Base64 encoding of the
+//!   derived key, which is as long as the output of the underlying hash.
+//!
+//! The legacy digests have the format
+//! *`{algorithm}`*__$__*`{salt}`*__$__*`{checksum}`*, where *`{algorithm}`*
+//! is one of **sha1** or **md5**, the salt may be empty, and *`{checksum}`*
+//! is the lowercase hexadecimal encoding of the digest of the salt followed
+//! by the password.
+
+use std::fmt;
+
+use md5::Md5;
+use pbkdf2::pbkdf2_hmac;
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+
+use crate::{
+    AlgorithmParams, HashSetup, consteq,
+    encode::{base64_encode, hex_encode},
+    error::{Error, Result},
+    hash::{Hash, HashV},
+    parse::{self, HashIterator},
+    random,
+};
+
+const MIN_ROUNDS: u32 = 1;
+const DEFAULT_ALGORITHM: DjangoAlgorithm = DjangoAlgorithm::Pbkdf2Sha256;
+
+/// Default number of rounds.
+pub const DEFAULT_ROUNDS: u32 = 1_000_000;
+
+/// Default salt length.
+pub const DEFAULT_SALT_LEN: usize = 22;
+
+/// Identifiers of the supported Django password hashers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DjangoAlgorithm {
+    /// PBKDF2 with HMAC-SHA256, the Django default.
+    Pbkdf2Sha256,
+    /// PBKDF2 with HMAC-SHA1.
+    Pbkdf2Sha1,
+    /// Legacy salted SHA-1 digest.
+    Sha1,
+    /// Legacy salted MD5 digest.
+    Md5,
+}
+
+impl DjangoAlgorithm {
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix {
+            "pbkdf2_sha256" => Some(DjangoAlgorithm::Pbkdf2Sha256),
+            "pbkdf2_sha1" => Some(DjangoAlgorithm::Pbkdf2Sha1),
+            "sha1" => Some(DjangoAlgorithm::Sha1),
+            "md5" => Some(DjangoAlgorithm::Md5),
+            _ => None,
+        }
+    }
+
+    fn is_legacy(&self) -> bool {
+        matches!(self, DjangoAlgorithm::Sha1 | DjangoAlgorithm::Md5)
+    }
+}
+
+impl fmt::Display for DjangoAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let algorithm = match self {
+            DjangoAlgorithm::Pbkdf2Sha256 => "pbkdf2_sha256",
+            DjangoAlgorithm::Pbkdf2Sha1 => "pbkdf2_sha1",
+            DjangoAlgorithm::Sha1 => "sha1",
+            DjangoAlgorithm::Md5 => "md5",
+        };
+        write!(f, "{algorithm}")
+    }
+}
+
+/// Setup struct for Django hashes.
+///
+/// In addition to custom salt and rounds values, a Django hash can use
+/// different hashing algorithms.
+#[derive(Default)]
+pub struct DjangoSetup<'a> {
+    /// Custom salt.
+    pub salt: Option<&'a str>,
+    /// Number of rounds.
+    pub rounds: Option<u32>,
+    /// Hashing algorithm.
+    pub algorithm: Option<DjangoAlgorithm>,
+}
+
+impl<'a> DjangoSetup<'a> {
+    /// Configure custom salt for Django hash
+    pub fn salt(mut self, salt: &'a str) -> Self {
+        self.salt = Some(salt);
+        self
+    }
+    /// Configure custom number of rounds for Django hash
+    pub fn rounds(mut self, rounds: u32) -> Self {
+        self.rounds = Some(rounds);
+        self
+    }
+    /// Configure hashing algorithm for Django hash
+    pub fn algorithm(mut self, algorithm: DjangoAlgorithm) -> Self {
+        self.algorithm = Some(algorithm);
+        self
+    }
+}
+
+/// A trait for converting a type into a `DjangoSetup` struct.
+pub trait IntoDjangoSetup<'a> {
+    /// The conversion function.
+    fn into_django_setup(self) -> Result<DjangoSetup<'a>>;
+}
+
+impl<'a> IntoDjangoSetup<'a> for &'a str {
+    fn into_django_setup(self) -> Result<DjangoSetup<'a>> {
+        let mut hs = parse::HashSlice::new(self);
+        let algorithm = hs
+            .take_until(b'$')
+            .and_then(DjangoAlgorithm::from_prefix)
+            .ok_or(Error::InvalidHashString)?;
+        let rounds = match algorithm.is_legacy() {
+            true => None,
+            false => Some(
+                hs.take_until(b'$')
+                    .ok_or(Error::InvalidHashString)?
+                    .parse::<u32>()
+                    .map_err(Error::InvalidNumber)?,
+            ),
+        };
+        let salt = hs.take_until(b'$').ok_or(Error::InvalidHashString)?;
+        Ok(DjangoSetup {
+            salt: Some(salt),
+            rounds,
+            algorithm: Some(algorithm),
+        })
+    }
+}
+
+impl<'a> IntoDjangoSetup<'a> for HashSetup<'a> {
+    fn into_django_setup(self) -> Result<DjangoSetup<'a>> {
+        Ok(DjangoSetup {
+            salt: self.salt_str()?,
+            rounds: self.rounds,
+            algorithm: Some(DEFAULT_ALGORITHM),
+        })
+    }
+}
+
+impl<'a> IntoDjangoSetup<'a> for DjangoSetup<'a> {
+    fn into_django_setup(self) -> Result<DjangoSetup<'a>> {
+        Ok(self)
+    }
+}
+
+impl<'a> IntoDjangoSetup<'a> for AlgorithmParams<'a> {
+    fn into_django_setup(self) -> Result<DjangoSetup<'a>> {
+        #[allow(unreachable_patterns)]
+        match self {
+            AlgorithmParams::Basic(hs) => hs.into_django_setup(),
+            AlgorithmParams::Django(setup) => Ok(setup),
+            _ => Err(Error::InvalidHashString),
+        }
+    }
+}
+
+// algorithm + `$` + [rounds + `$`] + salt + `$` + checksum
+pub(crate) fn is_django_hash(hash: &str) -> bool {
+    let mut hs = parse::HashSlice::new(hash);
+    let fields = hash.bytes().filter(|&b| b == b'$').count();
+    match hs.take_until(b'$').and_then(DjangoAlgorithm::from_prefix) {
+        Some(algorithm) if algorithm.is_legacy() => fields == 2,
+        Some(_) => fields == 3,
+        None => false,
+    }
+}
+
+// Django's `unsalted_md5` and `unsalted_sha1` hashers produce legacy hashes
+// with an empty salt.
+pub(crate) fn is_legacy_hash(hash: &str) -> bool {
+    hash.split_once('$')
+        .and_then(|(prefix, _)| DjangoAlgorithm::from_prefix(prefix))
+        .is_some_and(|algorithm| algorithm.is_legacy())
+}
+
+fn legacy_digest<D: Digest>(pass: &[u8], salt: &str) -> String {
+    let mut dgst = D::new();
+    dgst.update(salt.as_bytes());
+    dgst.update(pass);
+    hex_encode(&dgst.finalize())
+}
+
+fn do_django_crypt(
+    pass: &[u8],
+    salt: &str,
+    rounds: u32,
+    algorithm: DjangoAlgorithm,
+) -> Result<String> {
+    if salt.contains('$') {
+        return Err(Error::EncodingError);
+    }
+    let hash = match algorithm {
+        DjangoAlgorithm::Pbkdf2Sha256 => {
+            let mut output = [0u8; 32];
+            pbkdf2_hmac::<Sha256>(pass, salt.as_bytes(), rounds, &mut output);
+            format!("{algorithm}${rounds}${salt}${}", base64_encode(&output))
+        }
+        DjangoAlgorithm::Pbkdf2Sha1 => {
+            let mut output = [0u8; 20];
+            pbkdf2_hmac::<Sha1>(pass, salt.as_bytes(), rounds, &mut output);
+            format!("{algorithm}${rounds}${salt}${}", base64_encode(&output))
+        }
+        DjangoAlgorithm::Sha1 => {
+            format!("{algorithm}${salt}${}", legacy_digest::<Sha1>(pass, salt))
+        }
+        DjangoAlgorithm::Md5 => format!("{algorithm}${salt}${}", legacy_digest::<Md5>(pass, salt)),
+    };
+    Ok(hash)
+}
+
+/// Hash a password with a randomly generated salt, the default number
+/// of rounds, and the default algorithm (__pbkdf2_sha256__).
+///
+/// An error is returned if the system random number generator cannot
+/// be opened.
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    let saltstr = random::gen_salt_str(DEFAULT_SALT_LEN);
+    let hash = do_django_crypt(pass.as_ref(), &saltstr, DEFAULT_ROUNDS, DEFAULT_ALGORITHM)?;
+    Ok(Hash::Django(HashV(hash)))
+}
+
+/// Hash a password with user-provided parameters.
+///
+/// If the `param` argument is a `&str`, it must be in the final hash
+/// format. The algorithm, number of rounds, and the salt are parsed out
+/// of that value. An ordinary `HashSetup` can be converted into `DjangoSetup`,
+/// which will set the algorithm to default. An error is returned if the salt
+/// contains a `$`, or if the rounds value is out of range. The rounds value
+/// is ignored by the legacy digests.
+pub fn hash_with<'a, IDS, B>(param: IDS, pass: B) -> Result<Hash>
+where
+    IDS: IntoDjangoSetup<'a>,
+    B: AsRef<[u8]>,
+{
+    let ds = param.into_django_setup()?;
+    let rounds = if let Some(r) = ds.rounds {
+        if r < MIN_ROUNDS {
+            return Err(Error::BadRounds {
+                value: r,
+                allowed: MIN_ROUNDS..=u32::MAX,
+            });
+        }
+        r
+    } else {
+        DEFAULT_ROUNDS
+    };
+
+    let algorithm = ds.algorithm.unwrap_or(DEFAULT_ALGORITHM);
+    let hash = match ds.salt {
+        Some(salt) => do_django_crypt(pass.as_ref(), salt, rounds, algorithm),
+        None => {
+            let saltstr = random::gen_salt_str(DEFAULT_SALT_LEN);
+            do_django_crypt(pass.as_ref(), &saltstr, rounds, algorithm)
+        }
+    }?;
+    Ok(Hash::Django(HashV(hash)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    consteq(hash, hash_with(hash, pass))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DjangoAlgorithm, DjangoSetup, Hash, HashSetup};
+
+    #[test]
+    fn custom() {
+        assert_eq!(
+            super::hash_with(
+                "pbkdf2_sha256$36000$7Vv7jlObSUcH$CUzgrdCVDegjpJ8Sx3fAFNDxi8T4HZCCDoKpm4ic/48=",
+                "password"
+            )
+            .unwrap(),
+            "pbkdf2_sha256$36000$7Vv7jlObSUcH$CUzgrdCVDegjpJ8Sx3fAFNDxi8T4HZCCDoKpm4ic/48="
+        );
+        assert_eq!(
+            super::hash_with(
+                HashSetup::default().salt("7Vv7jlObSUcH").rounds(36000),
+                "password"
+            )
+            .unwrap(),
+            "pbkdf2_sha256$36000$7Vv7jlObSUcH$CUzgrdCVDegjpJ8Sx3fAFNDxi8T4HZCCDoKpm4ic/48="
+        );
+        assert_eq!(
+            super::hash_with(
+                DjangoSetup::default()
+                    .salt("KRDsbiA6Sfbc")
+                    .rounds(12000)
+                    .algorithm(DjangoAlgorithm::Pbkdf2Sha1),
+                "password"
+            )
+            .unwrap(),
+            "pbkdf2_sha1$12000$KRDsbiA6Sfbc$bRBS5lkoeBTfwa0fKfqtvf4K+nY="
+        );
+        assert!(!super::verify(
+            "passwore",
+            "pbkdf2_sha1$12000$KRDsbiA6Sfbc$bRBS5lkoeBTfwa0fKfqtvf4K+nY="
+        ));
+    }
+
+    #[test]
+    fn legacy() {
+        assert!(super::verify(
+            "password",
+            "sha1$4sMZU$f9afd3dcc4b7ba80213cbac6bc32c2f4a32b1e87"
+        ));
+        assert!(super::verify(
+            "password",
+            "md5$4sMZU$bb946887404b940f51a8c495834e099a"
+        ));
+        assert!(super::verify(
+            "password",
+            "sha1$$5baa61e4c9b93f3f0682250b6cf8331b7ee68fd8"
+        ));
+        assert_eq!(
+            super::hash_with(
+                DjangoSetup::default()
+                    .salt("4sMZU")
+                    .algorithm(DjangoAlgorithm::Md5),
+                "password"
+            )
+            .unwrap(),
+            "md5$4sMZU$bb946887404b940f51a8c495834e099a"
+        );
+    }
+
+    #[test]
+    fn deprecated() {
+        for h in [
+            "sha1$4sMZU$f9afd3dcc4b7ba80213cbac6bc32c2f4a32b1e87",
+            "md5$4sMZU$bb946887404b940f51a8c495834e099a",
+            "sha1$$5baa61e4c9b93f3f0682250b6cf8331b7ee68fd8",
+            "md5$$5f4dcc3b5aa765d61d8327deb882cf99",
+        ] {
+            assert!(Hash::try_from(h).unwrap().is_deprecated());
+        }
+        let h = Hash::try_from(
+            "pbkdf2_sha256$36000$7Vv7jlObSUcH$CUzgrdCVDegjpJ8Sx3fAFNDxi8T4HZCCDoKpm4ic/48=",
+        )
+        .unwrap();
+        assert!(!h.is_deprecated());
+    }
+
+    #[test]
+    #[should_panic(expected = "value: EncodingError")]
+    fn bad_salt_chars() {
+        let _ = super::hash_with(HashSetup::default().salt("a$b").rounds(1), "password").unwrap();
+    }
+}
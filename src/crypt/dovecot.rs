@@ -0,0 +1,448 @@
+//! Dovecot scheme-prefixed password hashes.
+//!
+//! Dovecot password databases store each hash prefixed with the name of its
+//! scheme in curly braces, e.g. `{SHA512-CRYPT}$6$...` or `{PLAIN-MD5}...`.
+//! The crypt-based schemes wrap a hash in one of the formats recognized by
+//! [`crate::unix::crypt`], and the digest-based schemes contain
+//! an encoded digest of the password, optionally followed by a salt. This
+//! module strips the prefix, and hands the rest of the value over to the
+//! appropriate algorithm, so that mail server credential files can be
+//! verified directly.
+//!
+//! The scheme name may carry a __`.b64`__, __`.base64`__, or __`.hex`__
+//! suffix, which overrides the default encoding of the digest. Without a
+//! suffix, a value consisting of exactly as many hexadecimal characters as a
+//! hex encoded digest is accepted as such, like Dovecot does.
+//!
+//! Only the crypt-based schemes using bcrypt and SHA-512 or SHA-256 crypt
+//! are suitable for new passwords; [`DovecotScheme::is_weak`] reports the
+//! other ones as weak.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::dovecot;
+//!
+//! let h = "{BLF-CRYPT}$2y$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe";
+//! assert!(dovecot::verify("password", h));
+//! assert!(dovecot::verify("password", "{PLAIN-MD5}5f4dcc3b5aa765d61d8327deb882cf99"));
+//! ```
+//!
+//! # Hash Format
+//!
+//! The format of the hash is
+//! __`{`__*`{scheme}`*\[__`.`__*`{encoding}`*\]__`}`__*`{value}`*, where:
+//!
+//! * *`{scheme}`* is the case-insensitive scheme name, one of
+//!   **SHA512-CRYPT**, **SHA256-CRYPT**, **BLF-CRYPT**, **MD5-CRYPT**,
+//!   **CRYPT** or **DES-CRYPT**, **PLAIN** or **CLEAR** or **CLEARTEXT**,
+//!   **CRAM-MD5**, **PLAIN-MD5**, **LDAP-MD5**, **SHA** or **SHA1**, **SHA256**,
+//!   **SHA512**, **SSHA**, **SSHA256**, **SSHA512**, or **SMD5**.
+//!
+//! * *`{encoding}`* is the optional encoding override, shown in brackets.
+//!
+//! * *`{value}`* is the crypt hash for the crypt-based schemes, the password
+//!   itself for the plaintext schemes, the [CRAM-MD5](super::cram_md5)
+//!   context for __CRAM-MD5__, and the encoded digest of the password
+//!   followed by the salt, concatenated with the salt, for the digest-based
+//!   schemes. The digest is hex encoded for __PLAIN-MD5__, and Base64 encoded
+//!   for the other schemes.
+
+use std::fmt;
+
+use md5::Md5;
+use sha1::{Digest, Sha1};
+use sha2::{Sha256, Sha512};
+
+use crate::{
+    ct::ct_eq,
+    encode::{base64_decode, base64_encode, hex_decode, hex_encode},
+    error::{Error, Result},
+    random, unix,
+};
+
+/// Default salt length of the salted digest schemes, in bytes.
+pub const DEFAULT_SALT_LEN: usize = 8;
+
+/// Identifiers of the supported Dovecot password schemes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DovecotScheme {
+    /// SHA-512 crypt.
+    Sha512Crypt,
+    /// SHA-256 crypt.
+    Sha256Crypt,
+    /// bcrypt.
+    BlfCrypt,
+    /// MD5 crypt.
+    Md5Crypt,
+    /// DES crypt, or any hash recognized by __crypt__(3).
+    Crypt,
+    /// Plaintext password.
+    Plain,
+    /// CRAM-MD5 context.
+    CramMd5,
+    /// Unsalted MD5, hex encoded.
+    PlainMd5,
+    /// Unsalted MD5, Base64 encoded.
+    LdapMd5,
+    /// Unsalted SHA-1.
+    Sha1,
+    /// Unsalted SHA-256.
+    Sha256,
+    /// Unsalted SHA-512.
+    Sha512,
+    /// Salted SHA-1.
+    Ssha,
+    /// Salted SHA-256.
+    Ssha256,
+    /// Salted SHA-512.
+    Ssha512,
+    /// Salted MD5.
+    Smd5,
+}
+
+/// Encodings of the digest-based schemes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DovecotEncoding {
+    /// Standard Base64 encoding, with padding.
+    Base64,
+    /// Hexadecimal encoding.
+    Hex,
+}
+
+impl DovecotScheme {
+    fn from_name(name: &str) -> Option<Self> {
+        let scheme = match name.to_ascii_uppercase().as_str() {
+            "SHA512-CRYPT" => DovecotScheme::Sha512Crypt,
+            "SHA256-CRYPT" => DovecotScheme::Sha256Crypt,
+            "BLF-CRYPT" => DovecotScheme::BlfCrypt,
+            "MD5-CRYPT" => DovecotScheme::Md5Crypt,
+            "CRYPT" | "DES-CRYPT" => DovecotScheme::Crypt,
+            "PLAIN" | "CLEAR" | "CLEARTEXT" => DovecotScheme::Plain,
+            "CRAM-MD5" => DovecotScheme::CramMd5,
+            "PLAIN-MD5" => DovecotScheme::PlainMd5,
+            "LDAP-MD5" => DovecotScheme::LdapMd5,
+            "SHA" | "SHA1" => DovecotScheme::Sha1,
+            "SHA256" => DovecotScheme::Sha256,
+            "SHA512" => DovecotScheme::Sha512,
+            "SSHA" => DovecotScheme::Ssha,
+            "SSHA256" => DovecotScheme::Ssha256,
+            "SSHA512" => DovecotScheme::Ssha512,
+            "SMD5" => DovecotScheme::Smd5,
+            _ => return None,
+        };
+        Some(scheme)
+    }
+
+    fn is_crypt(&self) -> bool {
+        matches!(
+            self,
+            DovecotScheme::Sha512Crypt
+                | DovecotScheme::Sha256Crypt
+                | DovecotScheme::BlfCrypt
+                | DovecotScheme::Md5Crypt
+                | DovecotScheme::Crypt
+        )
+    }
+
+    fn is_salted(&self) -> bool {
+        matches!(
+            self,
+            DovecotScheme::Ssha
+                | DovecotScheme::Ssha256
+                | DovecotScheme::Ssha512
+                | DovecotScheme::Smd5
+        )
+    }
+
+    fn digest_len(&self) -> usize {
+        match self {
+            DovecotScheme::PlainMd5 | DovecotScheme::LdapMd5 | DovecotScheme::Smd5 => 16,
+            DovecotScheme::Sha1 | DovecotScheme::Ssha => 20,
+            DovecotScheme::Sha256 | DovecotScheme::Ssha256 => 32,
+            DovecotScheme::Sha512 | DovecotScheme::Ssha512 => 64,
+            _ => 0,
+        }
+    }
+
+    fn default_encoding(&self) -> DovecotEncoding {
+        match self {
+            DovecotScheme::PlainMd5 => DovecotEncoding::Hex,
+            _ => DovecotEncoding::Base64,
+        }
+    }
+
+    /// Whether the scheme is considered too weak for new passwords.
+    ///
+    /// This is true for all schemes except __SHA512-CRYPT__,
+    /// __SHA256-CRYPT__, and __BLF-CRYPT__.
+    pub fn is_weak(&self) -> bool {
+        !matches!(
+            self,
+            DovecotScheme::Sha512Crypt | DovecotScheme::Sha256Crypt | DovecotScheme::BlfCrypt
+        )
+    }
+}
+
+impl fmt::Display for DovecotScheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scheme = match self {
+            DovecotScheme::Sha512Crypt => "SHA512-CRYPT",
+            DovecotScheme::Sha256Crypt => "SHA256-CRYPT",
+            DovecotScheme::BlfCrypt => "BLF-CRYPT",
+            DovecotScheme::Md5Crypt => "MD5-CRYPT",
+            DovecotScheme::Crypt => "CRYPT",
+            DovecotScheme::Plain => "PLAIN",
+            DovecotScheme::CramMd5 => "CRAM-MD5",
+            DovecotScheme::PlainMd5 => "PLAIN-MD5",
+            DovecotScheme::LdapMd5 => "LDAP-MD5",
+            DovecotScheme::Sha1 => "SHA",
+            DovecotScheme::Sha256 => "SHA256",
+            DovecotScheme::Sha512 => "SHA512",
+            DovecotScheme::Ssha => "SSHA",
+            DovecotScheme::Ssha256 => "SSHA256",
+            DovecotScheme::Ssha512 => "SSHA512",
+            DovecotScheme::Smd5 => "SMD5",
+        };
+        write!(f, "{{{scheme}}}")
+    }
+}
+
+/// Split a prefixed hash into its scheme, encoding override, and value.
+///
+/// An error is returned if the hash doesn't start with a recognized scheme
+/// prefix, or if the encoding suffix isn't recognized.
+pub fn parse(hash: &str) -> Result<(DovecotScheme, Option<DovecotEncoding>, &str)> {
+    let (name, value) = hash
+        .strip_prefix('{')
+        .and_then(|rest| rest.split_once('}'))
+        .ok_or(Error::InvalidHashString)?;
+    let (name, encoding) = match name.rsplit_once('.') {
+        Some((name, suffix)) => {
+            let encoding = match suffix.to_ascii_lowercase().as_str() {
+                "b64" | "base64" => DovecotEncoding::Base64,
+                "hex" => DovecotEncoding::Hex,
+                _ => return Err(Error::InvalidHashString),
+            };
+            (name, Some(encoding))
+        }
+        None => (name, None),
+    };
+    let scheme = DovecotScheme::from_name(name).ok_or(Error::InvalidHashString)?;
+    Ok((scheme, encoding, value))
+}
+
+fn decode(value: &str, encoding: DovecotEncoding) -> Result<Vec<u8>> {
+    match encoding {
+        DovecotEncoding::Base64 => base64_decode(value),
+        DovecotEncoding::Hex => hex_decode(value),
+    }
+}
+
+fn salted_digest<D: Digest>(pass: &[u8], salt: &[u8]) -> Vec<u8> {
+    let mut dgst = D::new();
+    dgst.update(pass);
+    dgst.update(salt);
+    let mut output = dgst.finalize().to_vec();
+    output.extend_from_slice(salt);
+    output
+}
+
+fn do_dovecot_digest(pass: &[u8], salt: &[u8], scheme: DovecotScheme) -> Vec<u8> {
+    match scheme {
+        DovecotScheme::PlainMd5 | DovecotScheme::LdapMd5 => salted_digest::<Md5>(pass, &[]),
+        DovecotScheme::Sha1 => salted_digest::<Sha1>(pass, &[]),
+        DovecotScheme::Sha256 => salted_digest::<Sha256>(pass, &[]),
+        DovecotScheme::Sha512 => salted_digest::<Sha512>(pass, &[]),
+        DovecotScheme::Ssha => salted_digest::<Sha1>(pass, salt),
+        DovecotScheme::Ssha256 => salted_digest::<Sha256>(pass, salt),
+        DovecotScheme::Ssha512 => salted_digest::<Sha512>(pass, salt),
+        DovecotScheme::Smd5 => salted_digest::<Md5>(pass, salt),
+        _ => pass.to_vec(),
+    }
+}
+
+/// Hash a password with the given scheme and default parameters.
+///
+/// The crypt-based schemes use the default parameters of the corresponding
+/// algorithm, and the salted digest schemes a randomly generated salt. The
+/// digest is written in the default encoding of the scheme. An error is
+/// returned if the system random number generator cannot be opened, or if a
+/// __PLAIN__ password isn't valid UTF-8. Check [`DovecotScheme::is_weak`]
+/// before using a scheme for new passwords.
+pub fn hash<B: AsRef<[u8]>>(pass: B, scheme: DovecotScheme) -> Result<String> {
+    let pass = pass.as_ref();
+    #[allow(deprecated)]
+    if scheme == DovecotScheme::CramMd5 {
+        return Ok(super::cram_md5::hash(pass));
+    }
+    #[allow(deprecated)]
+    let value = match scheme {
+        DovecotScheme::Sha512Crypt => super::sha512::hash(pass)?.into(),
+        DovecotScheme::Sha256Crypt => super::sha256::hash(pass)?.into(),
+        DovecotScheme::BlfCrypt => super::bcrypt::hash(pass)?.into(),
+        DovecotScheme::Md5Crypt => super::md5::hash(pass)?.into(),
+        DovecotScheme::Crypt => super::unix::hash(pass)?.into(),
+        DovecotScheme::Plain => std::str::from_utf8(pass)
+            .map_err(Error::InvalidUtf8)?
+            .to_owned(),
+        _ => {
+            let mut salt_buf = vec![0u8; DEFAULT_SALT_LEN];
+            if scheme.is_salted() {
+                random::gen_salt_bytes(&mut salt_buf);
+            }
+            let checksum = do_dovecot_digest(pass, &salt_buf, scheme);
+            match scheme.default_encoding() {
+                DovecotEncoding::Base64 => base64_encode(&checksum),
+                DovecotEncoding::Hex => hex_encode(&checksum),
+            }
+        }
+    };
+    Ok(format!("{scheme}{value}"))
+}
+
+fn do_dovecot_verify(pass: &[u8], hash: &str) -> Result<bool> {
+    let (scheme, encoding, value) = parse(hash)?;
+    if scheme.is_crypt() {
+        return Ok(unix::verify(pass, value));
+    }
+    if scheme == DovecotScheme::CramMd5 {
+        return Ok(super::cram_md5::verify(pass, value));
+    }
+    if scheme == DovecotScheme::Plain {
+        let plain = match encoding {
+            Some(encoding) => decode(value, encoding)?,
+            None => value.as_bytes().to_vec(),
+        };
+        return Ok(ct_eq(plain, pass));
+    }
+
+    let digest_len = scheme.digest_len();
+    let encoding = encoding.unwrap_or_else(|| {
+        if value.len() == 2 * digest_len && value.bytes().all(|b| b.is_ascii_hexdigit()) {
+            DovecotEncoding::Hex
+        } else {
+            scheme.default_encoding()
+        }
+    });
+    let decoded = decode(value, encoding)?;
+    if decoded.len() < digest_len || (!scheme.is_salted() && decoded.len() != digest_len) {
+        return Err(Error::InvalidHashString);
+    }
+    Ok(ct_eq(
+        &decoded,
+        do_dovecot_digest(pass, &decoded[digest_len..], scheme),
+    ))
+}
+
+/// Verify that the hash corresponds to a password.
+///
+/// A hash with an unrecognized scheme never verifies.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    do_dovecot_verify(pass.as_ref(), hash).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DovecotEncoding, DovecotScheme};
+
+    #[test]
+    fn crypt() {
+        assert!(super::verify(
+            "password",
+            "{BLF-CRYPT}$2y$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe"
+        ));
+        assert!(super::verify(
+            "test",
+            "{sha512-crypt}$6$G/gkPn17kHYo0gTF$xhDFU0QYExdMH2ghOWKrrVtu1BuTpNMSJ\
+             URCXk43.EYekmK8iwV6RNqftUUC8mqDel1J7m3JEbUkbu4YyqSyv/"
+        ));
+        assert!(super::verify(
+            "password",
+            "{MD5-CRYPT}$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0"
+        ));
+        assert!(!super::verify(
+            "passwore",
+            "{MD5-CRYPT}$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0"
+        ));
+    }
+
+    #[test]
+    fn digest() {
+        assert!(super::verify(
+            "password",
+            "{PLAIN-MD5}5f4dcc3b5aa765d61d8327deb882cf99"
+        ));
+        assert!(super::verify(
+            "password",
+            "{PLAIN-MD5.b64}X03MO1qnZdYdgyfeuILPmQ=="
+        ));
+        assert!(super::verify(
+            "password",
+            "{LDAP-MD5}X03MO1qnZdYdgyfeuILPmQ=="
+        ));
+        assert!(super::verify(
+            "password",
+            "{LDAP-MD5}5F4DCC3B5AA765D61D8327DEB882CF99"
+        ));
+        assert!(super::verify(
+            "password",
+            "{SHA}W6ph5Mm5Pz8GgiULbPgzG37mj9g="
+        ));
+        assert!(super::verify(
+            "password",
+            "{SSHA256}DIzeh0gCRMTRu9dAH3C3rr7fWkRT0Bp2ZdtRqvTX3XJzYWx0c2FsdA=="
+        ));
+        assert!(super::verify(
+            "password",
+            "{SSHA256.hex}0c8cde87480244c4d1bbd7401f70b7aebedf5a4453d01a7665db51aaf4d7dd72\
+             73616c7473616c74"
+        ));
+        assert!(!super::verify(
+            "passwore",
+            "{SHA}W6ph5Mm5Pz8GgiULbPgzG37mj9g="
+        ));
+    }
+
+    #[test]
+    fn plain() {
+        assert!(super::verify("password", "{PLAIN}password"));
+        assert!(super::verify("password", "{PLAIN.b64}cGFzc3dvcmQ="));
+        assert!(!super::verify("password", "{CLEARTEXT}Password"));
+        assert!(!super::verify("password", "{UNKNOWN}password"));
+        assert!(super::verify(
+            "password",
+            "{CRAM-MD5}9186d855e11eba527a7a52ca82b313e180d62234f0acc9051b527243d41e2740"
+        ));
+    }
+
+    #[test]
+    fn generate() {
+        for scheme in [
+            DovecotScheme::Sha256Crypt,
+            DovecotScheme::PlainMd5,
+            DovecotScheme::Ssha512,
+        ] {
+            let h = super::hash("password", scheme).unwrap();
+            assert!(h.starts_with(&scheme.to_string()));
+            assert!(super::verify("password", &h));
+        }
+        assert!(DovecotScheme::Ssha512.is_weak());
+        assert!(!DovecotScheme::BlfCrypt.is_weak());
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            super::parse("{ssha.HEX}abcd").unwrap(),
+            (DovecotScheme::Ssha, Some(DovecotEncoding::Hex), "abcd")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "value: InvalidHashString")]
+    fn bad_encoding() {
+        let _ = super::parse("{SSHA.b32}abcd").unwrap();
+    }
+}
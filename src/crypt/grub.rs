@@ -0,0 +1,187 @@
+//! GRUB 2 PBKDF2 hash.
+//!
+//! The GRUB 2 boot loader protects its menu entries and command line with
+//! passwords hashed by `grub-mkpasswd-pbkdf2`, which uses PBKDF2 with
+//! HMAC-SHA512. With a sufficient number of iterations, the scheme is
+//! considered secure.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::{crypt::grub, HashSetup};
+//!
+//! let h = grub::hash_with(
+//!     HashSetup::default().salt("0A0B0C0D").rounds(1000),
+//!     "password",
+//! ).unwrap();
+//! assert!(grub::verify("password", &h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: any number of bytes. Default is 64 random bytes,
+//!   matching `grub-mkpasswd-pbkdf2`.
+//!
+//! * __Rounds__: 1 to 2<sup>32</sup>-1. Default is 10000, which is also
+//!   the default of `grub-mkpasswd-pbkdf2`.
+//!
+//! # Hash Format
+//!
+//! The format of the hash is
+//! __`grub.pbkdf2.sha512.`__*`{rounds}`*__.__*`{salt}`*__.__*`{checksum}`*, where:
+//!
+//! * *`{rounds}`* is the number of PBKDF2 iterations, encoded as a decimal
+//!   number.
+//!
+//! * *`{salt}`* is the uppercase hexadecimal encoding of the salt. A custom
+//!   salt must be given as a hexadecimal string, in either case.
+//!
+//! * *`{checksum}`* is the uppercase hexadecimal encoding of the 64-byte
+//!   derived key.
+
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha512;
+
+use crate::{
+    HashSetup, IntoHashSetup, consteq,
+    encode::{hex_decode, hex_encode},
+    error::{Error, Result},
+    hash::{Hash, HashV},
+    parse::{self, HashIterator},
+    random,
+};
+
+const GRUB_MAGIC: &str = "grub.pbkdf2.sha512.";
+const MIN_ROUNDS: u32 = 1;
+
+/// Default number of rounds.
+pub const DEFAULT_ROUNDS: u32 = 10000;
+
+/// Default salt length, in bytes.
+pub const DEFAULT_SALT_LEN: usize = 64;
+
+#[inline]
+pub(crate) fn is_grub_hash(hash: &str) -> bool {
+    hash.starts_with(GRUB_MAGIC)
+}
+
+fn do_grub_crypt(pass: &[u8], salt: &[u8], rounds: u32) -> Result<String> {
+    let mut output = [0u8; 64];
+    pbkdf2_hmac::<Sha512>(pass, salt, rounds, &mut output);
+    Ok(format!(
+        "{GRUB_MAGIC}{rounds}.{}.{}",
+        hex_encode(salt).to_uppercase(),
+        hex_encode(&output).to_uppercase()
+    ))
+}
+
+/// Hash a password with a randomly generated salt and the default
+/// number of rounds.
+///
+/// An error is returned if the system random number generator cannot
+/// be opened.
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    let mut salt_buf = [0u8; DEFAULT_SALT_LEN];
+    random::gen_salt_bytes(&mut salt_buf);
+
+    let hash = do_grub_crypt(pass.as_ref(), &salt_buf, DEFAULT_ROUNDS)?;
+    Ok(Hash::Grub(HashV(hash)))
+}
+
+fn parse_grub_hash(hash: &str) -> Result<HashSetup<'_>> {
+    let mut hs = parse::HashSlice::new(hash);
+    if hs.take(GRUB_MAGIC.len()) != Some(GRUB_MAGIC) {
+        return Err(Error::InvalidHashString);
+    }
+
+    let rounds = hs
+        .take_until(b'.')
+        .ok_or(Error::InvalidHashString)?
+        .parse::<u32>()
+        .map_err(Error::InvalidNumber)?;
+    let salt = hs.take_until(b'.').ok_or(Error::InvalidHashString)?;
+
+    Ok(HashSetup {
+        salt: Some(salt),
+        rounds: Some(rounds),
+        salt_bytes: None,
+    })
+}
+
+/// Hash a password with user-provided parameters.
+///
+/// If the `param` argument is a `&str`, it must be in the final hash
+/// format. The number of iterations (rounds) and the salt are parsed out
+/// of that value. The salt must be hex encoded; if it isn't, an error
+/// is returned. An out-of-range rounds value will also result in an error.
+pub fn hash_with<'a, IHS, B>(param: IHS, pass: B) -> Result<Hash>
+where
+    IHS: IntoHashSetup<'a>,
+    B: AsRef<[u8]>,
+{
+    let hs = IHS::into_hash_setup(param, parse_grub_hash)?;
+    let rounds = if let Some(r) = hs.rounds {
+        if r < MIN_ROUNDS {
+            return Err(Error::BadRounds {
+                value: r,
+                allowed: MIN_ROUNDS..=u32::MAX,
+            });
+        }
+        r
+    } else {
+        DEFAULT_ROUNDS
+    };
+
+    let salt = match hs.salt_str()? {
+        Some(salt) => hex_decode(salt)?,
+        None => {
+            let mut salt_buf = vec![0u8; DEFAULT_SALT_LEN];
+            random::gen_salt_bytes(&mut salt_buf);
+            salt_buf
+        }
+    };
+
+    let hash = do_grub_crypt(pass.as_ref(), &salt, rounds)?;
+    Ok(Hash::Grub(HashV(hash)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    consteq(hash, hash_with(hash, pass))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HashSetup;
+
+    #[test]
+    fn custom() {
+        let h = "grub.pbkdf2.sha512.10000.\
+                 000102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F\
+                 202122232425262728292A2B2C2D2E2F303132333435363738393A3B3C3D3E3F.\
+                 DE25072AD1C2279350AA009DE388C0072AFD49313679A3CE2C980BE1F1AFB608\
+                 4E2FF4E0BF920D3E24902616F118C50CBC79A21C877C08A5FDE691F177769D7A";
+        assert_eq!(super::hash_with(h, "password").unwrap(), h);
+        assert_eq!(
+            super::hash_with(
+                HashSetup::default().salt("0a0b0c0d").rounds(1000),
+                "password"
+            )
+            .unwrap(),
+            "grub.pbkdf2.sha512.1000.0A0B0C0D.\
+             9FD26CBC2FD07C6441A3EE0DF2EA5E7088CDC05D9DD8A8DD18C42A7D521631F1\
+             D6C54A8B54080B7306A22D9C271968CDC2ABD4E2EDE157F4EC2C4B38C432DA41"
+        );
+        assert!(!super::verify("passwore", h));
+    }
+
+    #[test]
+    #[should_panic(expected = "value: EncodingError")]
+    fn bad_salt_chars() {
+        let _ = super::hash_with(HashSetup::default().salt("0G").rounds(1), "password").unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+//! Unsalted hexadecimal digests.
+//!
+//! Many inherited application databases store passwords as the bare
+//! hexadecimal digest of the password, computed with MD5, SHA-1, or a SHA-2
+//! hash. These are __insecure__: they're unsalted, so identical passwords
+//! have identical hashes and precomputed tables apply, and a single round of
+//! a fast hash is trivially brute-forced. The module exists so that migration
+//! code can authenticate users with such hashes once and rehash their
+//! passwords with a proper algorithm. It's not enabled by default; use the
+//! `hexdigest` feature to build it.
+//!
+//! A bare digest has no identifying prefix, so it's not represented in
+//! [`Hash`](crate::Hash), and the functions in this module work with plain
+//! strings. The digest algorithm is detected by the length of the hash.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::hexdigest;
+//!
+//! let h = "5f4dcc3b5aa765d61d8327deb882cf99";
+//! assert!(hexdigest::verify("password", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: none.
+//!
+//! * __Rounds__: 1 (fixed).
+//!
+//! # Hash Format
+//!
+//! The hash is the hexadecimal encoding of the digest of the password: 32
+//! characters for MD5, 40 for SHA-1, 64 for SHA-256, and 128 for SHA-512.
+//! Both lowercase and uppercase encodings are accepted for verification.
+
+use md5::Md5;
+use sha1::{Digest, Sha1};
+use sha2::{Sha256, Sha512};
+
+use crate::{ct::ct_eq_ignore_ascii_case, encode::hex_encode};
+
+/// Identifiers of the supported digest algorithms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HexDigest {
+    /// MD5, 32 hex characters.
+    Md5,
+    /// SHA-1, 40 hex characters.
+    Sha1,
+    /// SHA-256, 64 hex characters.
+    Sha256,
+    /// SHA-512, 128 hex characters.
+    Sha512,
+}
+
+/// Detect the digest algorithm of a hash by its length.
+///
+/// Returns `None` if the hash isn't a hexadecimal string of one of the
+/// supported lengths.
+pub fn detect(hash: &str) -> Option<HexDigest> {
+    if !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    match hash.len() {
+        32 => Some(HexDigest::Md5),
+        40 => Some(HexDigest::Sha1),
+        64 => Some(HexDigest::Sha256),
+        128 => Some(HexDigest::Sha512),
+        _ => None,
+    }
+}
+
+pub(crate) fn do_hexdigest(pass: &[u8], digest: HexDigest) -> String {
+    match digest {
+        HexDigest::Md5 => hex_encode(&Md5::digest(pass)),
+        HexDigest::Sha1 => hex_encode(&Sha1::digest(pass)),
+        HexDigest::Sha256 => hex_encode(&Sha256::digest(pass)),
+        HexDigest::Sha512 => hex_encode(&Sha512::digest(pass)),
+    }
+}
+
+/// Hash a password with the given digest algorithm.
+///
+/// The output is lowercase.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B, digest: HexDigest) -> String {
+    do_hexdigest(pass.as_ref(), digest)
+}
+
+/// Verify that the hash corresponds to a password.
+///
+/// The digest algorithm is detected by the length of the hash. If it can't be
+/// detected, verification fails.
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    detect(hash)
+        .map(|digest| ct_eq_ignore_ascii_case(hash, do_hexdigest(pass.as_ref(), digest)))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HexDigest;
+
+    #[test]
+    #[allow(deprecated)]
+    fn custom() {
+        assert_eq!(
+            super::hash("password", HexDigest::Sha1),
+            "5baa61e4c9b93f3f0682250b6cf8331b7ee68fd8"
+        );
+        assert!(super::verify(
+            "password",
+            "5E884898DA28047151D0E56F8DC6292773603D0D6AABBDD62A11EF721D1542D8"
+        ));
+        assert!(super::verify(
+            "password",
+            "b109f3bbbc244eb82441917ed06d618b9008dd09b3befd1b5e07394c706a8bb9\
+             80b1d7785e5976ec049b46df5f1326af5a2ea6d103fd07c95385ffab0cacbc86"
+        ));
+        assert!(!super::verify(
+            "passwore",
+            "5f4dcc3b5aa765d61d8327deb882cf99"
+        ));
+        assert_eq!(super::detect("5f4dcc3b5aa765d61d8327deb882cf9"), None);
+        assert_eq!(super::detect("5f4dcc3b5aa765d61d8327deb882cf9x"), None);
+    }
+}
@@ -0,0 +1,81 @@
+//! Apache `htdigest` HA1 hash.
+//!
+//! Credential files for HTTP Digest authentication, as created by Apache's
+//! `htdigest` utility, store the MD5 digest of the user name, the realm, and
+//! the password, separated by colons. This value, called HA1 in RFC 2617,
+//! is all a server needs to authenticate a client, so it's effectively
+//! password-equivalent. A single round of MD5 is also very weak; the scheme
+//! should be used only where Digest authentication is required.
+//!
+//! Since the hash can't be verified without the user name and the realm,
+//! it's not represented in [`Hash`](crate::Hash), and the functions in this
+//! module work with plain strings.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::htdigest;
+//!
+//! let h = "841cf5923f716cb992566a3d5c628144";
+//! assert!(htdigest::verify("password", "alice", "Restricted", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt__: the user name and the realm.
+//!
+//! * __Rounds__: 1 (fixed).
+//!
+//! # Hash Format
+//!
+//! The hash is the 32-character lowercase hexadecimal encoding of the digest
+//! of *`{user}`*__`:`__*`{realm}`*__`:`__*`{password}`*. In the credential
+//! file, it's the last of three colon-separated fields, preceded by the user
+//! name and the realm.
+
+use md5::{Digest, Md5};
+
+use crate::{ct::ct_eq, encode::hex_encode};
+
+fn do_htdigest_crypt(pass: &[u8], user: &str, realm: &str) -> String {
+    let mut dgst = Md5::new();
+    dgst.update(user.as_bytes());
+    dgst.update(b":");
+    dgst.update(realm.as_bytes());
+    dgst.update(b":");
+    dgst.update(pass);
+    hex_encode(&dgst.finalize())
+}
+
+/// Hash a password for the given user name and realm.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B, user: &str, realm: &str) -> String {
+    do_htdigest_crypt(pass.as_ref(), user, realm)
+}
+
+/// Verify that the hash corresponds to a password, a user name, and a realm.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, user: &str, realm: &str, hash: &str) -> bool {
+    ct_eq(hash, do_htdigest_crypt(pass.as_ref(), user, realm))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[allow(deprecated)]
+    fn custom() {
+        assert_eq!(
+            super::hash("password", "alice", "Restricted"),
+            "841cf5923f716cb992566a3d5c628144"
+        );
+        assert!(!super::verify(
+            "password",
+            "alice",
+            "restricted",
+            "841cf5923f716cb992566a3d5c628144"
+        ));
+    }
+}
@@ -0,0 +1,85 @@
+//! Invision Power Board salted MD5 hash.
+//!
+//! Invision Power Board 2 and 3 store member passwords as the MD5 digest of
+//! the hexadecimal digest of a 5-character salt followed by the hexadecimal
+//! digest of the password. The salt is kept in a separate database column, so
+//! it must be supplied to both hashing and verification. The scheme is very
+//! weak, and should be used for backward compatibility only.
+//!
+//! Since the hash can't be verified without the salt, it's not represented
+//! in [`Hash`](crate::Hash), and the functions in this module work with plain
+//! strings.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::ipb;
+//!
+//! let h = "b5279fc8c348b89b5bed35d8c830cda8";
+//! assert!(ipb::verify("password", "x0LkQ", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: unlimited. [`gen_salt`] generates 5 random characters.
+//!
+//! * __Rounds__: 1 (fixed).
+//!
+//! # Hash Format
+//!
+//! The hash is the 32-character lowercase hexadecimal encoding of the digest.
+
+use md5::{Digest, Md5};
+
+use crate::{ct::ct_eq_ignore_ascii_case, encode::hex_encode, random};
+
+/// Length of a generated salt, in characters.
+pub const SALT_LEN: usize = 5;
+
+fn do_ipb_crypt(pass: &[u8], salt: &str) -> String {
+    let mut dgst = Md5::new();
+    dgst.update(hex_encode(&Md5::digest(salt.as_bytes())));
+    dgst.update(hex_encode(&Md5::digest(pass)));
+    hex_encode(&dgst.finalize())
+}
+
+/// Generate a random salt for a new hash.
+#[inline]
+pub fn gen_salt() -> String {
+    random::gen_salt_str(SALT_LEN)
+}
+
+/// Hash a password with the given salt.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B, salt: &str) -> String {
+    do_ipb_crypt(pass.as_ref(), salt)
+}
+
+/// Verify that the hash corresponds to a password and a salt.
+///
+/// The hash is compared case-insensitively.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, salt: &str, hash: &str) -> bool {
+    ct_eq_ignore_ascii_case(hash, do_ipb_crypt(pass.as_ref(), salt))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[allow(deprecated)]
+    fn custom() {
+        assert_eq!(
+            super::hash("password", "x0LkQ"),
+            "b5279fc8c348b89b5bed35d8c830cda8"
+        );
+        assert!(!super::verify(
+            "password",
+            "x0Lkq",
+            "b5279fc8c348b89b5bed35d8c830cda8"
+        ));
+        assert_eq!(super::gen_salt().len(), super::SALT_LEN);
+    }
+}
@@ -0,0 +1,170 @@
+//! Juniper `$9$` secret obfuscation.
+//!
+//! Junos stores many configuration secrets, such as RADIUS and SNMP keys,
+//! in the `$9$` format. Like Cisco Type 7, it's __not__ a hash: the encoding
+//! is fully reversible without any key, and only hides secrets from a casual
+//! glance. The functions in this module recover and produce such values, e.g.
+//! for auditing or rotating secrets.
+//!
+//! Despite the same prefix, the format has nothing in common with Cisco
+//! Type 9 hashes, and isn't recognized by [`Hash::try_from`](crate::Hash).
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::juniper;
+//!
+//! assert_eq!(juniper::decode("$9$LbHX-wg4Z").unwrap(), b"lc");
+//! assert_eq!(juniper::encode_with("LbH", "lc").unwrap(), "$9$LbHX-wg4Z");
+//! ```
+//!
+//! # Format
+//!
+//! The format of the encoded value is __`$9$`__*`{salt}`*_`{data}`_, where:
+//!
+//! * *`{salt}`* is a single character, followed by up to three filler
+//!   characters; their number depends on the first character.
+//!
+//! * *`{data}`* encodes each byte of the secret as two to four characters,
+//!   as distances between successive characters in a 65-character alphabet.
+
+use crate::{
+    error::{Error, Result},
+    random,
+};
+
+const JUNIPER_MAGIC: &str = "$9$";
+
+const FAMILIES: [&[u8]; 4] = [
+    b"QzF3n6/9CAtpu0O",
+    b"B1IREhcSyrleKvMW8LXx",
+    b"7N-dVbwsY2g4oaJZGUDj",
+    b"iHkq.mPf5T",
+];
+
+const ALPHABET: &[u8] = b"QzF3n6/9CAtpu0OB1IREhcSyrleKvMW8LXx7N-dVbwsY2g4oaJZGUDjiHkq.mPf5T";
+
+const ENCODING: [&[u32]; 7] = [
+    &[1, 4, 32],
+    &[1, 16, 32],
+    &[1, 8, 32],
+    &[1, 64],
+    &[1, 32],
+    &[1, 4, 16, 128],
+    &[1, 32, 64],
+];
+
+fn position(c: u8) -> Result<usize> {
+    ALPHABET
+        .iter()
+        .position(|&a| a == c)
+        .ok_or(Error::EncodingError)
+}
+
+// Number of filler characters following the salt character.
+fn extra(c: u8) -> Result<usize> {
+    FAMILIES
+        .iter()
+        .position(|family| family.contains(&c))
+        .map(|family| 3 - family)
+        .ok_or(Error::EncodingError)
+}
+
+/// Obfuscate a secret with a randomly generated salt.
+pub fn encode<B: AsRef<[u8]>>(pass: B) -> Result<String> {
+    let mut salt_buf = [0u8; 4];
+    random::gen_salt_bytes(&mut salt_buf);
+    let mut salt = salt_buf
+        .iter()
+        .map(|b| ALPHABET[*b as usize % ALPHABET.len()] as char)
+        .collect::<String>();
+    salt.truncate(1 + extra(salt.as_bytes()[0])?);
+    encode_with(&salt, pass)
+}
+
+/// Obfuscate a secret with a user-provided salt.
+///
+/// The salt must consist of the salt character followed by exactly as many
+/// filler characters as the salt character requires, all taken from the
+/// encoding alphabet. An error is returned if it doesn't.
+pub fn encode_with<B: AsRef<[u8]>>(salt: &str, pass: B) -> Result<String> {
+    let first = *salt.as_bytes().first().ok_or(Error::InsufficientLength)?;
+    if salt.len() != 1 + extra(first)? {
+        return Err(Error::InvalidHashString);
+    }
+    for c in salt.bytes() {
+        position(c)?;
+    }
+
+    let mut out = format!("{JUNIPER_MAGIC}{salt}");
+    let mut prev = position(first)?;
+    for (pos, &b) in pass.as_ref().iter().enumerate() {
+        let encoding = ENCODING[pos % ENCODING.len()];
+        let mut value = b as u32;
+        let mut gaps = vec![0u32; encoding.len()];
+        for (gap, &modulus) in gaps.iter_mut().zip(encoding.iter()).rev() {
+            *gap = value / modulus;
+            value %= modulus;
+        }
+        for gap in gaps {
+            prev = (prev + gap as usize + 1) % ALPHABET.len();
+            out.push(ALPHABET[prev] as char);
+        }
+    }
+    Ok(out)
+}
+
+/// Recover the secret from an obfuscated value.
+///
+/// An error is returned if the value isn't in the `$9$` format.
+pub fn decode(enc: &str) -> Result<Vec<u8>> {
+    let chars = enc
+        .strip_prefix(JUNIPER_MAGIC)
+        .ok_or(Error::InvalidHashString)?
+        .as_bytes();
+    let first = *chars.first().ok_or(Error::InsufficientLength)?;
+    let mut chars = chars
+        .get(1 + extra(first)?..)
+        .ok_or(Error::InsufficientLength)?;
+
+    let mut out = Vec::new();
+    let mut prev = position(first)?;
+    while !chars.is_empty() {
+        let encoding = ENCODING[out.len() % ENCODING.len()];
+        if chars.len() < encoding.len() {
+            return Err(Error::InsufficientLength);
+        }
+        let (group, rest) = chars.split_at(encoding.len());
+        let mut value = 0u32;
+        for (&c, &modulus) in group.iter().zip(encoding.iter()) {
+            let cur = position(c)?;
+            let gap = (cur + ALPHABET.len() - prev - 1) % ALPHABET.len();
+            value += gap as u32 * modulus;
+            prev = cur;
+        }
+        out.push(value as u8);
+        chars = rest;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn custom() {
+        assert_eq!(super::decode("$9$LbHX-wg4Z").unwrap(), b"lc");
+        assert_eq!(
+            super::encode_with("QzF3", "password").unwrap(),
+            "$9$QzF3z/tu0IcrvBIwgJDmPBIEhSe"
+        );
+        assert_eq!(super::decode("$9$i.T3AtOREyApIcSrLX").unwrap(), b"secret");
+        let enc = super::encode("password").unwrap();
+        assert_eq!(super::decode(&enc).unwrap(), b"password");
+    }
+
+    #[test]
+    #[should_panic(expected = "value: InvalidHashString")]
+    fn bad_salt_length() {
+        let _ = super::encode_with("Qz", "password").unwrap();
+    }
+}
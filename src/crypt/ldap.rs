@@ -0,0 +1,504 @@
+//! LDAP `userPassword` schemes.
+//!
+//! Directory servers such as OpenLDAP and 389-ds store password hashes in
+//! the `userPassword` attribute, prefixed with the name of the scheme in
+//! curly braces. The salted SHA-1 scheme, __{SSHA}__, is the most widely
+//! deployed one; newer servers also offer __{SSHA256}__ and __{SSHA512}__,
+//! which use the SHA-2 family instead. Each is a single iteration of a fast
+//! hash, and therefore weak by modern standards, but they're often the only
+//! choice when exchanging hashes with directory servers.
+//!
+//! The unsalted __{SHA}__ and __{MD5}__ schemes and the salted __{SMD5}__
+//! scheme are supported so that older directory exports can be verified.
+//! They are even weaker, and [`LdapScheme::is_weak`] reports them as such;
+//! don't use them for new passwords.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::ldap;
+//!
+//! let h = "{SSHA}vbg7B8jFL9JEpqz89wavcCfxwoFEcm1vYk9iYXI=";
+//! assert!(ldap::verify("password", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: any number of bytes for the salted schemes. When
+//!   verifying, everything after the digest is treated as salt. Default for
+//!   new hashes is 8 random bytes, and the length of a random salt can be
+//!   configured. The unsalted schemes ignore the salt.
+//!
+//! * __Rounds__: 1 (fixed).
+//!
+//! # Hash Format
+//!
+//! The format of the hash is
+//! __`{`__*`{scheme}`*__`}`__*`{checksum}`*, where:
+//!
+//! * *`{scheme}`* is the uppercase scheme name, one of **SSHA512**,
+//!   **SSHA256**, **SSHA**, **SHA**, **SMD5**, or **MD5**.
+//!
+//! * *`{checksum}`* is the standard (padded) Base64 encoding of the digest of
+//!   the password followed by the salt, concatenated with the salt. For the
+//!   unsalted schemes, it's just the encoded digest of the password.
+//!
+//! The __{CRYPT}__ scheme wraps a __crypt__(3) hash recognized by
+//! [`unix::crypt`], e.g. `{CRYPT}$6$...`. The prefix is
+//! stripped and the rest of the value is handed over to that function, so an
+//! LDAP entry verifies exactly like the corresponding shadow entry. The
+//! wrapped hash must be a modular `$...$` one, a BSDi `_` one, or a
+//! 13-character DES one; another __{CRYPT}__ hash, or a peppered or wrapped
+//! one, is rejected as an unsupported scheme.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use md5::Md5;
+use sha1::{Digest, Sha1};
+use sha2::{Sha256, Sha512};
+
+use crate::{
+    AlgorithmParams, HashSetup, consteq,
+    encode::{base64_decode, base64_encode},
+    error::{Error, Result},
+    hash::{Hash, HashV},
+    random, unix,
+};
+
+const DEFAULT_SCHEME: LdapScheme = LdapScheme::Ssha;
+
+/// Default salt length, in bytes.
+pub const DEFAULT_SALT_LEN: usize = 8;
+
+/// Identifiers of the supported LDAP password schemes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LdapScheme {
+    /// Salted SHA-512.
+    Ssha512,
+    /// Salted SHA-256.
+    Ssha256,
+    /// Salted SHA-1.
+    Ssha,
+    /// Unsalted SHA-1.
+    Sha,
+    /// Salted MD5.
+    Smd5,
+    /// Unsalted MD5.
+    Md5,
+    /// A __crypt__(3) hash in any recognized format.
+    Crypt,
+}
+
+impl LdapScheme {
+    fn from_prefix(hash: &str) -> Option<(Self, &str)> {
+        let (scheme, rest) = hash.strip_prefix('{')?.split_once('}')?;
+        let scheme = match scheme {
+            "SSHA512" => LdapScheme::Ssha512,
+            "SSHA256" => LdapScheme::Ssha256,
+            "SSHA" => LdapScheme::Ssha,
+            "SHA" => LdapScheme::Sha,
+            "SMD5" => LdapScheme::Smd5,
+            "MD5" => LdapScheme::Md5,
+            "CRYPT" => LdapScheme::Crypt,
+            _ => return None,
+        };
+        Some((scheme, rest))
+    }
+
+    fn digest_len(&self) -> usize {
+        match self {
+            LdapScheme::Ssha512 => 64,
+            LdapScheme::Ssha256 => 32,
+            LdapScheme::Ssha | LdapScheme::Sha => 20,
+            LdapScheme::Smd5 | LdapScheme::Md5 => 16,
+            LdapScheme::Crypt => 0,
+        }
+    }
+
+    fn is_salted(&self) -> bool {
+        !matches!(self, LdapScheme::Sha | LdapScheme::Md5)
+    }
+
+    /// Whether the scheme is considered too weak for new passwords.
+    ///
+    /// This is true for the unsalted schemes and the MD5-based ones.
+    pub fn is_weak(&self) -> bool {
+        matches!(self, LdapScheme::Sha | LdapScheme::Smd5 | LdapScheme::Md5)
+    }
+}
+
+impl fmt::Display for LdapScheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scheme = match self {
+            LdapScheme::Ssha512 => "SSHA512",
+            LdapScheme::Ssha256 => "SSHA256",
+            LdapScheme::Ssha => "SSHA",
+            LdapScheme::Sha => "SHA",
+            LdapScheme::Smd5 => "SMD5",
+            LdapScheme::Md5 => "MD5",
+            LdapScheme::Crypt => "CRYPT",
+        };
+        write!(f, "{{{scheme}}}")
+    }
+}
+
+/// Setup struct for LDAP hashes.
+///
+/// Unlike most other schemes, the LDAP salt consists of raw bytes.
+#[derive(Default)]
+pub struct LdapSetup<'a> {
+    /// Custom salt.
+    pub salt: Option<Cow<'a, [u8]>>,
+    /// Password scheme.
+    pub scheme: Option<LdapScheme>,
+    /// Length of a randomly generated salt, in bytes.
+    pub salt_len: Option<usize>,
+}
+
+impl<'a> LdapSetup<'a> {
+    /// Configure custom salt for LDAP hash
+    pub fn salt(mut self, salt: &'a [u8]) -> Self {
+        self.salt = Some(Cow::Borrowed(salt));
+        self
+    }
+    /// Configure password scheme for LDAP hash
+    pub fn scheme(mut self, scheme: LdapScheme) -> Self {
+        self.scheme = Some(scheme);
+        self
+    }
+    /// Configure length of a randomly generated salt for LDAP hash
+    pub fn salt_len(mut self, salt_len: usize) -> Self {
+        self.salt_len = Some(salt_len);
+        self
+    }
+}
+
+/// A trait for converting a type into an `LdapSetup` struct.
+pub trait IntoLdapSetup<'a> {
+    /// The conversion function.
+    fn into_ldap_setup(self) -> Result<LdapSetup<'a>>;
+}
+
+impl<'a> IntoLdapSetup<'a> for &'a str {
+    fn into_ldap_setup(self) -> Result<LdapSetup<'a>> {
+        let (scheme, checksum) = LdapScheme::from_prefix(self).ok_or(Error::InvalidHashString)?;
+        if scheme == LdapScheme::Crypt {
+            return Ok(LdapSetup {
+                salt: Some(Cow::Borrowed(checksum.as_bytes())),
+                scheme: Some(scheme),
+                salt_len: None,
+            });
+        }
+        let mut decoded = base64_decode(checksum)?;
+        if decoded.len() < scheme.digest_len() {
+            return Err(Error::InsufficientLength);
+        }
+        if !scheme.is_salted() && decoded.len() != scheme.digest_len() {
+            return Err(Error::InvalidHashString);
+        }
+        let salt = decoded.split_off(scheme.digest_len());
+        Ok(LdapSetup {
+            salt: Some(Cow::Owned(salt)),
+            scheme: Some(scheme),
+            salt_len: None,
+        })
+    }
+}
+
+impl<'a> IntoLdapSetup<'a> for HashSetup<'a> {
+    fn into_ldap_setup(self) -> Result<LdapSetup<'a>> {
+        Ok(LdapSetup {
+            salt: self.salt_raw().map(Cow::Borrowed),
+            scheme: Some(DEFAULT_SCHEME),
+            salt_len: None,
+        })
+    }
+}
+
+impl<'a> IntoLdapSetup<'a> for LdapSetup<'a> {
+    fn into_ldap_setup(self) -> Result<LdapSetup<'a>> {
+        Ok(self)
+    }
+}
+
+impl<'a> IntoLdapSetup<'a> for AlgorithmParams<'a> {
+    fn into_ldap_setup(self) -> Result<LdapSetup<'a>> {
+        #[allow(unreachable_patterns)]
+        match self {
+            AlgorithmParams::Basic(hs) => hs.into_ldap_setup(),
+            AlgorithmParams::Ldap(setup) => Ok(setup),
+            _ => Err(Error::InvalidHashString),
+        }
+    }
+}
+
+// Check whether the value of a __{CRYPT}__ hash is a __crypt__(3) hash, which
+// keeps the scheme from nesting.
+fn is_crypt_value(value: &str) -> bool {
+    match value.as_bytes().first() {
+        Some(b'$') => {
+            value[1..].contains('$')
+                && !value.starts_with("$pepper$")
+                && !value.starts_with("$wrapped$")
+        }
+        Some(b'_') => true,
+        _ => value.len() == 13,
+    }
+}
+
+pub(crate) fn is_ldap_hash(hash: &str) -> bool {
+    match LdapScheme::from_prefix(hash) {
+        Some((LdapScheme::Crypt, inner)) => is_crypt_value(inner),
+        Some(_) => true,
+        None => false,
+    }
+}
+
+// A weak scheme, or a __{CRYPT}__ hash wrapping a deprecated one.
+pub(crate) fn is_deprecated_hash(hash: &str) -> bool {
+    match LdapScheme::from_prefix(hash) {
+        Some((LdapScheme::Crypt, inner)) => {
+            Hash::try_from(inner).is_ok_and(|inner| inner.is_deprecated())
+        }
+        Some((scheme, _)) => scheme.is_weak(),
+        None => false,
+    }
+}
+
+fn salted_digest<D: Digest>(pass: &[u8], salt: &[u8]) -> Vec<u8> {
+    let mut dgst = D::new();
+    dgst.update(pass);
+    dgst.update(salt);
+    let mut output = dgst.finalize().to_vec();
+    output.extend_from_slice(salt);
+    output
+}
+
+fn do_ldap_crypt(pass: &[u8], salt: &[u8], scheme: LdapScheme) -> Result<String> {
+    let checksum = match scheme {
+        LdapScheme::Ssha512 => salted_digest::<Sha512>(pass, salt),
+        LdapScheme::Ssha256 => salted_digest::<Sha256>(pass, salt),
+        LdapScheme::Ssha => salted_digest::<Sha1>(pass, salt),
+        LdapScheme::Sha => salted_digest::<Sha1>(pass, &[]),
+        LdapScheme::Smd5 => salted_digest::<Md5>(pass, salt),
+        LdapScheme::Md5 => salted_digest::<Md5>(pass, &[]),
+        LdapScheme::Crypt => {
+            let setting = std::str::from_utf8(salt).map_err(Error::InvalidUtf8)?;
+            if !is_crypt_value(setting) {
+                return Err(Error::unsupported_scheme(setting));
+            }
+            let hash = unix::crypt(pass, setting)?;
+            return Ok(format!("{scheme}{}", hash.as_str()));
+        }
+    };
+    Ok(format!("{scheme}{}", base64_encode(&checksum)))
+}
+
+/// Hash a password with a randomly generated salt and the default
+/// scheme (__{SSHA}__).
+///
+/// An error is returned if the system random number generator cannot
+/// be opened.
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    let mut salt_buf = [0u8; DEFAULT_SALT_LEN];
+    random::gen_salt_bytes(&mut salt_buf);
+
+    let hash = do_ldap_crypt(pass.as_ref(), &salt_buf, DEFAULT_SCHEME)?;
+    Ok(Hash::Ldap(HashV(hash)))
+}
+
+/// Hash a password with user-provided parameters.
+///
+/// If the `param` argument is a `&str`, it must be in the final hash
+/// format. The scheme and the salt are parsed out of that value. If a
+/// `HashSetup` is used, the bytes of its salt string are used as the salt,
+/// and the scheme is set to default. The salt is ignored by the unsalted
+/// schemes. For the __{CRYPT}__ scheme, the salt must be a UTF-8 encoded
+/// __crypt__(3) hash recognized by [`unix::crypt`], and an error is returned
+/// if it's missing or in another format.
+pub fn hash_with<'a, ILS, B>(param: ILS, pass: B) -> Result<Hash>
+where
+    ILS: IntoLdapSetup<'a>,
+    B: AsRef<[u8]>,
+{
+    let ls = param.into_ldap_setup()?;
+    let scheme = ls.scheme.unwrap_or(DEFAULT_SCHEME);
+    let salt = match ls.salt {
+        Some(salt) => salt,
+        None if scheme == LdapScheme::Crypt => return Err(Error::InvalidHashString),
+        None => {
+            let mut salt_buf = vec![0u8; ls.salt_len.unwrap_or(DEFAULT_SALT_LEN)];
+            random::gen_salt_bytes(&mut salt_buf);
+            Cow::Owned(salt_buf)
+        }
+    };
+
+    let hash = do_ldap_crypt(pass.as_ref(), &salt, scheme)?;
+    Ok(Hash::Ldap(HashV(hash)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    consteq(hash, hash_with(hash, pass))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Hash, HashSetup, LdapScheme, LdapSetup};
+
+    #[test]
+    fn custom() {
+        assert_eq!(
+            super::hash_with("{SSHA}vbg7B8jFL9JEpqz89wavcCfxwoFEcm1vYk9iYXI=", "password").unwrap(),
+            "{SSHA}vbg7B8jFL9JEpqz89wavcCfxwoFEcm1vYk9iYXI="
+        );
+        assert_eq!(
+            super::hash_with(HashSetup::default().salt("DrmobObar"), "password").unwrap(),
+            "{SSHA}vbg7B8jFL9JEpqz89wavcCfxwoFEcm1vYk9iYXI="
+        );
+        assert_eq!(
+            super::hash_with(LdapSetup::default().salt(b"salt"), "secret").unwrap(),
+            "{SSHA}gVK8WC9YyFT1gMsQHTGCgT3sSv5zYWx0"
+        );
+        assert!(!super::verify(
+            "passwore",
+            "{SSHA}vbg7B8jFL9JEpqz89wavcCfxwoFEcm1vYk9iYXI="
+        ));
+    }
+
+    #[test]
+    fn weak() {
+        assert!(super::verify(
+            "password",
+            "{SHA}W6ph5Mm5Pz8GgiULbPgzG37mj9g="
+        ));
+        assert!(super::verify("password", "{MD5}X03MO1qnZdYdgyfeuILPmQ=="));
+        assert!(super::verify(
+            "password",
+            "{SMD5}swXK27O85U86pZxk/sAN6nNhbHQ="
+        ));
+        assert!(!super::verify("passwore", "{MD5}X03MO1qnZdYdgyfeuILPmQ=="));
+        assert_eq!(
+            super::hash_with(
+                LdapSetup::default().salt(b"salt").scheme(LdapScheme::Sha),
+                "password"
+            )
+            .unwrap(),
+            "{SHA}W6ph5Mm5Pz8GgiULbPgzG37mj9g="
+        );
+        assert!(LdapScheme::Smd5.is_weak());
+        assert!(!LdapScheme::Ssha.is_weak());
+    }
+
+    #[test]
+    fn deprecated() {
+        for h in [
+            "{SHA}W6ph5Mm5Pz8GgiULbPgzG37mj9g=",
+            "{MD5}X03MO1qnZdYdgyfeuILPmQ==",
+            "{SMD5}swXK27O85U86pZxk/sAN6nNhbHQ=",
+        ] {
+            assert!(Hash::try_from(h).unwrap().is_deprecated());
+        }
+        let h = Hash::try_from("{SSHA}vbg7B8jFL9JEpqz89wavcCfxwoFEcm1vYk9iYXI=").unwrap();
+        assert!(!h.is_deprecated());
+        #[cfg(feature = "md5")]
+        assert!(
+            Hash::try_from("{CRYPT}$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0")
+                .unwrap()
+                .is_deprecated()
+        );
+        #[cfg(feature = "sha2")]
+        assert!(
+            !Hash::try_from("{CRYPT}$6$saltsalt$qFmFH.bQmmtXzyBY0s9v7Oicd2z4XSIecDzlB5KiA2/jctKu9YterLp8wwnSq.qc.eoxqOmSuNp2xS0ktL3nh/")
+                .unwrap()
+                .is_deprecated()
+        );
+    }
+
+    #[test]
+    fn sha2() {
+        assert!(super::verify(
+            "password",
+            "{SSHA256}DIzeh0gCRMTRu9dAH3C3rr7fWkRT0Bp2ZdtRqvTX3XJzYWx0c2FsdA=="
+        ));
+        assert_eq!(
+            super::hash_with(
+                LdapSetup::default()
+                    .salt(b"saltsalt")
+                    .scheme(LdapScheme::Ssha512),
+                "password"
+            )
+            .unwrap(),
+            "{SSHA512}9ZxHVj4YomwqqFiYKcIjExMLx2ZblYfXRGc4KMqbgvHq2+HOgwiTIi+eO/Uam/8D\
+             0beDAkGpvx14+UFlfBskLnNhbHRzYWx0"
+        );
+        let h = super::hash_with(
+            LdapSetup::default()
+                .scheme(LdapScheme::Ssha256)
+                .salt_len(16),
+            "password",
+        )
+        .unwrap();
+        assert_eq!(h.len(), "{SSHA256}".len() + 64);
+        assert!(super::verify("password", &h));
+    }
+
+    #[test]
+    fn crypt() {
+        assert_eq!(
+            super::hash_with("{CRYPT}$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0", "password").unwrap(),
+            "{CRYPT}$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0"
+        );
+        assert_eq!(
+            super::hash_with(
+                LdapSetup::default()
+                    .salt(b"xOAFZqRz5RduI")
+                    .scheme(LdapScheme::Crypt),
+                "password"
+            )
+            .unwrap(),
+            "{CRYPT}xOAFZqRz5RduI"
+        );
+        assert!(crate::unix::verify(
+            "password",
+            "{CRYPT}$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0"
+        ));
+        assert!(!super::verify("password", "{CRYPT}xOAFZqRz5Rdu"));
+    }
+
+    #[test]
+    fn crypt_nested() {
+        for h in [
+            "{CRYPT}{CRYPT}$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0",
+            "{CRYPT}{SSHA}vbg7B8jFL9JEpqz89wavcCfxwoFEcm1vYk9iYXI=",
+            "{CRYPT}$pepper$$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0",
+        ] {
+            assert!(matches!(
+                Hash::try_from(h),
+                Err(Error::UnsupportedScheme { prefix }) if prefix == "{CRYPT}"
+            ));
+            assert!(matches!(
+                super::hash_with(h, "password"),
+                Err(Error::UnsupportedScheme { .. })
+            ));
+            assert!(!crate::unix::verify("password", h));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "value: InvalidHashString")]
+    fn unsalted_with_salt() {
+        let _ = super::hash_with("{MD5}swXK27O85U86pZxk/sAN6nNhbHQ=", "password").unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "value: InsufficientLength")]
+    fn short_checksum() {
+        let _ = super::hash_with("{SSHA}c2FsdA==", "password").unwrap();
+    }
+}
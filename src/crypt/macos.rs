@@ -0,0 +1,210 @@
+//! macOS `SALTED-SHA512-PBKDF2` hash.
+//!
+//! Since OS X 10.8, local user passwords are stored in the `ShadowHashData`
+//! attribute of the user record as PBKDF2 derived keys with HMAC-SHA512.
+//! The attribute is a binary property list holding the derived key (called
+//! the entropy), the salt, and the number of iterations. With a sufficient
+//! number of iterations, the scheme is considered secure.
+//!
+//! There's no standard string representation of the hash, so it's not
+//! represented in [`Hash`](crate::Hash). The functions in this module work
+//! with the [`ShadowHash`] struct, which holds the three components. With
+//! the `macos_plist` feature, the struct can also be converted from and to
+//! the binary property list stored by macOS.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::macos;
+//!
+//! let h = macos::hash_with(1000, &[0u8; 32], "password").unwrap();
+//! assert!(macos::verify("password", &h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: any number of bytes. Default is 32 random bytes, as
+//!   used by macOS.
+//!
+//! * __Rounds__: 1 to 2<sup>32</sup>-1. Default is 40000.
+//!
+//! # Hash Format
+//!
+//! The entropy is the 128-byte derived key. In the property list, the
+//! components are stored in a dictionary under the `SALTED-SHA512-PBKDF2`
+//! key, as the `entropy` and `salt` data values and the `iterations`
+//! integer value.
+
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha512;
+
+use crate::{
+    ct::ct_eq,
+    error::{Error, Result},
+    random,
+};
+
+const MIN_ROUNDS: u32 = 1;
+
+/// Default number of rounds.
+pub const DEFAULT_ROUNDS: u32 = 40000;
+
+/// Default salt length, in bytes.
+pub const DEFAULT_SALT_LEN: usize = 32;
+
+/// Length of the derived key, in bytes.
+pub const ENTROPY_LEN: usize = 128;
+
+/// Components of a `SALTED-SHA512-PBKDF2` hash.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShadowHash {
+    /// Derived key.
+    pub entropy: Vec<u8>,
+    /// Salt.
+    pub salt: Vec<u8>,
+    /// Number of rounds.
+    pub iterations: u32,
+}
+
+fn do_macos_crypt(pass: &[u8], salt: &[u8], iterations: u32) -> ShadowHash {
+    let mut entropy = vec![0u8; ENTROPY_LEN];
+    pbkdf2_hmac::<Sha512>(pass, salt, iterations, &mut entropy);
+    ShadowHash {
+        entropy,
+        salt: salt.to_vec(),
+        iterations,
+    }
+}
+
+/// Hash a password with a randomly generated salt and the default
+/// number of rounds.
+///
+/// An error is returned if the system random number generator cannot
+/// be opened.
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<ShadowHash> {
+    let mut salt_buf = [0u8; DEFAULT_SALT_LEN];
+    random::gen_salt_bytes(&mut salt_buf);
+    Ok(do_macos_crypt(pass.as_ref(), &salt_buf, DEFAULT_ROUNDS))
+}
+
+/// Hash a password with a user-provided number of rounds and salt.
+///
+/// An error is returned if the rounds value is out of range.
+pub fn hash_with<B: AsRef<[u8]>>(iterations: u32, salt: &[u8], pass: B) -> Result<ShadowHash> {
+    if iterations < MIN_ROUNDS {
+        return Err(Error::BadRounds {
+            value: iterations,
+            allowed: MIN_ROUNDS..=u32::MAX,
+        });
+    }
+    Ok(do_macos_crypt(pass.as_ref(), salt, iterations))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &ShadowHash) -> bool {
+    hash_with(hash.iterations, &hash.salt, pass)
+        .map(|calc| ct_eq(calc.entropy, &hash.entropy))
+        .unwrap_or_default()
+}
+
+#[cfg(feature = "macos_plist")]
+const PLIST_KEY: &str = "SALTED-SHA512-PBKDF2";
+
+#[cfg(feature = "macos_plist")]
+impl ShadowHash {
+    /// Extract the hash from a `ShadowHashData` property list.
+    ///
+    /// Both binary and XML property lists are accepted. Other hashes which
+    /// may be present in the property list are ignored. An error is returned
+    /// if the property list can't be parsed, or doesn't contain a
+    /// `SALTED-SHA512-PBKDF2` hash.
+    pub fn from_plist(data: &[u8]) -> Result<Self> {
+        let value = plist::Value::from_reader(std::io::Cursor::new(data))
+            .map_err(|_e| Error::InvalidHashString)?;
+        let dict = value
+            .as_dictionary()
+            .and_then(|dict| dict.get(PLIST_KEY))
+            .and_then(plist::Value::as_dictionary)
+            .ok_or(Error::InvalidHashString)?;
+        let field = |key: &str| dict.get(key).ok_or(Error::InvalidHashString);
+        Ok(ShadowHash {
+            entropy: field("entropy")?
+                .as_data()
+                .ok_or(Error::InvalidHashString)?
+                .to_vec(),
+            salt: field("salt")?
+                .as_data()
+                .ok_or(Error::InvalidHashString)?
+                .to_vec(),
+            iterations: field("iterations")?
+                .as_unsigned_integer()
+                .and_then(|i| u32::try_from(i).ok())
+                .ok_or(Error::InvalidRounds)?,
+        })
+    }
+
+    /// Serialize the hash as a binary `ShadowHashData` property list.
+    pub fn to_plist(&self) -> Result<Vec<u8>> {
+        let mut hash = plist::Dictionary::new();
+        hash.insert("entropy".into(), plist::Value::Data(self.entropy.clone()));
+        hash.insert("iterations".into(), plist::Value::from(self.iterations));
+        hash.insert("salt".into(), plist::Value::Data(self.salt.clone()));
+        let mut dict = plist::Dictionary::new();
+        dict.insert(PLIST_KEY.into(), plist::Value::Dictionary(hash));
+
+        let mut out = Vec::new();
+        plist::Value::Dictionary(dict)
+            .to_writer_binary(&mut out)
+            .map_err(|_e| Error::EncodingError)?;
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "macos_plist")]
+    use crate::encode::hex_decode;
+    use crate::encode::hex_encode;
+
+    const ENTROPY: &str = "de1f233bd64631eab85c064963bee16c945f880371d245221b5324c308876784\
+                           0292e5c885b9a71f8d2f516eea4a05ba76b457dd05574e9e23d609dc282f63d5\
+                           23b1908913d0cdf2b702b7dacfa6df60df679d64251cdec91ed6da27ff705f8d\
+                           d59707b4d6037e97c8812c2f3c41b584b69dc450e11a824d6e39502e1bd51ab4";
+
+    #[test]
+    fn custom() {
+        let salt = (0..32).collect::<Vec<u8>>();
+        let h = super::hash_with(1000, &salt, "password").unwrap();
+        assert_eq!(hex_encode(&h.entropy), ENTROPY);
+        assert!(super::verify("password", &h));
+        assert!(!super::verify("passwore", &h));
+    }
+
+    #[test]
+    #[cfg(feature = "macos_plist")]
+    fn plist() {
+        let blob = hex_decode(
+            "62706c6973743030d101025f101453414c5445442d5348413531322d50424b444632d3030405\
+             06070857656e74726f70795a697465726174696f6e735473616c744f1080de1f233bd64631ea\
+             b85c064963bee16c945f880371d245221b5324c3088767840292e5c885b9a71f8d2f516eea4a\
+             05ba76b457dd05574e9e23d609dc282f63d523b1908913d0cdf2b702b7dacfa6df60df679d64\
+             251cdec91ed6da27ff705f8dd59707b4d6037e97c8812c2f3c41b584b69dc450e11a824d6e39\
+             502e1bd51ab41103e84f1020000102030405060708090a0b0c0d0e0f101112131415161718191a\
+             1b1c1d1e1f080b2229313c41c4c700000000000001010000000000000009000000000000000000\
+             000000000000ea",
+        )
+        .unwrap();
+        let h = super::ShadowHash::from_plist(&blob).unwrap();
+        assert_eq!(h.iterations, 1000);
+        assert_eq!(h.entropy, hex_decode(ENTROPY).unwrap());
+        assert!(super::verify("password", &h));
+        assert_eq!(
+            super::ShadowHash::from_plist(&h.to_plist().unwrap()).unwrap(),
+            h
+        );
+    }
+}
@@ -26,7 +26,8 @@
 //!
 //! # Parameters
 //!
-//! * __Password length__: unlimited.
+//! * __Password length__: up to the [process-wide
+//!   maximum](crate::set_max_password_len), 4096 bytes by default.
 //!
 //! * __Salt length__: 0 to 8 characters. Default is 8.
 //!
@@ -55,16 +56,17 @@ use crate::{
 };
 
 // magic + (0..8 salt) + '$' + checksum
-pub(crate) const HASH_LENGTH_MIN: usize = MAGIC_LEN + 0 + 1 + 22;
+pub(crate) const HASH_LENGTH_MIN: usize = MAGIC_LEN + 1 + 22;
 pub(crate) const HASH_LENGTH_MAX: usize = MAGIC_LEN + 8 + 1 + 22;
 pub(crate) const HASH_LENGTH: RangeInclusive<usize> = HASH_LENGTH_MIN..=HASH_LENGTH_MAX;
 
 /// Maximium salt length.
 pub const MAX_SALT_LEN: usize = 8;
 const MD5_MAGIC: &str = "$1$";
-const MD5_TRANSPOSE: &[u8] = b"\x0c\x06\x00\x0d\x07\x01\x0e\x08\x02\x0f\x09\x03\x05\x0a\x04\x0b";
+pub(crate) const MD5_TRANSPOSE: &[u8] = b"\x0c\x06\x00\x0d\x07\x01\x0e\x08\x02\x0f\x09\x03\x05\x0a\x04\x0b";
 
 pub(crate) fn do_md5_crypt(pass: &[u8], salt: &str, magic: &str) -> Result<String> {
+    crate::policy::check_password_len(pass)?;
     let mut dummy_buf = [0u8; 6];
     bcrypt_hash64_decode(salt, &mut dummy_buf)?;
 
@@ -140,9 +142,9 @@ pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
 
 const MAGIC_LEN: usize = 3;
 
-fn parse_md5_hash(hash: &str) -> Result<HashSetup> {
+fn parse_md5_hash(hash: &str) -> Result<HashSetup<'_>> {
     let mut hs = parse::HashSlice::new(hash);
-    if hs.take(MAGIC_LEN).unwrap_or("X") != MD5_MAGIC {
+    if hs.take(MAGIC_LEN) != Some(MD5_MAGIC) {
         return Err(Error::InvalidHashString);
     }
 
@@ -150,6 +152,7 @@ fn parse_md5_hash(hash: &str) -> Result<HashSetup> {
     Ok(HashSetup {
         salt: Some(salt),
         rounds: None,
+        salt_bytes: None,
     })
 }
 
@@ -166,7 +169,7 @@ where
     B: AsRef<[u8]>,
 {
     let hs = IHS::into_hash_setup(param, parse_md5_hash)?;
-    let salt = match hs.salt {
+    let salt = match hs.salt_str()? {
         None => &random::gen_salt_str(MAX_SALT_LEN),
         Some(salt) => (salt.len() <= MAX_SALT_LEN)
             .then_some(salt)
@@ -199,7 +202,8 @@ mod tests {
             super::hash_with(
                 HashSetup {
                     salt: Some("5pZSV9va"),
-                    rounds: None
+                    rounds: None,
+                    salt_bytes: None,
                 },
                 "password"
             )
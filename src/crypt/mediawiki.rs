@@ -0,0 +1,161 @@
+//! MediaWiki MD5 password hashes.
+//!
+//! Before switching to PBKDF2, MediaWiki stored user passwords in the
+//! `user_password` column as MD5 digests. The __:B:__ type is the digest of
+//! the salt, a hyphen, and the hexadecimal digest of the password; the older
+//! __:A:__ type is the unsalted digest of the password. Both are very weak,
+//! and are supported so that wiki user migrations can verify them.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::mediawiki;
+//!
+//! let h = ":B:838c83e1:e4ab7024509eef084cdabd03d8b2972c";
+//! assert!(mediawiki::verify("password", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: unlimited, may not contain a `:`. Default is 8 random
+//!   hexadecimal characters. An empty salt produces the unsalted __:A:__
+//!   type.
+//!
+//! * __Rounds__: 1 (fixed).
+//!
+//! # Hash Format
+//!
+//! The format of the hash is __`:B:`__*`{salt}`*__`:`__*`{checksum}`*, or
+//! __`:A:`__*`{checksum}`* for the unsalted type, where *`{checksum}`* is the
+//! 32-character lowercase hexadecimal encoding of the digest.
+
+use md5::{Digest, Md5};
+
+use crate::{
+    HashSetup, IntoHashSetup, consteq,
+    encode::hex_encode,
+    error::{Error, Result},
+    hash::{Hash, HashV},
+    parse::{self, HashIterator},
+    random,
+};
+
+const UNSALTED_MAGIC: &str = ":A:";
+const SALTED_MAGIC: &str = ":B:";
+pub(crate) const DEFAULT_SALT_LEN: usize = 4;
+
+#[inline]
+pub(crate) fn is_mediawiki_hash(hash: &str) -> bool {
+    hash.starts_with(UNSALTED_MAGIC) || hash.starts_with(SALTED_MAGIC)
+}
+
+fn do_mediawiki_crypt(pass: &[u8], salt: &str) -> Result<String> {
+    if salt.contains(':') {
+        return Err(Error::EncodingError);
+    }
+    let pass_hex = hex_encode(&Md5::digest(pass));
+    if salt.is_empty() {
+        return Ok(format!("{UNSALTED_MAGIC}{pass_hex}"));
+    }
+    let mut dgst = Md5::new();
+    dgst.update(salt.as_bytes());
+    dgst.update(b"-");
+    dgst.update(pass_hex.as_bytes());
+    Ok(format!(
+        "{SALTED_MAGIC}{salt}:{}",
+        hex_encode(&dgst.finalize())
+    ))
+}
+
+/// Hash a password with a randomly generated salt.
+///
+/// An error is returned if the system random number generator cannot
+/// be opened.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    let mut salt_buf = [0u8; DEFAULT_SALT_LEN];
+    random::gen_salt_bytes(&mut salt_buf);
+
+    let hash = do_mediawiki_crypt(pass.as_ref(), &hex_encode(&salt_buf))?;
+    Ok(Hash::Mediawiki(HashV(hash)))
+}
+
+fn parse_mediawiki_hash(hash: &str) -> Result<HashSetup<'_>> {
+    let mut hs = parse::HashSlice::new(hash);
+    let salt = match hs.take(SALTED_MAGIC.len()) {
+        Some(UNSALTED_MAGIC) => "",
+        Some(SALTED_MAGIC) => hs.take_until(b':').ok_or(Error::InvalidHashString)?,
+        _ => return Err(Error::InvalidHashString),
+    };
+    Ok(HashSetup {
+        salt: Some(salt),
+        rounds: None,
+        salt_bytes: None,
+    })
+}
+
+/// Hash a password with user-provided parameters.
+///
+/// If the `param` argument is a `&str`, it must be in the final hash
+/// format. The salt is parsed out of that value; for the __:A:__ type, it's
+/// empty. If the salt contains a `:`, an error is returned.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+pub fn hash_with<'a, IHS, B>(param: IHS, pass: B) -> Result<Hash>
+where
+    IHS: IntoHashSetup<'a>,
+    B: AsRef<[u8]>,
+{
+    let hs = IHS::into_hash_setup(param, parse_mediawiki_hash)?;
+    let hash = match hs.salt_str()? {
+        Some(salt) => do_mediawiki_crypt(pass.as_ref(), salt)?,
+        None => {
+            let mut salt_buf = [0u8; DEFAULT_SALT_LEN];
+            random::gen_salt_bytes(&mut salt_buf);
+            do_mediawiki_crypt(pass.as_ref(), &hex_encode(&salt_buf))?
+        }
+    };
+    Ok(Hash::Mediawiki(HashV(hash)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    #[allow(deprecated)]
+    consteq(hash, hash_with(hash, pass))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HashSetup;
+
+    #[test]
+    #[allow(deprecated)]
+    fn custom() {
+        assert_eq!(
+            super::hash_with(":B:838c83e1:e4ab7024509eef084cdabd03d8b2972c", "password").unwrap(),
+            ":B:838c83e1:e4ab7024509eef084cdabd03d8b2972c"
+        );
+        assert_eq!(
+            super::hash_with(HashSetup::default().salt("838c83e1"), "password").unwrap(),
+            ":B:838c83e1:e4ab7024509eef084cdabd03d8b2972c"
+        );
+        assert!(super::verify(
+            "password",
+            ":A:5f4dcc3b5aa765d61d8327deb882cf99"
+        ));
+        assert!(!super::verify(
+            "passwore",
+            ":B:838c83e1:e4ab7024509eef084cdabd03d8b2972c"
+        ));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    #[should_panic(expected = "value: EncodingError")]
+    fn bad_salt_chars() {
+        let _ = super::hash_with(HashSetup::default().salt("83:c"), "password").unwrap();
+    }
+}
@@ -6,15 +6,99 @@ mod md5;
 #[cfg(feature = "md5")]
 pub mod md5;
 
+#[cfg(feature = "aix")]
+pub mod aix;
+
 #[cfg(feature = "apr1")]
 pub mod apr1;
 
+#[cfg(feature = "balloon")]
+pub mod balloon;
+
 #[cfg(feature = "bcrypt")]
 pub mod bcrypt;
 
+#[cfg(feature = "bcrypt_sha256")]
+pub mod bcrypt_sha256;
+
+#[cfg(feature = "bigcrypt")]
+pub mod bigcrypt;
+
 #[cfg(feature = "bsdi")]
 pub mod bsdi;
 
+#[cfg(feature = "cisco")]
+pub mod cisco;
+
+#[cfg(feature = "cram_md5")]
+pub mod cram_md5;
+
+#[cfg(feature = "crypt16")]
+pub mod crypt16;
+
+#[cfg(feature = "django")]
+pub mod django;
+
+#[cfg(feature = "dovecot")]
+pub mod dovecot;
+
+#[cfg(feature = "grub")]
+pub mod grub;
+
+#[cfg(feature = "hexdigest")]
+pub mod hexdigest;
+
+#[cfg(feature = "htdigest")]
+pub mod htdigest;
+
+#[cfg(feature = "vbulletin")]
+pub mod ipb;
+
+#[cfg(feature = "juniper")]
+pub mod juniper;
+
+#[cfg(feature = "ldap")]
+pub mod ldap;
+
+#[cfg(feature = "macos")]
+pub mod macos;
+
+#[cfg(feature = "mediawiki")]
+pub mod mediawiki;
+
+#[cfg(feature = "mscash")]
+pub mod mscash;
+
+#[cfg(feature = "mscash")]
+pub mod mscash2;
+
+#[cfg(feature = "mssql")]
+pub mod mssql;
+
+#[cfg(feature = "mysql323")]
+pub mod mysql323;
+
+#[cfg(feature = "mysql41")]
+pub mod mysql41;
+
+#[cfg(feature = "oracle11g")]
+pub mod oracle11g;
+
+#[cfg(feature = "pbkdf2")]
+pub mod pbkdf2;
+
+#[cfg(feature = "pepper")]
+pub mod pepper;
+
+#[cfg(feature = "pg_md5")]
+pub mod pg_md5;
+
+#[cfg(feature = "scram")]
+pub mod scram;
+
+#[cfg(feature = "scrypt")]
+pub mod scrypt;
+
 #[cfg(feature = "sha1")]
 pub mod sha1;
 
@@ -24,5 +108,14 @@ pub mod sha256;
 #[cfg(feature = "sha2")]
 pub mod sha512;
 
+#[cfg(feature = "smbpasswd")]
+pub mod smbpasswd;
+
 #[cfg(feature = "unix")]
 pub mod unix;
+
+#[cfg(feature = "vbulletin")]
+pub mod vbulletin;
+
+#[cfg(feature = "wrapped")]
+pub mod wrapped;
@@ -0,0 +1,91 @@
+//! Windows Domain Cached Credentials (DCC, mscash).
+//!
+//! Windows caches the credentials of domain users who logged on to a machine,
+//! so that they can log on again when the domain controller is unreachable.
+//! The first version of the scheme, used up to Windows XP and Server 2003,
+//! stores the MD4 digest of the NT hash of the password followed by the
+//! lowercased user name. The user name acts as a salt, so it must be supplied
+//! to both hashing and verification. A single round of MD4 is very weak; the
+//! scheme should be used for backward compatibility and forensics only. Newer
+//! releases use [DCC2](super::mscash2).
+//!
+//! The hash can't be verified without the user name, so it's not represented
+//! in [`Hash`](crate::Hash), and the functions in this module work with plain
+//! strings.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::mscash;
+//!
+//! let h = "64cd29e36a8431a2b111378564a10631";
+//! assert!(mscash::verify("test1", "test1", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited. The password is encoded as UTF-16LE.
+//!
+//! * __Salt__: the user name, lowercased and encoded as UTF-16LE.
+//!
+//! * __Rounds__: 1 (fixed).
+//!
+//! # Hash Format
+//!
+//! The hash is the 32-character lowercase hexadecimal encoding of the digest.
+
+use crate::{
+    ct::ct_eq,
+    encode::hex_encode,
+    internal::md4::{md4, nt_hash},
+};
+
+fn utf16le(s: &str) -> Vec<u8> {
+    s.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+pub(crate) fn do_mscash(pass: &str, user: &str) -> ([u8; 16], Vec<u8>) {
+    let user = utf16le(&user.to_lowercase());
+    let mut data = nt_hash(pass).to_vec();
+    data.extend_from_slice(&user);
+    (md4(&data), user)
+}
+
+/// Hash a password for the given user name.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash(pass: &str, user: &str) -> String {
+    hex_encode(&do_mscash(pass, user).0)
+}
+
+/// Verify that the hash corresponds to a password and a user name.
+#[inline]
+pub fn verify(pass: &str, user: &str, hash: &str) -> bool {
+    ct_eq(hash, hex_encode(&do_mscash(pass, user).0))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[allow(deprecated)]
+    fn custom() {
+        assert_eq!(
+            super::hash("test1", "test1"),
+            "64cd29e36a8431a2b111378564a10631"
+        );
+        assert_eq!(
+            super::hash("hashcat", "3060147285011"),
+            "4dd8965d1d476fa0d026722989a6b772"
+        );
+        assert!(super::verify(
+            "test1",
+            "TEST1",
+            "64cd29e36a8431a2b111378564a10631"
+        ));
+        assert!(!super::verify(
+            "test2",
+            "test1",
+            "64cd29e36a8431a2b111378564a10631"
+        ));
+    }
+}
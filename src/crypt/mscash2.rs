@@ -0,0 +1,109 @@
+//! Windows Domain Cached Credentials version 2 (DCC2, mscash2).
+//!
+//! Starting with Windows Vista and Server 2008, cached domain credentials
+//! are derived from the [DCC](super::mscash) hash with PBKDF2-HMAC-SHA1,
+//! using the lowercased user name as the salt. The default iteration count
+//! makes it considerably stronger than its predecessor, though the user
+//! name is a poor salt.
+//!
+//! The hash can't be verified without the user name, so it's not represented
+//! in [`Hash`](crate::Hash), and the functions in this module work with plain
+//! strings.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::mscash2;
+//!
+//! let h = "e4e938d12fe5974dc42a90120bd9c90f";
+//! assert!(mscash2::verify("hashcat", "tom", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited. The password is encoded as UTF-16LE.
+//!
+//! * __Salt__: the user name, lowercased and encoded as UTF-16LE.
+//!
+//! * __Rounds__: 1 to 2<sup>32</sup>-1. Default is 10240, which is the
+//!   value used by Windows.
+//!
+//! # Hash Format
+//!
+//! The hash is the 32-character lowercase hexadecimal encoding of the
+//! 16-byte derived key.
+
+use pbkdf2::pbkdf2_hmac;
+use sha1::Sha1;
+
+use super::mscash::do_mscash;
+use crate::{
+    ct::ct_eq,
+    encode::hex_encode,
+    error::{Error, Result},
+};
+
+const MIN_ROUNDS: u32 = 1;
+
+/// Default number of rounds.
+pub const DEFAULT_ROUNDS: u32 = 10240;
+
+fn do_mscash2(pass: &str, user: &str, rounds: u32) -> String {
+    let (dcc, user) = do_mscash(pass, user);
+    let mut output = [0u8; 16];
+    pbkdf2_hmac::<Sha1>(&dcc, &user, rounds, &mut output);
+    hex_encode(&output)
+}
+
+/// Hash a password for the given user name, with the default number
+/// of rounds.
+#[inline]
+pub fn hash(pass: &str, user: &str) -> String {
+    do_mscash2(pass, user, DEFAULT_ROUNDS)
+}
+
+/// Hash a password for the given user name, with a custom number of rounds.
+///
+/// An error is returned if the rounds value is out of range.
+pub fn hash_with(rounds: u32, pass: &str, user: &str) -> Result<String> {
+    if rounds < MIN_ROUNDS {
+        return Err(Error::BadRounds {
+            value: rounds,
+            allowed: MIN_ROUNDS..=u32::MAX,
+        });
+    }
+    Ok(do_mscash2(pass, user, rounds))
+}
+
+/// Verify that the hash corresponds to a password and a user name, with
+/// the default number of rounds.
+#[inline]
+pub fn verify(pass: &str, user: &str, hash: &str) -> bool {
+    ct_eq(hash, do_mscash2(pass, user, DEFAULT_ROUNDS))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn custom() {
+        assert_eq!(
+            super::hash("test1", "test1"),
+            "607bbe89611e37446e736f7856515bf8"
+        );
+        assert_eq!(
+            super::hash_with(10240, "password", "Administrator").unwrap(),
+            "4c253e4b65c007a8cd683ea57bc43c76"
+        );
+        assert!(!super::verify(
+            "hashcas",
+            "tom",
+            "e4e938d12fe5974dc42a90120bd9c90f"
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "value: BadRounds")]
+    fn zero_rounds() {
+        let _ = super::hash_with(0, "password", "user").unwrap();
+    }
+}
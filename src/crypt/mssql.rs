@@ -0,0 +1,306 @@
+//! Microsoft SQL Server login password hashes.
+//!
+//! SQL Server stores the password hashes of SQL logins in
+//! `sys.sql_logins`, as a hexadecimal string starting with a version
+//! header. Since SQL Server 2012, the hash is the SHA-512 digest of the
+//! password and a short salt, with the __`0x0200`__ header. Earlier releases
+//! used SHA-1 with the __`0x0100`__ header; SQL Server 2000 additionally
+//! stored the digest of the uppercased password, which made the hash
+//! effectively case-insensitive.
+//!
+//! All versions use a single round of a fast hash, and are easily
+//! brute-forced. They are supported for credential auditing and migration.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::mssql;
+//!
+//! let h = "0x020001020304\
+//!          34EA1B17802FD95EA6316BD61D2C94622CA3812793E8FB1672487B5C904A45A3\
+//!          1B2AB4A78890D563D2FCF5663E46FE797D71550494BE50CF4915D3F4D55EC375";
+//! assert!(mssql::verify("hashcat", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: up to 128 characters, which isn't enforced. The
+//!   password must be valid UTF-8, and is encoded as UTF-16LE.
+//!
+//! * __Salt length__: 4 bytes.
+//!
+//! * __Rounds__: 1 (fixed).
+//!
+//! # Hash Format
+//!
+//! The format of the hash is *`{version}{salt}{checksum}`*, where:
+//!
+//! * *`{version}`* is __`0x0200`__ for SQL Server 2012 and later, and
+//!   __`0x0100`__ for SQL Server 2000 and 2005.
+//!
+//! * *`{salt}`* is the 8-character uppercase hexadecimal encoding of the
+//!   salt. A custom salt must be given in the same encoding.
+//!
+//! * *`{checksum}`* is the uppercase hexadecimal encoding of the digest of
+//!   the password followed by the salt. For SQL Server 2000, it's followed by
+//!   the encoding of the digest of the uppercased password and the salt.
+
+use sha1::{Digest, Sha1};
+use sha2::Sha512;
+
+use crate::{
+    AlgorithmParams, HashSetup, consteq,
+    encode::{hex_decode, hex_encode},
+    error::{Error, Result},
+    hash::{Hash, HashV},
+    random,
+};
+
+const DEFAULT_VERSION: MssqlVersion = MssqlVersion::V2012;
+const VERSION_LEN: usize = 6;
+
+/// Salt length, in bytes.
+pub const SALT_LEN: usize = 4;
+
+/// Identifiers of the SQL Server hash versions which can be produced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MssqlVersion {
+    /// SQL Server 2000, with the case-insensitive digest.
+    V2000,
+    /// SQL Server 2005 and 2008.
+    V2005,
+    /// SQL Server 2012 and later.
+    V2012,
+}
+
+impl MssqlVersion {
+    fn header(&self) -> &'static str {
+        match self {
+            MssqlVersion::V2000 | MssqlVersion::V2005 => "0x0100",
+            MssqlVersion::V2012 => "0x0200",
+        }
+    }
+
+    fn hash_len(&self) -> usize {
+        let checksum_len = match self {
+            MssqlVersion::V2000 => 80,
+            MssqlVersion::V2005 => 40,
+            MssqlVersion::V2012 => 128,
+        };
+        VERSION_LEN + 2 * SALT_LEN + checksum_len
+    }
+
+    fn from_hash(hash: &str) -> Option<Self> {
+        [
+            MssqlVersion::V2000,
+            MssqlVersion::V2005,
+            MssqlVersion::V2012,
+        ]
+        .into_iter()
+        .find(|v| hash.len() == v.hash_len() && hash.starts_with(v.header()))
+    }
+}
+
+/// Setup struct for SQL Server hashes.
+///
+/// In addition to a custom salt value, a SQL Server hash can use different
+/// versions of the algorithm.
+#[derive(Default)]
+pub struct MssqlSetup<'a> {
+    /// Custom salt.
+    pub salt: Option<&'a str>,
+    /// Algorithm version.
+    pub version: Option<MssqlVersion>,
+}
+
+impl<'a> MssqlSetup<'a> {
+    /// Configure custom salt for SQL Server hash
+    pub fn salt(mut self, salt: &'a str) -> Self {
+        self.salt = Some(salt);
+        self
+    }
+    /// Configure algorithm version for SQL Server hash
+    pub fn version(mut self, version: MssqlVersion) -> Self {
+        self.version = Some(version);
+        self
+    }
+}
+
+/// A trait for converting a type into an `MssqlSetup` struct.
+pub trait IntoMssqlSetup<'a> {
+    /// The conversion function.
+    fn into_mssql_setup(self) -> Result<MssqlSetup<'a>>;
+}
+
+impl<'a> IntoMssqlSetup<'a> for &'a str {
+    fn into_mssql_setup(self) -> Result<MssqlSetup<'a>> {
+        let version = MssqlVersion::from_hash(self).ok_or(Error::InvalidHashString)?;
+        let salt = self
+            .get(VERSION_LEN..VERSION_LEN + 2 * SALT_LEN)
+            .ok_or(Error::EncodingError)?;
+        Ok(MssqlSetup {
+            salt: Some(salt),
+            version: Some(version),
+        })
+    }
+}
+
+impl<'a> IntoMssqlSetup<'a> for HashSetup<'a> {
+    fn into_mssql_setup(self) -> Result<MssqlSetup<'a>> {
+        Ok(MssqlSetup {
+            salt: self.salt_str()?,
+            version: Some(DEFAULT_VERSION),
+        })
+    }
+}
+
+impl<'a> IntoMssqlSetup<'a> for MssqlSetup<'a> {
+    fn into_mssql_setup(self) -> Result<MssqlSetup<'a>> {
+        Ok(self)
+    }
+}
+
+impl<'a> IntoMssqlSetup<'a> for AlgorithmParams<'a> {
+    fn into_mssql_setup(self) -> Result<MssqlSetup<'a>> {
+        #[allow(unreachable_patterns)]
+        match self {
+            AlgorithmParams::Basic(hs) => hs.into_mssql_setup(),
+            AlgorithmParams::Mssql(setup) => Ok(setup),
+            _ => Err(Error::InvalidHashString),
+        }
+    }
+}
+
+#[inline]
+pub(crate) fn is_mssql_hash(hash: &str) -> bool {
+    MssqlVersion::from_hash(hash).is_some()
+}
+
+fn salted_digest<D: Digest>(pass: &str, salt: &[u8]) -> String {
+    let mut dgst = D::new();
+    dgst.update(
+        pass.encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<u8>>(),
+    );
+    dgst.update(salt);
+    hex_encode(&dgst.finalize()).to_uppercase()
+}
+
+fn do_mssql_crypt(pass: &[u8], salt: &[u8], version: MssqlVersion) -> Result<String> {
+    if salt.len() != SALT_LEN {
+        return Err(Error::InsufficientLength);
+    }
+    let pass = std::str::from_utf8(pass).map_err(Error::InvalidUtf8)?;
+    let checksum = match version {
+        MssqlVersion::V2000 => {
+            salted_digest::<Sha1>(pass, salt) + &salted_digest::<Sha1>(&pass.to_uppercase(), salt)
+        }
+        MssqlVersion::V2005 => salted_digest::<Sha1>(pass, salt),
+        MssqlVersion::V2012 => salted_digest::<Sha512>(pass, salt),
+    };
+    Ok(format!(
+        "{}{}{checksum}",
+        version.header(),
+        hex_encode(salt).to_uppercase()
+    ))
+}
+
+/// Hash a password with a randomly generated salt and the default
+/// version (SQL Server 2012).
+///
+/// An error is returned if the system random number generator cannot
+/// be opened, or if the password isn't valid UTF-8.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    let mut salt_buf = [0u8; SALT_LEN];
+    random::gen_salt_bytes(&mut salt_buf);
+
+    let hash = do_mssql_crypt(pass.as_ref(), &salt_buf, DEFAULT_VERSION)?;
+    Ok(Hash::Mssql(HashV(hash)))
+}
+
+/// Hash a password with user-provided parameters.
+///
+/// If the `param` argument is a `&str`, it must be in the final hash
+/// format. The version and the salt are parsed out of that value. If a
+/// `HashSetup` is used, the version is set to default, and the rounds value is
+/// ignored. The salt must be hex encoded and exactly 4 bytes long; if it
+/// isn't, an error is returned.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+pub fn hash_with<'a, IMS, B>(param: IMS, pass: B) -> Result<Hash>
+where
+    IMS: IntoMssqlSetup<'a>,
+    B: AsRef<[u8]>,
+{
+    let ms = param.into_mssql_setup()?;
+    let version = ms.version.unwrap_or(DEFAULT_VERSION);
+    let salt = match ms.salt {
+        Some(salt) => hex_decode(salt)?,
+        None => {
+            let mut salt_buf = vec![0u8; SALT_LEN];
+            random::gen_salt_bytes(&mut salt_buf);
+            salt_buf
+        }
+    };
+
+    let hash = do_mssql_crypt(pass.as_ref(), &salt, version)?;
+    Ok(Hash::Mssql(HashV(hash)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    #[allow(deprecated)]
+    consteq(hash, hash_with(hash, pass))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HashSetup, MssqlSetup, MssqlVersion};
+
+    #[test]
+    #[allow(deprecated)]
+    fn custom() {
+        assert_eq!(
+            super::hash_with(HashSetup::default().salt("01020304"), "hashcat").unwrap(),
+            "0x020001020304\
+             34EA1B17802FD95EA6316BD61D2C94622CA3812793E8FB1672487B5C904A45A3\
+             1B2AB4A78890D563D2FCF5663E46FE797D71550494BE50CF4915D3F4D55EC375"
+        );
+        assert!(!super::verify(
+            "hashcae",
+            "0x020001020304\
+             34EA1B17802FD95EA6316BD61D2C94622CA3812793E8FB1672487B5C904A45A3\
+             1B2AB4A78890D563D2FCF5663E46FE797D71550494BE50CF4915D3F4D55EC375"
+        ));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn sha1() {
+        assert_eq!(
+            super::hash_with(
+                MssqlSetup::default()
+                    .salt("0A0B0C0D")
+                    .version(MssqlVersion::V2005),
+                "password"
+            )
+            .unwrap(),
+            "0x01000A0B0C0D047F6C48CEFD1E1B260F727880F3DFB211F8D7E0"
+        );
+        assert!(super::verify(
+            "password",
+            "0x01000A0B0C0D047F6C48CEFD1E1B260F727880F3DFB211F8D7E0\
+             AD611718456E99F9D9D9F0F60736AA423C98E26A"
+        ));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    #[should_panic(expected = "value: InsufficientLength")]
+    fn short_salt() {
+        let _ = super::hash_with(HashSetup::default().salt("0102"), "hashcat").unwrap();
+    }
+}
@@ -0,0 +1,92 @@
+//! MySQL pre-4.1 `OLD_PASSWORD()` hash.
+//!
+//! Before version 4.1, MySQL stored account passwords as a 64-bit value
+//! computed by an ad hoc, non-cryptographic mixing function. It's unsalted,
+//! trivially reversible by brute force, and ignores spaces and tabs in the
+//! password. It's supported only so that migration tooling can recognize and
+//! verify such hashes, and is not enabled by default; use the `mysql323`
+//! feature to build it.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::mysql323;
+//!
+//! let h = "6f8c114b58f2ce9e";
+//! assert!(mysql323::verify("mypass", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited. Spaces and tabs are ignored.
+//!
+//! * __Salt length__: none.
+//!
+//! * __Rounds__: 1 (fixed).
+//!
+//! # Hash Format
+//!
+//! The hash is the 16-character lowercase hexadecimal encoding of the two
+//! 31-bit halves of the checksum.
+
+use crate::{
+    consteq,
+    error::Result,
+    hash::{Hash, HashV},
+};
+
+pub(crate) const HASH_LENGTH: usize = 16;
+
+pub(crate) fn is_mysql323_hash(hash: &str) -> bool {
+    hash.len() == HASH_LENGTH && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn do_mysql323_crypt(pass: &[u8]) -> Result<String> {
+    let mut nr1: u32 = 0x5030_5735;
+    let mut nr2: u32 = 0x1234_5671;
+    let mut add: u32 = 7;
+    for &c in pass.iter().filter(|&&c| c != b' ' && c != b'\t') {
+        let tmp = c as u32;
+        nr1 ^= ((nr1 & 63).wrapping_add(add))
+            .wrapping_mul(tmp)
+            .wrapping_add(nr1 << 8);
+        nr2 = nr2.wrapping_add((nr2 << 8) ^ nr1);
+        add = add.wrapping_add(tmp);
+    }
+    Ok(format!(
+        "{:08x}{:08x}",
+        nr1 & 0x7fff_ffff,
+        nr2 & 0x7fff_ffff
+    ))
+}
+
+/// Hash a password.
+///
+/// The scheme is unsalted, so the same password always produces the
+/// same hash.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    Ok(Hash::Mysql323(HashV(do_mysql323_crypt(pass.as_ref())?)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    consteq(
+        hash,
+        do_mysql323_crypt(pass.as_ref()).map(|s| Hash::Mysql323(HashV(s))),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[allow(deprecated)]
+    fn custom() {
+        assert_eq!(super::hash("mypass").unwrap(), "6f8c114b58f2ce9e");
+        assert_eq!(super::hash("password").unwrap(), "5d2e19393cc5ef67");
+        assert!(super::verify("pass word", "5d2e19393cc5ef67"));
+        assert!(!super::verify("passwore", "5d2e19393cc5ef67"));
+    }
+}
@@ -0,0 +1,85 @@
+//! MySQL 4.1+ double SHA-1 hash.
+//!
+//! Starting with version 4.1, MySQL stored account passwords as the SHA-1
+//! digest of the SHA-1 digest of the password. The scheme is unsalted and
+//! uses a single round of a fast hash, so it's easily brute-forced and should
+//! be used for backward compatibility only, e.g. to check passwords before
+//! migrating accounts to a stronger algorithm.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::mysql41;
+//!
+//! let h = "*2470C0C06DEE42FD1618BB99005ADCA2EC9D1E19";
+//! assert!(mysql41::verify("password", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: none.
+//!
+//! * __Rounds__: 1 (fixed).
+//!
+//! # Hash Format
+//!
+//! The format of the hash is __`*`__*`{checksum}`*, where *`{checksum}`* is
+//! the 40-character uppercase hexadecimal encoding of the digest.
+
+use sha1::{Digest, Sha1};
+
+use crate::{
+    consteq,
+    encode::hex_encode,
+    error::Result,
+    hash::{Hash, HashV},
+};
+
+// `*` + checksum
+pub(crate) const HASH_LENGTH: usize = 1 + 40;
+
+fn do_mysql41_crypt(pass: &[u8]) -> Result<String> {
+    let digest = Sha1::digest(Sha1::digest(pass));
+    Ok(format!("*{}", hex_encode(&digest).to_uppercase()))
+}
+
+/// Hash a password.
+///
+/// The scheme is unsalted, so the same password always produces the
+/// same hash.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    Ok(Hash::Mysql41(HashV(do_mysql41_crypt(pass.as_ref())?)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    consteq(
+        hash,
+        do_mysql41_crypt(pass.as_ref()).map(|s| Hash::Mysql41(HashV(s))),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[allow(deprecated)]
+    fn custom() {
+        assert_eq!(
+            super::hash("password").unwrap(),
+            "*2470C0C06DEE42FD1618BB99005ADCA2EC9D1E19"
+        );
+        assert_eq!(
+            super::hash("mysql").unwrap(),
+            "*E74858DB86EBA20BC33D0AECAE8A8108C56B17FA"
+        );
+        assert!(!super::verify(
+            "passwore",
+            "*2470C0C06DEE42FD1618BB99005ADCA2EC9D1E19"
+        ));
+    }
+}
@@ -0,0 +1,176 @@
+//! Oracle 11g `S:` password hash.
+//!
+//! Oracle Database 11g stores a salted SHA-1 password hash in the `SPARE4`
+//! column of `SYS.USER$`, prefixed with `S:`. It's a single round of a fast
+//! hash with a short salt, and has been superseded by the PBKDF2-based `T:`
+//! verifier in 12c. The scheme is supported so that database audits can
+//! check such hashes, and is not enabled by default; use the `oracle11g`
+//! feature to build it.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::oracle11g;
+//!
+//! let h = "S:BA1806B1C8C76C8AD5F1D7845361B79C604C09C11C2D3E4F5A6B7C8D9E0F";
+//! assert!(oracle11g::verify("password", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited. Oracle uppercases case-insensitive
+//!   passwords before hashing, which must be done by the caller.
+//!
+//! * __Salt length__: 10 bytes.
+//!
+//! * __Rounds__: 1 (fixed).
+//!
+//! # Hash Format
+//!
+//! The format of the hash is __`S:`__*`{checksum}{salt}`*, where
+//! *`{checksum}`* is the 40-character uppercase hexadecimal encoding of the
+//! digest of the password followed by the salt, and *`{salt}`* is the
+//! 20-character uppercase hexadecimal encoding of the salt. A custom salt
+//! must be given in the same encoding. If the `SPARE4` value also contains
+//! other verifiers, e.g. __`;T:`__*`{...}`*, only the `S:` part should be
+//! passed to the functions in this module.
+
+use sha1::{Digest, Sha1};
+
+use crate::{
+    HashSetup, IntoHashSetup, consteq,
+    encode::{hex_decode, hex_encode},
+    error::{Error, Result},
+    hash::{Hash, HashV},
+    parse::{self, HashIterator},
+    random,
+};
+
+const ORACLE11G_MAGIC: &str = "S:";
+const CHECKSUM_LEN: usize = 40;
+
+/// Salt length, in bytes.
+pub const SALT_LEN: usize = 10;
+
+// magic + checksum + salt
+pub(crate) const HASH_LENGTH: usize = 2 + CHECKSUM_LEN + 2 * SALT_LEN;
+
+pub(crate) fn is_oracle11g_hash(hash: &str) -> bool {
+    hash.len() == HASH_LENGTH
+        && hash.starts_with(ORACLE11G_MAGIC)
+        && hash[ORACLE11G_MAGIC.len()..]
+            .bytes()
+            .all(|b| b.is_ascii_hexdigit())
+}
+
+fn do_oracle11g_crypt(pass: &[u8], salt: &[u8]) -> Result<String> {
+    if salt.len() != SALT_LEN {
+        return Err(Error::InsufficientLength);
+    }
+    let mut dgst = Sha1::new();
+    dgst.update(pass);
+    dgst.update(salt);
+    Ok(format!(
+        "{ORACLE11G_MAGIC}{}{}",
+        hex_encode(&dgst.finalize()).to_uppercase(),
+        hex_encode(salt).to_uppercase()
+    ))
+}
+
+/// Hash a password with a randomly generated salt.
+///
+/// An error is returned if the system random number generator cannot
+/// be opened.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    let mut salt_buf = [0u8; SALT_LEN];
+    random::gen_salt_bytes(&mut salt_buf);
+
+    let hash = do_oracle11g_crypt(pass.as_ref(), &salt_buf)?;
+    Ok(Hash::Oracle11g(HashV(hash)))
+}
+
+fn parse_oracle11g_hash(hash: &str) -> Result<HashSetup<'_>> {
+    let mut hs = parse::HashSlice::new(hash);
+    if hs.take(ORACLE11G_MAGIC.len()) != Some(ORACLE11G_MAGIC) {
+        return Err(Error::InvalidHashString);
+    }
+    hs.take(CHECKSUM_LEN).ok_or(Error::InsufficientLength)?;
+    let salt = hs.take(2 * SALT_LEN).ok_or(Error::InsufficientLength)?;
+    Ok(HashSetup {
+        salt: Some(salt),
+        rounds: None,
+        salt_bytes: None,
+    })
+}
+
+/// Hash a password with user-provided parameters.
+///
+/// If the `param` argument is a `&str`, it must be in the final hash
+/// format. The salt is parsed out of that value. The salt must be
+/// hex encoded and exactly 10 bytes long; if it isn't, an error is returned.
+/// The rounds value is ignored.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+pub fn hash_with<'a, IHS, B>(param: IHS, pass: B) -> Result<Hash>
+where
+    IHS: IntoHashSetup<'a>,
+    B: AsRef<[u8]>,
+{
+    let hs = IHS::into_hash_setup(param, parse_oracle11g_hash)?;
+    let salt = match hs.salt_str()? {
+        Some(salt) => hex_decode(salt)?,
+        None => {
+            let mut salt_buf = vec![0u8; SALT_LEN];
+            random::gen_salt_bytes(&mut salt_buf);
+            salt_buf
+        }
+    };
+
+    let hash = do_oracle11g_crypt(pass.as_ref(), &salt)?;
+    Ok(Hash::Oracle11g(HashV(hash)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    #[allow(deprecated)]
+    consteq(hash, hash_with(hash, pass))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HashSetup;
+
+    #[test]
+    #[allow(deprecated)]
+    fn custom() {
+        assert_eq!(
+            super::hash_with(
+                "S:BA1806B1C8C76C8AD5F1D7845361B79C604C09C11C2D3E4F5A6B7C8D9E0F",
+                "password"
+            )
+            .unwrap(),
+            "S:BA1806B1C8C76C8AD5F1D7845361B79C604C09C11C2D3E4F5A6B7C8D9E0F"
+        );
+        assert_eq!(
+            super::hash_with(
+                HashSetup::default().salt("1c2d3e4f5a6b7c8d9e0f"),
+                "password"
+            )
+            .unwrap(),
+            "S:BA1806B1C8C76C8AD5F1D7845361B79C604C09C11C2D3E4F5A6B7C8D9E0F"
+        );
+        assert!(!super::verify(
+            "PASSWORD",
+            "S:BA1806B1C8C76C8AD5F1D7845361B79C604C09C11C2D3E4F5A6B7C8D9E0F"
+        ));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    #[should_panic(expected = "value: InsufficientLength")]
+    fn short_salt() {
+        let _ = super::hash_with(HashSetup::default().salt("1c2d3e4f"), "password").unwrap();
+    }
+}
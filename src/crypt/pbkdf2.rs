@@ -0,0 +1,339 @@
+//! Passlib PBKDF2 based hashes.
+//!
+//! The Python Passlib library defines modular crypt formats for PBKDF2
+//! with HMAC-SHA1, HMAC-SHA256, and HMAC-SHA512. They are commonly found
+//! in databases of Python applications. With a sufficient number of rounds,
+//! all three variants are considered secure.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::pbkdf2;
+//!
+//! let h = "$pbkdf2-sha256$6400$0ZrzXitFSGltTQnBWOsdAw$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M";
+//! assert!(pbkdf2::verify("password", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: 0 to 1024 bytes. Default is 16 random bytes.
+//!
+//! * __Rounds__: 1 to 2<sup>32</sup>-1. Default is 131000 for SHA-1,
+//!   29000 for SHA-256, and 25000 for SHA-512, matching Passlib.
+//!
+//! # Hash Format
+//!
+//! The format of the hash is
+//! __`$`__*`{ident}`*__`$`__*`{rounds}`*__$__*`{salt}`*__$__*`{checksum}`*, where:
+//!
+//! * *`{ident}`* is one of **pbkdf2** (SHA-1), **pbkdf2-sha256**, or
+//!   **pbkdf2-sha512**.
+//!
+//! * *`{rounds}`* is the number of PBKDF2 iterations, encoded as a decimal
+//!   number.
+//!
+//! * *`{salt}`* is the salt, encoded in Passlib's adapted Base64 (standard
+//!   Base64 with `.` in place of `+` and without padding).
+//!
+//! * *`{checksum}`* is the derived key, as long as the output of the underlying
+//!   hash, in the same encoding as the salt.
+
+use std::fmt;
+
+use pbkdf2::pbkdf2_hmac;
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+
+use crate::{
+    AlgorithmParams, HashSetup, consteq,
+    encode::{ab64_decode, ab64_encode},
+    error::{Error, Result},
+    hash::{Hash, HashV},
+    parse::{self, HashIterator},
+    random,
+};
+
+const MIN_ROUNDS: u32 = 1;
+pub(crate) const MAX_SALT_LEN: usize = 1024;
+pub(crate) const DEFAULT_ALGORITHM: Pbkdf2Algorithm = Pbkdf2Algorithm::Sha256;
+
+/// Default salt length, in bytes.
+pub const DEFAULT_SALT_LEN: usize = 16;
+
+/// Identifiers of the supported PBKDF2 pseudo-random functions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pbkdf2Algorithm {
+    /// PBKDF2 with HMAC-SHA1.
+    Sha1,
+    /// PBKDF2 with HMAC-SHA256.
+    Sha256,
+    /// PBKDF2 with HMAC-SHA512.
+    Sha512,
+}
+
+impl Pbkdf2Algorithm {
+    fn from_ident(ident: &str) -> Option<Self> {
+        match ident {
+            "pbkdf2" => Some(Pbkdf2Algorithm::Sha1),
+            "pbkdf2-sha256" => Some(Pbkdf2Algorithm::Sha256),
+            "pbkdf2-sha512" => Some(Pbkdf2Algorithm::Sha512),
+            _ => None,
+        }
+    }
+
+    /// Default number of rounds for the algorithm.
+    pub fn default_rounds(&self) -> u32 {
+        match self {
+            Pbkdf2Algorithm::Sha1 => 131000,
+            Pbkdf2Algorithm::Sha256 => 29000,
+            Pbkdf2Algorithm::Sha512 => 25000,
+        }
+    }
+}
+
+impl fmt::Display for Pbkdf2Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ident = match self {
+            Pbkdf2Algorithm::Sha1 => "pbkdf2",
+            Pbkdf2Algorithm::Sha256 => "pbkdf2-sha256",
+            Pbkdf2Algorithm::Sha512 => "pbkdf2-sha512",
+        };
+        write!(f, "{ident}")
+    }
+}
+
+/// Setup struct for Passlib PBKDF2 hashes.
+///
+/// In addition to custom salt and rounds values, a PBKDF2 hash can use
+/// different pseudo-random functions. The salt is given in the adapted
+/// Base64 encoding used in the hash string.
+#[derive(Default)]
+pub struct Pbkdf2Setup<'a> {
+    /// Custom salt.
+    pub salt: Option<&'a str>,
+    /// Number of rounds.
+    pub rounds: Option<u32>,
+    /// Pseudo-random function.
+    pub algorithm: Option<Pbkdf2Algorithm>,
+}
+
+impl<'a> Pbkdf2Setup<'a> {
+    /// Configure custom salt for PBKDF2 hash
+    pub fn salt(mut self, salt: &'a str) -> Self {
+        self.salt = Some(salt);
+        self
+    }
+    /// Configure custom number of rounds for PBKDF2 hash
+    pub fn rounds(mut self, rounds: u32) -> Self {
+        self.rounds = Some(rounds);
+        self
+    }
+    /// Configure pseudo-random function for PBKDF2 hash
+    pub fn algorithm(mut self, algorithm: Pbkdf2Algorithm) -> Self {
+        self.algorithm = Some(algorithm);
+        self
+    }
+}
+
+/// A trait for converting a type into a `Pbkdf2Setup` struct.
+pub trait IntoPbkdf2Setup<'a> {
+    /// The conversion function.
+    fn into_pbkdf2_setup(self) -> Result<Pbkdf2Setup<'a>>;
+}
+
+impl<'a> IntoPbkdf2Setup<'a> for &'a str {
+    fn into_pbkdf2_setup(self) -> Result<Pbkdf2Setup<'a>> {
+        let mut hs = parse::HashSlice::new(self);
+        if hs.take(1) != Some("$") {
+            return Err(Error::InvalidHashString);
+        }
+        let algorithm = hs
+            .take_until(b'$')
+            .and_then(Pbkdf2Algorithm::from_ident)
+            .ok_or(Error::InvalidHashString)?;
+        let rounds = hs
+            .take_until(b'$')
+            .ok_or(Error::InvalidHashString)?
+            .parse::<u32>()
+            .map_err(Error::InvalidNumber)?;
+        let salt = hs.take_until(b'$').ok_or(Error::InvalidHashString)?;
+        Ok(Pbkdf2Setup {
+            salt: Some(salt),
+            rounds: Some(rounds),
+            algorithm: Some(algorithm),
+        })
+    }
+}
+
+impl<'a> IntoPbkdf2Setup<'a> for HashSetup<'a> {
+    fn into_pbkdf2_setup(self) -> Result<Pbkdf2Setup<'a>> {
+        Ok(Pbkdf2Setup {
+            salt: self.salt_str()?,
+            rounds: self.rounds,
+            algorithm: Some(DEFAULT_ALGORITHM),
+        })
+    }
+}
+
+impl<'a> IntoPbkdf2Setup<'a> for Pbkdf2Setup<'a> {
+    fn into_pbkdf2_setup(self) -> Result<Pbkdf2Setup<'a>> {
+        Ok(self)
+    }
+}
+
+impl<'a> IntoPbkdf2Setup<'a> for AlgorithmParams<'a> {
+    fn into_pbkdf2_setup(self) -> Result<Pbkdf2Setup<'a>> {
+        #[allow(unreachable_patterns)]
+        match self {
+            AlgorithmParams::Basic(hs) => hs.into_pbkdf2_setup(),
+            AlgorithmParams::Pbkdf2(setup) => Ok(setup),
+            _ => Err(Error::InvalidHashString),
+        }
+    }
+}
+
+pub(crate) fn is_pbkdf2_ident(ident: &str) -> bool {
+    Pbkdf2Algorithm::from_ident(ident).is_some()
+}
+
+fn do_pbkdf2_crypt(
+    pass: &[u8],
+    salt: &[u8],
+    rounds: u32,
+    algorithm: Pbkdf2Algorithm,
+) -> Result<String> {
+    let mut output = [0u8; 64];
+    let output = match algorithm {
+        Pbkdf2Algorithm::Sha1 => {
+            pbkdf2_hmac::<Sha1>(pass, salt, rounds, &mut output[..20]);
+            &output[..20]
+        }
+        Pbkdf2Algorithm::Sha256 => {
+            pbkdf2_hmac::<Sha256>(pass, salt, rounds, &mut output[..32]);
+            &output[..32]
+        }
+        Pbkdf2Algorithm::Sha512 => {
+            pbkdf2_hmac::<Sha512>(pass, salt, rounds, &mut output);
+            &output[..]
+        }
+    };
+    Ok(format!(
+        "${algorithm}${rounds}${}${}",
+        ab64_encode(salt),
+        ab64_encode(output)
+    ))
+}
+
+/// Hash a password with a randomly generated salt and the default number
+/// of rounds for the default algorithm (__pbkdf2-sha256__).
+///
+/// An error is returned if the system random number generator cannot
+/// be opened.
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    let mut salt_buf = [0u8; DEFAULT_SALT_LEN];
+    random::gen_salt_bytes(&mut salt_buf);
+
+    let hash = do_pbkdf2_crypt(
+        pass.as_ref(),
+        &salt_buf,
+        DEFAULT_ALGORITHM.default_rounds(),
+        DEFAULT_ALGORITHM,
+    )?;
+    Ok(Hash::Pbkdf2(HashV(hash)))
+}
+
+/// Hash a password with user-provided parameters.
+///
+/// If the `param` argument is a `&str`, it must be in the final hash
+/// format. The algorithm, number of rounds, and the salt are parsed out
+/// of that value. An ordinary `HashSetup` can be converted into `Pbkdf2Setup`,
+/// which will set the algorithm to default. An error is returned if the salt
+/// is too long or incorrectly encoded, or if the rounds value is out of range.
+pub fn hash_with<'a, IPS, B>(param: IPS, pass: B) -> Result<Hash>
+where
+    IPS: IntoPbkdf2Setup<'a>,
+    B: AsRef<[u8]>,
+{
+    let ps = param.into_pbkdf2_setup()?;
+    let algorithm = ps.algorithm.unwrap_or(DEFAULT_ALGORITHM);
+    let rounds = if let Some(r) = ps.rounds {
+        if r < MIN_ROUNDS {
+            return Err(Error::BadRounds {
+                value: r,
+                allowed: MIN_ROUNDS..=u32::MAX,
+            });
+        }
+        r
+    } else {
+        algorithm.default_rounds()
+    };
+
+    let salt = match ps.salt {
+        Some(salt) => ab64_decode(salt)?,
+        None => {
+            let mut salt_buf = vec![0u8; DEFAULT_SALT_LEN];
+            random::gen_salt_bytes(&mut salt_buf);
+            salt_buf
+        }
+    };
+    if salt.len() > MAX_SALT_LEN {
+        return Err(Error::InvalidHashString);
+    }
+
+    let hash = do_pbkdf2_crypt(pass.as_ref(), &salt, rounds, algorithm)?;
+    Ok(Hash::Pbkdf2(HashV(hash)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    consteq(hash, hash_with(hash, pass))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HashSetup, Pbkdf2Algorithm, Pbkdf2Setup};
+
+    #[test]
+    fn custom() {
+        assert_eq!(
+            super::hash_with("$pbkdf2$1212$qPfg0ytMmmseX218i5oPHg$", "password").unwrap(),
+            "$pbkdf2$1212$qPfg0ytMmmseX218i5oPHg$Qvu5vf1PErMRkjHHGRRGNpWsgX0"
+        );
+        assert_eq!(
+            super::hash_with(
+                HashSetup::default()
+                    .salt("qPfg0ytMmmseX218i5oPHg")
+                    .rounds(1212),
+                "password"
+            )
+            .unwrap(),
+            "$pbkdf2-sha256$1212$qPfg0ytMmmseX218i5oPHg$VnT468lT8cvBAf.lBVC9fL8GJtGLsaxFGpt7sqZdIEs"
+        );
+        assert_eq!(
+            super::hash_with(
+                Pbkdf2Setup::default()
+                    .salt("qPfg0ytMmmseX218i5oPHg")
+                    .rounds(1212)
+                    .algorithm(Pbkdf2Algorithm::Sha512),
+                "password"
+            )
+            .unwrap(),
+            "$pbkdf2-sha512$1212$qPfg0ytMmmseX218i5oPHg$78BpF6teth9brya1tK67sKOAOm7Z1HiF3GiF/\
+             ov2IJf5uqf1GumDCcHjGUQwafkH/oE.R6zWzteOR2YerShXbg"
+        );
+        assert!(!super::verify(
+            "passwore",
+            "$pbkdf2$1212$qPfg0ytMmmseX218i5oPHg$Qvu5vf1PErMRkjHHGRRGNpWsgX0"
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "value: EncodingError")]
+    fn bad_salt_chars() {
+        let _ = super::hash_with(HashSetup::default().salt("a+b").rounds(1), "password").unwrap();
+    }
+}
@@ -0,0 +1,197 @@
+//! Peppered hashes.
+//!
+//! A pepper is a secret key which is kept apart from the password database,
+//! e.g. in the application's configuration or in a key management service.
+//! The password is first hashed with HMAC-SHA256 keyed with the pepper, and
+//! the Base64 encoding of the result is hashed with an ordinary algorithm.
+//! Without the pepper, a stolen database can't be brute-forced, since the
+//! inputs of the inner hashes are unknown.
+//!
+//! The hash is marked as peppered, so that [`verify`] knows to apply the
+//! pepper, and can still check the unpeppered hashes stored before the
+//! pepper was introduced. The pepper itself is never stored, and must be
+//! supplied whenever a password is hashed or verified; for this reason,
+//! the module has no `hash` function, and [`Hash::verify`] always fails for
+//! peppered hashes. The module is not enabled by default; use the `pepper`
+//! feature to build it.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::{Algorithm, crypt::pepper::{self, PepperSetup}};
+//!
+//! let h = pepper::hash_with(PepperSetup::new(b"secret", Algorithm::Sha512), "password").unwrap();
+//! assert!(pepper::verify("password", b"secret", &h));
+//! assert!(!pepper::verify("password", b"public", &h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Pepper length__: unlimited.
+//!
+//! * __Inner hash__: any algorithm which can hash a password by itself, with
+//!   its own parameters. Since the inner password is a 44-character string,
+//!   the password length limits of the algorithm don't apply.
+//!
+//! # Hash Format
+//!
+//! The format of the hash is __`$pepper$`__*`{inner}`*, where *`{inner}`*
+//! is the complete hash of the peppered password, including its leading
+//! __`$`__ if it has one. The inner hash can't be another peppered hash, a
+//! [wrapped](mod@super::wrapped) one, or an LDAP __{CRYPT}__ one.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{
+    Algorithm, AlgorithmParams,
+    encode::base64_encode,
+    error::{Error, Result},
+    hash::{Hash, HashV},
+};
+
+const PEPPER_MAGIC: &str = "$pepper$";
+
+/// Setup struct for peppered hashes.
+///
+/// The pepper and the algorithm of the inner hash are required. The inner
+/// hash is customized with the parameters of its algorithm; if they're not
+/// given, the algorithm's defaults are used.
+pub struct PepperSetup<'a> {
+    /// Secret key.
+    pub pepper: &'a [u8],
+    /// Algorithm of the inner hash.
+    pub algorithm: Algorithm,
+    /// Parameters of the inner hash.
+    pub params: Option<AlgorithmParams<'a>>,
+}
+
+impl<'a> PepperSetup<'a> {
+    /// Create a setup with the pepper and the algorithm of the inner hash
+    pub fn new(pepper: &'a [u8], algorithm: Algorithm) -> Self {
+        PepperSetup {
+            pepper,
+            algorithm,
+            params: None,
+        }
+    }
+    /// Configure parameters of the inner hash
+    pub fn params<P: Into<AlgorithmParams<'a>>>(mut self, params: P) -> Self {
+        self.params = Some(params.into());
+        self
+    }
+}
+
+fn apply_pepper(pepper: &[u8], pass: &[u8]) -> String {
+    let mut hmac = Hmac::<Sha256>::new_from_slice(pepper).expect("HMAC accepts any key length");
+    hmac.update(pass);
+    base64_encode(&hmac.finalize().into_bytes())
+}
+
+/// Hash a password with a pepper.
+///
+/// An error is returned if the inner algorithm can't hash a password by
+/// itself, or if hashing with the given parameters fails.
+pub fn hash_with<B: AsRef<[u8]>>(setup: PepperSetup<'_>, pass: B) -> Result<Hash> {
+    if setup.algorithm == Algorithm::Peppered {
+        return Err(Error::InvalidHashString);
+    }
+    let peppered = apply_pepper(setup.pepper, pass.as_ref());
+    let inner = match setup.params {
+        Some(params) => setup.algorithm.hash_with(params, peppered)?,
+        None => setup.algorithm.hash(peppered)?,
+    };
+    Ok(Hash::Peppered(HashV(format!(
+        "{PEPPER_MAGIC}{}",
+        inner.as_str()
+    ))))
+}
+
+/// Verify that the hash corresponds to a password.
+///
+/// If the hash is peppered, the pepper is applied to the password before
+/// checking it against the inner hash. Other hashes are checked like with
+/// [`Hash::verify`], which allows verifying hashes made before the pepper
+/// was introduced.
+pub fn verify<B: AsRef<[u8]>>(pass: B, pepper: &[u8], hash: &str) -> bool {
+    match hash.strip_prefix(PEPPER_MAGIC) {
+        Some(inner) => Hash::try_from(inner)
+            .is_ok_and(|inner| inner.verify(apply_pepper(pepper, pass.as_ref()))),
+        None => Hash::try_from(hash).is_ok_and(|hash| hash.verify(pass)),
+    }
+}
+
+/// Split the inner hash out of a peppered hash.
+///
+/// The inner hash can't be a peppered, wrapped, or LDAP __{CRYPT}__ hash,
+/// which keeps the formats from nesting.
+pub(crate) fn inner_hash(hash: &str) -> Option<&str> {
+    hash.strip_prefix(PEPPER_MAGIC).filter(|inner| {
+        !inner.starts_with(PEPPER_MAGIC)
+            && !inner.starts_with("$wrapped$")
+            && !inner.starts_with("{CRYPT}")
+            && Hash::try_from(*inner).is_ok()
+    })
+}
+
+/// Check whether a string is in the peppered hash format.
+pub(crate) fn is_peppered_hash(hash: &str) -> bool {
+    inner_hash(hash).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PepperSetup;
+    use crate::{Algorithm, Hash, HashSetup, error::Error};
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn custom() {
+        let setup = PepperSetup::new(b"pepper", Algorithm::Sha512)
+            .params(HashSetup::default().salt("saltsalt").rounds(1000));
+        let h = super::hash_with(setup, "password").unwrap();
+        assert_eq!(
+            h,
+            "$pepper$$6$rounds=1000$saltsalt$fIX8O5J8gVCXsuO5YuX3N6FAJzyEzL67wu0OJCFdk4oxJfLLl\
+             zZImjnHLJStrl2mDkXy.z1fLuzPoa8fuyXgG."
+        );
+        assert_eq!(Hash::try_from(h.as_str()).unwrap(), h);
+        assert!(super::verify("password", b"pepper", &h));
+        assert!(!super::verify("password", b"peppe", &h));
+        assert!(!h.verify("password"));
+    }
+
+    #[test]
+    #[cfg(feature = "md5")]
+    fn unpeppered() {
+        assert!(super::verify(
+            "password",
+            b"pepper",
+            "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0"
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "md5")]
+    fn nested_hash() {
+        for h in [
+            "$pepper$$pepper$$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0",
+            "$pepper${CRYPT}$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0",
+        ] {
+            assert!(matches!(
+                Hash::try_from(h),
+                Err(Error::UnsupportedScheme { prefix }) if prefix == "$pepper$"
+            ));
+            assert!(!super::verify("password", b"pepper", h));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "value: InvalidHashString")]
+    fn nested() {
+        let _ =
+            super::hash_with(PepperSetup::new(b"pepper", Algorithm::Peppered), "password").unwrap();
+    }
+}
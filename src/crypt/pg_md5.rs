@@ -0,0 +1,78 @@
+//! PostgreSQL MD5 role password hash.
+//!
+//! PostgreSQL releases before 10 stored role passwords in `pg_authid` as the
+//! MD5 digest of the password concatenated with the role name. The role name
+//! acts as a salt, so it must be supplied to both hashing and verification.
+//! A single round of MD5 is very weak, and newer servers use
+//! [SCRAM-SHA-256](super::scram) instead; the scheme should be used for
+//! backward compatibility only.
+//!
+//! Since the hash can't be verified without the role name, it's not
+//! represented in [`Hash`](crate::Hash), and the functions in this module
+//! work with plain strings.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::pg_md5;
+//!
+//! let h = "md532e12f215ba27cb750c9e093ce4b5127";
+//! assert!(pg_md5::verify("password", "postgres", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt__: the role name.
+//!
+//! * __Rounds__: 1 (fixed).
+//!
+//! # Hash Format
+//!
+//! The format of the hash is __`md5`__*`{checksum}`*, where *`{checksum}`*
+//! is the 32-character lowercase hexadecimal encoding of the digest of the
+//! password followed by the role name.
+
+use md5::{Digest, Md5};
+
+use crate::{ct::ct_eq, encode::hex_encode};
+
+const PG_MD5_MAGIC: &str = "md5";
+
+fn do_pg_md5_crypt(pass: &[u8], user: &str) -> String {
+    let mut dgst = Md5::new();
+    dgst.update(pass);
+    dgst.update(user.as_bytes());
+    format!("{PG_MD5_MAGIC}{}", hex_encode(&dgst.finalize()))
+}
+
+/// Hash a password for the given role name.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B, user: &str) -> String {
+    do_pg_md5_crypt(pass.as_ref(), user)
+}
+
+/// Verify that the hash corresponds to a password and a role name.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, user: &str, hash: &str) -> bool {
+    ct_eq(hash, do_pg_md5_crypt(pass.as_ref(), user))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[allow(deprecated)]
+    fn custom() {
+        assert_eq!(
+            super::hash("password", "postgres"),
+            "md532e12f215ba27cb750c9e093ce4b5127"
+        );
+        assert!(!super::verify(
+            "password",
+            "postgre",
+            "md532e12f215ba27cb750c9e093ce4b5127"
+        ));
+    }
+}
@@ -0,0 +1,217 @@
+//! PostgreSQL SCRAM-SHA-256 verifier.
+//!
+//! Since version 10, PostgreSQL can store role passwords in `pg_authid` as
+//! SCRAM-SHA-256 verifiers, as defined by RFC 5802 and RFC 7677. The server
+//! keeps the keys derived from the password with PBKDF2-HMAC-SHA256, which
+//! are enough to authenticate a client, and to verify a plaintext password.
+//! With a sufficient number of iterations, the scheme is considered secure.
+//!
+//! PostgreSQL normalizes passwords with SASLprep before deriving the keys,
+//! if they are valid UTF-8. This module uses the password bytes as given, which
+//! produces identical results for passwords consisting of printable ASCII.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::scram;
+//!
+//! let h = "SCRAM-SHA-256$4096:AAECAwQFBgcICQoLDA0ODw==$\
+//!          4PSH04DiBM59z6mw0gs6x1r6+duXYQ+R0KwGZr+W5/o=:\
+//!          IgPInY95tTazYxnARISZb/eTxuX/JRwWgrM9ByaOUIk=";
+//! assert!(scram::verify("password", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: any number of bytes. Default is 16 random bytes.
+//!
+//! * __Rounds__: 1 to 2<sup>32</sup>-1. Default is 4096, which is the value
+//!   used by PostgreSQL.
+//!
+//! # Hash Format
+//!
+//! The format of the hash is
+//! __`SCRAM-SHA-256$`__*`{rounds}`*__:__*`{salt}`*__$__*`{storedkey}`*__:__*`{serverkey}`*,
+//! where:
+//!
+//! * *`{rounds}`* is the number of PBKDF2 iterations, encoded as a decimal
+//!   number.
+//!
+//! * *`{salt}`* is the standard (padded) Base64 encoding of the salt. A custom
+//!   salt must be given in the same encoding.
+//!
+//! * *`{storedkey}`* and *`{serverkey}`* are the standard (padded) Base64
+//!   encodings of the SCRAM StoredKey and ServerKey.
+
+use hmac::{Hmac, Mac};
+use pbkdf2::pbkdf2_hmac;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    HashSetup, IntoHashSetup, consteq,
+    encode::{base64_decode, base64_encode},
+    error::{Error, Result},
+    hash::{Hash, HashV},
+    parse::{self, HashIterator},
+    random,
+};
+
+const SCRAM_MAGIC: &str = "SCRAM-SHA-256$";
+const MIN_ROUNDS: u32 = 1;
+
+/// Default number of rounds.
+pub const DEFAULT_ROUNDS: u32 = 4096;
+
+/// Default salt length, in bytes.
+pub const DEFAULT_SALT_LEN: usize = 16;
+
+#[inline]
+pub(crate) fn is_scram_hash(hash: &str) -> bool {
+    hash.starts_with(SCRAM_MAGIC)
+}
+
+fn scram_key(salted: &[u8], name: &[u8]) -> Result<Vec<u8>> {
+    let mut hmac = Hmac::<Sha256>::new_from_slice(salted).map_err(|_| Error::InsufficientLength)?;
+    hmac.update(name);
+    Ok(hmac.finalize().into_bytes().to_vec())
+}
+
+fn do_scram_crypt(pass: &[u8], salt: &[u8], rounds: u32) -> Result<String> {
+    let mut salted = [0u8; 32];
+    pbkdf2_hmac::<Sha256>(pass, salt, rounds, &mut salted);
+
+    let client_key = scram_key(&salted, b"Client Key")?;
+    let stored_key = Sha256::digest(&client_key);
+    let server_key = scram_key(&salted, b"Server Key")?;
+    Ok(format!(
+        "{SCRAM_MAGIC}{rounds}:{}${}:{}",
+        base64_encode(salt),
+        base64_encode(&stored_key),
+        base64_encode(&server_key)
+    ))
+}
+
+/// Hash a password with a randomly generated salt and the default
+/// number of rounds.
+///
+/// An error is returned if the system random number generator cannot
+/// be opened.
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    let mut salt_buf = [0u8; DEFAULT_SALT_LEN];
+    random::gen_salt_bytes(&mut salt_buf);
+
+    let hash = do_scram_crypt(pass.as_ref(), &salt_buf, DEFAULT_ROUNDS)?;
+    Ok(Hash::Scram(HashV(hash)))
+}
+
+fn parse_scram_hash(hash: &str) -> Result<HashSetup<'_>> {
+    let mut hs = parse::HashSlice::new(hash);
+    if hs.take(SCRAM_MAGIC.len()) != Some(SCRAM_MAGIC) {
+        return Err(Error::InvalidHashString);
+    }
+
+    let rounds = hs
+        .take_until(b':')
+        .ok_or(Error::InvalidHashString)?
+        .parse::<u32>()
+        .map_err(Error::InvalidNumber)?;
+    let salt = hs.take_until(b'$').ok_or(Error::InvalidHashString)?;
+
+    Ok(HashSetup {
+        salt: Some(salt),
+        rounds: Some(rounds),
+        salt_bytes: None,
+    })
+}
+
+/// Hash a password with user-provided parameters.
+///
+/// If the `param` argument is a `&str`, it must be in the final hash
+/// format. The number of iterations (rounds) and the salt are parsed out
+/// of that value. The salt must be Base64 encoded; if it isn't, an error
+/// is returned. An out-of-range rounds value will also result in an error.
+pub fn hash_with<'a, IHS, B>(param: IHS, pass: B) -> Result<Hash>
+where
+    IHS: IntoHashSetup<'a>,
+    B: AsRef<[u8]>,
+{
+    let hs = IHS::into_hash_setup(param, parse_scram_hash)?;
+    let rounds = if let Some(r) = hs.rounds {
+        if r < MIN_ROUNDS {
+            return Err(Error::BadRounds {
+                value: r,
+                allowed: MIN_ROUNDS..=u32::MAX,
+            });
+        }
+        r
+    } else {
+        DEFAULT_ROUNDS
+    };
+
+    let salt = match hs.salt_str()? {
+        Some(salt) => base64_decode(salt)?,
+        None => {
+            let mut salt_buf = vec![0u8; DEFAULT_SALT_LEN];
+            random::gen_salt_bytes(&mut salt_buf);
+            salt_buf
+        }
+    };
+
+    let hash = do_scram_crypt(pass.as_ref(), &salt, rounds)?;
+    Ok(Hash::Scram(HashV(hash)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    consteq(hash, hash_with(hash, pass))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HashSetup;
+
+    #[test]
+    fn custom() {
+        assert_eq!(
+            super::hash_with(
+                "SCRAM-SHA-256$4096:AAECAwQFBgcICQoLDA0ODw==$\
+                 4PSH04DiBM59z6mw0gs6x1r6+duXYQ+R0KwGZr+W5/o=:\
+                 IgPInY95tTazYxnARISZb/eTxuX/JRwWgrM9ByaOUIk=",
+                "password"
+            )
+            .unwrap(),
+            "SCRAM-SHA-256$4096:AAECAwQFBgcICQoLDA0ODw==$\
+             4PSH04DiBM59z6mw0gs6x1r6+duXYQ+R0KwGZr+W5/o=:\
+             IgPInY95tTazYxnARISZb/eTxuX/JRwWgrM9ByaOUIk="
+        );
+        assert_eq!(
+            super::hash_with(
+                HashSetup::default()
+                    .salt("c2FsdHNhbHRzYWx0c2FsdA==")
+                    .rounds(1000),
+                "password"
+            )
+            .unwrap(),
+            "SCRAM-SHA-256$1000:c2FsdHNhbHRzYWx0c2FsdA==$\
+             TQgQ/ULZMHOt/8NsCPH9D8aYP6CI2DfFTisDD4VrInM=:\
+             a8G2SD9IzL9WIL4txAzJFZhu+/sAn93tu6hM+jX1754="
+        );
+        assert!(!super::verify(
+            "passwore",
+            "SCRAM-SHA-256$1000:c2FsdHNhbHRzYWx0c2FsdA==$\
+             TQgQ/ULZMHOt/8NsCPH9D8aYP6CI2DfFTisDD4VrInM=:\
+             a8G2SD9IzL9WIL4txAzJFZhu+/sAn93tu6hM+jX1754="
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "value: EncodingError")]
+    fn bad_salt_chars() {
+        let _ =
+            super::hash_with(HashSetup::default().salt("c2Fsd!==").rounds(1), "password").unwrap();
+    }
+}
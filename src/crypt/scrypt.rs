@@ -0,0 +1,293 @@
+//! PHC-format scrypt hash.
+//!
+//! scrypt, by Colin Percival, is a memory-hard key derivation function with
+//! separately tunable CPU/memory cost, block size, and parallelization
+//! parameters. This module implements the encoding used by Passlib and
+//! other applications following the Password Hashing Competition string
+//! format. With the default parameters, scrypt is considered secure, and is
+//! suitable for new passwords.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::scrypt;
+//!
+//! let h = "$scrypt$ln=10,r=8,p=1$c2FsdHNhbHRzYWx0c2FsdA$\
+//!          BVMRKqdiVYikKAaPR1wucsKUKvw4TuPLkdEYtoSHas4";
+//! assert!(scrypt::verify("password", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: any number of bytes. Default is 16 random bytes.
+//!
+//! * __Cost__: 1 to 63, the base-2 logarithm of the CPU/memory cost N.
+//!   Default is 16.
+//!
+//! * __Block size__: r, default is 8.
+//!
+//! * __Parallelization__: p, default is 1.
+//!
+//! The memory required is approximately 128 * N * r bytes, and the parameters
+//! must satisfy the limits of the scrypt specification.
+//!
+//! # Hash Format
+//!
+//! The format of the hash is
+//! __`$scrypt$ln=`__*`{cost}`*__`,r=`__*`{r}`*__`,p=`__*`{p}`*__`$`__*`{salt}`*__`$`__*`{checksum}`*,
+//! where:
+//!
+//! * *`{cost}`*, *`{r}`*, and *`{p}`* are the parameters, encoded as decimal
+//!   numbers.
+//!
+//! * *`{salt}`* is the standard Base64 encoding of the salt, without padding.
+//!   A custom salt must be given in the same encoding.
+//!
+//! * *`{checksum}`* is the 32-byte derived key, in the same encoding as the
+//!   salt.
+
+use scrypt::{Params, scrypt};
+
+use crate::{
+    AlgorithmParams, HashSetup, consteq,
+    encode::{base64_nopad_decode, base64_nopad_encode},
+    error::{Error, Result},
+    hash::{Hash, HashV},
+    phc::PhcString,
+    random,
+};
+
+const SCRYPT_MAGIC: &str = "$scrypt$";
+const OUTPUT_LEN: usize = 32;
+
+/// Default base-2 logarithm of the CPU/memory cost.
+pub const DEFAULT_LOG_N: u8 = 16;
+
+/// Default block size.
+pub const DEFAULT_R: u32 = 8;
+
+/// Default parallelization.
+pub const DEFAULT_P: u32 = 1;
+
+/// Default salt length, in bytes.
+pub const DEFAULT_SALT_LEN: usize = 16;
+
+/// Setup struct for scrypt hashes.
+///
+/// Instead of the number of rounds, scrypt has three cost parameters.
+#[derive(Default)]
+pub struct ScryptSetup<'a> {
+    /// Custom salt.
+    pub salt: Option<&'a str>,
+    /// Custom base-2 logarithm of the CPU/memory cost.
+    pub log_n: Option<u8>,
+    /// Custom block size.
+    pub r: Option<u32>,
+    /// Custom parallelization.
+    pub p: Option<u32>,
+}
+
+impl<'a> ScryptSetup<'a> {
+    /// Configure custom salt for scrypt hash
+    pub fn salt(mut self, salt: &'a str) -> Self {
+        self.salt = Some(salt);
+        self
+    }
+    /// Configure custom CPU/memory cost for scrypt hash
+    pub fn log_n(mut self, log_n: u8) -> Self {
+        self.log_n = Some(log_n);
+        self
+    }
+    /// Configure custom block size for scrypt hash
+    pub fn r(mut self, r: u32) -> Self {
+        self.r = Some(r);
+        self
+    }
+    /// Configure custom parallelization for scrypt hash
+    pub fn p(mut self, p: u32) -> Self {
+        self.p = Some(p);
+        self
+    }
+}
+
+/// A trait for converting a type into a `ScryptSetup` struct.
+pub trait IntoScryptSetup<'a> {
+    /// The conversion function.
+    fn into_scrypt_setup(self) -> Result<ScryptSetup<'a>>;
+}
+
+impl<'a> IntoScryptSetup<'a> for &'a str {
+    fn into_scrypt_setup(self) -> Result<ScryptSetup<'a>> {
+        let phc = PhcString::parse(self)?;
+        if phc.id != "scrypt" || phc.version.is_some() {
+            return Err(Error::InvalidHashString);
+        }
+        let param = |name: &str| phc.param_u32(name)?.ok_or(Error::InvalidHashString);
+        let log_n = u8::try_from(param("ln")?).map_err(|_e| Error::InvalidRounds)?;
+        let r = param("r")?;
+        let p = param("p")?;
+
+        let salt = phc.salt.ok_or(Error::InvalidHashString)?;
+        Ok(ScryptSetup {
+            salt: Some(salt),
+            log_n: Some(log_n),
+            r: Some(r),
+            p: Some(p),
+        })
+    }
+}
+
+impl<'a> IntoScryptSetup<'a> for HashSetup<'a> {
+    fn into_scrypt_setup(self) -> Result<ScryptSetup<'a>> {
+        let log_n = match self.rounds {
+            Some(rounds) => Some(u8::try_from(rounds).map_err(|_e| Error::InvalidRounds)?),
+            None => None,
+        };
+        Ok(ScryptSetup {
+            salt: self.salt_str()?,
+            log_n,
+            r: None,
+            p: None,
+        })
+    }
+}
+
+impl<'a> IntoScryptSetup<'a> for ScryptSetup<'a> {
+    fn into_scrypt_setup(self) -> Result<ScryptSetup<'a>> {
+        Ok(self)
+    }
+}
+
+impl<'a> IntoScryptSetup<'a> for AlgorithmParams<'a> {
+    fn into_scrypt_setup(self) -> Result<ScryptSetup<'a>> {
+        #[allow(unreachable_patterns)]
+        match self {
+            AlgorithmParams::Basic(hs) => hs.into_scrypt_setup(),
+            AlgorithmParams::Scrypt(setup) => Ok(setup),
+            _ => Err(Error::InvalidHashString),
+        }
+    }
+}
+
+fn do_scrypt_crypt(pass: &[u8], salt: &[u8], log_n: u8, r: u32, p: u32) -> Result<String> {
+    let params = Params::new(log_n, r, p, OUTPUT_LEN).map_err(|_e| Error::InvalidRounds)?;
+    let mut output = [0u8; OUTPUT_LEN];
+    scrypt(pass, salt, &params, &mut output).map_err(|_e| Error::InsufficientLength)?;
+    Ok(format!(
+        "{SCRYPT_MAGIC}ln={log_n},r={r},p={p}${}${}",
+        base64_nopad_encode(salt),
+        base64_nopad_encode(&output)
+    ))
+}
+
+/// Hash a password with a randomly generated salt and the default
+/// cost parameters.
+///
+/// An error is returned if the system random number generator cannot
+/// be opened.
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    let mut salt_buf = [0u8; DEFAULT_SALT_LEN];
+    random::gen_salt_bytes(&mut salt_buf);
+
+    let hash = do_scrypt_crypt(
+        pass.as_ref(),
+        &salt_buf,
+        DEFAULT_LOG_N,
+        DEFAULT_R,
+        DEFAULT_P,
+    )?;
+    Ok(Hash::Scrypt(HashV(hash)))
+}
+
+/// Hash a password with user-provided parameters.
+///
+/// If the `param` argument is a `&str`, it must be in the final hash
+/// format. The cost parameters and the salt are parsed out of that value.
+/// If a `HashSetup` is used, its rounds value is used as the base-2 logarithm
+/// of the CPU/memory cost, and the other parameters are set to default. The
+/// salt must be Base64 encoded; if it isn't, an error is returned. A set of
+/// cost parameters outside the limits of scrypt will also result in an error.
+pub fn hash_with<'a, ISS, B>(param: ISS, pass: B) -> Result<Hash>
+where
+    ISS: IntoScryptSetup<'a>,
+    B: AsRef<[u8]>,
+{
+    let ss = param.into_scrypt_setup()?;
+    let log_n = ss.log_n.unwrap_or(DEFAULT_LOG_N);
+    let r = ss.r.unwrap_or(DEFAULT_R);
+    let p = ss.p.unwrap_or(DEFAULT_P);
+
+    let salt = match ss.salt {
+        Some(salt) => base64_nopad_decode(salt)?,
+        None => {
+            let mut salt_buf = vec![0u8; DEFAULT_SALT_LEN];
+            random::gen_salt_bytes(&mut salt_buf);
+            salt_buf
+        }
+    };
+
+    let hash = do_scrypt_crypt(pass.as_ref(), &salt, log_n, r, p)?;
+    Ok(Hash::Scrypt(HashV(hash)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    consteq(hash, hash_with(hash, pass))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HashSetup, ScryptSetup};
+
+    #[test]
+    fn custom() {
+        assert_eq!(
+            super::hash_with(
+                "$scrypt$ln=10,r=8,p=1$c2FsdHNhbHRzYWx0c2FsdA$\
+                 BVMRKqdiVYikKAaPR1wucsKUKvw4TuPLkdEYtoSHas4",
+                "password"
+            )
+            .unwrap(),
+            "$scrypt$ln=10,r=8,p=1$c2FsdHNhbHRzYWx0c2FsdA$\
+             BVMRKqdiVYikKAaPR1wucsKUKvw4TuPLkdEYtoSHas4"
+        );
+        assert_eq!(
+            super::hash_with(
+                HashSetup::default()
+                    .salt("c2FsdHNhbHRzYWx0c2FsdA")
+                    .rounds(10),
+                "password"
+            )
+            .unwrap(),
+            "$scrypt$ln=10,r=8,p=1$c2FsdHNhbHRzYWx0c2FsdA$\
+             BVMRKqdiVYikKAaPR1wucsKUKvw4TuPLkdEYtoSHas4"
+        );
+        assert!(!super::verify(
+            "passwore",
+            "$scrypt$ln=10,r=8,p=1$c2FsdHNhbHRzYWx0c2FsdA$\
+             BVMRKqdiVYikKAaPR1wucsKUKvw4TuPLkdEYtoSHas4"
+        ));
+    }
+
+    #[test]
+    fn params() {
+        assert_eq!(
+            super::hash_with(
+                ScryptSetup::default().salt("c2FsdA").log_n(4).r(2).p(3),
+                "password"
+            )
+            .unwrap(),
+            "$scrypt$ln=4,r=2,p=3$c2FsdA$sR0e2LbO9O1nePQYHJpSwL+Gh/ETrfG6Ab41Mhn60z4"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "value: InvalidRounds")]
+    fn zero_r() {
+        let _ = super::hash_with(ScryptSetup::default().salt("c2FsdA").r(0), "password").unwrap();
+    }
+}
@@ -27,12 +27,14 @@
 //!
 //! # Parameters
 //!
-//! * __Password length__: unlimited.
+//! * __Password length__: up to the [process-wide
+//!   maximum](crate::set_max_password_len), 4096 bytes by default.
 //!
 //! * __Salt length__: 0 to 64 characters. Default is 8.
 //!
 //! * __Rounds__: 1 to 2<sup>32</sup>-1. Default is 24680, which
-//!   is slightly varied if chosen.
+//!   is lowered by up to 25% if chosen; see
+//!   [`set_rounds_variation`](crate::set_rounds_variation).
 //!
 //! # Hash Format
 //!
@@ -60,8 +62,8 @@ use crate::{
     random,
 };
 
-const MIN_ROUNDS: u32 = 1;
-const MAX_SALT_LEN: usize = 64;
+pub(crate) const MIN_ROUNDS: u32 = 1;
+pub(crate) const MAX_SALT_LEN: usize = 64;
 
 // magic + (0...999999999 rounds) + '$' + (0..64 salt) + `$` + checksum
 pub(crate) const HASH_LENGTH_MIN: usize = MAGIC_LEN + 1 + 1 + 1 + 1 + 28;
@@ -77,6 +79,7 @@ pub const DEFAULT_ROUNDS: u32 = 24680;
 pub const DEFAULT_SALT_LEN: usize = 8;
 
 fn do_sha1_crypt(pass: &[u8], salt: &str, rounds: u32) -> Result<String> {
+    crate::policy::check_password_len(pass)?;
     let mut dummy_buf = [0u8; 48];
     bcrypt_hash64_decode(salt, &mut dummy_buf)?;
 
@@ -97,7 +100,8 @@ fn do_sha1_crypt(pass: &[u8], salt: &str, rounds: u32) -> Result<String> {
 }
 
 /// Hash a password with a randomly generated salt and the default
-/// number of rounds (varied by a small amount, like on NetBSD).
+/// number of rounds (varied by a small amount, like on NetBSD; see
+/// [`vary_rounds`](crate::vary_rounds)).
 ///
 /// An error is returned if the system random number generator cannot
 /// be opened.
@@ -110,9 +114,9 @@ pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
 
 const MAGIC_LEN: usize = 6;
 
-fn parse_sha1_hash(hash: &str) -> Result<HashSetup> {
+fn parse_sha1_hash(hash: &str) -> Result<HashSetup<'_>> {
     let mut hs = parse::HashSlice::new(hash);
-    if hs.take(MAGIC_LEN).unwrap_or("X") != "$sha1$" {
+    if hs.take(MAGIC_LEN) != Some("$sha1$") {
         return Err(Error::InvalidHashString);
     }
 
@@ -120,12 +124,13 @@ fn parse_sha1_hash(hash: &str) -> Result<HashSetup> {
         .take_until(b'$')
         .ok_or(Error::InvalidHashString)?
         .parse::<u32>()
-        .map_err(|_e| Error::InvalidRounds)?;
+        .map_err(Error::InvalidNumber)?;
     let salt = hs.take_until(b'$').ok_or(Error::InvalidHashString)?;
 
     Ok(HashSetup {
         salt: Some(salt),
         rounds: Some(rounds),
+        salt_bytes: None,
     })
 }
 
@@ -145,14 +150,17 @@ where
     let hs = IHS::into_hash_setup(param, parse_sha1_hash)?;
     let rounds = if let Some(r) = hs.rounds {
         if r < MIN_ROUNDS {
-            return Err(Error::InvalidRounds);
+            return Err(Error::BadRounds {
+                value: r,
+                allowed: MIN_ROUNDS..=u32::MAX,
+            });
         }
         r
     } else {
         random::vary_rounds(DEFAULT_ROUNDS)
     };
 
-    let salt = match hs.salt {
+    let salt = match hs.salt_str()? {
         None => &random::gen_salt_str(MAX_SALT_LEN),
         Some(salt) => (salt.len() <= MAX_SALT_LEN)
             .then_some(salt)
@@ -160,7 +168,7 @@ where
             .ok_or(Error::InvalidHashString)?,
     };
 
-    let hash = do_sha1_crypt(pass.as_ref(), &salt, rounds)?;
+    let hash = do_sha1_crypt(pass.as_ref(), salt, rounds)?;
     Ok(Hash::Sha1(HashV(hash)))
 }
 
@@ -188,22 +196,28 @@ mod tests {
             super::hash_with(
                 HashSetup {
                     salt: Some("iVdJqfSE"),
-                    rounds: Some(19703)
+                    rounds: Some(19703),
+                    salt_bytes: None,
                 },
                 "password"
             )
             .unwrap(),
             "$sha1$19703$iVdJqfSE$v4qYKl1zqYThwpjJAoKX6UvlHq/a"
         );
+        assert_eq!(
+            super::hash_with(("iVdJqfSE", 19703), "password").unwrap(),
+            "$sha1$19703$iVdJqfSE$v4qYKl1zqYThwpjJAoKX6UvlHq/a"
+        );
     }
 
     #[test]
-    #[should_panic(expected = "value: InvalidRounds")]
+    #[should_panic(expected = "value: BadRounds")]
     fn bad_rounds() {
         let _ = super::hash_with(
             HashSetup {
                 salt: Some("K0Ay"),
                 rounds: Some(0),
+                salt_bytes: None,
             },
             "password",
         )
@@ -24,7 +24,8 @@
 //!
 //! # Parameters
 //!
-//! * __Password length__: unlimited.
+//! * __Password length__: up to the [process-wide
+//!   maximum](crate::set_max_password_len), 4096 bytes by default.
 //!
 //! * __Salt length__: 0 to 16 characters. Default is 16.
 //!
@@ -52,7 +53,7 @@ use std::ops::RangeInclusive;
 use sha2::Sha256;
 
 use crate::{
-    HashSetup, IntoHashSetup, consteq,
+    HashSetup, consteq,
     error::Result,
     hash::{Hash, HashV},
     internal::sha2 as sha2i,
@@ -60,16 +61,18 @@ use crate::{
 };
 
 pub use sha2i::DEFAULT_ROUNDS;
+pub use sha2i::IntoSha2Setup;
 pub use sha2i::MAX_ROUNDS;
 pub use sha2i::MAX_SALT_LEN;
 pub use sha2i::MIN_ROUNDS;
+pub use sha2i::Sha2Setup;
 
 const SHA256_MAGIC: &str = "$5$";
-const SHA256_TRANSPOSE: &[u8] = b"\x14\x0a\x00\x0b\x01\x15\x02\x16\x0c\x17\x0d\x03\x0e\x04\x18\x05\
+pub(crate) const SHA256_TRANSPOSE: &[u8] = b"\x14\x0a\x00\x0b\x01\x15\x02\x16\x0c\x17\x0d\x03\x0e\x04\x18\x05\
 					  \x19\x0f\x1a\x10\x06\x11\x07\x1b\x08\x1c\x12\x1d\x13\x09\x1e\x1f";
 
-// magic + `rounds=` + (1000..999999999 rounds) + `$` + (1..64 salt) + `$` + checksum
-pub(crate) const HASH_LENGTH_MIN: usize = SHA256_MAGIC.len() + 7 + 4 + 1 + 1 + 1 + 43;
+// magic + [`rounds=` + (1000..999999999 rounds) + `$`] + (0..64 salt) + `$` + checksum
+pub(crate) const HASH_LENGTH_MIN: usize = SHA256_MAGIC.len() + 1 + 43;
 pub(crate) const HASH_LENGTH_MAX: usize = SHA256_MAGIC.len() + 7 + 9 + 1 + 64 + 1 + 43;
 pub(crate) const HASH_LENGTH: RangeInclusive<usize> = HASH_LENGTH_MIN..=HASH_LENGTH_MAX;
 
@@ -98,7 +101,7 @@ pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
 }
 
 #[inline]
-fn parse_sha256_hash(hash: &str) -> Result<HashSetup> {
+fn parse_sha256_hash(hash: &str) -> Result<HashSetup<'_>> {
     sha2i::parse_sha2_hash(hash, SHA256_MAGIC)
 }
 
@@ -111,13 +114,13 @@ fn parse_sha256_hash(hash: &str) -> Result<HashSetup> {
 /// will be coerced into the allowed range.
 #[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
 #[inline]
-pub fn hash_with<'a, IHS, B>(param: IHS, pass: B) -> Result<Hash>
+pub fn hash_with<'a, ISS, B>(param: ISS, pass: B) -> Result<Hash>
 where
-    IHS: IntoHashSetup<'a>,
+    ISS: IntoSha2Setup<'a>,
     B: AsRef<[u8]>,
 {
     Ok(Hash::Sha256(HashV(sha2i::sha2_hash_with(
-        IHS::into_hash_setup(param, parse_sha256_hash)?,
+        ISS::into_sha2_setup(param, parse_sha256_hash)?,
         pass.as_ref(),
         do_sha256_crypt,
     )?)))
@@ -149,7 +152,8 @@ mod tests {
             super::hash_with(
                 HashSetup {
                     salt: Some("WH1ABM5sKhxbkgCK"),
-                    rounds: Some(11858)
+                    rounds: Some(11858),
+                    salt_bytes: None,
                 },
                 "test"
             )
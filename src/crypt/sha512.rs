@@ -25,7 +25,8 @@
 //!
 //! # Parameters
 //!
-//! * __Password length__: unlimited.
+//! * __Password length__: up to the [process-wide
+//!   maximum](crate::set_max_password_len), 4096 bytes by default.
 //!
 //! * __Salt length__: 0 to 16 characters. Default is 16.
 //!
@@ -53,7 +54,7 @@ use std::ops::RangeInclusive;
 use sha2::Sha512;
 
 use crate::{
-    HashSetup, IntoHashSetup, consteq,
+    HashSetup, consteq,
     error::Result,
     hash::{Hash, HashV},
     internal::sha2 as sha2i,
@@ -61,18 +62,20 @@ use crate::{
 };
 
 pub use sha2i::DEFAULT_ROUNDS;
+pub use sha2i::IntoSha2Setup;
 pub use sha2i::MAX_ROUNDS;
 pub use sha2i::MAX_SALT_LEN;
 pub use sha2i::MIN_ROUNDS;
+pub use sha2i::Sha2Setup;
 
 const SHA512_MAGIC: &str = "$6$";
-const SHA512_TRANSPOSE: &[u8] = b"\x2a\x15\x00\x01\x2b\x16\x17\x02\x2c\x2d\x18\x03\x04\x2e\x19\x1a\
+pub(crate) const SHA512_TRANSPOSE: &[u8] = b"\x2a\x15\x00\x01\x2b\x16\x17\x02\x2c\x2d\x18\x03\x04\x2e\x19\x1a\
 				  \x05\x2f\x30\x1b\x06\x07\x31\x1c\x1d\x08\x32\x33\x1e\x09\x0a\x34\
 				  \x1f\x20\x0b\x35\x36\x21\x0c\x0d\x37\x22\x23\x0e\x38\x39\x24\x0f\
 				  \x10\x3a\x25\x26\x11\x3b\x3c\x27\x12\x13\x3d\x28\x29\x14\x3e\x3f";
 
-// magic + `rounds=` + (1000..999999999 rounds) + `$` + (1..64 salt) + `$` + checksum
-pub(crate) const HASH_LENGTH_MIN: usize = SHA512_MAGIC.len() + 7 + 4 + 1 + 1 + 1 + 86;
+// magic + [`rounds=` + (1000..999999999 rounds) + `$`] + (0..64 salt) + `$` + checksum
+pub(crate) const HASH_LENGTH_MIN: usize = SHA512_MAGIC.len() + 1 + 86;
 pub(crate) const HASH_LENGTH_MAX: usize = SHA512_MAGIC.len() + 7 + 9 + 1 + 64 + 1 + 86;
 pub(crate) const HASH_LENGTH: RangeInclusive<usize> = HASH_LENGTH_MIN..=HASH_LENGTH_MAX;
 
@@ -101,7 +104,7 @@ pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
 }
 
 #[inline]
-fn parse_sha512_hash(hash: &str) -> Result<HashSetup> {
+fn parse_sha512_hash(hash: &str) -> Result<HashSetup<'_>> {
     sha2i::parse_sha2_hash(hash, SHA512_MAGIC)
 }
 
@@ -111,15 +114,17 @@ fn parse_sha512_hash(hash: &str) -> Result<HashSetup> {
 /// format. The number of rounds and the salt are parsed out of that value.
 /// If the salt is too long, it is truncated to maximum length. If it contains
 /// an invalid character, an error is returned. An out-of-range rounds value
-/// will be coerced into the allowed range.
+/// will be coerced into the allowed range. The __`rounds=`__ field is written
+/// into the hash if it was present in the hash string or the number of rounds
+/// was given, unless [`Sha2Setup`] specifies otherwise.
 #[inline]
-pub fn hash_with<'a, IHS, B>(param: IHS, pass: B) -> Result<Hash>
+pub fn hash_with<'a, ISS, B>(param: ISS, pass: B) -> Result<Hash>
 where
-    IHS: IntoHashSetup<'a>,
+    ISS: IntoSha2Setup<'a>,
     B: AsRef<[u8]>,
 {
     Ok(Hash::Sha512(HashV(sha2i::sha2_hash_with(
-        IHS::into_hash_setup(param, parse_sha512_hash)?,
+        ISS::into_sha2_setup(param, parse_sha512_hash)?,
         pass.as_ref(),
         do_sha512_crypt,
     )?)))
@@ -133,7 +138,7 @@ pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::HashSetup;
+    use super::{HashSetup, Sha2Setup};
 
     #[test]
     fn custom() {
@@ -151,7 +156,8 @@ mod tests {
             super::hash_with(
                 HashSetup {
                     salt: Some("G/gkPn17kHYo0gTF"),
-                    rounds: Some(11531)
+                    rounds: Some(11531),
+                    salt_bytes: None,
                 },
                 "test"
             )
@@ -161,6 +167,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn setup_shapes() {
+        let h = "$6$rounds=11531$G/gkPn17kHYo0gTF$Kq.uZBHlSBXyzsOJXtxJruOOH4yc0Is13\
+                 uY7yK0PvAvXxbvc1w8DO1RzREMhKsc82K/Jh8OquV8FZUlreYPJk1";
+        assert_eq!(
+            super::hash_with(("G/gkPn17kHYo0gTF", 11531), "test").unwrap(),
+            h
+        );
+        assert_eq!(super::hash_with(&h.to_string(), "test").unwrap(), h);
+        assert!(
+            super::hash_with(Some("G/gkPn17kHYo0gTF"), "test")
+                .unwrap()
+                .starts_with("$6$G/gkPn17kHYo0gTF$")
+        );
+        assert_eq!(
+            crate::Algorithm::Sha512
+                .hash_with(("G/gkPn17kHYo0gTF", 11531), "test")
+                .unwrap(),
+            h
+        );
+    }
+
     #[test]
     fn implicit_dflt_rounds() {
         assert_eq!(
@@ -174,4 +202,24 @@ mod tests {
 	     EYekmK8iwV6RNqftUUC8mqDel1J7m3JEbUkbu4YyqSyv/"
         );
     }
+
+    #[test]
+    fn emit_rounds() {
+        let setup = || Sha2Setup::default().salt("G/gkPn17kHYo0gTF");
+        assert_eq!(
+            super::hash_with(setup().emit_rounds(true), "test").unwrap(),
+            "$6$rounds=5000$G/gkPn17kHYo0gTF$xhDFU0QYExdMH2ghOWKrrVtu1BuTpNMSJURCXk43.\
+             EYekmK8iwV6RNqftUUC8mqDel1J7m3JEbUkbu4YyqSyv/"
+        );
+        assert_eq!(
+            super::hash_with(setup().rounds(5000).emit_rounds(false), "test").unwrap(),
+            "$6$G/gkPn17kHYo0gTF$xhDFU0QYExdMH2ghOWKrrVtu1BuTpNMSJURCXk43.\
+             EYekmK8iwV6RNqftUUC8mqDel1J7m3JEbUkbu4YyqSyv/"
+        );
+        assert!(
+            super::hash_with(setup().rounds(11531).emit_rounds(false), "test")
+                .unwrap()
+                .starts_with("$6$rounds=11531$")
+        );
+    }
 }
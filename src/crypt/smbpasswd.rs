@@ -0,0 +1,227 @@
+//! Samba `smbpasswd` file entries.
+//!
+//! The `smbpasswd` password backend of Samba stores one account per line,
+//! with the LanMan and NT hashes of the password, the account flags, and the
+//! time of the last password change. The NT hash is the MD4 digest of the
+//! password encoded as UTF-16LE, and the LanMan hash is an even weaker
+//! DES-based digest of the uppercased password. Both are unsalted and
+//! password-equivalent for NTLM authentication; they are supported so that
+//! existing Samba installations can be audited and maintained.
+//!
+//! Passwords are verified against the NT hash only. When a password is
+//! changed, the LanMan hash is disabled, as Samba does when LanMan
+//! authentication is turned off.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::smbpasswd::SmbpasswdEntry;
+//!
+//! let line = "alice:1000:XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX:\
+//!             8846F7EAEE8FB117AD06BDD830B7586C:[U          ]:LCT-5F5E1000:";
+//! let entry: SmbpasswdEntry = line.parse().unwrap();
+//! assert!(entry.verify("password"));
+//! assert_eq!(entry.to_string(), line);
+//! ```
+//!
+//! # Entry Format
+//!
+//! The format of an entry is
+//! *`{user}`*__`:`__*`{uid}`*__`:`__*`{lm}`*__`:`__*`{nt}`*__`:[`__*`{flags}`*__`]:LCT-`__*`{time}`*__`:`__,
+//! where:
+//!
+//! * *`{lm}`* and *`{nt}`* are the 32-character uppercase hexadecimal
+//!   encodings of the LanMan and NT hashes. A hash consisting of 32 `X`
+//!   characters is disabled.
+//!
+//! * *`{flags}`* are the account flags, padded with spaces to 11 characters,
+//!   e.g., `U` for a user account and `D` for a disabled one.
+//!
+//! * *`{time}`* is the time of the last password change, in seconds since
+//!   the Unix epoch, encoded as 8 uppercase hexadecimal digits. Entries
+//!   written by very old Samba releases lack this field.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    ct::ct_eq_ignore_ascii_case,
+    encode::hex_encode,
+    error::{Error, Result},
+    internal::md4,
+};
+
+const HASH_LEN: usize = 32;
+const FLAGS_LEN: usize = 11;
+const LCT_PREFIX: &str = "LCT-";
+
+/// A line of an `smbpasswd` file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SmbpasswdEntry {
+    /// User name.
+    pub user: String,
+    /// Numeric Unix user ID.
+    pub uid: u32,
+    /// LanMan hash, or a disabled hash.
+    pub lm_hash: String,
+    /// NT hash, or a disabled hash.
+    pub nt_hash: String,
+    /// Account flags, without the brackets and the padding.
+    pub flags: String,
+    /// Time of the last password change, in seconds since the Unix epoch.
+    pub last_change: Option<u32>,
+}
+
+/// Compute the NT hash of a password.
+///
+/// The result is the 32-character uppercase hexadecimal encoding of the
+/// hash, as stored in `smbpasswd`.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn nt_hash(pass: &str) -> String {
+    do_nt_hash(pass)
+}
+
+fn do_nt_hash(pass: &str) -> String {
+    hex_encode(&md4::nt_hash(pass)).to_uppercase()
+}
+
+fn is_valid_hash(hash: &str) -> bool {
+    hash.len() == HASH_LEN && hash.bytes().all(|b| b.is_ascii_hexdigit() || b == b'X')
+}
+
+impl SmbpasswdEntry {
+    /// Verify that the NT hash of the entry corresponds to a password.
+    ///
+    /// The account flags aren't consulted, so a disabled account with a valid
+    /// hash still verifies. A disabled hash never verifies.
+    pub fn verify(&self, pass: &str) -> bool {
+        ct_eq_ignore_ascii_case(&self.nt_hash, do_nt_hash(pass))
+    }
+
+    /// Change the password of the entry.
+    ///
+    /// The NT hash is recalculated, the LanMan hash is disabled, and the time
+    /// of the last password change is set to the current time.
+    pub fn set_password(&mut self, pass: &str) {
+        self.nt_hash = do_nt_hash(pass);
+        self.lm_hash = "X".repeat(HASH_LEN);
+        self.last_change = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .and_then(|d| u32::try_from(d.as_secs()).ok());
+    }
+}
+
+impl FromStr for SmbpasswdEntry {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Self> {
+        let mut fields = line
+            .trim_end_matches(['\r', '\n'])
+            .strip_suffix(':')
+            .ok_or(Error::InvalidHashString)?
+            .split(':');
+        let mut field = || fields.next().ok_or(Error::InvalidHashString);
+        let user = field()?.to_owned();
+        let uid = field()?.parse().map_err(|_e| Error::InvalidHashString)?;
+        let lm_hash = field()?.to_owned();
+        let nt_hash = field()?.to_owned();
+        let flags = field()?
+            .strip_prefix('[')
+            .and_then(|f| f.strip_suffix(']'))
+            .ok_or(Error::InvalidHashString)?
+            .trim_end()
+            .to_owned();
+        let last_change = match fields.next() {
+            Some(lct) => Some(
+                lct.strip_prefix(LCT_PREFIX)
+                    .and_then(|t| u32::from_str_radix(t, 16).ok())
+                    .ok_or(Error::InvalidHashString)?,
+            ),
+            None => None,
+        };
+        if user.is_empty()
+            || fields.next().is_some()
+            || !is_valid_hash(&lm_hash)
+            || !is_valid_hash(&nt_hash)
+        {
+            return Err(Error::InvalidHashString);
+        }
+        Ok(SmbpasswdEntry {
+            user,
+            uid,
+            lm_hash,
+            nt_hash,
+            flags,
+            last_change,
+        })
+    }
+}
+
+impl fmt::Display for SmbpasswdEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}:{}:[{:FLAGS_LEN$}]:",
+            self.user, self.uid, self.lm_hash, self.nt_hash, self.flags
+        )?;
+        if let Some(time) = self.last_change {
+            write!(f, "{LCT_PREFIX}{time:08X}:")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SmbpasswdEntry;
+
+    #[test]
+    #[allow(deprecated)]
+    fn nt_hash() {
+        assert_eq!(
+            super::nt_hash("password"),
+            "8846F7EAEE8FB117AD06BDD830B7586C"
+        );
+        assert_eq!(super::nt_hash(""), "31D6CFE0D16AE931B73C59D7E0C089C0");
+    }
+
+    #[test]
+    fn entry() {
+        let line = "bob:1001:E52CAC67419A9A224A3B108F3FA6CB6D:\
+                    8846F7EAEE8FB117AD06BDD830B7586C:[UX         ]:LCT-3B9ACA00:";
+        let mut entry: SmbpasswdEntry = line.parse().unwrap();
+        assert_eq!(entry.uid, 1001);
+        assert_eq!(entry.flags, "UX");
+        assert_eq!(entry.last_change, Some(1_000_000_000));
+        assert!(entry.verify("password"));
+        assert!(!entry.verify("Password"));
+        assert_eq!(entry.to_string(), line);
+
+        entry.set_password("hashcat");
+        assert!(entry.verify("hashcat"));
+        assert_eq!(entry.nt_hash, "B4B9B02E6F09A9BD760F388B67351E2B");
+        assert_eq!(entry.lm_hash, "XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX");
+        assert!(entry.last_change.unwrap() > 1_000_000_000);
+    }
+
+    #[test]
+    fn old_entry() {
+        let line = "carol:1002:XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX:\
+                    XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX:[DU         ]:";
+        let entry: SmbpasswdEntry = line.parse().unwrap();
+        assert_eq!(entry.last_change, None);
+        assert!(!entry.verify(""));
+        assert_eq!(entry.to_string(), line);
+    }
+
+    #[test]
+    #[should_panic(expected = "value: InvalidHashString")]
+    fn bad_hash() {
+        let _ = "dave:1003:XXXX:8846F7EAEE8FB117AD06BDD830B7586C:[U          ]:"
+            .parse::<SmbpasswdEntry>()
+            .unwrap();
+    }
+}
@@ -16,7 +16,7 @@
 //! use crypt3_rs::crypt::unix;
 //!
 //! assert_eq!(unix::hash_with("xO", "password").unwrap(), "xOAFZqRz5RduI");
-//! assert_eq!(unix::verify("password","xOAFZqRz5RduI"), true);
+//! assert!(unix::verify("password","xOAFZqRz5RduI"));
 //!
 //! ```
 //!
@@ -59,16 +59,37 @@ pub(crate) const HASH_LENGTH: usize = 2 + 11;
 #[inline]
 pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
     let saltstr = random::gen_salt_str(SALT_LEN);
-    Ok(Hash::Unix(HashV(unix_crypt(pass.as_ref(), &saltstr)?)))
+    Ok(Hash::Unix(HashV(unix_crypt(
+        pass.as_ref(),
+        saltstr.as_bytes(),
+    )?)))
 }
 
 /// Hash a password with a user-provided salt.
 ///
-/// An error is returned if the salt is too short or contains an invalid
-/// character.
+/// Only the first two characters of `salt` are used, so a complete hash
+/// can be passed in its place. Characters outside the alphabet are mapped
+/// to zero, like in the BSD implementations, which makes it possible to
+/// check historical hashes with such salts, and the salt is copied into the
+/// hash as it is. An error is returned if the salt is too short.
 #[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
 #[inline]
 pub fn hash_with<B: AsRef<[u8]>>(salt: &str, pass: B) -> Result<Hash> {
+    Ok(Hash::Unix(HashV(unix_crypt(
+        pass.as_ref(),
+        salt.as_bytes(),
+    )?)))
+}
+
+/// Hash a password with a user-provided salt, given as raw bytes.
+///
+/// Only the first two bytes of `salt` are used. They may have any value,
+/// and are decoded like by [`hash_with`]. The salt is copied into the hash
+/// if it's printable ASCII, and written in the standard encoding otherwise.
+/// An error is returned if the salt is too short.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash_with_bytes<B: AsRef<[u8]>>(salt: &[u8], pass: B) -> Result<Hash> {
     Ok(Hash::Unix(HashV(unix_crypt(pass.as_ref(), salt)?)))
 }
 
@@ -77,7 +98,7 @@ pub fn hash_with<B: AsRef<[u8]>>(salt: &str, pass: B) -> Result<Hash> {
 pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
     consteq(
         hash,
-        unix_crypt(pass.as_ref(), hash).map(|s| Hash::Unix(HashV(s))),
+        unix_crypt(pass.as_ref(), hash.as_bytes()).map(|s| Hash::Unix(HashV(s))),
     )
 }
 
@@ -87,16 +108,41 @@ mod tests {
     #[allow(deprecated)]
     fn custom() {
         assert_eq!("aZGJuE6EXrjEE", super::hash_with("aZ", "test").unwrap());
-        assert_eq!(super::verify("test", "aZGJuE6EXrjEE"), true);
-        assert_eq!(super::verify("test", "aZFJuE6EXrjEE"), false);
-        assert_eq!(super::verify("test", "!!"), false);
+        assert!(super::verify("test", "aZGJuE6EXrjEE"));
+        assert!(!super::verify("test", "aZFJuE6EXrjEE"));
+        assert!(!super::verify("test", "!!"));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn salt_bytes() {
+        assert_eq!(
+            super::hash_with_bytes(b"aZ", "test").unwrap(),
+            super::hash_with("aZ", "test").unwrap()
+        );
+        let h = super::hash_with("..", "test").unwrap();
+        assert_eq!(super::hash_with_bytes(b"\xff\x80", "test").unwrap(), h);
+        let h = format!("!!{}", &h[2..]);
+        assert_eq!(super::hash_with_bytes(b"!!", "test").unwrap(), h);
+        assert_eq!(
+            crate::Algorithm::Unix
+                .hash_with(crate::HashSetup::default().salt_bytes(b"!!"), "test")
+                .unwrap(),
+            h
+        );
+        assert!(matches!(
+            super::hash_with_bytes(b"!", "test"),
+            Err(crate::error::Error::InsufficientLength)
+        ));
     }
 
     #[test]
     #[allow(deprecated)]
-    #[should_panic(expected = "value: EncodingError")]
-    fn bad_salt_chars() {
-        let _ = super::hash_with("!!", "test").unwrap();
+    fn historical_salt() {
+        assert_eq!(super::hash_with("!!", "password").unwrap(), "!!UZoIyj/Hy/c");
+        assert!(super::verify("password", "!!UZoIyj/Hy/c"));
+        assert!(crate::unix::verify("password", "!!UZoIyj/Hy/c"));
+        assert!(!super::verify("password", "!!UZoIyj/Hy/d"));
     }
 
     #[test]
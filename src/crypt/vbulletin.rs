@@ -0,0 +1,93 @@
+//! vBulletin salted MD5 hash.
+//!
+//! vBulletin stores user passwords as the MD5 digest of the hexadecimal
+//! digest of the password followed by a salt. The salt is kept in a separate
+//! database column, so it must be supplied to both hashing and verification.
+//! Releases up to 3.8.4 used 3-character salts, and later ones 30-character
+//! salts. Two rounds of MD5 are very weak; the scheme should be used for
+//! backward compatibility only. The similar scheme used by Invision Power
+//! Board is implemented in the [ipb](super::ipb) module.
+//!
+//! Since the hash can't be verified without the salt, it's not represented
+//! in [`Hash`](crate::Hash), and the functions in this module work with plain
+//! strings.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::vbulletin;
+//!
+//! let h = "f24a0e9ce12069c72f77046ad708b655";
+//! assert!(vbulletin::verify("password", "abc", h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Salt length__: unlimited. [`gen_salt`] generates 30 random
+//!   characters.
+//!
+//! * __Rounds__: 1 (fixed).
+//!
+//! # Hash Format
+//!
+//! The hash is the 32-character lowercase hexadecimal encoding of the digest.
+
+use md5::{Digest, Md5};
+
+use crate::{ct::ct_eq_ignore_ascii_case, encode::hex_encode, random};
+
+/// Length of a generated salt, in characters.
+pub const SALT_LEN: usize = 30;
+
+fn do_vbulletin_crypt(pass: &[u8], salt: &str) -> String {
+    let mut dgst = Md5::new();
+    dgst.update(hex_encode(&Md5::digest(pass)));
+    dgst.update(salt.as_bytes());
+    hex_encode(&dgst.finalize())
+}
+
+/// Generate a random salt for a new hash.
+#[inline]
+pub fn gen_salt() -> String {
+    random::gen_salt_str(SALT_LEN)
+}
+
+/// Hash a password with the given salt.
+#[deprecated(since = "0.2.0", note = "don't use this algorithm for new passwords")]
+#[inline]
+pub fn hash<B: AsRef<[u8]>>(pass: B, salt: &str) -> String {
+    do_vbulletin_crypt(pass.as_ref(), salt)
+}
+
+/// Verify that the hash corresponds to a password and a salt.
+///
+/// The hash is compared case-insensitively.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, salt: &str, hash: &str) -> bool {
+    ct_eq_ignore_ascii_case(hash, do_vbulletin_crypt(pass.as_ref(), salt))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[allow(deprecated)]
+    fn custom() {
+        assert_eq!(
+            super::hash("password", "abc"),
+            "f24a0e9ce12069c72f77046ad708b655"
+        );
+        assert!(super::verify(
+            "password",
+            "abc",
+            "F24A0E9CE12069C72F77046AD708B655"
+        ));
+        assert!(!super::verify(
+            "password",
+            "abd",
+            "f24a0e9ce12069c72f77046ad708b655"
+        ));
+        assert_eq!(super::gen_salt().len(), super::SALT_LEN);
+    }
+}
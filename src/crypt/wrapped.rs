@@ -0,0 +1,206 @@
+//! Wrapped legacy digests.
+//!
+//! An inherited database of bare MD5 or SHA-1 digests can be strengthened
+//! without waiting for every user to log in: each stored digest is hashed
+//! again with a strong algorithm, and the weak digest is discarded. The
+//! password is verified by computing its digest first, and checking the
+//! digest against the outer hash. Since the outer hash only ever sees the
+//! inner digest, the result is no stronger than the outer algorithm applied
+//! to a high-entropy input, but it's immune to precomputed tables and to
+//! fast brute-forcing of the inner digest. Users should still be rehashed
+//! with a plain strong algorithm when they next log in.
+//!
+//! The inner digests are those of the [`hexdigest`](mod@super::hexdigest)
+//! module, and the outer algorithm is either [bcrypt](mod@super::bcrypt) or
+//! [SHA-512](super::sha512). The module is not enabled by default; use the
+//! `wrapped` feature to build it.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::crypt::wrapped::{self, WrappedOuter};
+//!
+//! let h = wrapped::wrap("5f4dcc3b5aa765d61d8327deb882cf99", WrappedOuter::Bcrypt).unwrap();
+//! assert!(wrapped::verify("password", &h));
+//! ```
+//!
+//! # Parameters
+//!
+//! * __Password length__: unlimited.
+//!
+//! * __Inner digest__: MD5, SHA-1, SHA-256, or SHA-512, as a lowercase
+//!   hexadecimal string. The digest is detected by its length when wrapping.
+//!
+//! * __Outer hash__: bcrypt or SHA-512 crypt, with their respective salt and
+//!   cost parameters. Since bcrypt uses only the first 72 bytes of its input,
+//!   a wrapped SHA-512 digest is truncated to 288 bits.
+//!
+//! # Hash Format
+//!
+//! The format of the hash is __`$wrapped$`__*`{inner}{outer}`*, where
+//! *`{inner}`* is one of __`md5`__, __`sha1`__, __`sha256`__, or
+//! __`sha512`__, and *`{outer}`* is the complete bcrypt or SHA-512 hash of
+//! the inner digest, including its leading __`$`__. Any other outer hash,
+//! like another wrapped or a peppered one, is rejected as an unsupported
+//! scheme.
+
+use crate::{
+    consteq,
+    crypt::{
+        bcrypt,
+        hexdigest::{self, HexDigest},
+        sha512,
+    },
+    error::{Error, Result},
+    hash::{Hash, HashV},
+};
+
+const WRAPPED_MAGIC: &str = "$wrapped$";
+
+/// Algorithms which can wrap a legacy digest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WrappedOuter {
+    /// [bcrypt](mod@super::bcrypt), with the default cost and variant.
+    Bcrypt,
+    /// [SHA-512](super::sha512), with the default number of rounds.
+    Sha512,
+}
+
+fn digest_name(digest: HexDigest) -> &'static str {
+    match digest {
+        HexDigest::Md5 => "md5",
+        HexDigest::Sha1 => "sha1",
+        HexDigest::Sha256 => "sha256",
+        HexDigest::Sha512 => "sha512",
+    }
+}
+
+fn parse_wrapped_hash(hash: &str) -> Result<(HexDigest, &str)> {
+    let rest = hash
+        .strip_prefix(WRAPPED_MAGIC)
+        .ok_or(Error::InvalidHashString)?;
+    let (name, _) = rest.split_once('$').ok_or(Error::InvalidHashString)?;
+    let digest = match name {
+        "md5" => HexDigest::Md5,
+        "sha1" => HexDigest::Sha1,
+        "sha256" => HexDigest::Sha256,
+        "sha512" => HexDigest::Sha512,
+        _ => return Err(Error::InvalidHashString),
+    };
+    let outer = &rest[name.len()..];
+    if !outer.starts_with("$2") && !outer.starts_with("$6$") {
+        return Err(Error::unsupported_scheme(outer));
+    }
+    Ok((digest, outer))
+}
+
+fn do_wrap(digest: HexDigest, inner: &str, outer: &str) -> Result<String> {
+    let outer = if outer.starts_with("$2") {
+        bcrypt::hash_with(outer, inner)?
+    } else {
+        sha512::hash_with(outer, inner)?
+    };
+    Ok(format!(
+        "{WRAPPED_MAGIC}{}{}",
+        digest_name(digest),
+        outer.as_str()
+    ))
+}
+
+/// Wrap a legacy digest with the outer algorithm.
+///
+/// The digest algorithm is detected by the length of `legacy`, which may be
+/// in either case. The outer hash gets a randomly generated salt. An error
+/// is returned if the digest isn't recognized, or if the system random
+/// number generator cannot be opened.
+pub fn wrap(legacy: &str, outer: WrappedOuter) -> Result<Hash> {
+    let digest = hexdigest::detect(legacy).ok_or(Error::InvalidHashString)?;
+    let inner = legacy.to_ascii_lowercase();
+    let outer = match outer {
+        WrappedOuter::Bcrypt => bcrypt::hash(&inner)?,
+        WrappedOuter::Sha512 => sha512::hash(&inner)?,
+    };
+    Ok(Hash::Wrapped(HashV(format!(
+        "{WRAPPED_MAGIC}{}{}",
+        digest_name(digest),
+        outer.as_str()
+    ))))
+}
+
+/// Hash a password with the parameters of an existing wrapped hash.
+///
+/// The `param` argument must be in the final hash format. The inner digest
+/// of the password is computed, and hashed with the outer algorithm, salt,
+/// and cost parsed out of `param`.
+pub fn hash_with<B: AsRef<[u8]>>(param: &str, pass: B) -> Result<Hash> {
+    let (digest, outer) = parse_wrapped_hash(param)?;
+    let inner = hexdigest::do_hexdigest(pass.as_ref(), digest);
+    Ok(Hash::Wrapped(HashV(do_wrap(digest, &inner, outer)?)))
+}
+
+/// Verify that the hash corresponds to a password.
+#[inline]
+pub fn verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+    consteq(hash, hash_with(hash, pass))
+}
+
+/// Check whether a string is in the wrapped hash format.
+pub(crate) fn is_wrapped_hash(hash: &str) -> bool {
+    parse_wrapped_hash(hash).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WrappedOuter;
+    use crate::{Hash, error::Error};
+
+    #[test]
+    fn custom() {
+        assert_eq!(
+            super::hash_with(
+                "$wrapped$md5$2b$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe",
+                "password"
+            )
+            .unwrap(),
+            "$wrapped$md5$2b$05$bvIG6Nmid91Mu9RcmmWZfOnrKcyxPXw/lp2oDFdHsUVQKnjkbtREW"
+        );
+        assert!(super::verify(
+            "password",
+            "$wrapped$sha1$6$rounds=1000$saltsalt$OP0HNAIbnX04ynWMXLKdUSsBA0MtbWSCntT.\
+             uLA.99zNs3au7lGUBkK67osuEj69w3zDS17ff4OwdnS56tLNK/"
+        ));
+    }
+
+    #[test]
+    fn wrap() {
+        let h = super::wrap(
+            "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8",
+            WrappedOuter::Sha512,
+        )
+        .unwrap();
+        assert!(h.starts_with("$wrapped$sha1$6$"));
+        assert!(super::verify("password", &h));
+        assert!(!super::verify("passwore", &h));
+    }
+
+    #[test]
+    fn nested() {
+        let h =
+            "$wrapped$md5$wrapped$md5$2b$05$bvIG6Nmid91Mu9RcmmWZfOnrKcyxPXw/lp2oDFdHsUVQKnjkbtREW";
+        assert!(matches!(
+            Hash::try_from(h),
+            Err(Error::UnsupportedScheme { prefix }) if prefix == "$wrapped$"
+        ));
+        assert!(matches!(
+            super::hash_with(h, "password"),
+            Err(Error::UnsupportedScheme { .. })
+        ));
+        assert!(!super::verify("password", h));
+    }
+
+    #[test]
+    #[should_panic(expected = "value: InvalidHashString")]
+    fn bad_legacy() {
+        let _ = super::wrap("5f4dcc3b5aa765d61d8327deb882cf9", WrappedOuter::Bcrypt).unwrap();
+    }
+}
@@ -0,0 +1,474 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::{PoisonError, RwLock};
+
+use crate::{
+    Algorithm, HashSetup, PasswordHasher, Policy,
+    error::{Error, Result},
+    hash::Hash,
+};
+
+/// Password hashing configured once for an application.
+///
+/// A crypter collects the decisions which would otherwise be repeated at
+/// every call site: the algorithm and cost of new hashes, the pepper, and
+/// whether passwords are normalized. New hashes are made with
+/// [`Crypter::hash`], and checked with [`Crypter::verify`];
+/// [`Crypter::needs_rehash`] tells whether a stored hash was made with other
+/// settings, and should be replaced after the next successful login.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::{Algorithm, Crypter};
+///
+/// let crypter = Crypter::builder().algorithm(Algorithm::Bcrypt).cost(5).build().unwrap();
+/// let h = crypter.hash("password").unwrap();
+/// assert!(h.starts_with("$2b$05$"));
+/// assert!(crypter.verify("password", &h));
+/// assert!(!crypter.needs_rehash(&h));
+/// assert!(crypter.needs_rehash("$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0"));
+/// ```
+#[derive(Clone)]
+pub struct Crypter {
+    algorithm: Algorithm,
+    cost: Option<u32>,
+    rounds_variation: Option<u32>,
+    #[cfg(feature = "pepper")]
+    pepper: Option<Vec<u8>>,
+    #[cfg(feature = "unicode")]
+    normalize: bool,
+    policy: Policy,
+}
+
+impl Crypter {
+    /// Start configuring a crypter.
+    pub fn builder() -> CrypterBuilder {
+        CrypterBuilder::default()
+    }
+
+    /// Get the algorithm of new hashes.
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Get the policy which decides whether a hash should be replaced.
+    ///
+    /// It requires the crypter's algorithm and, if set, its cost.
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    // Get the rounds of a new hash: the configured cost, or the default of
+    // the algorithm, varied by the configured percentage if the algorithm
+    // varies its default.
+    fn rounds(&self) -> Option<u32> {
+        #[cfg(feature = "sha1")]
+        if let (None, Algorithm::Sha1, Some(percent)) =
+            (self.cost, self.algorithm, self.rounds_variation)
+        {
+            let rounds = crate::crypt::sha1::DEFAULT_ROUNDS;
+            return Some(crate::random::vary_rounds_by(rounds, percent));
+        }
+        self.cost
+    }
+
+    // Normalize the password if the crypter is configured to do it.
+    fn prepare<'a>(&self, pass: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+        #[cfg(feature = "unicode")]
+        if self.normalize {
+            let pass = std::str::from_utf8(pass).map_err(Error::InvalidUtf8)?;
+            let pass = crate::Normalized::from(pass);
+            return Ok(Cow::Owned(pass.as_ref().to_vec()));
+        }
+        Ok(Cow::Borrowed(pass))
+    }
+
+    /// Hash a password with the configured settings.
+    ///
+    /// If normalization is enabled, the password must be valid UTF-8.
+    pub fn hash<B: AsRef<[u8]>>(&self, pass: B) -> Result<Hash> {
+        let pass = self.prepare(pass.as_ref())?;
+        let setup = self
+            .rounds()
+            .map(|rounds| HashSetup::default().rounds(rounds));
+        #[cfg(feature = "pepper")]
+        if let Some(pepper) = &self.pepper {
+            use crate::crypt::pepper::{self, PepperSetup};
+
+            let mut pepper_setup = PepperSetup::new(pepper, self.algorithm);
+            if let Some(setup) = setup {
+                pepper_setup = pepper_setup.params(setup);
+            }
+            return pepper::hash_with(pepper_setup, pass);
+        }
+        match setup {
+            Some(setup) => self.algorithm.hash_with(setup, pass),
+            None => self.algorithm.hash(pass),
+        }
+    }
+
+    /// Verify that the hash corresponds to a password.
+    ///
+    /// The format of the hash is recognized, so that hashes made with other
+    /// settings can still be verified. With a pepper, unpeppered hashes are
+    /// checked without it, as done by `crypt::pepper::verify`.
+    pub fn verify<B: AsRef<[u8]>>(&self, pass: B, hash: &str) -> bool {
+        let Ok(pass) = self.prepare(pass.as_ref()) else {
+            return false;
+        };
+        #[cfg(feature = "pepper")]
+        if let Some(pepper) = &self.pepper {
+            return crate::crypt::pepper::verify(pass, pepper, hash);
+        }
+        crate::unix::verify(pass, hash)
+    }
+
+    /// Check whether the hash should be replaced with one made with the
+    /// configured settings.
+    ///
+    /// Besides the conditions checked by [`Hash::needs_rehash`] with the
+    /// crypter's [policy](Crypter::policy), a hash should be replaced if it
+    /// isn't peppered and the crypter has a pepper, or if it can't be
+    /// recognized.
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        #[cfg(feature = "pepper")]
+        if self.pepper.is_some() {
+            return crate::crypt::pepper::inner_hash(hash)
+                .and_then(|inner| Hash::try_from(inner).ok())
+                .is_none_or(|inner| inner.needs_rehash(&self.policy));
+        }
+        Hash::try_from(hash).map_or(true, |hash| hash.needs_rehash(&self.policy))
+    }
+}
+
+impl PasswordHasher for Crypter {
+    fn hash(&self, pass: &[u8]) -> Result<String> {
+        Crypter::hash(self, pass).map(String::from)
+    }
+
+    fn verify(&self, pass: &[u8], hash: &str) -> bool {
+        Crypter::verify(self, pass, hash)
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        Crypter::needs_rehash(self, hash)
+    }
+}
+
+impl fmt::Debug for Crypter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("Crypter");
+        s.field("algorithm", &self.algorithm)
+            .field("cost", &self.cost)
+            .field("rounds_variation", &self.rounds_variation);
+        #[cfg(feature = "pepper")]
+        s.field("pepper", &self.pepper.as_ref().map(|_| "[REDACTED]"));
+        #[cfg(feature = "unicode")]
+        s.field("normalize", &self.normalize);
+        s.finish()
+    }
+}
+
+static DEFAULT_CRYPTER: RwLock<Option<Crypter>> = RwLock::new(None);
+
+// Algorithm of the default crypter if none was set: bcrypt, or SHA-512 if
+// bcrypt isn't enabled.
+#[allow(unreachable_code)]
+fn builtin_algorithm() -> Option<Algorithm> {
+    #[cfg(feature = "bcrypt")]
+    return Some(Algorithm::Bcrypt);
+    #[cfg(feature = "sha2")]
+    return Some(Algorithm::Sha512);
+    None
+}
+
+/// Set the crypter used by [`hash`](crate::hash) for the whole process.
+///
+/// This lets the algorithm and parameters of new hashes be chosen in one
+/// place, e.g. from the application's configuration at startup, instead of
+/// at every call site. The crypter can be replaced at any time.
+pub fn set_default_crypter(crypter: Crypter) {
+    *DEFAULT_CRYPTER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(crypter);
+}
+
+/// Get the crypter used by [`hash`](crate::hash).
+///
+/// If none was set with [`set_default_crypter`], it's a crypter for bcrypt
+/// with the default cost, or SHA-512 if the `bcrypt` feature isn't enabled.
+/// An `UnsupportedScheme` error is returned if none was set and neither
+/// algorithm is enabled.
+pub fn default_crypter() -> Result<Crypter> {
+    let crypter = DEFAULT_CRYPTER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner);
+    match &*crypter {
+        Some(crypter) => Ok(crypter.clone()),
+        None => {
+            let builder = Crypter::builder();
+            match builtin_algorithm() {
+                Some(algorithm) => builder.algorithm(algorithm),
+                None => builder,
+            }
+            .build()
+        }
+    }
+}
+
+/// Hash a password with the process-wide default settings.
+///
+/// The password is hashed by the [default crypter](default_crypter), which
+/// can be changed with [`set_default_crypter`]. It can be verified with
+/// [`Crypter::verify`] of the same crypter, or, unless the crypter has a
+/// pepper or normalizes passwords, with [`unix::verify`](crate::unix::verify).
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::{Algorithm, Crypter, set_default_crypter, unix};
+///
+/// set_default_crypter(Crypter::builder().algorithm(Algorithm::Sha512).build().unwrap());
+/// let h = crypt3_rs::hash("password").unwrap();
+/// assert!(h.starts_with("$6$"));
+/// assert!(unix::verify("password", &h));
+/// ```
+pub fn hash<B: AsRef<[u8]>>(pass: B) -> Result<Hash> {
+    default_crypter()?.hash(pass)
+}
+
+/// Builder for a [`Crypter`].
+#[derive(Clone, Default)]
+pub struct CrypterBuilder {
+    algorithm: Option<Algorithm>,
+    cost: Option<u32>,
+    rounds_variation: Option<u32>,
+    #[cfg(feature = "pepper")]
+    pepper: Option<Vec<u8>>,
+    #[cfg(feature = "unicode")]
+    normalize: bool,
+}
+
+impl CrypterBuilder {
+    /// Configure the algorithm for new hashes
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = Some(algorithm);
+        self
+    }
+    /// Configure the number of rounds or cost for new hashes
+    ///
+    /// Stored hashes with a lower number of rounds or cost need rehashing.
+    pub fn cost(mut self, cost: u32) -> Self {
+        self.cost = Some(cost);
+        self
+    }
+    /// Configure the variation of the default rounds, in percent, instead of
+    /// the process-wide [rounds variation](crate::set_rounds_variation)
+    ///
+    /// 0 turns the variation off. It applies only when no cost is
+    /// configured.
+    pub fn rounds_variation(mut self, percent: u32) -> Self {
+        self.rounds_variation = Some(percent);
+        self
+    }
+    /// Configure the pepper, applied as described in
+    /// [`crypt::pepper`](crate::crypt::pepper)
+    #[cfg(feature = "pepper")]
+    pub fn pepper<K: Into<Vec<u8>>>(mut self, pepper: K) -> Self {
+        self.pepper = Some(pepper.into());
+        self
+    }
+    /// Configure whether passwords are converted to Unicode normalization
+    /// form NFKC, as done by [`Normalized`](crate::Normalized)
+    #[cfg(feature = "unicode")]
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Create the crypter.
+    ///
+    /// An `UnsupportedScheme` error is returned if no algorithm was
+    /// configured, or if the algorithm can't hash a password by itself, like
+    /// wrapped legacy digests or peppered hashes; use the builder's pepper
+    /// instead of the latter.
+    pub fn build(self) -> Result<Crypter> {
+        #[allow(unreachable_patterns)]
+        let algorithm = match self.algorithm {
+            #[cfg(feature = "pepper")]
+            Some(Algorithm::Peppered) => None,
+            #[cfg(feature = "wrapped")]
+            Some(Algorithm::Wrapped) => None,
+            algorithm => algorithm,
+        }
+        .ok_or_else(|| Error::UnsupportedScheme {
+            prefix: self.algorithm.map_or("", |alg| alg.name()).to_string(),
+        })?;
+        let mut policy = Policy::new(algorithm).require_algorithm(true);
+        if let Some(cost) = self.cost {
+            policy = policy.min_rounds(algorithm, cost);
+        }
+        Ok(Crypter {
+            algorithm,
+            cost: self.cost,
+            rounds_variation: self.rounds_variation,
+            #[cfg(feature = "pepper")]
+            pepper: self.pepper,
+            #[cfg(feature = "unicode")]
+            normalize: self.normalize,
+            policy,
+        })
+    }
+}
+
+impl fmt::Debug for CrypterBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("CrypterBuilder");
+        s.field("algorithm", &self.algorithm)
+            .field("cost", &self.cost)
+            .field("rounds_variation", &self.rounds_variation);
+        #[cfg(feature = "pepper")]
+        s.field("pepper", &self.pepper.as_ref().map(|_| "[REDACTED]"));
+        #[cfg(feature = "unicode")]
+        s.field("normalize", &self.normalize);
+        s.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Crypter;
+    use crate::Algorithm;
+
+    #[test]
+    #[cfg(all(feature = "bcrypt", feature = "md5"))]
+    fn crypter() {
+        let crypter = Crypter::builder()
+            .algorithm(Algorithm::Bcrypt)
+            .cost(5)
+            .build()
+            .unwrap();
+        let h = crypter.hash("password").unwrap();
+        assert!(h.starts_with("$2b$05$"));
+        assert!(crypter.verify("password", &h));
+        assert!(!crypter.verify("passwore", &h));
+        assert!(!crypter.needs_rehash(&h));
+
+        let md5 = "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0";
+        assert!(crypter.verify("password", md5));
+        assert!(crypter.needs_rehash(md5));
+        assert!(
+            crypter.needs_rehash("$2y$04$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe")
+        );
+        assert!(crypter.needs_rehash("x"));
+        assert_eq!(crypter.algorithm(), Algorithm::Bcrypt);
+    }
+
+    #[test]
+    #[cfg(feature = "bcrypt")]
+    fn password_hasher() {
+        use crate::PasswordHasher;
+
+        let crypter = Crypter::builder()
+            .algorithm(Algorithm::Bcrypt)
+            .cost(4)
+            .build()
+            .unwrap();
+        let hasher: Box<dyn PasswordHasher> = Box::new(crypter);
+        let h = hasher.hash(b"password").unwrap();
+        assert!(h.starts_with("$2b$04$"));
+        assert!(hasher.verify(b"password", &h));
+        assert!(!hasher.needs_rehash(&h));
+    }
+
+    #[test]
+    #[cfg(all(feature = "bcrypt", feature = "sha2"))]
+    fn default() {
+        use super::{default_crypter, set_default_crypter};
+
+        assert_eq!(default_crypter().unwrap().algorithm(), Algorithm::Bcrypt);
+        set_default_crypter(
+            Crypter::builder()
+                .algorithm(Algorithm::Sha512)
+                .cost(1000)
+                .build()
+                .unwrap(),
+        );
+        let h = crate::hash("password").unwrap();
+        assert!(h.starts_with("$6$rounds=1000$"));
+        assert!(crate::unix::verify("password", &h));
+        *super::DEFAULT_CRYPTER.write().unwrap() = None;
+    }
+
+    #[test]
+    fn build() {
+        assert!(matches!(
+            Crypter::builder().cost(5).build(),
+            Err(crate::error::Error::UnsupportedScheme { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(all(feature = "pepper", feature = "sha2"))]
+    fn pepper() {
+        let crypter = Crypter::builder()
+            .algorithm(Algorithm::Sha512)
+            .cost(1000)
+            .pepper(b"secret".as_slice())
+            .build()
+            .unwrap();
+        let h = crypter.hash("password").unwrap();
+        assert!(h.starts_with("$pepper$$6$rounds=1000$"));
+        assert!(crypter.verify("password", &h));
+        assert!(!crypter.needs_rehash(&h));
+        assert!(!format!("{crypter:?}").contains("secret"));
+
+        let plain = crate::crypt::sha512::hash("password").unwrap();
+        assert!(crypter.verify("password", &plain));
+        assert!(crypter.needs_rehash(&plain));
+        assert!(matches!(
+            Crypter::builder().algorithm(Algorithm::Peppered).build(),
+            Err(crate::error::Error::UnsupportedScheme { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "sha1")]
+    fn rounds_variation() {
+        use crate::crypt::sha1::DEFAULT_ROUNDS;
+
+        let crypter = Crypter::builder()
+            .algorithm(Algorithm::Sha1)
+            .rounds_variation(0)
+            .build()
+            .unwrap();
+        let h = crypter.hash("password").unwrap();
+        assert!(h.starts_with(&format!("$sha1${DEFAULT_ROUNDS}$")));
+        assert!(crypter.verify("password", &h));
+        let crypter = Crypter::builder()
+            .algorithm(Algorithm::Sha1)
+            .cost(1000)
+            .rounds_variation(0)
+            .build()
+            .unwrap();
+        assert!(crypter.hash("password").unwrap().starts_with("$sha1$1000$"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "unicode", feature = "sha2"))]
+    fn normalize() {
+        let crypter = Crypter::builder()
+            .algorithm(Algorithm::Sha512)
+            .normalize(true)
+            .build()
+            .unwrap();
+        let h = crypter.hash("p\u{e4}ssword").unwrap();
+        assert!(crypter.verify("pa\u{308}ssword", &h));
+        assert!(!crypter.verify(b"p\xe4ssword", &h));
+        assert!(matches!(
+            crypter.hash(b"\xff"),
+            Err(crate::error::Error::InvalidUtf8(_))
+        ));
+    }
+}
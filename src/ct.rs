@@ -0,0 +1,82 @@
+//! Comparisons in constant time.
+//!
+//! Comparing a stored hash with a calculated one by `==` stops at the first
+//! differing byte, so that the time taken tells an attacker how much of a
+//! guessed hash is right. The functions here look at every byte of the
+//! longer input, and their time depends only on the lengths of the inputs.
+
+// Compare two byte strings, folding ASCII letters to lowercase if `fold` is
+// set. The folding is done with arithmetic rather than a branch.
+fn ct_eq_with(a: &[u8], b: &[u8], fold: bool) -> bool {
+    let fold = u8::from(fold) << 5;
+    let lower = |c: u8| c | (fold & (u8::from(c.is_ascii_uppercase()) << 5));
+    let mut diff = u8::from(a.len() != b.len());
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).copied().unwrap_or_default();
+        let y = b.get(i).copied().unwrap_or_default();
+        diff |= lower(x) ^ lower(y);
+    }
+    std::hint::black_box(diff) == 0
+}
+
+/// Check whether two byte strings are equal, in constant time.
+///
+/// Unlike `==`, the comparison doesn't stop at the first difference: its
+/// time depends only on the lengths of the inputs, not on their contents.
+/// It's used by the `verify` functions of all algorithms, and is suitable
+/// for comparing other secrets, such as API keys or session tokens, with a
+/// value supplied by a client. The lengths aren't hidden.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::ct_eq;
+///
+/// assert!(ct_eq("0f3a9c", "0f3a9c"));
+/// assert!(!ct_eq("0f3a9c", b"0f3a9d"));
+/// ```
+pub fn ct_eq<A: AsRef<[u8]>, B: AsRef<[u8]>>(a: A, b: B) -> bool {
+    ct_eq_with(a.as_ref(), b.as_ref(), false)
+}
+
+/// Check whether two byte strings are equal, ignoring the case of ASCII
+/// letters, in constant time.
+///
+/// This is [`ct_eq`] for values such as hexadecimal digests, which are
+/// equivalent in lowercase and uppercase.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::ct_eq_ignore_ascii_case;
+///
+/// assert!(ct_eq_ignore_ascii_case("5f4dcc3b", "5F4DCC3B"));
+/// ```
+pub fn ct_eq_ignore_ascii_case<A: AsRef<[u8]>, B: AsRef<[u8]>>(a: A, b: B) -> bool {
+    ct_eq_with(a.as_ref(), b.as_ref(), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ct_eq, ct_eq_ignore_ascii_case};
+
+    #[test]
+    fn eq() {
+        assert!(ct_eq("", ""));
+        assert!(ct_eq("$1$salt$hash", "$1$salt$hash"));
+        assert!(!ct_eq("$1$salt$hash", "$1$salt$hasi"));
+        assert!(!ct_eq("$1$salt$hash", "$1$salt$has"));
+        assert!(!ct_eq("$1$salt$has", "$1$salt$has\0"));
+        assert!(!ct_eq("abc", "ABC"));
+        assert!(ct_eq([0xff, 0x00], vec![0xff, 0x00]));
+    }
+
+    #[test]
+    fn eq_ignore_ascii_case() {
+        assert!(ct_eq_ignore_ascii_case("5f4dcc3b", "5F4DCC3B"));
+        assert!(!ct_eq_ignore_ascii_case("5f4dcc3b", "5F4DCC3C"));
+        assert!(!ct_eq_ignore_ascii_case("@", "`"));
+        assert!(!ct_eq_ignore_ascii_case("[", "{"));
+        assert!(!ct_eq_ignore_ascii_case("ab", "abc"));
+    }
+}
@@ -11,7 +11,7 @@ use std::str::from_utf8;
 
 use crate::error::{Error, Result};
 
-const CRYPT_HASH64: &[u8] = b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+pub(crate) const CRYPT_HASH64: &[u8] = b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
 
 const CRYPT_HASH64_ENC_MAP: &[u8] =
     b"\x40\x40\x40\x40\x40\x40\x40\x40\x40\x40\x40\x40\x40\x40\x00\x01\
@@ -21,7 +21,7 @@ const CRYPT_HASH64_ENC_MAP: &[u8] =
 				      \x40\x26\x27\x28\x29\x2a\x2b\x2c\x2d\x2e\x2f\x30\x31\x32\x33\x34\
 				      \x35\x36\x37\x38\x39\x3a\x3b\x3c\x3d\x3e\x3f\x40\x40\x40\x40\x40";
 
-const BCRYPT_HASH64: &[u8] = b"./ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+pub(crate) const BCRYPT_HASH64: &[u8] = b"./ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
 
 const BCRYPT_HASH64_ENC_MAP: &[u8] =
     b"\x40\x40\x40\x40\x40\x40\x40\x40\x40\x40\x40\x40\x40\x40\x00\x01\
@@ -36,8 +36,8 @@ pub fn bcrypt_hash64_decode(enc: &str, decbuf: &mut [u8]) -> Result<()> {
     let mut cpos = 0;
     let mut dec_idx = 0;
     for b in enc.chars() {
-        let b = b as u32 - 0x20;
-        if b > 0x60 {
+        let b = (b as u32).wrapping_sub(0x20);
+        if b >= 0x60 {
             return Err(Error::EncodingError);
         }
         let dec = BCRYPT_HASH64_ENC_MAP[b as usize];
@@ -64,6 +64,11 @@ pub fn bcrypt_hash64_decode(enc: &str, decbuf: &mut [u8]) -> Result<()> {
     Ok(())
 }
 
+#[inline]
+pub fn bcrypt_hash64_decode_vec(enc: &str) -> Result<Vec<u8>> {
+    b_c_hash64_decode(enc, BCRYPT_HASH64)
+}
+
 #[inline]
 pub fn bcrypt_hash64_encode(bs: &[u8]) -> String {
     b_c_hash64_encode(bs, BCRYPT_HASH64)
@@ -74,17 +79,77 @@ pub fn crypt_hash64_encode(bs: &[u8]) -> String {
     b_c_hash64_encode(bs, CRYPT_HASH64)
 }
 
+const STD_BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn base64_encode(bs: &[u8]) -> String {
+    let mut out = b_c_hash64_encode(bs, STD_BASE64);
+    while !out.len().is_multiple_of(4) {
+        out.push('=');
+    }
+    out
+}
+
+pub fn base64_decode(enc: &str) -> Result<Vec<u8>> {
+    let unpadded = enc.trim_end_matches('=');
+    if enc.len() - unpadded.len() > 2 || !enc.len().is_multiple_of(4) {
+        return Err(Error::EncodingError);
+    }
+    b_c_hash64_decode(unpadded, STD_BASE64)
+}
+
+#[inline]
+pub fn base64_nopad_encode(bs: &[u8]) -> String {
+    b_c_hash64_encode(bs, STD_BASE64)
+}
+
+#[inline]
+pub fn base64_nopad_decode(enc: &str) -> Result<Vec<u8>> {
+    b_c_hash64_decode(enc, STD_BASE64)
+}
+
+const AB64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789./";
+
+#[inline]
+pub fn ab64_encode(bs: &[u8]) -> String {
+    b_c_hash64_encode(bs, AB64)
+}
+
+#[inline]
+pub fn ab64_decode(enc: &str) -> Result<Vec<u8>> {
+    b_c_hash64_decode(enc, AB64)
+}
+
+fn b_c_hash64_decode(enc: &str, hs: &[u8]) -> Result<Vec<u8>> {
+    if enc.len() % 4 == 1 {
+        return Err(Error::InsufficientLength);
+    }
+    let mut out = Vec::with_capacity(enc.len() * 3 / 4);
+    let mut acc = 0u32;
+    let mut nbits = 0;
+    for b in enc.bytes() {
+        let dec = hs
+            .iter()
+            .position(|&c| c == b)
+            .ok_or(Error::EncodingError)?;
+        acc = (acc << 6) | dec as u32;
+        nbits += 6;
+        if nbits >= 8 {
+            nbits -= 8;
+            out.push((acc >> nbits) as u8);
+        }
+    }
+    Ok(out)
+}
+
 fn b_c_hash64_encode(bs: &[u8], hs: &[u8]) -> String {
     let ngroups = bs.len().div_ceil(3);
     let mut out = String::with_capacity(ngroups * 4);
     for g in 0..ngroups {
-        let mut g_idx = g * 3;
         let mut enc = 0u32;
-        for _ in 0..3 {
+        for g_idx in g * 3..g * 3 + 3 {
             let b = (if g_idx < bs.len() { bs[g_idx] } else { 0 }) as u32;
             enc <<= 8;
             enc |= b;
-            g_idx += 1;
         }
         for _ in 0..4 {
             out.push(char::from_u32(hs[((enc >> 18) & 0x3F) as usize] as u32).unwrap());
@@ -104,6 +169,44 @@ fn b_c_hash64_encode(bs: &[u8], hs: &[u8]) -> String {
     out
 }
 
+pub fn hex_encode(bs: &[u8]) -> String {
+    bs.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+pub fn hex_decode(enc: &str) -> Result<Vec<u8>> {
+    if !enc.len().is_multiple_of(2) {
+        return Err(Error::InsufficientLength);
+    }
+    enc.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or(Error::EncodingError)
+        })
+        .collect()
+}
+
+pub fn aix_hash64_encode(bs: &[u8]) -> String {
+    let ngroups = bs.len().div_ceil(3);
+    let mut out = String::with_capacity(ngroups * 4);
+    for g in 0..ngroups {
+        let mut enc = 0u32;
+        for g_idx in g * 3..g * 3 + 3 {
+            let b = (if g_idx < bs.len() { bs[g_idx] } else { 0 }) as u32;
+            enc <<= 8;
+            enc |= b;
+        }
+        for _ in 0..4 {
+            out.push(char::from_u32(CRYPT_HASH64[(enc & 0x3F) as usize] as u32).unwrap());
+            enc >>= 6;
+        }
+    }
+    out.truncate(out.len() - (3 - bs.len() % 3) % 3);
+    out
+}
+
 const SHA1_HASH_LEN: usize = 20;
 
 pub fn sha1crypt_hash64_encode(bs: &[u8]) -> String {
@@ -111,9 +214,8 @@ pub fn sha1crypt_hash64_encode(bs: &[u8]) -> String {
     let ngroups = SHA1_HASH_LEN.div_ceil(3);
     let mut out = String::with_capacity(ngroups * 4);
     for g in 0..ngroups {
-        let mut g_idx = g * 3;
         let mut enc: u32 = 0;
-        for _ in 0..3 {
+        for g_idx in g * 3..g * 3 + 3 {
             let b = (if g_idx < SHA1_HASH_LEN {
                 bs[g_idx]
             } else {
@@ -121,7 +223,6 @@ pub fn sha1crypt_hash64_encode(bs: &[u8]) -> String {
             }) as u32;
             enc <<= 8;
             enc |= b;
-            g_idx += 1;
         }
         for _ in 0..4 {
             out.push(char::from_u32(CRYPT_HASH64[(enc & 0x3F) as usize] as u32).unwrap());
@@ -135,13 +236,11 @@ pub fn md5_sha2_hash64_encode(bs: &[u8]) -> String {
     let ngroups = bs.len().div_ceil(3);
     let mut out = String::with_capacity(ngroups * 4);
     for g in 0..ngroups {
-        let mut g_idx = g * 3;
         let mut enc = 0u32;
-        for _ in 0..3 {
+        for g_idx in g * 3..g * 3 + 3 {
             let b = (if g_idx < bs.len() { bs[g_idx] } else { 0 }) as u32;
             enc >>= 8;
             enc |= b << 16;
-            g_idx += 1;
         }
         for _ in 0..4 {
             out.push(char::from_u32(CRYPT_HASH64[(enc & 0x3F) as usize] as u32).unwrap());
@@ -161,12 +260,62 @@ pub fn md5_sha2_hash64_encode(bs: &[u8]) -> String {
     out
 }
 
+// Decode the crypt Base64 encodings which write each group of three bytes
+// starting with the least significant six bits. The bytes of a group are
+// read in little-endian order by the MD5 and SHA-2 crypt encoding, and in
+// big-endian order by the AIX and SHA-1 crypt encodings.
+fn lsb_hash64_decode(enc: &str, big_endian: bool) -> Result<Vec<u8>> {
+    if enc.len() % 4 == 1 {
+        return Err(Error::InsufficientLength);
+    }
+    let mut out = Vec::with_capacity(enc.len() * 3 / 4);
+    for group in enc.as_bytes().chunks(4) {
+        let mut val = 0u32;
+        for (i, &c) in group.iter().enumerate() {
+            let dec = CRYPT_HASH64
+                .iter()
+                .position(|&a| a == c)
+                .ok_or(Error::EncodingError)?;
+            val |= (dec as u32) << (6 * i);
+        }
+        for i in 0..group.len() * 6 / 8 {
+            let shift = if big_endian { 16 - 8 * i } else { 8 * i };
+            out.push((val >> shift) as u8);
+        }
+    }
+    Ok(out)
+}
+
+#[inline]
+pub fn crypt_hash64_decode(enc: &str) -> Result<Vec<u8>> {
+    b_c_hash64_decode(enc, CRYPT_HASH64)
+}
+
+#[inline]
+pub fn md5_sha2_hash64_decode(enc: &str) -> Result<Vec<u8>> {
+    lsb_hash64_decode(enc, false)
+}
+
+#[inline]
+pub fn aix_hash64_decode(enc: &str) -> Result<Vec<u8>> {
+    lsb_hash64_decode(enc, true)
+}
+
+pub fn sha1crypt_hash64_decode(enc: &str) -> Result<Vec<u8>> {
+    let mut out = lsb_hash64_decode(enc, true)?;
+    if out.len() != SHA1_HASH_LEN + 1 {
+        return Err(Error::InvalidHashString);
+    }
+    out.truncate(SHA1_HASH_LEN);
+    Ok(out)
+}
+
 pub fn decode_val(val: &str, len: usize) -> Result<u32> {
     let mut processed = 0;
     let mut s = 0u32;
     for b in val.chars() {
-        let b = b as u32 - 0x20;
-        if b > 0x60 {
+        let b = (b as u32).wrapping_sub(0x20);
+        if b >= 0x60 {
             return Err(Error::EncodingError);
         }
         let dec = CRYPT_HASH64_ENC_MAP[b as usize];
@@ -186,6 +335,23 @@ pub fn decode_val(val: &str, len: usize) -> Result<u32> {
     Ok(s >> (32 - 6 * len))
 }
 
+// Map raw salt bytes to 6-bit values like the BSD implementations of DES
+// crypt, which accept any byte: bytes outside the alphabet become zero.
+pub fn decode_legacy_val(val: &[u8], len: usize) -> Result<u32> {
+    if val.len() < len {
+        return Err(Error::InsufficientLength);
+    }
+    Ok(val[..len].iter().rev().fold(0u32, |s, &b| {
+        let dec = match b {
+            b'.'..=b'9' => b - b'.',
+            b'A'..=b'Z' => b - b'A' + 12,
+            b'a'..=b'z' => b - b'a' + 38,
+            _ => 0,
+        };
+        (s << 6) | dec as u32
+    }))
+}
+
 pub fn encode_val(mut val: u32, mut nhex: usize) -> String {
     let mut val_arr = [0u8; 4];
     if nhex > 4 {
@@ -0,0 +1,138 @@
+//! Binary-to-text encodings used in hash strings.
+//!
+//! Hash strings carry their salts and checksums in one of a few encodings:
+//! Base64 variants with the alphabets of crypt and bcrypt, standard Base64,
+//! and hexadecimal. The same routines the algorithms use are exposed here,
+//! so that a [`Scheme`](crate::Scheme) implemented by the application can
+//! produce and parse hashes in the format of its neighbours.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::encoding::Encoding;
+//!
+//! assert_eq!(Encoding::Crypt64.encode(b"hello"), "O4JgP4w");
+//! assert_eq!(Encoding::Standard.encode(b"hello"), "aGVsbG8=");
+//! assert_eq!(Encoding::Hex.decode("68656c6c6f").unwrap(), b"hello");
+//! ```
+
+use crate::{encode, error::Result};
+
+/// Binary-to-text encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// Base64 with the crypt alphabet `./0-9A-Za-z`, without padding, as in
+    /// the checksums of the DES-based algorithms.
+    Crypt64,
+    /// Base64 with the bcrypt alphabet `./A-Za-z0-9`, without padding, as in
+    /// the salts and checksums of bcrypt.
+    Bcrypt64,
+    /// Standard Base64 (RFC 4648), with padding.
+    Standard,
+    /// Hexadecimal, in lowercase. Decoding accepts either case.
+    Hex,
+}
+
+impl Encoding {
+    /// Encode a byte string.
+    pub fn encode<B: AsRef<[u8]>>(&self, bytes: B) -> String {
+        let bytes = bytes.as_ref();
+        match self {
+            Self::Crypt64 => encode::crypt_hash64_encode(bytes),
+            Self::Bcrypt64 => encode::bcrypt_hash64_encode(bytes),
+            Self::Standard => encode::base64_encode(bytes),
+            Self::Hex => encode::hex_encode(bytes),
+        }
+    }
+
+    /// Decode a string into bytes.
+    ///
+    /// A character outside the alphabet, or misplaced padding, gives
+    /// [`Error::EncodingError`](crate::error::Error::EncodingError); a
+    /// length which no byte string encodes to gives
+    /// [`Error::InsufficientLength`](crate::error::Error::InsufficientLength).
+    /// The unused bits of the last character of the Base64 variants are
+    /// ignored.
+    pub fn decode(&self, enc: &str) -> Result<Vec<u8>> {
+        match self {
+            Self::Crypt64 => encode::crypt_hash64_decode(enc),
+            Self::Bcrypt64 => encode::bcrypt_hash64_decode_vec(enc),
+            Self::Standard => encode::base64_decode(enc),
+            Self::Hex => encode::hex_decode(enc),
+        }
+    }
+}
+
+/// Encode the low `len` groups of six bits of an integer with the crypt
+/// alphabet, least significant group first.
+///
+/// This is the encoding of the rounds and salts of the BSDi algorithm. At
+/// most four characters are produced.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::encoding::{decode_int, encode_int};
+///
+/// assert_eq!(encode_int(7250, 4), "Gl/.");
+/// assert_eq!(decode_int("Gl/.", 4).unwrap(), 7250);
+/// ```
+pub fn encode_int(val: u32, len: usize) -> String {
+    encode::encode_val(val, len)
+}
+
+/// Decode the first `len` characters of a string encoded by [`encode_int`].
+///
+/// Like for encoding, at most four characters are used.
+pub fn decode_int(enc: &str, len: usize) -> Result<u32> {
+    encode::decode_val(enc, len.min(4))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Encoding, decode_int, encode_int};
+
+    #[test]
+    fn round_trip() {
+        let encodings = [
+            Encoding::Crypt64,
+            Encoding::Bcrypt64,
+            Encoding::Standard,
+            Encoding::Hex,
+        ];
+        for encoding in encodings {
+            for len in 0..8 {
+                let bytes: Vec<u8> = (0..len).map(|i| 0xf0 ^ (i * 37)).collect();
+                let enc = encoding.encode(&bytes);
+                assert_eq!(encoding.decode(&enc).unwrap(), bytes, "{encoding:?}");
+            }
+        }
+        assert_eq!(Encoding::Bcrypt64.encode(b"hello"), "YETqZE6");
+        assert_eq!(Encoding::Hex.decode("FF00").unwrap(), [0xff, 0x00]);
+    }
+
+    #[test]
+    fn errors() {
+        use crate::error::Error;
+
+        assert!(matches!(
+            Encoding::Crypt64.decode("ab+"),
+            Err(Error::EncodingError)
+        ));
+        assert!(matches!(
+            Encoding::Standard.decode("aGVsbG8"),
+            Err(Error::EncodingError)
+        ));
+        assert!(matches!(
+            Encoding::Hex.decode("abc"),
+            Err(Error::InsufficientLength)
+        ));
+        assert!(matches!(
+            decode_int("..", 4),
+            Err(Error::InsufficientLength)
+        ));
+        assert_eq!(decode_int("Gl/.z", 5).unwrap(), 7250);
+        assert_eq!(decode_int(&encode_int(0xffffff, 4), 4).unwrap(), 0xffffff);
+    }
+}
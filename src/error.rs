@@ -7,16 +7,28 @@
 // modified, or distributed except according to the terms of this
 // license.
 //!
-//! For simplicity, there's no provision for recording the cause of any
-//! errors except I/O errors when opening the system entropy source.
+//! The errors about the scheme, the rounds, and the salt carry the failing
+//! component, without the rest of the hash string or any part of the
+//! password, so that they can be logged safely. Errors caused by a failed
+//! conversion in the standard library, such as parsing a number, keep the
+//! original error, which is returned by [`source`](StdError::source).
 use std::error::Error as StdError;
 use std::fmt;
+use std::num::ParseIntError;
+use std::ops::RangeInclusive;
+use std::str::Utf8Error;
+
+use crate::Algorithm;
+
+/// Maximum length of the prefix recorded in [`Error::UnsupportedScheme`].
+const MAX_PREFIX_LEN: usize = 16;
 
 /// Type alias for the Result type.
 pub type Result<T> = std::result::Result<T, Error>;
 
 /// Possible errors.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// Random value cannot be generated.
     RandomError(String),
@@ -28,18 +40,217 @@ pub enum Error {
     InvalidRounds,
     /// The hash string is not in the expected format.
     InvalidHashString,
+    /// The password is longer than the algorithm or the policy allows.
+    PasswordTooLong,
+    /// The hash string or the name doesn't belong to a supported algorithm.
+    UnsupportedScheme {
+        /// Identifying prefix of the hash string, such as `$6$` or `{SSHA}`,
+        /// or the name. It's empty if the hash string has no recognizable
+        /// prefix.
+        prefix: String,
+    },
+    /// The number of rounds or the cost is outside the algorithm's range.
+    BadRounds {
+        /// The rejected value.
+        value: u32,
+        /// The range accepted by the algorithm.
+        allowed: RangeInclusive<u32>,
+    },
+    /// The salt contains a character which isn't valid for the algorithm.
+    BadSaltChar {
+        /// Byte offset of the first invalid character in the salt.
+        pos: usize,
+    },
+    /// The length of the salt is outside the algorithm's range.
+    BadSaltLen {
+        /// Length of the rejected salt, in the unit of the algorithm's
+        /// range.
+        len: usize,
+        /// The range accepted by the algorithm.
+        allowed: RangeInclusive<usize>,
+    },
+    /// A numeric component, such as the number of rounds, isn't a decimal
+    /// number.
+    InvalidNumber(ParseIntError),
+    /// A salt or a password which must be text isn't valid UTF-8.
+    InvalidUtf8(Utf8Error),
+    /// The hash was made with an algorithm which the verifier doesn't
+    /// allow.
+    DisallowedAlgorithm {
+        /// Algorithm of the rejected hash.
+        algorithm: Algorithm,
+    },
+    /// The algorithm has no __crypt__(3) settings string, so that one can't
+    /// be made for it by [`unix::gensalt`](crate::unix::gensalt).
+    NoSettingString {
+        /// The algorithm asked for.
+        algorithm: Algorithm,
+    },
+    /// A line of a shadow or passwd file doesn't have the number of fields
+    /// of the format.
+    InvalidEntry {
+        /// Number of fields of the format.
+        expected: usize,
+        /// Number of fields of the line.
+        found: usize,
+    },
+    /// A field of a new entry, such as a user name, contains a colon or a
+    /// line break, which can't be written to a credential file.
+    InvalidField,
+    /// A credential file can't be read or written, or a password can't be
+    /// read from the terminal.
+    Io(std::io::Error),
+    /// The password entered for confirmation differs from the first one.
+    PasswordMismatch,
+}
+
+impl Error {
+    // Create an `UnsupportedScheme` error, keeping only the identifying
+    // prefix of the hash string: the text up to the second `$` or the first
+    // `}`, at most 16 characters long.
+    pub(crate) fn unsupported_scheme(hash: &str) -> Error {
+        let end = match hash.as_bytes().first() {
+            Some(b'$') => hash[1..].find('$').map_or(hash.len(), |i| i + 2),
+            Some(b'{') => hash.find('}').map_or(hash.len(), |i| i + 1),
+            _ => 0,
+        };
+        Error::UnsupportedScheme {
+            prefix: hash[..end].chars().take(MAX_PREFIX_LEN).collect(),
+        }
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            Error::RandomError(ref err) => write!(f, "{err}"),
+        match self {
+            Error::RandomError(err) => write!(f, "{err}"),
             Error::EncodingError => write!(f, "Invalid encoding"),
             Error::InsufficientLength => write!(f, "Encoded value is too short"),
             Error::InvalidRounds => write!(f, "Invalid rounds value"),
             Error::InvalidHashString => write!(f, "Invalid hash string"),
+            Error::PasswordTooLong => write!(f, "Password is too long"),
+            Error::UnsupportedScheme { prefix } if prefix.is_empty() => {
+                write!(f, "Unsupported hash scheme")
+            }
+            Error::UnsupportedScheme { prefix } => write!(f, "Unsupported hash scheme '{prefix}'"),
+            Error::BadRounds { value, allowed } if *allowed.end() == u32::MAX => write!(
+                f,
+                "Rounds value {value} is below the minimum of {}",
+                allowed.start()
+            ),
+            Error::BadRounds { value, allowed } => write!(
+                f,
+                "Rounds value {value} is outside the range {}..={}",
+                allowed.start(),
+                allowed.end()
+            ),
+            Error::BadSaltChar { pos } => write!(f, "Invalid salt character at position {pos}"),
+            Error::BadSaltLen { len, allowed } => write!(
+                f,
+                "Salt length {len} is outside the range {}..={}",
+                allowed.start(),
+                allowed.end()
+            ),
+            Error::InvalidNumber(_) => write!(f, "Invalid numeric value"),
+            Error::InvalidUtf8(_) => write!(f, "Invalid UTF-8 sequence"),
+            Error::DisallowedAlgorithm { algorithm } => {
+                write!(f, "Algorithm '{}' is not allowed", algorithm.name())
+            }
+            Error::NoSettingString { algorithm } => write!(
+                f,
+                "Algorithm '{}' has no crypt(3) settings string",
+                algorithm.name()
+            ),
+            Error::InvalidEntry { expected, found } => {
+                write!(f, "Entry has {found} fields instead of {expected}")
+            }
+            Error::InvalidField => write!(f, "Field contains a colon or a line break"),
+            Error::Io(err) => write!(f, "I/O error: {err}"),
+            Error::PasswordMismatch => write!(f, "Passwords don't match"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::InvalidNumber(err) => Some(err),
+            Error::InvalidUtf8(err) => Some(err),
+            Error::Io(err) => Some(err),
+            _ => None,
         }
     }
 }
 
-impl StdError for Error {}
+#[cfg(test)]
+mod tests {
+    use super::Error;
+
+    #[test]
+    fn unsupported_scheme() {
+        let prefix = |hash| match Error::unsupported_scheme(hash) {
+            Error::UnsupportedScheme { prefix } => prefix,
+            err => panic!("{err:?}"),
+        };
+        assert_eq!(prefix("$0$5pZSV9va$azfrPr6af3Fc7dLblQXVa0"), "$0$");
+        assert_eq!(prefix("{SSHA512}aGFzaA=="), "{SSHA512}");
+        assert_eq!(prefix("$argon2id"), "$argon2id");
+        assert_eq!(prefix("$averyveryverylongscheme$x"), "$averyveryverylo");
+        assert_eq!(prefix("secretpassword"), "");
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            Error::BadRounds {
+                value: 32,
+                allowed: 4..=31
+            }
+            .to_string(),
+            "Rounds value 32 is outside the range 4..=31"
+        );
+        assert_eq!(
+            Error::BadRounds {
+                value: 0,
+                allowed: 1..=u32::MAX
+            }
+            .to_string(),
+            "Rounds value 0 is below the minimum of 1"
+        );
+        assert_eq!(
+            Error::unsupported_scheme("$0$salt$hash").to_string(),
+            "Unsupported hash scheme '$0$'"
+        );
+        assert_eq!(
+            Error::BadSaltChar { pos: 3 }.to_string(),
+            "Invalid salt character at position 3"
+        );
+        assert_eq!(
+            Error::BadSaltLen {
+                len: 17,
+                allowed: 0..=16
+            }
+            .to_string(),
+            "Salt length 17 is outside the range 0..=16"
+        );
+        assert_eq!(
+            Error::NoSettingString {
+                algorithm: crate::Algorithm::Custom("test")
+            }
+            .to_string(),
+            "Algorithm 'test' has no crypt(3) settings string"
+        );
+    }
+
+    #[test]
+    fn source() {
+        use std::error::Error as _;
+
+        let err = Error::InvalidNumber("x".parse::<u32>().unwrap_err());
+        assert_eq!(
+            err.source().unwrap().to_string(),
+            "invalid digit found in string"
+        );
+        assert!(Error::InvalidRounds.source().is_none());
+    }
+}
@@ -0,0 +1,510 @@
+//! C interface compatible with libcrypt.
+//!
+//! With the `ffi` feature, the crate exports `crypt`, `crypt_r`, `crypt_rn`,
+//! `crypt_ra`, `crypt_gensalt`, `crypt_checksalt`, and
+//! `crypt_preferred_method` with the signatures of libxcrypt, so that the
+//! shared library built from it can stand in for libcrypt in C programs:
+//!
+//! ```c
+//! char *crypt(const char *phrase, const char *setting);
+//! char *crypt_r(const char *phrase, const char *setting, struct crypt_data *data);
+//! char *crypt_rn(const char *phrase, const char *setting, void *data, int size);
+//! char *crypt_ra(const char *phrase, const char *setting, void **data, int *size);
+//! char *crypt_gensalt(const char *prefix, unsigned long count,
+//!                     const char *rbytes, int nrbytes);
+//! int crypt_checksalt(const char *setting);
+//! const char *crypt_preferred_method(void);
+//! ```
+//!
+//! Like libxcrypt, `crypt`, and `crypt_r` given a `crypt_data`, never return
+//! a null pointer; a phrase or a setting which can't be hashed gives a
+//! *failure token*, `"*0"`, or `"*1"` if the setting itself starts with
+//! `"*0"`, which never verifies as a hash. `crypt_rn` and `crypt_ra` return
+//! a null pointer instead, and `crypt_gensalt` returns one on failure, but
+//! none of them sets `errno`. The strings returned by `crypt` and
+//! `crypt_gensalt` are overwritten by the next call in the same thread.
+//!
+//! The crate is built as a Rust library only; the shared library is built
+//! with:
+//!
+//! ```sh
+//! cargo rustc --release --crate-type cdylib --features ffi
+//! ```
+//!
+//! In Rust, [`CryptData::crypt`] hashes into a [`CryptData`] like
+//! `crypt_rn`, and reports failures as errors.
+
+use std::{
+    cell::UnsafeCell,
+    ffi::{CStr, CString, c_char, c_int, c_ulong, c_void},
+    ptr,
+    sync::OnceLock,
+};
+
+#[cfg(any(
+    feature = "apr1",
+    feature = "bcrypt",
+    feature = "bsdi",
+    feature = "md5",
+    feature = "sha1",
+    feature = "sha2",
+    feature = "unix"
+))]
+use crate::crypt;
+use crate::{
+    Hash, default_crypter,
+    error::{Error, Result},
+    unix::{self, SaltCheck},
+};
+
+/// Size of the buffer for the hash in [`CryptData`], including the NUL.
+pub const CRYPT_OUTPUT_SIZE: usize = 384;
+/// Size of the buffer for a phrase in [`CryptData`], including the NUL.
+pub const CRYPT_MAX_PASSPHRASE_SIZE: usize = 512;
+/// Size of the buffer returned by `crypt_gensalt`, including the NUL.
+pub const CRYPT_GENSALT_OUTPUT_SIZE: usize = 192;
+
+/// `crypt_checksalt`: the setting is fit for new hashes.
+pub const CRYPT_SALT_OK: c_int = 0;
+/// `crypt_checksalt`: the setting is invalid.
+pub const CRYPT_SALT_INVALID: c_int = 1;
+/// `crypt_checksalt`: the scheme isn't in this build.
+pub const CRYPT_SALT_METHOD_DISABLED: c_int = 2;
+/// `crypt_checksalt`: the scheme shouldn't be used for new hashes.
+pub const CRYPT_SALT_METHOD_LEGACY: c_int = 3;
+/// `crypt_checksalt`: the cost of the setting is too low.
+pub const CRYPT_SALT_TOO_CHEAP: c_int = 4;
+
+/// The `struct crypt_data` of libxcrypt, which holds the result of
+/// `crypt_r`.
+///
+/// The layout is the one of libxcrypt, 32768 bytes, so that C code can
+/// allocate it itself. Only `output` is used by this crate.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::ffi::CryptData;
+///
+/// let mut data = CryptData::new();
+/// let h = "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0";
+/// assert_eq!(data.crypt("password", h).unwrap(), h);
+/// assert!(data.crypt("password", "$x$").is_err());
+/// ```
+#[repr(C)]
+pub struct CryptData {
+    /// The hash, as a NUL-terminated string.
+    pub output: [c_char; CRYPT_OUTPUT_SIZE],
+    /// Setting of the last call, unused.
+    pub setting: [c_char; CRYPT_OUTPUT_SIZE],
+    /// Phrase of the last call, unused.
+    pub input: [c_char; CRYPT_MAX_PASSPHRASE_SIZE],
+    /// Reserved.
+    pub reserved: [c_char; 767],
+    /// Initialization flag, unused.
+    pub initialized: c_char,
+    /// Internal state, unused.
+    pub internal: [c_char; 30720],
+}
+
+impl CryptData {
+    /// Allocate a zeroed `CryptData`.
+    pub fn new() -> Box<Self> {
+        // SAFETY: all the fields are arrays of integers, for which zero is a
+        // valid value.
+        unsafe { Box::new_zeroed().assume_init() }
+    }
+
+    /// Hash a phrase with the algorithm and parameters of a setting, or
+    /// verify it against a hash, like `crypt_rn`.
+    ///
+    /// The hash is also stored in `output`, or a failure token if there's
+    /// an error.
+    pub fn crypt<B: AsRef<[u8]>>(&mut self, phrase: B, setting: &str) -> Result<Hash> {
+        crypt_into(
+            Some(phrase.as_ref()),
+            Some(setting.as_bytes()),
+            &mut self.output,
+        )
+    }
+}
+
+unsafe extern "C" {
+    fn realloc(ptr: *mut c_void, size: usize) -> *mut c_void;
+}
+
+thread_local! {
+    static CRYPT_OUTPUT: UnsafeCell<[c_char; CRYPT_OUTPUT_SIZE]> =
+        const { UnsafeCell::new([0; CRYPT_OUTPUT_SIZE]) };
+    static GENSALT_OUTPUT: UnsafeCell<[c_char; CRYPT_GENSALT_OUTPUT_SIZE]> =
+        const { UnsafeCell::new([0; CRYPT_GENSALT_OUTPUT_SIZE]) };
+}
+
+// Borrow a C string, if the pointer isn't null.
+unsafe fn c_bytes<'a>(ptr: *const c_char) -> Option<&'a [u8]> {
+    match ptr.is_null() {
+        true => None,
+        // SAFETY: the caller passes a NUL-terminated string.
+        false => Some(unsafe { CStr::from_ptr(ptr) }.to_bytes()),
+    }
+}
+
+// Copy a string and its NUL into a buffer, if it fits.
+fn copy_out(text: &str, out: &mut [c_char]) -> bool {
+    if text.len() >= out.len() {
+        return false;
+    }
+    for (dst, &src) in out.iter_mut().zip(text.as_bytes()) {
+        *dst = src as c_char;
+    }
+    out[text.len()] = 0;
+    true
+}
+
+// Hash a phrase with a hash, or with a setting made by `crypt_gensalt`,
+// which has no checksum. A setting starting with `*`, like a failure token,
+// is always rejected, although the DES-based hashes accept it as a salt.
+#[allow(deprecated)]
+fn crypt_setting(phrase: &[u8], setting: &str) -> Result<Hash> {
+    if setting.starts_with('*') {
+        return Err(Error::InvalidHashString);
+    }
+    unix::crypt(phrase, setting).or_else(|err| match setting {
+        #[cfg(feature = "md5")]
+        _ if setting.starts_with("$1$") => crypt::md5::hash_with(setting, phrase),
+        #[cfg(feature = "apr1")]
+        _ if setting.starts_with("$apr1$") => crypt::apr1::hash_with(setting, phrase),
+        #[cfg(feature = "bcrypt")]
+        _ if setting.starts_with("$2") => crypt::bcrypt::hash_with(setting, phrase),
+        #[cfg(feature = "sha1")]
+        _ if setting.starts_with("$sha1$") => crypt::sha1::hash_with(setting, phrase),
+        #[cfg(feature = "sha2")]
+        _ if setting.starts_with("$5$") => crypt::sha256::hash_with(setting, phrase),
+        #[cfg(feature = "sha2")]
+        _ if setting.starts_with("$6$") => crypt::sha512::hash_with(setting, phrase),
+        #[cfg(feature = "bsdi")]
+        _ if setting.starts_with('_') => crypt::bsdi::hash_with(setting, phrase),
+        #[cfg(feature = "unix")]
+        _ if setting.len() == 2 => crypt::unix::hash_with(setting, phrase),
+        _ => Err(err),
+    })
+}
+
+// Hash a phrase into a buffer, writing a failure token instead if that
+// fails.
+fn crypt_into(phrase: Option<&[u8]>, setting: Option<&[u8]>, out: &mut [c_char]) -> Result<Hash> {
+    let hash = match (phrase, setting.map(std::str::from_utf8)) {
+        (Some(phrase), Some(Ok(setting))) => crypt_setting(phrase, setting),
+        _ => Err(Error::InvalidHashString),
+    }
+    .and_then(|hash| match copy_out(hash.as_str(), out) {
+        true => Ok(hash),
+        false => Err(Error::InvalidHashString),
+    });
+    if hash.is_err() {
+        let token = match setting {
+            Some(setting) if setting.starts_with(b"*0") => "*1",
+            _ => "*0",
+        };
+        copy_out(token, out);
+    }
+    hash
+}
+
+// Make a setting as `crypt_gensalt` does.
+fn gensalt(prefix: Option<&[u8]>, count: c_ulong) -> Result<String> {
+    let rounds = match count {
+        0 => None,
+        count => Some(u32::try_from(count).map_err(|_| Error::InvalidRounds)?),
+    };
+    match prefix {
+        Some(prefix) => {
+            let prefix = std::str::from_utf8(prefix).map_err(|_| Error::InvalidHashString)?;
+            unix::gensalt(prefix, rounds)
+        }
+        None => unix::gensalt(default_crypter()?.algorithm(), rounds),
+    }
+}
+
+/// Hash a phrase with the algorithm and parameters of a setting, or verify
+/// it against a hash.
+///
+/// The result is in a buffer of the calling thread, which the next call
+/// overwrites.
+///
+/// # Safety
+///
+/// `phrase` and `setting` must be null or point to NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn crypt(phrase: *const c_char, setting: *const c_char) -> *mut c_char {
+    // SAFETY: the pointers are null or NUL-terminated, as required.
+    let (phrase, setting) = unsafe { (c_bytes(phrase), c_bytes(setting)) };
+    CRYPT_OUTPUT.with(|out| {
+        // SAFETY: the buffer belongs to the thread, and isn't borrowed
+        // elsewhere.
+        let out = unsafe { &mut *out.get() };
+        let _ = crypt_into(phrase, setting, out);
+        out.as_mut_ptr()
+    })
+}
+
+/// Hash a phrase like [`crypt()`], with the result in `data`.
+///
+/// A null pointer is returned if `data` is null.
+///
+/// # Safety
+///
+/// `phrase` and `setting` must be null or point to NUL-terminated strings,
+/// and `data` must point to a writable [`CryptData`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn crypt_r(
+    phrase: *const c_char,
+    setting: *const c_char,
+    data: *mut CryptData,
+) -> *mut c_char {
+    // SAFETY: the pointers are null or NUL-terminated, as required.
+    let (phrase, setting) = unsafe { (c_bytes(phrase), c_bytes(setting)) };
+    if data.is_null() {
+        return ptr::null_mut();
+    }
+    // SAFETY: `data` points to a writable `CryptData`, as required.
+    let output = unsafe { &mut (*data).output };
+    let _ = crypt_into(phrase, setting, output);
+    output.as_mut_ptr()
+}
+
+/// Hash a phrase like [`crypt_r`], with the result in a `crypt_data` of
+/// `size` bytes at `data`.
+///
+/// A null pointer is returned if `data` is null, if `size` is smaller than
+/// a `crypt_data`, or if the phrase can't be hashed; in the last case,
+/// `data` holds a failure token.
+///
+/// # Safety
+///
+/// `phrase` and `setting` must be null or point to NUL-terminated strings,
+/// and `data` must be null or point to `size` writable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn crypt_rn(
+    phrase: *const c_char,
+    setting: *const c_char,
+    data: *mut c_void,
+    size: c_int,
+) -> *mut c_char {
+    // SAFETY: the pointers are null or NUL-terminated, as required.
+    let (phrase, setting) = unsafe { (c_bytes(phrase), c_bytes(setting)) };
+    if data.is_null() || usize::try_from(size).map_or(true, |size| size < size_of::<CryptData>()) {
+        return ptr::null_mut();
+    }
+    // SAFETY: `data` points to enough writable bytes for a `CryptData`,
+    // whose alignment is one.
+    let output = unsafe { &mut (*data.cast::<CryptData>()).output };
+    match crypt_into(phrase, setting, output) {
+        Ok(_) => output.as_mut_ptr(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Hash a phrase like [`crypt_rn`], allocating the `crypt_data`.
+///
+/// If `*data` is null, or `*size` is smaller than a `crypt_data`, `*data`
+/// is reallocated with `realloc`, and `*data` and `*size` are updated; the
+/// caller frees it with `free`. A null pointer is returned if `data` or
+/// `size` is null, if the allocation fails, or if the phrase can't be
+/// hashed.
+///
+/// # Safety
+///
+/// `phrase` and `setting` must be null or point to NUL-terminated strings.
+/// `data` and `size` must be null or point to writable values, and `*data`
+/// must be null or have been allocated by `malloc`, with `*size` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn crypt_ra(
+    phrase: *const c_char,
+    setting: *const c_char,
+    data: *mut *mut c_void,
+    size: *mut c_int,
+) -> *mut c_char {
+    const SIZE: usize = size_of::<CryptData>();
+
+    if data.is_null() || size.is_null() {
+        return ptr::null_mut();
+    }
+    // SAFETY: `data` and `size` point to writable values, and `*data` to a
+    // `malloc` allocation, as required.
+    unsafe {
+        if (*data).is_null() || usize::try_from(*size).map_or(true, |size| size < SIZE) {
+            let new = realloc(*data, SIZE);
+            if new.is_null() {
+                return ptr::null_mut();
+            }
+            ptr::write_bytes(new.cast::<u8>(), 0, SIZE);
+            *data = new;
+            *size = SIZE as c_int;
+        }
+        crypt_rn(phrase, setting, *data, *size)
+    }
+}
+
+/// Create a setting for a new hash with [`unix::gensalt`].
+///
+/// `prefix` selects the scheme, or the one of the
+/// [default crypter](crate::default_crypter) if it's null, and `count` the
+/// rounds or cost, or the default if it's zero. The salt is always drawn
+/// from the crate's random source; `rbytes` and `nrbytes` are ignored. The
+/// result is in a buffer of the calling thread, which the next call
+/// overwrites, or null on failure.
+///
+/// # Safety
+///
+/// `prefix` must be null or point to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn crypt_gensalt(
+    prefix: *const c_char,
+    count: c_ulong,
+    _rbytes: *const c_char,
+    _nrbytes: c_int,
+) -> *mut c_char {
+    // SAFETY: the pointer is null or NUL-terminated, as required.
+    let prefix = unsafe { c_bytes(prefix) };
+    let Ok(setting) = gensalt(prefix, count) else {
+        return ptr::null_mut();
+    };
+    GENSALT_OUTPUT.with(|out| {
+        // SAFETY: the buffer belongs to the thread, and isn't borrowed
+        // elsewhere.
+        let out = unsafe { &mut *out.get() };
+        match copy_out(&setting, out) {
+            true => out.as_mut_ptr(),
+            false => ptr::null_mut(),
+        }
+    })
+}
+
+/// Check whether a setting is fit for new hashes with
+/// [`unix::checksalt`], and return one of the `CRYPT_SALT_*` values.
+///
+/// # Safety
+///
+/// `setting` must be null or point to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn crypt_checksalt(setting: *const c_char) -> c_int {
+    // SAFETY: the pointer is null or NUL-terminated, as required.
+    let setting = unsafe { c_bytes(setting) };
+    let Some(Ok(setting)) = setting.map(std::str::from_utf8) else {
+        return CRYPT_SALT_INVALID;
+    };
+    match unix::checksalt(setting) {
+        SaltCheck::Ok => CRYPT_SALT_OK,
+        SaltCheck::MethodDisabled => CRYPT_SALT_METHOD_DISABLED,
+        SaltCheck::MethodLegacy => CRYPT_SALT_METHOD_LEGACY,
+        SaltCheck::TooCheap => CRYPT_SALT_TOO_CHEAP,
+        _ => CRYPT_SALT_INVALID,
+    }
+}
+
+/// Get the prefix of the strongest scheme in the build with
+/// [`unix::preferred_method`], or null if there's none.
+///
+/// The string is static, and must not be freed.
+#[unsafe(no_mangle)]
+pub extern "C" fn crypt_preferred_method() -> *const c_char {
+    static PREFERRED: OnceLock<Option<CString>> = OnceLock::new();
+
+    PREFERRED
+        .get_or_init(|| unix::preferred_method().and_then(|prefix| CString::new(prefix).ok()))
+        .as_ref()
+        .map_or(ptr::null(), |prefix| prefix.as_ptr())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{CStr, CString, c_char, c_int, c_void};
+    use std::ptr;
+
+    use super::{
+        CRYPT_SALT_INVALID, CRYPT_SALT_OK, CRYPT_SALT_TOO_CHEAP, CryptData, crypt, crypt_checksalt,
+        crypt_gensalt, crypt_preferred_method, crypt_r, crypt_ra, crypt_rn,
+    };
+
+    fn string(ptr: *mut c_char) -> String {
+        unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    #[cfg(feature = "md5")]
+    fn crypt_md5() {
+        let h = c"$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0";
+        let out = unsafe { crypt(c"password".as_ptr(), h.as_ptr()) };
+        assert_eq!(string(out), "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0");
+        let out = unsafe { crypt(c"password".as_ptr(), c"$x$".as_ptr()) };
+        assert_eq!(string(out), "*0");
+        let out = unsafe { crypt(c"password".as_ptr(), c"*0".as_ptr()) };
+        assert_eq!(string(out), "*1");
+        let out = unsafe { crypt(ptr::null(), h.as_ptr()) };
+        assert_eq!(string(out), "*0");
+
+        let mut data = CryptData::new();
+        let out = unsafe { crypt_r(c"password".as_ptr(), h.as_ptr(), &mut *data) };
+        assert_eq!(out, data.output.as_mut_ptr());
+        assert_eq!(string(out), "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0");
+        assert_eq!(size_of::<CryptData>(), 32768);
+
+        let size = size_of::<CryptData>() as c_int;
+        let data = Box::into_raw(data).cast::<c_void>();
+        let out = unsafe { crypt_rn(c"password".as_ptr(), h.as_ptr(), data, size) };
+        assert_eq!(string(out), "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0");
+        assert!(unsafe { crypt_rn(c"password".as_ptr(), c"$x$".as_ptr(), data, size) }.is_null());
+        assert!(unsafe { crypt_rn(c"password".as_ptr(), h.as_ptr(), data, 384) }.is_null());
+        let mut data = unsafe { Box::from_raw(data.cast::<CryptData>()) };
+        assert!(data.crypt("password", "$x$").is_err());
+        assert_eq!(string(data.output.as_mut_ptr()), "*0");
+    }
+
+    #[test]
+    #[cfg(feature = "md5")]
+    fn crypt_alloc() {
+        unsafe extern "C" {
+            fn free(ptr: *mut c_void);
+        }
+
+        let h = c"$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0";
+        let mut data = ptr::null_mut();
+        let mut size = 0;
+        let out = unsafe { crypt_ra(c"password".as_ptr(), h.as_ptr(), &mut data, &mut size) };
+        assert!(!data.is_null());
+        assert_eq!(size as usize, size_of::<CryptData>());
+        assert_eq!(string(out), "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0");
+        let out = unsafe { crypt_ra(c"password".as_ptr(), c"*0".as_ptr(), &mut data, &mut size) };
+        assert!(out.is_null());
+        unsafe { free(data) };
+    }
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn gensalt() {
+        let setting = unsafe { crypt_gensalt(c"$6$".as_ptr(), 10000, ptr::null(), 0) };
+        let setting = CString::new(string(setting)).unwrap();
+        assert!(setting.to_str().unwrap().starts_with("$6$rounds=10000$"));
+        let h = string(unsafe { crypt(c"password".as_ptr(), setting.as_ptr()) });
+        assert!(crate::unix::verify("password", &h));
+        let h = string(unsafe { crypt(c"password".as_ptr(), c"ab".as_ptr()) });
+        assert!(crate::unix::verify("password", &h) && h.starts_with("ab"));
+        assert!(unsafe { crypt_gensalt(c"$x$".as_ptr(), 0, ptr::null(), 0) }.is_null());
+
+        assert_eq!(unsafe { crypt_checksalt(setting.as_ptr()) }, CRYPT_SALT_OK);
+        let cheap = unsafe { crypt_gensalt(c"$6$".as_ptr(), 1000, ptr::null(), 0) };
+        assert_eq!(unsafe { crypt_checksalt(cheap) }, CRYPT_SALT_TOO_CHEAP);
+        assert_eq!(
+            unsafe { crypt_checksalt(c"$6".as_ptr()) },
+            CRYPT_SALT_INVALID
+        );
+        assert_eq!(unsafe { crypt_checksalt(ptr::null()) }, CRYPT_SALT_INVALID);
+
+        let prefix = unsafe { CStr::from_ptr(crypt_preferred_method()) };
+        assert_eq!(
+            Some(prefix.to_str().unwrap()),
+            crate::unix::preferred_method()
+        );
+    }
+}
@@ -1,8 +1,12 @@
+use std::borrow::{Borrow, Cow};
+use std::fmt;
 use std::ops::{Deref, RangeInclusive};
 use std::str::FromStr;
+use std::sync::Arc;
 
 use crate::crypt;
 use crate::error::{Error, Result};
+use crate::{Algorithm, Scheme};
 
 #[derive(Debug, Clone)]
 pub(crate) struct HashV(pub(crate) String);
@@ -15,22 +19,90 @@ impl Deref for HashV {
     }
 }
 
+#[derive(Clone)]
+pub(crate) struct CustomHash(pub(crate) String, pub(crate) Arc<dyn Scheme>);
+
+impl fmt::Debug for CustomHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CustomHash")
+            .field(&self.0)
+            .field(&self.1.name())
+            .finish()
+    }
+}
+
 /// Object oriented hash abstraction
 #[allow(private_interfaces)]
 #[derive(Clone, Debug)]
 pub enum Hash {
+    /// [`crypt::aix`] hash value
+    #[cfg(feature = "aix")]
+    Aix(HashV),
     /// [`crypt::apr1`] hash value
     #[cfg(feature = "apr1")]
     Apr1(HashV),
+    /// [`crypt::balloon`] hash value
+    #[cfg(feature = "balloon")]
+    Balloon(HashV),
     /// [`crypt::bcrypt`] hash value
     #[cfg(feature = "bcrypt")]
     Bcrypt(HashV),
+    /// [`crypt::bcrypt_sha256`] hash value
+    #[cfg(feature = "bcrypt_sha256")]
+    BcryptSha256(HashV),
+    /// [`crypt::bigcrypt`] hash value
+    #[cfg(feature = "bigcrypt")]
+    Bigcrypt(HashV),
     /// [`crypt::bsdi`] hash value
     #[cfg(feature = "bsdi")]
     Bsdi(HashV),
+    /// [`crypt::cisco::type8`] hash value
+    #[cfg(feature = "cisco")]
+    CiscoType8(HashV),
+    /// [`crypt::cisco::type9`] hash value
+    #[cfg(feature = "cisco")]
+    CiscoType9(HashV),
+    /// Hash of a [`Scheme`] registered by the application
+    Custom(CustomHash),
+    /// [`crypt::django`] hash value
+    #[cfg(feature = "django")]
+    Django(HashV),
+    /// [`crypt::grub`] hash value
+    #[cfg(feature = "grub")]
+    Grub(HashV),
+    /// [`crypt::ldap`] hash value
+    #[cfg(feature = "ldap")]
+    Ldap(HashV),
     /// [`crypt::md5`] hash value
     #[cfg(feature = "md5")]
     Md5(HashV),
+    /// [`crypt::mediawiki`] hash value
+    #[cfg(feature = "mediawiki")]
+    Mediawiki(HashV),
+    /// [`crypt::mssql`] hash value
+    #[cfg(feature = "mssql")]
+    Mssql(HashV),
+    /// [`crypt::mysql323`] hash value
+    #[cfg(feature = "mysql323")]
+    Mysql323(HashV),
+    /// [`crypt::mysql41`] hash value
+    #[cfg(feature = "mysql41")]
+    Mysql41(HashV),
+    /// [`crypt::oracle11g`] hash value
+    #[cfg(feature = "oracle11g")]
+    Oracle11g(HashV),
+    /// [`crypt::pbkdf2`] hash value
+    #[cfg(feature = "pbkdf2")]
+    Pbkdf2(HashV),
+    /// [`crypt::pepper`] hash value
+    #[cfg(feature = "pepper")]
+    Peppered(HashV),
+    /// [`crypt::scram`] hash value
+    #[cfg(feature = "scram")]
+    Scram(HashV),
+    /// [`crypt::scrypt`] hash value
+    #[cfg(feature = "scrypt")]
+    Scrypt(HashV),
     /// [`crypt::sha1`] hash value
     #[cfg(feature = "sha1")]
     Sha1(HashV),
@@ -43,21 +115,62 @@ pub enum Hash {
     /// [`crypt::unix`] hash value
     #[cfg(feature = "unix")]
     Unix(HashV),
+    /// [`crypt::wrapped`] hash value
+    #[cfg(feature = "wrapped")]
+    Wrapped(HashV),
 }
 
 impl Hash {
     /// Hash a password with same mechansim and parameters as base hash.
     pub fn hash_with<B: AsRef<[u8]>>(&self, pass: B) -> Result<Self> {
+        crate::policy::check_password_len(pass.as_ref())?;
         #[allow(deprecated)]
         match self {
+            #[cfg(feature = "aix")]
+            Self::Aix(hash) => crypt::aix::hash_with(hash.0.as_str(), pass),
             #[cfg(feature = "apr1")]
             Self::Apr1(hash) => crypt::apr1::hash_with(hash.0.as_str(), pass),
+            #[cfg(feature = "balloon")]
+            Self::Balloon(hash) => crypt::balloon::hash_with(hash.0.as_str(), pass),
             #[cfg(feature = "bcrypt")]
             Self::Bcrypt(hash) => crypt::bcrypt::hash_with(hash.0.as_str(), pass),
+            #[cfg(feature = "bcrypt_sha256")]
+            Self::BcryptSha256(hash) => crypt::bcrypt_sha256::hash_with(hash.0.as_str(), pass),
+            #[cfg(feature = "bigcrypt")]
+            Self::Bigcrypt(hash) => crypt::bigcrypt::hash_with(hash.0.as_str(), pass),
             #[cfg(feature = "bsdi")]
             Self::Bsdi(hash) => crypt::bsdi::hash_with(hash.0.as_str(), pass),
+            #[cfg(feature = "cisco")]
+            Self::CiscoType8(hash) => crypt::cisco::type8::hash_with(hash.0.as_str(), pass),
+            #[cfg(feature = "cisco")]
+            Self::CiscoType9(hash) => crypt::cisco::type9::hash_with(hash.0.as_str(), pass),
+            Self::Custom(hash) => Hash::custom(&hash.1, &hash.0, pass.as_ref()),
+            #[cfg(feature = "django")]
+            Self::Django(hash) => crypt::django::hash_with(hash.0.as_str(), pass),
+            #[cfg(feature = "grub")]
+            Self::Grub(hash) => crypt::grub::hash_with(hash.0.as_str(), pass),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(hash) => crypt::ldap::hash_with(hash.0.as_str(), pass),
             #[cfg(feature = "md5")]
             Self::Md5(hash) => crypt::md5::hash_with(hash.0.as_str(), pass),
+            #[cfg(feature = "mediawiki")]
+            Self::Mediawiki(hash) => crypt::mediawiki::hash_with(hash.0.as_str(), pass),
+            #[cfg(feature = "mssql")]
+            Self::Mssql(hash) => crypt::mssql::hash_with(hash.0.as_str(), pass),
+            #[cfg(feature = "mysql323")]
+            Self::Mysql323(_) => crypt::mysql323::hash(pass),
+            #[cfg(feature = "mysql41")]
+            Self::Mysql41(_) => crypt::mysql41::hash(pass),
+            #[cfg(feature = "oracle11g")]
+            Self::Oracle11g(hash) => crypt::oracle11g::hash_with(hash.0.as_str(), pass),
+            #[cfg(feature = "pbkdf2")]
+            Self::Pbkdf2(hash) => crypt::pbkdf2::hash_with(hash.0.as_str(), pass),
+            #[cfg(feature = "pepper")]
+            Self::Peppered(_) => Err(Error::InvalidHashString),
+            #[cfg(feature = "scram")]
+            Self::Scram(hash) => crypt::scram::hash_with(hash.0.as_str(), pass),
+            #[cfg(feature = "scrypt")]
+            Self::Scrypt(hash) => crypt::scrypt::hash_with(hash.0.as_str(), pass),
             #[cfg(feature = "sha1")]
             Self::Sha1(hash) => crypt::sha1::hash_with(hash.0.as_str(), pass),
             #[cfg(feature = "sha2")]
@@ -66,20 +179,62 @@ impl Hash {
             Self::Sha512(hash) => crypt::sha512::hash_with(hash.0.as_str(), pass),
             #[cfg(feature = "unix")]
             Self::Unix(hash) => crypt::unix::hash_with(hash.0.as_str(), pass),
+            #[cfg(feature = "wrapped")]
+            Self::Wrapped(hash) => crypt::wrapped::hash_with(hash.0.as_str(), pass),
         }
     }
 
     /// Verify that the hash corresponds to a password.
     pub fn verify<B: AsRef<[u8]>>(&self, pass: B) -> bool {
+        if crate::policy::check_password_len(pass.as_ref()).is_err() {
+            return false;
+        }
         match self {
+            #[cfg(feature = "aix")]
+            Self::Aix(hash) => crypt::aix::verify(pass, &hash.0),
             #[cfg(feature = "apr1")]
             Self::Apr1(hash) => crypt::apr1::verify(pass, &hash.0),
+            #[cfg(feature = "balloon")]
+            Self::Balloon(hash) => crypt::balloon::verify(pass, &hash.0),
             #[cfg(feature = "bcrypt")]
             Self::Bcrypt(hash) => crypt::bcrypt::verify(pass, &hash.0),
+            #[cfg(feature = "bcrypt_sha256")]
+            Self::BcryptSha256(hash) => crypt::bcrypt_sha256::verify(pass, &hash.0),
+            #[cfg(feature = "bigcrypt")]
+            Self::Bigcrypt(hash) => crypt::bigcrypt::verify(pass, &hash.0),
             #[cfg(feature = "bsdi")]
             Self::Bsdi(hash) => crypt::bsdi::verify(pass, &hash.0),
+            #[cfg(feature = "cisco")]
+            Self::CiscoType8(hash) => crypt::cisco::type8::verify(pass, &hash.0),
+            #[cfg(feature = "cisco")]
+            Self::CiscoType9(hash) => crypt::cisco::type9::verify(pass, &hash.0),
+            Self::Custom(hash) => hash.1.verify(pass.as_ref(), &hash.0),
+            #[cfg(feature = "django")]
+            Self::Django(hash) => crypt::django::verify(pass, &hash.0),
+            #[cfg(feature = "grub")]
+            Self::Grub(hash) => crypt::grub::verify(pass, &hash.0),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(hash) => crypt::ldap::verify(pass, &hash.0),
             #[cfg(feature = "md5")]
             Self::Md5(hash) => crypt::md5::verify(pass, &hash.0),
+            #[cfg(feature = "mediawiki")]
+            Self::Mediawiki(hash) => crypt::mediawiki::verify(pass, &hash.0),
+            #[cfg(feature = "mssql")]
+            Self::Mssql(hash) => crypt::mssql::verify(pass, &hash.0),
+            #[cfg(feature = "mysql323")]
+            Self::Mysql323(hash) => crypt::mysql323::verify(pass, &hash.0),
+            #[cfg(feature = "mysql41")]
+            Self::Mysql41(hash) => crypt::mysql41::verify(pass, &hash.0),
+            #[cfg(feature = "oracle11g")]
+            Self::Oracle11g(hash) => crypt::oracle11g::verify(pass, &hash.0),
+            #[cfg(feature = "pbkdf2")]
+            Self::Pbkdf2(hash) => crypt::pbkdf2::verify(pass, &hash.0),
+            #[cfg(feature = "pepper")]
+            Self::Peppered(_) => false,
+            #[cfg(feature = "scram")]
+            Self::Scram(hash) => crypt::scram::verify(pass, &hash.0),
+            #[cfg(feature = "scrypt")]
+            Self::Scrypt(hash) => crypt::scrypt::verify(pass, &hash.0),
             #[cfg(feature = "sha1")]
             Self::Sha1(hash) => crypt::sha1::verify(pass, &hash.0),
             #[cfg(feature = "sha2")]
@@ -88,54 +243,474 @@ impl Hash {
             Self::Sha512(hash) => crypt::sha512::verify(pass, &hash.0),
             #[cfg(feature = "unix")]
             Self::Unix(hash) => crypt::unix::verify(pass, &hash.0),
+            #[cfg(feature = "wrapped")]
+            Self::Wrapped(hash) => crypt::wrapped::verify(pass, &hash.0),
         }
     }
 }
 
 impl Hash {
+    /// Check whether the hash was made with a deprecated algorithm.
+    ///
+    /// Besides the algorithms for which [`Algorithm::is_deprecated`] is true,
+    /// this covers the weak schemes of otherwise sound families: the LDAP
+    /// __{SHA}__, __{SMD5}__, and __{MD5}__ schemes, an LDAP __{CRYPT}__ hash
+    /// wrapping a deprecated one, and the legacy Django __md5__ and __sha1__
+    /// digests, including the unsalted ones.
+    pub fn is_deprecated(&self) -> bool {
+        match self {
+            #[cfg(feature = "django")]
+            Self::Django(hash) => crypt::django::is_legacy_hash(&hash.0),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(hash) => crypt::ldap::is_deprecated_hash(&hash.0),
+            _ => self.algorithm().is_deprecated(),
+        }
+    }
+
+    /// Check whether the hash was made with an algorithm recommended for new
+    /// passwords.
+    ///
+    /// A peppered hash is recommended if its inner hash is. See
+    /// [`Algorithm::is_recommended`].
+    pub fn is_recommended(&self) -> bool {
+        #[cfg(feature = "pepper")]
+        if let Self::Peppered(hash) = self {
+            return crypt::pepper::inner_hash(hash)
+                .and_then(|inner| Hash::try_from(inner).ok())
+                .is_some_and(|inner| inner.is_recommended());
+        }
+        self.algorithm().is_recommended()
+    }
+
+    /// Return the algorithm of the hash.
+    pub fn algorithm(&self) -> Algorithm {
+        match self {
+            #[cfg(feature = "aix")]
+            Self::Aix(_) => Algorithm::Aix,
+            #[cfg(feature = "apr1")]
+            Self::Apr1(_) => Algorithm::Apr1,
+            #[cfg(feature = "balloon")]
+            Self::Balloon(_) => Algorithm::Balloon,
+            #[cfg(feature = "bcrypt")]
+            Self::Bcrypt(_) => Algorithm::Bcrypt,
+            #[cfg(feature = "bcrypt_sha256")]
+            Self::BcryptSha256(_) => Algorithm::BcryptSha256,
+            #[cfg(feature = "bigcrypt")]
+            Self::Bigcrypt(_) => Algorithm::Bigcrypt,
+            #[cfg(feature = "bsdi")]
+            Self::Bsdi(_) => Algorithm::Bsdi,
+            #[cfg(feature = "cisco")]
+            Self::CiscoType8(_) => Algorithm::CiscoType8,
+            #[cfg(feature = "cisco")]
+            Self::CiscoType9(_) => Algorithm::CiscoType9,
+            Self::Custom(hash) => Algorithm::Custom(hash.1.name()),
+            #[cfg(feature = "django")]
+            Self::Django(_) => Algorithm::Django,
+            #[cfg(feature = "grub")]
+            Self::Grub(_) => Algorithm::Grub,
+            #[cfg(feature = "ldap")]
+            Self::Ldap(_) => Algorithm::Ldap,
+            #[cfg(feature = "md5")]
+            Self::Md5(_) => Algorithm::Md5,
+            #[cfg(feature = "mediawiki")]
+            Self::Mediawiki(_) => Algorithm::Mediawiki,
+            #[cfg(feature = "mssql")]
+            Self::Mssql(_) => Algorithm::Mssql,
+            #[cfg(feature = "mysql323")]
+            Self::Mysql323(_) => Algorithm::Mysql323,
+            #[cfg(feature = "mysql41")]
+            Self::Mysql41(_) => Algorithm::Mysql41,
+            #[cfg(feature = "oracle11g")]
+            Self::Oracle11g(_) => Algorithm::Oracle11g,
+            #[cfg(feature = "pbkdf2")]
+            Self::Pbkdf2(_) => Algorithm::Pbkdf2,
+            #[cfg(feature = "pepper")]
+            Self::Peppered(_) => Algorithm::Peppered,
+            #[cfg(feature = "scram")]
+            Self::Scram(_) => Algorithm::Scram,
+            #[cfg(feature = "scrypt")]
+            Self::Scrypt(_) => Algorithm::Scrypt,
+            #[cfg(feature = "sha1")]
+            Self::Sha1(_) => Algorithm::Sha1,
+            #[cfg(feature = "sha2")]
+            Self::Sha256(_) => Algorithm::Sha256,
+            #[cfg(feature = "sha2")]
+            Self::Sha512(_) => Algorithm::Sha512,
+            #[cfg(feature = "unix")]
+            Self::Unix(_) => Algorithm::Unix,
+            #[cfg(feature = "wrapped")]
+            Self::Wrapped(_) => Algorithm::Wrapped,
+        }
+    }
+
+    /// Split the hash into its components.
+    ///
+    /// An error is returned if the hash string doesn't have the structure
+    /// expected for its algorithm.
+    pub fn parts(&self) -> Result<ParsedHash<'_>> {
+        parse_parts(self.algorithm(), self.as_str()).ok_or(Error::InvalidHashString)
+    }
+
+    /// Return the salt of the hash, if it's encoded separately.
+    #[inline]
+    pub fn salt(&self) -> Option<&str> {
+        self.parts().ok()?.salt
+    }
+
+    /// Return the number of rounds or the cost of the hash, if it's encoded.
+    #[inline]
+    pub fn rounds(&self) -> Option<u32> {
+        self.parts().ok()?.rounds
+    }
+
+    /// Return the checksum of the hash.
+    #[inline]
+    pub fn checksum(&self) -> Option<&str> {
+        self.parts().ok().map(|parts| parts.checksum)
+    }
+
+    /// Return the checksum of the hash, decoded into raw bytes.
+    ///
+    /// The checksum is decoded with the alphabet and bit order used by the
+    /// algorithm. For MD5 and SHA-2 crypt hashes, whose encoding shuffles the
+    /// bytes, they're returned in the order of the final digest, and the
+    /// checksum of bigcrypt hashes is the concatenation of their segments.
+    /// Some algorithms store more than the digest in the checksum field: the
+    /// salted LDAP schemes append the salt, SCRAM
+    /// verifiers consist of the StoredKey followed by the ServerKey, and SQL
+    /// Server 2000 hashes have a second digest of the uppercased password.
+    /// The checksum of a wrapping hash, like a peppered one or an LDAP
+    /// __{CRYPT}__ hash, is the one of the wrapped hash.
+    ///
+    /// An error is returned if the hash can't be split into its components,
+    /// or if the checksum isn't encoded correctly.
+    pub fn checksum_bytes(&self) -> Result<Vec<u8>> {
+        #[allow(unused_imports)]
+        use crate::encode::{
+            ab64_decode, aix_hash64_decode, base64_decode, base64_nopad_decode,
+            bcrypt_hash64_decode, crypt_hash64_decode, hex_decode, md5_sha2_hash64_decode,
+            sha1crypt_hash64_decode,
+        };
+
+        // Put the bytes of an MD5 or SHA-2 crypt checksum back in order.
+        #[allow(dead_code)]
+        fn untranspose(bytes: Vec<u8>, table: &[u8]) -> Result<Vec<u8>> {
+            if bytes.len() != table.len() {
+                return Err(Error::InvalidHashString);
+            }
+            let mut out = vec![0u8; table.len()];
+            for (&b, &t) in bytes.iter().zip(table) {
+                out[t as usize] = b;
+            }
+            Ok(out)
+        }
+
+        let parts = self.parts()?;
+        #[allow(unused_variables)]
+        let checksum = parts.checksum;
+        match self.algorithm() {
+            #[cfg(feature = "aix")]
+            Algorithm::Aix if parts.ident == Some("smd5") => {
+                untranspose(md5_sha2_hash64_decode(checksum)?, crypt::md5::MD5_TRANSPOSE)
+            }
+            #[cfg(feature = "aix")]
+            Algorithm::Aix => aix_hash64_decode(checksum),
+            #[cfg(feature = "apr1")]
+            Algorithm::Apr1 => {
+                untranspose(md5_sha2_hash64_decode(checksum)?, crypt::md5::MD5_TRANSPOSE)
+            }
+            #[cfg(feature = "balloon")]
+            Algorithm::Balloon => base64_nopad_decode(checksum),
+            #[cfg(feature = "bcrypt")]
+            Algorithm::Bcrypt => {
+                let mut buf = vec![0u8; 23];
+                bcrypt_hash64_decode(checksum, &mut buf)?;
+                Ok(buf)
+            }
+            #[cfg(feature = "bcrypt_sha256")]
+            Algorithm::BcryptSha256 => {
+                let mut buf = vec![0u8; 23];
+                bcrypt_hash64_decode(checksum, &mut buf)?;
+                Ok(buf)
+            }
+            #[cfg(feature = "bigcrypt")]
+            Algorithm::Bigcrypt => descrypt_segments(checksum),
+            #[cfg(feature = "bsdi")]
+            Algorithm::Bsdi => crypt_hash64_decode(checksum),
+            #[cfg(feature = "cisco")]
+            Algorithm::CiscoType8 | Algorithm::CiscoType9 => crypt_hash64_decode(checksum),
+            Algorithm::Custom(_) => Err(Error::InvalidHashString),
+            #[cfg(feature = "django")]
+            Algorithm::Django if parts.rounds.is_some() => base64_decode(checksum),
+            #[cfg(feature = "django")]
+            Algorithm::Django => hex_decode(checksum),
+            #[cfg(feature = "grub")]
+            Algorithm::Grub => hex_decode(checksum),
+            #[cfg(feature = "ldap")]
+            Algorithm::Ldap
+                if parts
+                    .ident
+                    .is_some_and(|id| id.eq_ignore_ascii_case("CRYPT")) =>
+            {
+                let (_, inner) = self.split_once('}').ok_or(Error::InvalidHashString)?;
+                Hash::try_from(inner)?.checksum_bytes()
+            }
+            #[cfg(feature = "ldap")]
+            Algorithm::Ldap => base64_decode(checksum),
+            #[cfg(feature = "md5")]
+            Algorithm::Md5 => {
+                untranspose(md5_sha2_hash64_decode(checksum)?, crypt::md5::MD5_TRANSPOSE)
+            }
+            #[cfg(feature = "mediawiki")]
+            Algorithm::Mediawiki => hex_decode(checksum),
+            #[cfg(feature = "mssql")]
+            Algorithm::Mssql => hex_decode(checksum),
+            #[cfg(feature = "mysql323")]
+            Algorithm::Mysql323 => hex_decode(checksum),
+            #[cfg(feature = "mysql41")]
+            Algorithm::Mysql41 => hex_decode(checksum),
+            #[cfg(feature = "oracle11g")]
+            Algorithm::Oracle11g => hex_decode(checksum),
+            #[cfg(feature = "pbkdf2")]
+            Algorithm::Pbkdf2 => ab64_decode(checksum),
+            #[cfg(feature = "pepper")]
+            Algorithm::Peppered => {
+                let inner = crypt::pepper::inner_hash(self).ok_or(Error::InvalidHashString)?;
+                Hash::try_from(inner)?.checksum_bytes()
+            }
+            #[cfg(feature = "scram")]
+            Algorithm::Scram => {
+                let (stored_key, server_key) =
+                    checksum.split_once(':').ok_or(Error::InvalidHashString)?;
+                let mut out = base64_decode(stored_key)?;
+                out.extend(base64_decode(server_key)?);
+                Ok(out)
+            }
+            #[cfg(feature = "scrypt")]
+            Algorithm::Scrypt => base64_nopad_decode(checksum),
+            #[cfg(feature = "sha1")]
+            Algorithm::Sha1 => sha1crypt_hash64_decode(checksum),
+            #[cfg(feature = "sha2")]
+            Algorithm::Sha256 => untranspose(
+                md5_sha2_hash64_decode(checksum)?,
+                crypt::sha256::SHA256_TRANSPOSE,
+            ),
+            #[cfg(feature = "sha2")]
+            Algorithm::Sha512 => untranspose(
+                md5_sha2_hash64_decode(checksum)?,
+                crypt::sha512::SHA512_TRANSPOSE,
+            ),
+            #[cfg(feature = "unix")]
+            Algorithm::Unix => crypt_hash64_decode(checksum),
+            #[cfg(feature = "wrapped")]
+            Algorithm::Wrapped => {
+                let rest = self
+                    .strip_prefix("$wrapped$")
+                    .ok_or(Error::InvalidHashString)?;
+                let outer = rest.find('$').map(|pos| &rest[pos..]);
+                Hash::try_from(outer.ok_or(Error::InvalidHashString)?)?.checksum_bytes()
+            }
+        }
+    }
+
     /// Return ref to inner hash value string.
     #[inline]
     pub fn as_str(&self) -> &str {
-        &self
+        self
+    }
+
+    /// Parse a hash string in the given mode.
+    ///
+    /// In [`ParseMode::Lenient`] mode, this is the same as `Hash::try_from`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crypt3_rs::{Hash, ParseMode};
+    ///
+    /// // The unused bits of the last checksum character aren't zero.
+    /// let h = "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa2";
+    /// assert!(Hash::parse(h, ParseMode::Lenient).is_ok());
+    /// assert!(Hash::parse(h, ParseMode::Strict).is_err());
+    /// ```
+    pub fn parse(value: &str, mode: ParseMode) -> Result<Hash> {
+        let hash = Hash::try_from(value)?;
+        if mode == ParseMode::Strict {
+            hash.check_canonical()?;
+        }
+        Ok(hash)
+    }
+
+    // Check that the salt and checksum of a crypt-style hash are in the form
+    // the algorithm produces.
+    fn check_canonical(&self) -> Result<()> {
+        #[allow(unused_imports)]
+        use crate::encode::{BCRYPT_HASH64, CRYPT_HASH64};
+
+        let algorithm = self.algorithm();
+        let parts = self.parts()?;
+        #[allow(unreachable_patterns, unused_variables)]
+        let (len, alphabet, mask) = match algorithm {
+            #[cfg(feature = "pepper")]
+            Algorithm::Peppered => {
+                let inner = crypt::pepper::inner_hash(self).ok_or(Error::InvalidHashString)?;
+                return Hash::parse(inner, ParseMode::Strict).map(|_| ());
+            }
+            #[cfg(feature = "apr1")]
+            Algorithm::Apr1 => (22, CRYPT_HASH64, 0b111100),
+            #[cfg(feature = "bcrypt")]
+            Algorithm::Bcrypt => {
+                let salt = parts.salt.ok_or(Error::InvalidHashString)?;
+                check_hash64(salt, BCRYPT_HASH64, 22, 0b001111)?;
+                (31, BCRYPT_HASH64, 0b000011)
+            }
+            #[cfg(feature = "bsdi")]
+            Algorithm::Bsdi => (11, CRYPT_HASH64, 0b000011),
+            #[cfg(feature = "md5")]
+            Algorithm::Md5 => (22, CRYPT_HASH64, 0b111100),
+            #[cfg(feature = "sha1")]
+            Algorithm::Sha1 => (28, CRYPT_HASH64, 0),
+            #[cfg(feature = "sha2")]
+            Algorithm::Sha256 => (43, CRYPT_HASH64, 0b110000),
+            #[cfg(feature = "sha2")]
+            Algorithm::Sha512 => (86, CRYPT_HASH64, 0b111100),
+            #[cfg(feature = "unix")]
+            Algorithm::Unix => (11, CRYPT_HASH64, 0b000011),
+            _ => return Ok(()),
+        };
+        if let Some(salt) = parts.salt {
+            crate::Salt::from_str_checked(salt, algorithm)?;
+        }
+        check_hash64(parts.checksum, alphabet, len, mask)
     }
 }
 
-impl Into<String> for Hash {
-    fn into(self) -> String {
-        match self {
+impl From<Hash> for String {
+    fn from(hash: Hash) -> String {
+        match hash {
+            #[cfg(feature = "aix")]
+            Hash::Aix(hash) => hash.0,
             #[cfg(feature = "apr1")]
-            Self::Apr1(hash) => hash.0,
+            Hash::Apr1(hash) => hash.0,
+            #[cfg(feature = "balloon")]
+            Hash::Balloon(hash) => hash.0,
             #[cfg(feature = "bcrypt")]
-            Self::Bcrypt(hash) => hash.0,
+            Hash::Bcrypt(hash) => hash.0,
+            #[cfg(feature = "bcrypt_sha256")]
+            Hash::BcryptSha256(hash) => hash.0,
+            #[cfg(feature = "bigcrypt")]
+            Hash::Bigcrypt(hash) => hash.0,
             #[cfg(feature = "bsdi")]
-            Self::Bsdi(hash) => hash.0,
+            Hash::Bsdi(hash) => hash.0,
+            #[cfg(feature = "cisco")]
+            Hash::CiscoType8(hash) => hash.0,
+            #[cfg(feature = "cisco")]
+            Hash::CiscoType9(hash) => hash.0,
+            Hash::Custom(hash) => hash.0,
+            #[cfg(feature = "django")]
+            Hash::Django(hash) => hash.0,
+            #[cfg(feature = "grub")]
+            Hash::Grub(hash) => hash.0,
+            #[cfg(feature = "ldap")]
+            Hash::Ldap(hash) => hash.0,
             #[cfg(feature = "md5")]
-            Self::Md5(hash) => hash.0,
+            Hash::Md5(hash) => hash.0,
+            #[cfg(feature = "mediawiki")]
+            Hash::Mediawiki(hash) => hash.0,
+            #[cfg(feature = "mssql")]
+            Hash::Mssql(hash) => hash.0,
+            #[cfg(feature = "mysql323")]
+            Hash::Mysql323(hash) => hash.0,
+            #[cfg(feature = "mysql41")]
+            Hash::Mysql41(hash) => hash.0,
+            #[cfg(feature = "oracle11g")]
+            Hash::Oracle11g(hash) => hash.0,
+            #[cfg(feature = "pbkdf2")]
+            Hash::Pbkdf2(hash) => hash.0,
+            #[cfg(feature = "pepper")]
+            Hash::Peppered(hash) => hash.0,
+            #[cfg(feature = "scram")]
+            Hash::Scram(hash) => hash.0,
+            #[cfg(feature = "scrypt")]
+            Hash::Scrypt(hash) => hash.0,
             #[cfg(feature = "sha1")]
-            Self::Sha1(hash) => hash.0,
+            Hash::Sha1(hash) => hash.0,
             #[cfg(feature = "sha2")]
-            Self::Sha256(hash) => hash.0,
+            Hash::Sha256(hash) => hash.0,
             #[cfg(feature = "sha2")]
-            Self::Sha512(hash) => hash.0,
+            Hash::Sha512(hash) => hash.0,
             #[cfg(feature = "unix")]
-            Self::Unix(hash) => hash.0,
+            Hash::Unix(hash) => hash.0,
+            #[cfg(feature = "wrapped")]
+            Hash::Wrapped(hash) => hash.0,
         }
     }
 }
 
+impl fmt::Display for Hash {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl AsRef<str> for Hash {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
 impl Deref for Hash {
     type Target = str;
 
     fn deref(&self) -> &Self::Target {
         match self {
+            #[cfg(feature = "aix")]
+            Self::Aix(hash) => &hash.0,
             #[cfg(feature = "apr1")]
             Self::Apr1(hash) => &hash.0,
+            #[cfg(feature = "balloon")]
+            Self::Balloon(hash) => &hash.0,
             #[cfg(feature = "bcrypt")]
             Self::Bcrypt(hash) => &hash.0,
+            #[cfg(feature = "bcrypt_sha256")]
+            Self::BcryptSha256(hash) => &hash.0,
+            #[cfg(feature = "bigcrypt")]
+            Self::Bigcrypt(hash) => &hash.0,
             #[cfg(feature = "bsdi")]
             Self::Bsdi(hash) => &hash.0,
+            #[cfg(feature = "cisco")]
+            Self::CiscoType8(hash) => &hash.0,
+            #[cfg(feature = "cisco")]
+            Self::CiscoType9(hash) => &hash.0,
+            Self::Custom(hash) => &hash.0,
+            #[cfg(feature = "django")]
+            Self::Django(hash) => &hash.0,
+            #[cfg(feature = "grub")]
+            Self::Grub(hash) => &hash.0,
+            #[cfg(feature = "ldap")]
+            Self::Ldap(hash) => &hash.0,
             #[cfg(feature = "md5")]
             Self::Md5(hash) => &hash.0,
+            #[cfg(feature = "mediawiki")]
+            Self::Mediawiki(hash) => &hash.0,
+            #[cfg(feature = "mssql")]
+            Self::Mssql(hash) => &hash.0,
+            #[cfg(feature = "mysql323")]
+            Self::Mysql323(hash) => &hash.0,
+            #[cfg(feature = "mysql41")]
+            Self::Mysql41(hash) => &hash.0,
+            #[cfg(feature = "oracle11g")]
+            Self::Oracle11g(hash) => &hash.0,
+            #[cfg(feature = "pbkdf2")]
+            Self::Pbkdf2(hash) => &hash.0,
+            #[cfg(feature = "pepper")]
+            Self::Peppered(hash) => &hash.0,
+            #[cfg(feature = "scram")]
+            Self::Scram(hash) => &hash.0,
+            #[cfg(feature = "scrypt")]
+            Self::Scrypt(hash) => &hash.0,
             #[cfg(feature = "sha1")]
             Self::Sha1(hash) => &hash.0,
             #[cfg(feature = "sha2")]
@@ -144,6 +719,8 @@ impl Deref for Hash {
             Self::Sha512(hash) => &hash.0,
             #[cfg(feature = "unix")]
             Self::Unix(hash) => &hash.0,
+            #[cfg(feature = "wrapped")]
+            Self::Wrapped(hash) => &hash.0,
         }
     }
 }
@@ -155,6 +732,37 @@ impl PartialEq<Hash> for Hash {
     }
 }
 
+impl Eq for Hash {}
+
+// Consistent with `PartialEq` and `Borrow<str>`: hashes of the same string
+// are equal, whatever the variant.
+impl std::hash::Hash for Hash {
+    #[inline]
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::hash::Hash::hash(self.as_str(), state);
+    }
+}
+
+/// Allows looking up a `Hash` in a `HashSet` or a `HashMap` by its string.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashSet;
+/// use crypt3_rs::Hash;
+///
+/// let h = "$2b$10$saltsaltsaltsaltsaltsOF8gOe7Q2djsDdlP9U528dfcxNNK.BXC";
+/// let mut seen = HashSet::new();
+/// seen.insert(Hash::try_from(h).unwrap());
+/// assert!(seen.contains(h));
+/// ```
+impl Borrow<str> for Hash {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
 impl PartialEq<Hash> for String {
     #[inline]
     fn eq(&self, other: &Hash) -> bool {
@@ -183,53 +791,485 @@ impl PartialEq<&str> for Hash {
     }
 }
 
+/// Strictness of hash string parsing, for [`Hash::parse`].
+///
+/// Some producers emit hashes which aren't quite in the canonical form,
+/// e.g. with salts longer than the algorithm uses, or with nonzero bits in
+/// the unused part of the last checksum character. The reference
+/// implementations accept such hashes, silently truncating the salt or
+/// ignoring the extra bits, so they're accepted in lenient mode; strict mode
+/// rejects them, which is useful for validating hashes before storing them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Accept whatever the reference implementation of the algorithm
+    /// accepts. This is the mode used by `Hash::try_from`.
+    #[default]
+    Lenient,
+    /// Additionally check that the salt and checksum of crypt-style hashes
+    /// (MD5, Apr1, bcrypt, BSDi, SHA-1, SHA-256, SHA-512, and traditional
+    /// Unix) have the length, alphabet, and unused bits of the encoding
+    /// produced by the algorithm. Other hashes are parsed as in lenient
+    /// mode.
+    Strict,
+}
+
+/// Components of a hash string.
+///
+/// The components are borrowed from the hash, and aren't decoded. Fields
+/// which aren't present in the string are `None`; in particular, the salt
+/// of the LDAP schemes is encoded together with the checksum, and
+/// isn't reported separately, and the number of rounds isn't reported for
+/// algorithms which use a fixed or implicit value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParsedHash<'a> {
+    /// Scheme identifier, without delimiters, e.g. `2b` for bcrypt or
+    /// `SSHA` for LDAP.
+    pub ident: Option<&'a str>,
+    /// Number of rounds or cost parameter, as encoded in the hash. For
+    /// scrypt it's the base-2 logarithm of N, and for Balloon hashing the
+    /// time cost.
+    pub rounds: Option<u32>,
+    /// Salt, in the encoding used by the hash.
+    pub salt: Option<&'a str>,
+    /// Checksum, in the encoding used by the hash.
+    pub checksum: &'a str,
+}
+
+impl<'a> ParsedHash<'a> {
+    #[inline]
+    fn new(ident: Option<&'a str>, salt: Option<&'a str>, checksum: &'a str) -> Self {
+        ParsedHash {
+            ident,
+            rounds: None,
+            salt,
+            checksum,
+        }
+    }
+
+    #[inline]
+    fn rounds(mut self, rounds: &str) -> Option<Self> {
+        self.rounds = Some(rounds.parse().ok()?);
+        Some(self)
+    }
+}
+
+// Find the value of `key` in a comma-separated list of `key=value` pairs.
+fn param<'a>(params: &'a str, key: &str) -> Option<&'a str> {
+    params
+        .split(',')
+        .find_map(|kv| kv.strip_prefix(key)?.strip_prefix('='))
+}
+
+// Split a `$ident$salt$checksum` hash.
+#[allow(dead_code)]
+fn mcf_salted(s: &str) -> Option<ParsedHash<'_>> {
+    let (ident, rest) = s.strip_prefix('$')?.split_once('$')?;
+    let (salt, checksum) = rest.split_once('$')?;
+    Some(ParsedHash::new(Some(ident), Some(salt), checksum))
+}
+
+// Split a hash consisting of a two-character salt and a checksum.
+#[allow(dead_code)]
+fn descrypt(s: &str) -> Option<ParsedHash<'_>> {
+    let (salt, checksum) = s.split_at_checked(2)?;
+    Some(ParsedHash::new(None, Some(salt), checksum))
+}
+
+#[allow(unused_variables)]
+fn parse_parts(algorithm: Algorithm, s: &str) -> Option<ParsedHash<'_>> {
+    let mcf = || {
+        s.strip_prefix('$')
+            .map(|s| s.split('$').collect::<Vec<_>>())
+    };
+    match algorithm {
+        #[cfg(feature = "aix")]
+        Algorithm::Aix => {
+            let (scheme, rest) = s.strip_prefix('{')?.split_once('}')?;
+            if scheme == "smd5" {
+                let (salt, checksum) = rest.split_once('$')?;
+                return Some(ParsedHash::new(Some(scheme), Some(salt), checksum));
+            }
+            let [cost, salt, checksum] = rest.split('$').collect::<Vec<_>>()[..] else {
+                return None;
+            };
+            ParsedHash::new(Some(scheme), Some(salt), checksum).rounds(cost)
+        }
+        #[cfg(feature = "apr1")]
+        Algorithm::Apr1 => mcf_salted(s),
+        #[cfg(feature = "md5")]
+        Algorithm::Md5 => mcf_salted(s),
+        #[cfg(feature = "balloon")]
+        Algorithm::Balloon => match mcf()?[..] {
+            [ident, _, params, salt, checksum] => {
+                ParsedHash::new(Some(ident), Some(salt), checksum).rounds(param(params, "t")?)
+            }
+            _ => None,
+        },
+        #[cfg(feature = "bcrypt")]
+        Algorithm::Bcrypt => match mcf()?[..] {
+            [ident, cost, rest] if rest.len() == 53 && rest.is_ascii() => {
+                ParsedHash::new(Some(ident), Some(&rest[..22]), &rest[22..]).rounds(cost)
+            }
+            _ => None,
+        },
+        #[cfg(feature = "bcrypt_sha256")]
+        Algorithm::BcryptSha256 => match mcf()?[..] {
+            [ident, params, salt, checksum] => {
+                ParsedHash::new(Some(ident), Some(salt), checksum).rounds(param(params, "r")?)
+            }
+            _ => None,
+        },
+        #[cfg(feature = "bigcrypt")]
+        Algorithm::Bigcrypt => descrypt(s),
+        #[cfg(feature = "bsdi")]
+        Algorithm::Bsdi => {
+            let rest = s
+                .strip_prefix('_')
+                .filter(|rest| rest.len() > 8 && rest.is_ascii())?;
+            let rounds = crate::encode::decode_val(&rest[..4], 4).ok()?;
+            let mut parts = ParsedHash::new(None, Some(&rest[4..8]), &rest[8..]);
+            parts.rounds = Some(rounds);
+            Some(parts)
+        }
+        #[cfg(feature = "cisco")]
+        Algorithm::CiscoType8 | Algorithm::CiscoType9 => mcf_salted(s),
+        Algorithm::Custom(_) => None,
+        #[cfg(feature = "django")]
+        Algorithm::Django => match s.split('$').collect::<Vec<_>>()[..] {
+            [ident, rounds, salt, checksum] => {
+                ParsedHash::new(Some(ident), Some(salt), checksum).rounds(rounds)
+            }
+            [ident, salt, checksum] => Some(ParsedHash::new(Some(ident), Some(salt), checksum)),
+            _ => None,
+        },
+        #[cfg(feature = "grub")]
+        Algorithm::Grub => {
+            let (ident, rest) = s.split_at_checked(18)?;
+            match rest.strip_prefix('.')?.split('.').collect::<Vec<_>>()[..] {
+                [rounds, salt, checksum] => {
+                    ParsedHash::new(Some(ident), Some(salt), checksum).rounds(rounds)
+                }
+                _ => None,
+            }
+        }
+        #[cfg(feature = "ldap")]
+        Algorithm::Ldap => {
+            let (scheme, rest) = s.strip_prefix('{')?.split_once('}')?;
+            if scheme.eq_ignore_ascii_case("CRYPT") {
+                let inner = Hash::try_from(rest).ok()?.algorithm();
+                let parts = parse_parts(inner, rest)?;
+                return Some(ParsedHash {
+                    ident: Some(scheme),
+                    ..parts
+                });
+            }
+            Some(ParsedHash::new(Some(scheme), None, rest))
+        }
+        #[cfg(feature = "mediawiki")]
+        Algorithm::Mediawiki => match s.strip_prefix(':')?.split(':').collect::<Vec<_>>()[..] {
+            [ident, checksum] => Some(ParsedHash::new(Some(ident), None, checksum)),
+            [ident, salt, checksum] => Some(ParsedHash::new(Some(ident), Some(salt), checksum)),
+            _ => None,
+        },
+        #[cfg(feature = "mssql")]
+        Algorithm::Mssql => {
+            let (ident, rest) = s.split_at_checked(6)?;
+            let (salt, checksum) = rest.split_at_checked(8)?;
+            Some(ParsedHash::new(Some(ident), Some(salt), checksum))
+        }
+        #[cfg(feature = "mysql323")]
+        Algorithm::Mysql323 => Some(ParsedHash::new(None, None, s)),
+        #[cfg(feature = "mysql41")]
+        Algorithm::Mysql41 => Some(ParsedHash::new(None, None, s.strip_prefix('*')?)),
+        #[cfg(feature = "oracle11g")]
+        Algorithm::Oracle11g => {
+            let (checksum, salt) = s.strip_prefix("S:")?.split_at_checked(40)?;
+            Some(ParsedHash::new(Some("S"), Some(salt), checksum))
+        }
+        #[cfg(feature = "pbkdf2")]
+        Algorithm::Pbkdf2 => match mcf()?[..] {
+            [ident, rounds, salt, checksum] => {
+                ParsedHash::new(Some(ident), Some(salt), checksum).rounds(rounds)
+            }
+            _ => None,
+        },
+        #[cfg(feature = "pepper")]
+        Algorithm::Peppered => {
+            let inner = crypt::pepper::inner_hash(s)?;
+            let parts = parse_parts(Hash::try_from(inner).ok()?.algorithm(), inner)?;
+            Some(ParsedHash {
+                ident: Some("pepper"),
+                ..parts
+            })
+        }
+        #[cfg(feature = "scram")]
+        Algorithm::Scram => {
+            let (ident, rest) = s.split_once('$')?;
+            let (params, checksum) = rest.split_once('$')?;
+            let (rounds, salt) = params.split_once(':')?;
+            ParsedHash::new(Some(ident), Some(salt), checksum).rounds(rounds)
+        }
+        #[cfg(feature = "scrypt")]
+        Algorithm::Scrypt => match mcf()?[..] {
+            [ident, params, salt, checksum] => {
+                ParsedHash::new(Some(ident), Some(salt), checksum).rounds(param(params, "ln")?)
+            }
+            _ => None,
+        },
+        #[cfg(feature = "sha1")]
+        Algorithm::Sha1 => match mcf()?[..] {
+            [ident, rounds, salt, checksum] => {
+                ParsedHash::new(Some(ident), Some(salt), checksum).rounds(rounds)
+            }
+            _ => None,
+        },
+        #[cfg(feature = "sha2")]
+        Algorithm::Sha256 | Algorithm::Sha512 => match mcf()?[..] {
+            [ident, rounds, salt, checksum] => ParsedHash::new(Some(ident), Some(salt), checksum)
+                .rounds(rounds.strip_prefix("rounds=")?),
+            [ident, salt, checksum] => Some(ParsedHash::new(Some(ident), Some(salt), checksum)),
+            _ => None,
+        },
+        #[cfg(feature = "unix")]
+        Algorithm::Unix => descrypt(s),
+        #[cfg(feature = "wrapped")]
+        Algorithm::Wrapped => {
+            let rest = s.strip_prefix("$wrapped$")?;
+            let (ident, _) = rest.split_once('$')?;
+            let outer = &rest[ident.len()..];
+            let parts = parse_parts(Hash::try_from(outer).ok()?.algorithm(), outer)?;
+            Some(ParsedHash {
+                ident: Some("wrapped"),
+                ..parts
+            })
+        }
+    }
+}
+
+// Decode the concatenated 11-character segments of a bigcrypt checksum.
+#[allow(dead_code)]
+fn descrypt_segments(checksum: &str) -> Result<Vec<u8>> {
+    if checksum.is_empty() || !checksum.len().is_multiple_of(11) || !checksum.is_ascii() {
+        return Err(Error::InvalidHashString);
+    }
+    let mut out = Vec::with_capacity(checksum.len() / 11 * 8);
+    for segment in checksum.as_bytes().chunks(11) {
+        let segment = std::str::from_utf8(segment).map_err(Error::InvalidUtf8)?;
+        out.extend(crate::encode::crypt_hash64_decode(segment)?);
+    }
+    Ok(out)
+}
+
 #[inline]
-fn gatel(s: &str, size: usize) -> Result<HashV> {
+fn gatel(s: Cow<'_, str>, size: usize) -> Result<HashV> {
     (s.len() == size)
-        .then(|| HashV(s.to_owned()))
+        .then(|| HashV(s.into_owned()))
         .ok_or(Error::InsufficientLength)
 }
 
 #[inline]
-fn gater(s: &str, range: RangeInclusive<usize>) -> Result<HashV> {
+fn gater(s: Cow<'_, str>, range: RangeInclusive<usize>) -> Result<HashV> {
     range
         .contains(&s.len())
-        .then(|| HashV(s.to_owned()))
+        .then(|| HashV(s.into_owned()))
         .ok_or(Error::InsufficientLength)
 }
 
-impl TryFrom<&str> for Hash {
-    type Error = Error;
+// Check the length and alphabet of a crypt Base64 string, and that the bits
+// of its last character selected by `mask`, which don't encode any data, are
+// zero.
+#[allow(dead_code)]
+fn check_hash64(enc: &str, alphabet: &[u8], len: usize, mask: u8) -> Result<()> {
+    if enc.len() != len {
+        return Err(Error::InvalidHashString);
+    }
+    let mut last = 0;
+    for c in enc.bytes() {
+        last = alphabet
+            .iter()
+            .position(|&a| a == c)
+            .ok_or(Error::EncodingError)? as u8;
+    }
+    if last & mask != 0 {
+        return Err(Error::EncodingError);
+    }
+    Ok(())
+}
 
-    fn try_from(value: &str) -> std::result::Result<Self, Self::Error> {
+impl Hash {
+    // Hash a password with a custom scheme, checking that the scheme
+    // recognizes the result.
+    pub(crate) fn custom(scheme: &Arc<dyn Scheme>, setting: &str, pass: &[u8]) -> Result<Hash> {
+        let hash = scheme.hash_with(setting, pass)?;
+        if !scheme.identify(&hash) {
+            return Err(Error::InvalidHashString);
+        }
+        Ok(Hash::Custom(CustomHash(hash, Arc::clone(scheme))))
+    }
+
+    // Recognize a hash of a registered scheme.
+    fn identify_custom(value: Cow<'_, str>) -> Result<Hash> {
+        match crate::scheme::identify(&value) {
+            Some(scheme) => Ok(Hash::Custom(CustomHash(value.into_owned(), scheme))),
+            None => Err(Error::unsupported_scheme(&value)),
+        }
+    }
+
+    // Recognize a hash, taking over the buffer of an owned string.
+    fn from_cow(value: Cow<'_, str>) -> Result<Hash> {
         use crate::parse::HashIterator;
 
-        let mut hs = crate::parse::HashSlice::new(value);
-        match hs.take(1).unwrap_or("X") {
+        let mut hs = crate::parse::HashSlice::new(&value);
+        match hs.take(1) {
             #[cfg(feature = "bsdi")]
-            "_" => Ok(Self::Bsdi(gatel(value, crypt::bsdi::HASH_LENGTH)?)),
-            "$" => match hs.take_until(b'$').unwrap_or("X") {
+            Some("_") => Ok(Self::Bsdi(gatel(value, crypt::bsdi::HASH_LENGTH)?)),
+            #[cfg(feature = "mysql41")]
+            Some("*") => Ok(Self::Mysql41(gatel(value, crypt::mysql41::HASH_LENGTH)?)),
+            #[cfg(feature = "mediawiki")]
+            Some(":") if crypt::mediawiki::is_mediawiki_hash(&value) => {
+                Ok(Self::Mediawiki(HashV(value.into_owned())))
+            }
+            #[cfg(feature = "mssql")]
+            Some("0") if crypt::mssql::is_mssql_hash(&value) => {
+                Ok(Self::Mssql(HashV(value.into_owned())))
+            }
+            #[cfg(feature = "aix")]
+            Some("{") if crypt::aix::is_aix_hash(&value) => Ok(Self::Aix(HashV(value.into_owned()))),
+            #[cfg(feature = "ldap")]
+            Some("{") if crypt::ldap::is_ldap_hash(&value) => {
+                Ok(Self::Ldap(HashV(value.into_owned())))
+            }
+            Some("$") => match hs.take_until(b'$') {
                 #[cfg(feature = "md5")]
-                "1" => Ok(Self::Md5(gater(value, crypt::md5::HASH_LENGTH)?)),
+                Some("1") => Ok(Self::Md5(gater(value, crypt::md5::HASH_LENGTH)?)),
                 #[cfg(feature = "apr1")]
-                "apr1" => Ok(Self::Apr1(gater(value, crypt::apr1::HASH_LENGTH)?)),
+                Some("apr1") => Ok(Self::Apr1(gater(value, crypt::apr1::HASH_LENGTH)?)),
+                #[cfg(feature = "balloon")]
+                Some("balloon") => Ok(Self::Balloon(HashV(value.into_owned()))),
                 #[cfg(feature = "bcrypt")]
-                "2a" | "2b" | "2y" => Ok(Self::Bcrypt(gatel(value, crypt::bcrypt::HASH_LENGTH)?)),
+                Some("2a" | "2b" | "2x" | "2y") => {
+                    Ok(Self::Bcrypt(gatel(value, crypt::bcrypt::HASH_LENGTH)?))
+                }
+                #[cfg(feature = "bcrypt")]
+                Some("2") => Ok(Self::Bcrypt(gatel(
+                    value,
+                    crypt::bcrypt::LEGACY_HASH_LENGTH,
+                )?)),
+                #[cfg(feature = "bcrypt_sha256")]
+                Some("bcrypt-sha256") => Ok(Self::BcryptSha256(gater(
+                    value,
+                    crypt::bcrypt_sha256::HASH_LENGTH,
+                )?)),
+                #[cfg(feature = "cisco")]
+                Some("8") => Ok(Self::CiscoType8(gatel(
+                    value,
+                    crypt::cisco::type8::HASH_LENGTH,
+                )?)),
+                #[cfg(feature = "cisco")]
+                Some("9") => Ok(Self::CiscoType9(gatel(
+                    value,
+                    crypt::cisco::type9::HASH_LENGTH,
+                )?)),
+                #[cfg(feature = "pepper")]
+                Some("pepper") if crypt::pepper::is_peppered_hash(&value) => {
+                    Ok(Self::Peppered(HashV(value.into_owned())))
+                }
+                #[cfg(feature = "pbkdf2")]
+                Some(ident)
+                    if crypt::pbkdf2::is_pbkdf2_ident(ident) && value.matches('$').count() == 4 =>
+                {
+                    Ok(Self::Pbkdf2(HashV(value.into_owned())))
+                }
+                #[cfg(feature = "scrypt")]
+                Some("scrypt") => Ok(Self::Scrypt(HashV(value.into_owned()))),
                 #[cfg(feature = "sha1")]
-                "sha1" => Ok(Self::Sha1(gater(value, crypt::sha1::HASH_LENGTH)?)),
+                Some("sha1") => Ok(Self::Sha1(gater(value, crypt::sha1::HASH_LENGTH)?)),
                 #[cfg(feature = "sha2")]
-                "5" => Ok(Self::Sha256(gater(value, crypt::sha256::HASH_LENGTH)?)),
+                Some("5") => Ok(Self::Sha256(gater(value, crypt::sha256::HASH_LENGTH)?)),
                 #[cfg(feature = "sha2")]
-                "6" => Ok(Self::Sha512(gater(value, crypt::sha512::HASH_LENGTH)?)),
-                _ => Err(Error::InvalidHashString),
+                Some("6") => Ok(Self::Sha512(gater(value, crypt::sha512::HASH_LENGTH)?)),
+                #[cfg(feature = "wrapped")]
+                Some("wrapped") if crypt::wrapped::is_wrapped_hash(&value) => {
+                    Ok(Self::Wrapped(HashV(value.into_owned())))
+                }
+                _ => Hash::identify_custom(value),
             },
+            #[cfg(feature = "django")]
+            _ if crypt::django::is_django_hash(&value) => Ok(Self::Django(HashV(value.into_owned()))),
+            #[cfg(feature = "grub")]
+            _ if crypt::grub::is_grub_hash(&value) => Ok(Self::Grub(HashV(value.into_owned()))),
+            #[cfg(feature = "scram")]
+            _ if crypt::scram::is_scram_hash(&value) => Ok(Self::Scram(HashV(value.into_owned()))),
+            #[cfg(feature = "oracle11g")]
+            _ if crypt::oracle11g::is_oracle11g_hash(&value) => {
+                Ok(Self::Oracle11g(HashV(value.into_owned())))
+            }
+            #[cfg(feature = "mysql323")]
+            _ if crypt::mysql323::is_mysql323_hash(&value) => {
+                Ok(Self::Mysql323(HashV(value.into_owned())))
+            }
             #[cfg(feature = "unix")]
-            _ if value.len() == crypt::unix::HASH_LENGTH => Ok(Self::Unix(HashV(value.to_owned()))),
-            _ => Err(Error::InvalidHashString),
+            _ if value.len() == crypt::unix::HASH_LENGTH => Ok(Self::Unix(HashV(value.into_owned()))),
+            #[cfg(feature = "bigcrypt")]
+            _ if crypt::bigcrypt::is_hash_length(value.len()) => {
+                Ok(Self::Bigcrypt(HashV(value.into_owned())))
+            }
+            _ => Hash::identify_custom(value),
         }
     }
 }
 
+impl TryFrom<&str> for Hash {
+    type Error = Error;
+
+    fn try_from(value: &str) -> std::result::Result<Self, Self::Error> {
+        Hash::from_cow(Cow::Borrowed(value))
+    }
+}
+
+/// Recognize a hash without copying it: the string becomes the buffer of the
+/// `Hash`, which gives it back by [`String::from`].
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::{Algorithm, Hash};
+///
+/// let stored = String::from("$2b$10$saltsaltsaltsaltsaltsOF8gOe7Q2djsDdlP9U528dfcxNNK.BXC");
+/// let ptr = stored.as_ptr();
+/// let h = Hash::try_from(stored).unwrap();
+/// assert_eq!(h.algorithm(), Algorithm::Bcrypt);
+/// let stored = String::from(h);
+/// assert_eq!(stored.as_ptr(), ptr);
+/// ```
+impl TryFrom<String> for Hash {
+    type Error = Error;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        Hash::from_cow(Cow::Owned(value))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Hash {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Hash {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let value = <Cow<'de, str>>::deserialize(deserializer)?;
+        Hash::from_cow(value).map_err(serde::de::Error::custom)
+    }
+}
+
 impl FromStr for Hash {
     type Err = Error;
 
@@ -242,30 +1282,259 @@ impl FromStr for Hash {
 #[cfg(test)]
 mod tests {
 
-    use super::Hash;
+    use super::{Algorithm, Hash, ParseMode, ParsedHash};
+
+    #[test]
+    #[cfg(all(feature = "md5", feature = "sha2"))]
+    fn algorithm() {
+        assert_eq!(
+            Hash::try_from("$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0")
+                .unwrap()
+                .algorithm(),
+            Algorithm::Md5
+        );
+        assert_ne!(
+            Hash::try_from(
+                "$5$rounds=11858$WH1ABM5sKhxbkgCK$aTQsjPkz0rBsH3lQlJxw9HDTDXPKBxC0LlVeV69P.t1"
+            )
+            .unwrap()
+            .algorithm(),
+            Algorithm::Sha512
+        );
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "bcrypt",
+        feature = "bsdi",
+        feature = "sha2",
+        feature = "ldap"
+    ))]
+    fn parts() {
+        let h =
+            Hash::try_from("$2y$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe").unwrap();
+        assert_eq!(
+            h.parts().unwrap(),
+            ParsedHash {
+                ident: Some("2y"),
+                rounds: Some(5),
+                salt: Some("bvIG6Nmid91Mu9RcmmWZfO"),
+                checksum: "5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe",
+            }
+        );
+        let h = Hash::try_from("_Gl/.K0Ay.aosctsbJ1k").unwrap();
+        assert_eq!(h.rounds(), Some(7250));
+        assert_eq!(h.salt(), Some("K0Ay"));
+        let h = Hash::try_from("$5$WH1ABM5sKhxbkgCK$aTQsjPkz0rBsH3lQlJxw9HDTDXPKBxC0LlVeV69P.t1")
+            .unwrap();
+        assert_eq!(h.rounds(), None);
+        assert_eq!(
+            h.checksum(),
+            Some("aTQsjPkz0rBsH3lQlJxw9HDTDXPKBxC0LlVeV69P.t1")
+        );
+        let h = Hash::try_from(
+            "{CRYPT}$6$rounds=11531$G/gkPn17kHYo0gTF$Kq.uZBHlSBXyzsOJXtxJruOOH4yc0Is13\
+             uY7yK0PvAvXxbvc1w8DO1RzREMhKsc82K/Jh8OquV8FZUlreYPJk1",
+        )
+        .unwrap();
+        assert_eq!(h.parts().unwrap().ident, Some("CRYPT"));
+        assert_eq!(h.rounds(), Some(11531));
+        assert_eq!(h.salt(), Some("G/gkPn17kHYo0gTF"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "serde", feature = "md5"))]
+    fn serde() {
+        let h = Hash::try_from("$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0").unwrap();
+        let json = serde_json::to_string(&h).unwrap();
+        assert_eq!(json, r#""$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0""#);
+        assert_eq!(serde_json::from_str::<Hash>(&json).unwrap(), h);
+        let err = serde_json::from_str::<Hash>(r#""$x$5pZSV9va""#).unwrap_err();
+        assert_eq!(err.to_string(), "Unsupported hash scheme '$x$'");
+    }
+
+    #[test]
+    #[cfg(feature = "md5")]
+    fn conversions() {
+        let h = Hash::try_from("$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0").unwrap();
+        assert_eq!(format!("{h}"), "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0");
+        assert_eq!(
+            <Hash as AsRef<str>>::as_ref(&h),
+            "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0"
+        );
+        assert_eq!(String::from(h), "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0");
+
+        let owned = String::from("$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0");
+        let ptr = owned.as_ptr();
+        let h = Hash::try_from(owned).unwrap();
+        assert_eq!(h.algorithm(), Algorithm::Md5);
+        let owned = String::from(h);
+        assert_eq!(owned.as_ptr(), ptr);
+        assert!(matches!(
+            Hash::try_from(String::from("$x$")),
+            Err(crate::error::Error::UnsupportedScheme { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(all(feature = "md5", feature = "sha2"))]
+    fn map_key() {
+        use std::collections::HashMap;
+
+        let md5 = "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0";
+        let sha512 = "$6$saltsalt$qFmFH.bQmmtXzyBY0s9v7Oicd2z4XSIecDzlB5KiA2/jctKu9YterLp8wwnSq.qc.eoxqOmSuNp2xS0ktL3nh/";
+        let mut users = HashMap::new();
+        users.insert(Hash::try_from(md5).unwrap(), "alice");
+        users.insert(Hash::try_from(sha512).unwrap(), "bob");
+        users.insert(Hash::try_from(md5).unwrap(), "carol");
+        assert_eq!(users.len(), 2);
+        assert_eq!(users.get(md5), Some(&"carol"));
+        assert_eq!(users.get(sha512), Some(&"bob"));
+        assert_eq!(users.get("$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa1"), None);
+    }
 
     #[test]
     fn fromstr() {
+        #[cfg(feature = "aix")]
+        assert!(matches!(
+            Hash::try_from("{smd5}17800721$WkGka7tXcrfpUQS6WOQyw/").unwrap(),
+            Hash::Aix(_)
+        ));
         #[cfg(feature = "apr1")]
         assert!(matches!(
             Hash::try_from("$apr1$63JlJ2NH$smE0mnB5h3tDri0zkpWXt1").unwrap(),
             Hash::Apr1(_)
         ));
+        #[cfg(feature = "balloon")]
+        assert!(matches!(
+            Hash::try_from(
+                "$balloon$v=1$s=16,t=1,p=1$ZXhhbXBsZXNhbHQ$\
+                 GJdj7r0AtOnWhMJMJzPwgVgq3aVTFdwlghFjcxUihjU"
+            )
+            .unwrap(),
+            Hash::Balloon(_)
+        ));
         #[cfg(feature = "bcrypt")]
         assert!(matches!(
             Hash::try_from("$2y$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe").unwrap(),
             Hash::Bcrypt(_)
         ));
+        #[cfg(feature = "bigcrypt")]
+        assert!(matches!(
+            Hash::try_from("f8.SVpL2fvwjkAnxn8/rgTkwvrif6bjYB5c").unwrap(),
+            Hash::Bigcrypt(_)
+        ));
+        #[cfg(feature = "bcrypt_sha256")]
+        assert!(matches!(
+            Hash::try_from(
+                "$bcrypt-sha256$v=2,t=2b,r=12$n79VH.0Q2TMWmt3Oqt9uku$Kq4Noyk3094Y2QlB8NdRT8SvGiI4ft2"
+            )
+            .unwrap(),
+            Hash::BcryptSha256(_)
+        ));
         #[cfg(feature = "bcrypt")]
         assert!(matches!(
             Hash::try_from("_Gl/.K0Ay.aosctsbJ1k").unwrap(),
             Hash::Bsdi(_)
         ));
+        #[cfg(feature = "cisco")]
+        assert!(matches!(
+            Hash::try_from("$8$TnGX/fE4KGHOVU$pEhnEvxrvaynpi8j4f.EMHr6M.FzU8xnZnBr/tJdFWk")
+                .unwrap(),
+            Hash::CiscoType8(_)
+        ));
+        #[cfg(feature = "cisco")]
+        assert!(matches!(
+            Hash::try_from("$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6")
+                .unwrap(),
+            Hash::CiscoType9(_)
+        ));
+        #[cfg(feature = "django")]
+        assert!(matches!(
+            Hash::try_from(
+                "pbkdf2_sha256$36000$7Vv7jlObSUcH$CUzgrdCVDegjpJ8Sx3fAFNDxi8T4HZCCDoKpm4ic/48="
+            )
+            .unwrap(),
+            Hash::Django(_)
+        ));
+        #[cfg(feature = "django")]
+        assert!(matches!(
+            Hash::try_from("md5$4sMZU$bb946887404b940f51a8c495834e099a").unwrap(),
+            Hash::Django(_)
+        ));
+        #[cfg(feature = "grub")]
+        assert!(matches!(
+            Hash::try_from(
+                "grub.pbkdf2.sha512.1000.0A0B0C0D.\
+                 9FD26CBC2FD07C6441A3EE0DF2EA5E7088CDC05D9DD8A8DD18C42A7D521631F1\
+                 D6C54A8B54080B7306A22D9C271968CDC2ABD4E2EDE157F4EC2C4B38C432DA41"
+            )
+            .unwrap(),
+            Hash::Grub(_)
+        ));
+        #[cfg(feature = "ldap")]
+        assert!(matches!(
+            Hash::try_from("{SSHA}vbg7B8jFL9JEpqz89wavcCfxwoFEcm1vYk9iYXI=").unwrap(),
+            Hash::Ldap(_)
+        ));
         #[cfg(feature = "md5")]
         assert!(matches!(
             Hash::try_from("$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0").unwrap(),
             Hash::Md5(_)
         ));
+        #[cfg(feature = "mediawiki")]
+        assert!(matches!(
+            Hash::try_from(":A:5f4dcc3b5aa765d61d8327deb882cf99").unwrap(),
+            Hash::Mediawiki(_)
+        ));
+        #[cfg(feature = "mssql")]
+        assert!(matches!(
+            Hash::try_from("0x01000A0B0C0D047F6C48CEFD1E1B260F727880F3DFB211F8D7E0").unwrap(),
+            Hash::Mssql(_)
+        ));
+        #[cfg(feature = "mysql323")]
+        assert!(matches!(
+            Hash::try_from("6f8c114b58f2ce9e").unwrap(),
+            Hash::Mysql323(_)
+        ));
+        #[cfg(feature = "mysql41")]
+        assert!(matches!(
+            Hash::try_from("*2470C0C06DEE42FD1618BB99005ADCA2EC9D1E19").unwrap(),
+            Hash::Mysql41(_)
+        ));
+        #[cfg(feature = "oracle11g")]
+        assert!(matches!(
+            Hash::try_from("S:BA1806B1C8C76C8AD5F1D7845361B79C604C09C11C2D3E4F5A6B7C8D9E0F")
+                .unwrap(),
+            Hash::Oracle11g(_)
+        ));
+        #[cfg(feature = "pbkdf2")]
+        assert!(matches!(
+            Hash::try_from(
+                "$pbkdf2-sha256$6400$0ZrzXitFSGltTQnBWOsdAw$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M"
+            )
+            .unwrap(),
+            Hash::Pbkdf2(_)
+        ));
+        #[cfg(feature = "scram")]
+        assert!(matches!(
+            Hash::try_from(
+                "SCRAM-SHA-256$1000:c2FsdHNhbHRzYWx0c2FsdA==$\
+                 TQgQ/ULZMHOt/8NsCPH9D8aYP6CI2DfFTisDD4VrInM=:\
+                 a8G2SD9IzL9WIL4txAzJFZhu+/sAn93tu6hM+jX1754="
+            )
+            .unwrap(),
+            Hash::Scram(_)
+        ));
+        #[cfg(feature = "scrypt")]
+        assert!(matches!(
+            Hash::try_from(
+                "$scrypt$ln=10,r=8,p=1$c2FsdHNhbHRzYWx0c2FsdA$\
+                 BVMRKqdiVYikKAaPR1wucsKUKvw4TuPLkdEYtoSHas4"
+            )
+            .unwrap(),
+            Hash::Scrypt(_)
+        ));
         #[cfg(feature = "sha1")]
         assert!(matches!(
             Hash::try_from("$sha1$19703$iVdJqfSE$v4qYKl1zqYThwpjJAoKX6UvlHq/a").unwrap(),
@@ -288,10 +1557,188 @@ mod tests {
             .unwrap(),
             Hash::Sha512(_)
         ));
+        #[cfg(all(feature = "pepper", feature = "sha2"))]
+        assert!(matches!(
+            Hash::try_from(
+                "$pepper$$6$rounds=1000$saltsalt$fIX8O5J8gVCXsuO5YuX3N6FAJzyEzL67wu0OJCFdk4oxJfLLl\
+                 zZImjnHLJStrl2mDkXy.z1fLuzPoa8fuyXgG."
+            )
+            .unwrap(),
+            Hash::Peppered(_)
+        ));
         #[cfg(feature = "unix")]
         assert!(matches!(
             Hash::try_from("aZGJuE6EXrjEE").unwrap(),
             Hash::Unix(_)
         ));
+        #[cfg(feature = "wrapped")]
+        assert!(matches!(
+            Hash::try_from(
+                "$wrapped$md5$2b$05$bvIG6Nmid91Mu9RcmmWZfOnrKcyxPXw/lp2oDFdHsUVQKnjkbtREW"
+            )
+            .unwrap(),
+            Hash::Wrapped(_)
+        ));
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "apr1",
+        feature = "bcrypt",
+        feature = "bsdi",
+        feature = "md5",
+        feature = "sha1",
+        feature = "sha2",
+        feature = "unix"
+    ))]
+    fn parse_mode() {
+        use super::ParseMode;
+
+        for h in [
+            "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0",
+            "$apr1$63JlJ2NH$smE0mnB5h3tDri0zkpWXt1",
+            "$2y$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe",
+            "_J9..saltJW8FtKdEkNM",
+            "$sha1$19703$iVdJqfSE$v4qYKl1zqYThwpjJAoKX6UvlHq/a",
+            "$5$rounds=11858$WH1ABM5sKhxbkgCK$aTQsjPkz0rBsH3lQlJxw9HDTDXPKBxC0LlVeV69P.t1",
+            "$6$ab$WfYjcVtm04.lEYV07CdYGA5G9xet7/eU/m3ApNyi7sD.pE7qFDG1ek7dRQpI2KCf9ESl1WoIH04x.DMDvmIed1",
+            "abJnggxhB/yWI",
+        ] {
+            assert_eq!(Hash::parse(h, ParseMode::Strict).unwrap(), h);
+        }
+        for h in [
+            "$6$saltsaltsaltsaltsalt$bcXJ8qxwY5sQ4v8MTl.0B1jeZ0z0JlA9jjmbUoCJZ.1wYXiLTU.q2ILyrDJLm890lyfuF7sWAeli0yjOyFPkf0",
+            "$5$rounds=11858$WH1ABM5sKhxbkgCK$aTQsjPkz0rBsH3lQlJxw9HDTDXPKBxC0LlVeV69P.tz",
+            "$2y$05$bvIG6Nmid91Mu9RcmmWZfP5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe",
+            "$2y$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpf",
+            "abJnggxhB/yWJ",
+        ] {
+            assert!(Hash::parse(h, ParseMode::Lenient).is_ok());
+            assert!(Hash::parse(h, ParseMode::Strict).is_err());
+        }
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "bcrypt",
+        feature = "ldap",
+        feature = "md5",
+        feature = "scrypt",
+        feature = "sha1",
+        feature = "sha2",
+        feature = "unix"
+    ))]
+    fn checksum_bytes() {
+        use crate::encode::{
+            bcrypt_hash64_encode, crypt_hash64_encode, hex_encode, sha1crypt_hash64_encode,
+        };
+
+        let bytes = |h: &str| Hash::try_from(h).unwrap().checksum_bytes().unwrap();
+        assert_eq!(
+            hex_encode(&bytes("$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0")),
+            "de98a19d8631bf8d117a37a6e6db6b49"
+        );
+        assert_eq!(
+            hex_encode(&bytes("{CRYPT}$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0")),
+            "de98a19d8631bf8d117a37a6e6db6b49"
+        );
+        assert_eq!(
+            hex_encode(&bytes(
+                "$5$rounds=1000$saltsalt$azOwbpkvuuBKkE82dQPwTsQE8JyT9Fflpr9aKid3aT9"
+            )),
+            "f10dba10b71f7fb4f516af6758a42941e54b989be6efde30f1ce4ac6bd96e6b7"
+        );
+        assert_eq!(
+            hex_encode(&bytes(
+                "$6$rounds=1000$saltsalt$Z/J9iYO1iE9xnr8JPQL57ZWsVRtVjrUv3CiWc/wKWseqXgSqn3HF\
+                 YJ/Ng7YXa8XlLj.wpdAwHOJJzuGFqBBRa0"
+            )),
+            "2d2eb4541b2987efe35b22eb456442c6d7ca55bfd3500d2ead1d4997ee85c0da\
+             2331646c32f07556457665a9f4f377e2610d8a68aed973158ea60bf0932e74a6"
+        );
+        assert_eq!(
+            bcrypt_hash64_encode(&bytes(
+                "$2y$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe"
+            )),
+            "5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe"
+        );
+        assert_eq!(
+            sha1crypt_hash64_encode(&bytes("$sha1$19703$iVdJqfSE$v4qYKl1zqYThwpjJAoKX6UvlHq/a")),
+            "v4qYKl1zqYThwpjJAoKX6UvlHq/a"
+        );
+        assert_eq!(crypt_hash64_encode(&bytes("aZGJuE6EXrjEE")), "GJuE6EXrjEE");
+        assert_eq!(
+            bytes(
+                "$scrypt$ln=10,r=8,p=1$c2FsdHNhbHRzYWx0c2FsdA$\
+                 BVMRKqdiVYikKAaPR1wucsKUKvw4TuPLkdEYtoSHas4"
+            )
+            .len(),
+            32
+        );
+        assert!(matches!(
+            Hash::try_from("$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa!")
+                .unwrap()
+                .checksum_bytes(),
+            Err(crate::error::Error::EncodingError)
+        ));
+    }
+
+    #[test]
+    fn no_panics() {
+        use crate::{Salt, phc::PhcString, unix};
+
+        const CORPUS: &[&str] = &[
+            "$1$LMejezCX$fMX7SzYqoSible/q5ag.v.",
+            "$apr1$sI1GS1eE$e1VhXMPl0KMuSiX1DcBcx.",
+            "$2b$04$lyD6uHEfBfjwUc/X3pKbTOCyiDXHWekJS.7TTqeRgHJqTOJkFMXUa",
+            "$bcrypt-sha256$v=2,t=2b,r=4$KxNQBBumN/zirmfT1MuJ4O$Jv/wcAb418H/VD/S.u/twJFttpPHN82",
+            "$5$rounds=1000$zPYEMQ.0k/NqMiZ6$OtJgjTHcRusg5031EPKBW.HjhLZuG75D0UCpyK0g9f0",
+            "$sha1$1$Xpr8jYrTTDdd9cj9OL1PHgzWPbM7cGF5ZOnP8tOZjMXeAdZe5eHxY8PNbibpaSFC$cSrPV0BWE\
+             g0O/eBNT8JSLtyrj3An",
+            "_/...X4mG9Fo0d3zGy7Q",
+            "hNlN3uQOf0Hyg",
+            "d2jMNRfSAuOpgj8ANu1Ohjew",
+            "$balloon$v=1$s=3,t=3,p=1$c2FsdA$XwL4IG+c0hJIXGvfhVJ7aYlWcBrQhSEG+UuU7pRXc3g",
+            "$scrypt$ln=1,r=8,p=1$lJusoAB2+G5TiEr5X7tpOw$Xn24zUgHeqstkd2tTRp8T5dsuUICjz2Dc/NrI1R5/yk",
+            "$pbkdf2-sha256$1$DvR/Aab/Ch2im9BMtYxhoQ$/96tktAq73XKWUzN64qkF2/B1OniPgNBo8EaBBGr4KU",
+            "pbkdf2_sha256$1$.Jd0BdLEC52IJzn1iVzWfq$gQDwP/jKToiR+an7hGLP28zt/xMkzQYjg3BCb3WGPsU=",
+            "SCRAM-SHA-256$1:az4jNvpJzzuw9Ovr/VVZoQ==$F7ce45FAxOoqrIaRfOyCkBq5WDZ9J2JtyUEFu9yrqUI=\
+             :Z+7pJEuGL8tweUwYackpgQsVwjR5MEmJD9rJzDnyi3o=",
+            "{ssha512}04$jTLKaRU62n8qUDPe$8KIFnmFC1wEGIr6B85KXzj2gbD1mnCgRsee7p3axDZ0ntQVYiL7et\
+             /rQgdbde6YksnJMyl8txnA322XWGqYO..",
+            "{SSHA}THEmXfdMQaGYP2kSfMOYyYeYRNx4BMOsGe7ELg==",
+            ":B:d7579f68:6cde5debd59280a9a4f3af6b7b4ed323",
+            "*2470C0C06DEE42FD1618BB99005ADCA2EC9D1E19",
+            "5d2e19393cc5ef67",
+        ];
+
+        // Every truncation of the corpus, and every variant with one character
+        // replaced by a delimiter, a control character, or a non-ASCII one.
+        let mut inputs = Vec::new();
+        for hash in CORPUS {
+            for (i, c) in hash.char_indices() {
+                inputs.push(hash[..i].to_string());
+                for r in ['\0', '$', '\u{80}'] {
+                    inputs.push(format!("{}{r}{}", &hash[..i], &hash[i + c.len_utf8()..]));
+                }
+            }
+        }
+        // Wrapping hashes nested far deeper than any stack allows.
+        for prefix in ["{CRYPT}", "$pepper$", "$wrapped$md5", "$pepper${CRYPT}"] {
+            inputs.push(format!("{}{}", prefix.repeat(100_000), CORPUS[0]));
+        }
+        for input in &inputs {
+            for mode in [ParseMode::Lenient, ParseMode::Strict] {
+                if let Ok(h) = Hash::parse(input, mode) {
+                    let _ = (h.salt(), h.checksum(), h.parts(), h.checksum_bytes());
+                    let _ = (h.is_deprecated(), h.is_recommended());
+                }
+            }
+            let _ = unix::verify("password", input);
+            let _ = unix::classify(input);
+            let _ = PhcString::parse(input);
+            let _ = input.parse::<Algorithm>();
+            let _ = Salt::from_str_checked(input, Algorithm::Sha512);
+        }
     }
 }
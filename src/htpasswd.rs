@@ -0,0 +1,546 @@
+//! Apache credential files.
+//!
+//! The `htpasswd` utility of the Apache HTTP Server maintains files of
+//! *`{user}`*__`:`__*`{hash}`* lines for HTTP Basic authentication.
+//! [`HtpasswdFile`] reads such a file, adds, replaces, and removes users,
+//! verifies their passwords, and writes the file back. Lines which aren't
+//! entries, such as comments, are kept as they are.
+//!
+//! The `htdigest` utility maintains files of
+//! *`{user}`*__`:`__*`{realm}`*__`:`__*`{hash}`* lines for HTTP Digest
+//! authentication, where the hash is computed by
+//! [`crypt::htdigest`](crate::crypt::htdigest). [`HtdigestFile`] manages
+//! them in the same way; it's available with the `htdigest` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::{Algorithm, htpasswd::HtpasswdFile};
+//!
+//! let mut file = HtpasswdFile::new();
+//! file.set("alice", "password", Algorithm::Bcrypt).unwrap();
+//! assert!(file.verify("alice", "password"));
+//! assert!(file.get("alice").unwrap().starts_with("$2y$"));
+//! assert!(file.remove("alice"));
+//! assert!(!file.verify("alice", "password"));
+//! ```
+
+use std::{
+    ffi::OsString,
+    fmt, fs,
+    io::{self, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use crate::{
+    Algorithm, Hash,
+    error::{Error, Result},
+    unix,
+};
+
+// Check that a field of a new entry can be written to a credential file.
+pub(crate) fn check_field(field: &str) -> Result<()> {
+    match field.contains([':', '\n', '\r']) {
+        true => Err(Error::InvalidField),
+        false => Ok(()),
+    }
+}
+
+// Read a credential file.
+pub(crate) fn read_file(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(Error::Io)
+}
+
+// Write a credential file. The text is written to a temporary file in the
+// same directory, which is synced and renamed over the old file, so that a
+// reader or a crash never sees a partial file.
+pub(crate) fn write_file(path: &Path, text: String) -> Result<()> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| Error::Io(io::ErrorKind::InvalidInput.into()))?;
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(name);
+    tmp_name.push(format!(".{}.tmp", std::process::id()));
+    let tmp = path.with_file_name(tmp_name);
+    let result = write_tmp(path, &tmp, text.as_bytes()).and_then(|()| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result.map_err(Error::Io)
+}
+
+// Write the temporary file of `write_file`, with the permissions and, on
+// Unix, the owner of the file it replaces.
+fn write_tmp(path: &Path, tmp: &Path, data: &[u8]) -> io::Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(tmp)?;
+    match fs::metadata(path) {
+        Ok(meta) => {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::MetadataExt;
+
+                std::os::unix::fs::fchown(&file, Some(meta.uid()), Some(meta.gid()))?;
+            }
+            file.set_permissions(meta.permissions())?;
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+    file.write_all(data)?;
+    file.sync_all()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Line {
+    // An entry; `rest` is everything after the colon following the user
+    // name, which starts with the hash.
+    Entry { user: String, rest: String },
+    // A comment, an empty line, or a line without a colon.
+    Other(String),
+}
+
+/// An `htpasswd` credential file.
+///
+/// Like Apache, which uses the first entry of a user, lookups and updates
+/// find the first entry with the given user name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HtpasswdFile {
+    lines: Vec<Line>,
+}
+
+impl HtpasswdFile {
+    /// Create an empty file.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        read_file(path.as_ref())?.parse()
+    }
+
+    /// Write the file, replacing its previous contents.
+    ///
+    /// The file is replaced atomically, keeping its permissions and owner.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write_file(path.as_ref(), self.to_string())
+    }
+
+    /// Get the hash of a user.
+    pub fn get(&self, user: &str) -> Option<&str> {
+        self.lines.iter().find_map(|line| match line {
+            Line::Entry { user: u, rest } if u == user => rest.split(':').next(),
+            _ => None,
+        })
+    }
+
+    /// Iterate over the user names of the entries.
+    pub fn users(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Entry { user, .. } => Some(user.as_str()),
+            Line::Other(_) => None,
+        })
+    }
+
+    /// Set the password of a user, adding the user if there's no entry.
+    ///
+    /// The hash is made like `htpasswd` does with the corresponding option:
+    ///
+    /// * [`Algorithm::Bcrypt`] (`-B`): a `$2y$` hash, with the default cost
+    ///   of this crate, which is higher than the one of `htpasswd`.
+    /// * [`Algorithm::Apr1`] (`-m`, the default of `htpasswd`): an Apache
+    ///   MD5 hash.
+    /// * [`Algorithm::Ldap`] (`-s`): an unsalted `{SHA}` hash.
+    /// * [`Algorithm::Sha256`] and [`Algorithm::Sha512`] (`-2` and `-5`):
+    ///   SHA-crypt hashes.
+    /// * [`Algorithm::Unix`] (`-d`): a traditional DES hash, which uses only
+    ///   the first eight bytes of the password.
+    ///
+    /// Other algorithms give [`Error::UnsupportedScheme`]. A user name with
+    /// a colon or a line break gives [`Error::InvalidField`].
+    pub fn set<B: AsRef<[u8]>>(&mut self, user: &str, pass: B, algorithm: Algorithm) -> Result<()> {
+        check_field(user)?;
+        let hash = hash_for(pass.as_ref(), algorithm)?;
+        self.set_hash(user, hash.as_str())
+    }
+
+    /// Set the hash of a user, adding the user if there's no entry.
+    ///
+    /// This is for hashes made with other parameters than those of
+    /// [`set`](HtpasswdFile::set). The hash must be a recognized one. Fields
+    /// after the hash in an existing entry are kept.
+    pub fn set_hash(&mut self, user: &str, hash: &str) -> Result<()> {
+        check_field(user)?;
+        check_field(hash)?;
+        Hash::try_from(hash)?;
+        let entry = self.lines.iter_mut().find_map(|line| match line {
+            Line::Entry { user: u, rest } if u == user => Some(rest),
+            _ => None,
+        });
+        match entry {
+            Some(rest) => {
+                let end = rest.find(':').unwrap_or(rest.len());
+                rest.replace_range(..end, hash);
+            }
+            None => self.lines.push(Line::Entry {
+                user: user.to_string(),
+                rest: hash.to_string(),
+            }),
+        }
+        Ok(())
+    }
+
+    /// Remove all entries of a user, returning whether there was one.
+    pub fn remove(&mut self, user: &str) -> bool {
+        let len = self.lines.len();
+        self.lines
+            .retain(|line| !matches!(line, Line::Entry { user: u, .. } if u == user));
+        self.lines.len() != len
+    }
+
+    /// Verify the password of a user.
+    ///
+    /// Unknown users never verify.
+    pub fn verify<B: AsRef<[u8]>>(&self, user: &str, pass: B) -> bool {
+        self.get(user).is_some_and(|hash| unix::verify(pass, hash))
+    }
+}
+
+// Hash a password in one of the formats of `htpasswd`.
+#[allow(unused_variables)]
+fn hash_for(pass: &[u8], algorithm: Algorithm) -> Result<Hash> {
+    match algorithm {
+        #[cfg(feature = "bcrypt")]
+        Algorithm::Bcrypt => {
+            use crate::crypt::bcrypt::{self, BcryptSetup, BcryptVariant};
+
+            crate::policy::check_password_len(pass)?;
+            bcrypt::hash_with(BcryptSetup::default().variant(BcryptVariant::V2y), pass)
+        }
+        #[cfg(feature = "ldap")]
+        Algorithm::Ldap => {
+            use crate::crypt::ldap::{self, LdapScheme, LdapSetup};
+
+            crate::policy::check_password_len(pass)?;
+            ldap::hash_with(LdapSetup::default().scheme(LdapScheme::Sha), pass)
+        }
+        #[cfg(feature = "apr1")]
+        Algorithm::Apr1 => algorithm.hash(pass),
+        #[cfg(feature = "sha2")]
+        Algorithm::Sha256 | Algorithm::Sha512 => algorithm.hash(pass),
+        #[cfg(feature = "unix")]
+        Algorithm::Unix => algorithm.hash(pass),
+        _ => Err(Error::UnsupportedScheme {
+            prefix: algorithm.name().to_string(),
+        }),
+    }
+}
+
+impl FromStr for HtpasswdFile {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let lines = text
+            .lines()
+            .map(|line| match line.split_once(':') {
+                Some((user, rest)) if !line.starts_with('#') => Line::Entry {
+                    user: user.to_string(),
+                    rest: rest.to_string(),
+                },
+                _ => Line::Other(line.to_string()),
+            })
+            .collect();
+        Ok(HtpasswdFile { lines })
+    }
+}
+
+impl fmt::Display for HtpasswdFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.lines {
+            match line {
+                Line::Entry { user, rest } => writeln!(f, "{user}:{rest}")?,
+                Line::Other(line) => writeln!(f, "{line}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An `htdigest` credential file.
+///
+/// A user can have an entry for each realm. Lookups and updates find the
+/// first entry with the given user name and realm.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::htpasswd::HtdigestFile;
+///
+/// let mut file: HtdigestFile = "alice:Restricted:841cf5923f716cb992566a3d5c628144\n"
+///     .parse()
+///     .unwrap();
+/// assert!(file.verify("alice", "Restricted", "password"));
+/// file.set("bob", "Restricted", "secret").unwrap();
+/// assert!(file.verify("bob", "Restricted", "secret"));
+/// assert!(!file.verify("bob", "Private", "secret"));
+/// ```
+#[cfg(feature = "htdigest")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HtdigestFile {
+    lines: Vec<DigestLine>,
+}
+
+#[cfg(feature = "htdigest")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum DigestLine {
+    Entry {
+        user: String,
+        realm: String,
+        hash: String,
+    },
+    // A comment, an empty line, or a line without three fields.
+    Other(String),
+}
+
+#[cfg(feature = "htdigest")]
+impl HtdigestFile {
+    /// Create an empty file.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        read_file(path.as_ref())?.parse()
+    }
+
+    /// Write the file, replacing its previous contents.
+    ///
+    /// The file is replaced atomically, keeping its permissions and owner.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write_file(path.as_ref(), self.to_string())
+    }
+
+    /// Get the hash of a user in a realm.
+    pub fn get(&self, user: &str, realm: &str) -> Option<&str> {
+        self.lines.iter().find_map(|line| match line {
+            DigestLine::Entry {
+                user: u,
+                realm: r,
+                hash,
+            } if u == user && r == realm => Some(hash.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Iterate over the user names and realms of the entries.
+    pub fn users(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.lines.iter().filter_map(|line| match line {
+            DigestLine::Entry { user, realm, .. } => Some((user.as_str(), realm.as_str())),
+            DigestLine::Other(_) => None,
+        })
+    }
+
+    /// Set the password of a user in a realm, adding an entry if there's
+    /// none.
+    ///
+    /// A user name or a realm with a colon or a line break gives
+    /// [`Error::InvalidField`].
+    pub fn set<B: AsRef<[u8]>>(&mut self, user: &str, realm: &str, pass: B) -> Result<()> {
+        check_field(user)?;
+        check_field(realm)?;
+        #[allow(deprecated)]
+        let hash = crate::crypt::htdigest::hash(pass, user, realm);
+        let entry = self.lines.iter_mut().find_map(|line| match line {
+            DigestLine::Entry {
+                user: u,
+                realm: r,
+                hash,
+            } if u == user && r == realm => Some(hash),
+            _ => None,
+        });
+        match entry {
+            Some(old) => *old = hash,
+            None => self.lines.push(DigestLine::Entry {
+                user: user.to_string(),
+                realm: realm.to_string(),
+                hash,
+            }),
+        }
+        Ok(())
+    }
+
+    /// Remove all entries of a user in a realm, returning whether there was
+    /// one.
+    pub fn remove(&mut self, user: &str, realm: &str) -> bool {
+        let len = self.lines.len();
+        self.lines.retain(|line| {
+            !matches!(line, DigestLine::Entry { user: u, realm: r, .. } if u == user && r == realm)
+        });
+        self.lines.len() != len
+    }
+
+    /// Verify the password of a user in a realm.
+    ///
+    /// Unknown users and realms never verify.
+    pub fn verify<B: AsRef<[u8]>>(&self, user: &str, realm: &str, pass: B) -> bool {
+        self.get(user, realm)
+            .is_some_and(|hash| crate::crypt::htdigest::verify(pass, user, realm, hash))
+    }
+}
+
+#[cfg(feature = "htdigest")]
+impl FromStr for HtdigestFile {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let lines = text
+            .lines()
+            .map(|line| match line.split(':').collect::<Vec<_>>()[..] {
+                [user, realm, hash] if !line.starts_with('#') => DigestLine::Entry {
+                    user: user.to_string(),
+                    realm: realm.to_string(),
+                    hash: hash.to_string(),
+                },
+                _ => DigestLine::Other(line.to_string()),
+            })
+            .collect();
+        Ok(HtdigestFile { lines })
+    }
+}
+
+#[cfg(feature = "htdigest")]
+impl fmt::Display for HtdigestFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.lines {
+            match line {
+                DigestLine::Entry { user, realm, hash } => writeln!(f, "{user}:{realm}:{hash}")?,
+                DigestLine::Other(line) => writeln!(f, "{line}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HtpasswdFile;
+    use crate::{Algorithm, error::Error};
+
+    const TEXT: &str = "# users\n\
+                        alice:$apr1$63JlJ2NH$smE0mnB5h3tDri0zkpWXt1\n\
+                        \n\
+                        bob:{SHA}W6ph5Mm5Pz8GgiULbPgzG37mj9g=:extra\n";
+
+    #[test]
+    #[cfg(all(feature = "apr1", feature = "ldap"))]
+    fn parse() {
+        let file: HtpasswdFile = TEXT.parse().unwrap();
+        assert_eq!(file.users().collect::<Vec<_>>(), ["alice", "bob"]);
+        assert_eq!(file.get("bob"), Some("{SHA}W6ph5Mm5Pz8GgiULbPgzG37mj9g="));
+        assert!(file.verify("bob", "password"));
+        assert!(!file.verify("bob", "passwort"));
+        assert!(!file.verify("carol", "password"));
+        assert_eq!(file.to_string(), TEXT);
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "apr1",
+        feature = "bcrypt",
+        feature = "bsdi",
+        feature = "ldap",
+        feature = "unix"
+    ))]
+    fn update() {
+        let mut file: HtpasswdFile = TEXT.parse().unwrap();
+        file.set("bob", "secret", Algorithm::Ldap).unwrap();
+        assert!(file.verify("bob", "secret"));
+        assert_eq!(file.get("bob"), Some("{SHA}5en6G6MezRroT3XKqkdPOmY/BfQ="));
+        assert!(
+            file.to_string()
+                .ends_with("bob:{SHA}5en6G6MezRroT3XKqkdPOmY/BfQ=:extra\n")
+        );
+        file.set("carol", "secret", Algorithm::Unix).unwrap();
+        assert_eq!(file.get("carol").unwrap().len(), 13);
+        file.set("dave", "secret", Algorithm::Apr1).unwrap();
+        assert!(file.get("dave").unwrap().starts_with("$apr1$"));
+        assert!(file.remove("alice"));
+        assert!(!file.remove("alice"));
+        assert_eq!(file.users().collect::<Vec<_>>(), ["bob", "carol", "dave"]);
+        assert!(file.to_string().starts_with("# users\n\nbob:{SHA}"));
+
+        assert!(matches!(
+            file.set("e:ve", "secret", Algorithm::Bcrypt),
+            Err(Error::InvalidField)
+        ));
+        assert!(matches!(
+            file.set("eve", "secret", Algorithm::Bsdi),
+            Err(Error::UnsupportedScheme { .. })
+        ));
+        assert!(file.set_hash("eve", "plain").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "htdigest")]
+    fn htdigest() {
+        use super::HtdigestFile;
+
+        let text = "alice:Restricted:841cf5923f716cb992566a3d5c628144\n\
+                    # admins\n\
+                    alice:Admin:841cf5923f716cb992566a3d5c628144\n";
+        let mut file: HtdigestFile = text.parse().unwrap();
+        assert_eq!(
+            file.users().collect::<Vec<_>>(),
+            [("alice", "Restricted"), ("alice", "Admin")]
+        );
+        assert!(file.verify("alice", "Restricted", "password"));
+        assert!(!file.verify("alice", "Admin", "password"));
+        assert_eq!(file.to_string(), text);
+
+        file.set("alice", "Admin", "secret").unwrap();
+        assert!(file.verify("alice", "Admin", "secret"));
+        assert!(file.remove("alice", "Restricted"));
+        assert!(!file.verify("alice", "Restricted", "password"));
+        assert_eq!(file.users().count(), 1);
+        assert!(matches!(
+            file.set("bob", "Re:stricted", "secret"),
+            Err(Error::InvalidField)
+        ));
+
+        let path = std::env::temp_dir().join(format!("crypt3_htdigest_{}", std::process::id()));
+        file.save(&path).unwrap();
+        let loaded = HtdigestFile::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, file);
+    }
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn load_save() {
+        let path = std::env::temp_dir().join(format!("crypt3_htpasswd_{}", std::process::id()));
+        let mut file = HtpasswdFile::new();
+        file.set("alice", "password", Algorithm::Sha512).unwrap();
+        file.save(&path).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = std::fs::Permissions::from_mode(0o640);
+            std::fs::set_permissions(&path, mode).unwrap();
+            file.set("bob", "password", Algorithm::Sha512).unwrap();
+            file.save(&path).unwrap();
+            let meta = std::fs::metadata(&path).unwrap();
+            assert_eq!(meta.permissions().mode() & 0o777, 0o640);
+        }
+        let loaded = HtpasswdFile::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, file);
+        assert!(loaded.verify("alice", "password"));
+        assert!(matches!(HtpasswdFile::load(&path), Err(Error::Io(_))));
+    }
+}
@@ -1968,11 +1968,16 @@ pub fn des_cipher(input: u64, keyword: u64, salt: u32, mut num_iter: u32) -> u64
     perm6464(L, &CF6464)
 }
 
-use crate::crypt::{bsdi, unix};
-use crate::encode::{crypt_hash64_encode, decode_val, encode_val};
+#[cfg(feature = "bsdi")]
+use crate::crypt::bsdi;
+#[cfg(feature = "unix")]
+use crate::crypt::unix;
+#[cfg(any(feature = "bigcrypt", feature = "crypt16"))]
+use crate::encode::decode_val;
+use crate::encode::{crypt_hash64_encode, decode_legacy_val, encode_val};
 use crate::error::Result;
 
-use std::{cmp::min, iter};
+use std::{borrow::Cow, cmp::min, iter};
 
 #[inline]
 fn secret_to_key(key: &[u8]) -> u64 {
@@ -1982,6 +1987,18 @@ fn secret_to_key(key: &[u8]) -> u64 {
         .fold(0u64, |kw, b| (kw << 8) | (b << 1) as u64)
 }
 
+// Decode the salt of a hash. Like in the BSD implementations, any byte is
+// accepted, and the salt is copied into the hash as it is; only a salt which
+// isn't printable ASCII is replaced by its standard encoding.
+fn legacy_salt(salt: &[u8], len: usize) -> Result<(Cow<'_, str>, u32)> {
+    let salt_val = decode_legacy_val(salt, len)?;
+    let text = match std::str::from_utf8(&salt[..len]) {
+        Ok(text) if text.bytes().all(|b| b.is_ascii_graphic()) => Cow::Borrowed(text),
+        _ => Cow::Owned(encode_val(salt_val, len)),
+    };
+    Ok((text, salt_val))
+}
+
 fn do_0_crypt(keyword: u64, salt: u32, rounds: u32) -> String {
     let mut result_block = des_cipher(0, keyword, salt, rounds);
     let mut result_array = [0u8; 8];
@@ -1992,19 +2009,19 @@ fn do_0_crypt(keyword: u64, salt: u32, rounds: u32) -> String {
     crypt_hash64_encode(&result_array)
 }
 
+#[cfg(feature = "unix")]
 const DES_ROUNDS: u32 = 25;
 
-pub fn unix_crypt(key: &[u8], salt: &str) -> Result<String> {
+#[cfg(feature = "unix")]
+pub fn unix_crypt(key: &[u8], salt: &[u8]) -> Result<String> {
+    let (salt, salt_val) = legacy_salt(salt, unix::SALT_LEN)?;
     let keyword = secret_to_key(key);
-    let salt_val = decode_val(salt, unix::SALT_LEN)?;
-    Ok(format!(
-        "{}{}",
-        encode_val(salt_val, unix::SALT_LEN),
-        do_0_crypt(keyword, salt_val, DES_ROUNDS)
-    ))
+    Ok(format!("{salt}{}", do_0_crypt(keyword, salt_val, DES_ROUNDS)))
 }
 
-pub fn bsdi_crypt(key: &[u8], salt: &str, rounds: u32) -> Result<String> {
+#[cfg(feature = "bsdi")]
+pub fn bsdi_crypt(key: &[u8], salt: &[u8], rounds: u32) -> Result<String> {
+    let (salt, salt_val) = legacy_salt(salt, bsdi::SALT_LEN)?;
     let keylen = key.len();
     let mut keyword = secret_to_key(&key[..min(keylen, 8)]);
     let mut idx = 8;
@@ -2013,11 +2030,46 @@ pub fn bsdi_crypt(key: &[u8], salt: &str, rounds: u32) -> Result<String> {
         keyword = des_cipher(keyword, keyword, 0, 1) ^ next_keyword;
         idx += 8;
     }
-    let salt_val = decode_val(salt, bsdi::SALT_LEN)?;
     Ok(format!(
-        "_{}{}{}",
+        "_{}{salt}{}",
         encode_val(rounds, bsdi::SALT_LEN),
-        encode_val(salt_val, bsdi::SALT_LEN),
         do_0_crypt(keyword, salt_val, rounds)
     ))
 }
+
+#[cfg(feature = "bigcrypt")]
+pub fn bigcrypt(key: &[u8], salt: &[u8]) -> Result<String> {
+    let (salt, mut salt_val) = legacy_salt(salt, unix::SALT_LEN)?;
+    let keylen = key.len();
+    let mut out = salt.into_owned();
+    let mut idx = 0;
+    loop {
+        let keyword = secret_to_key(&key[idx..min(keylen, idx + 8)]);
+        let segment = do_0_crypt(keyword, salt_val, DES_ROUNDS);
+        salt_val = decode_val(&segment, unix::SALT_LEN)?;
+        out.push_str(&segment);
+        idx += 8;
+        if idx >= keylen {
+            break;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(feature = "crypt16")]
+pub fn crypt16(key: &[u8], salt: &str) -> Result<String> {
+    Ok(crypt16_val(key, decode_val(salt, unix::SALT_LEN)?))
+}
+
+#[cfg(feature = "crypt16")]
+pub fn crypt16_val(key: &[u8], salt_val: u32) -> String {
+    let keylen = key.len();
+    let keyword1 = secret_to_key(&key[..min(keylen, 8)]);
+    let keyword2 = secret_to_key(&key[min(keylen, 8)..min(keylen, 16)]);
+    format!(
+        "{}{}{}",
+        encode_val(salt_val, unix::SALT_LEN),
+        do_0_crypt(keyword1, salt_val, 20),
+        do_0_crypt(keyword2, salt_val, 5)
+    )
+}
@@ -0,0 +1,120 @@
+// MD4 message digest (RFC 1320).
+//
+// MD4 is long broken, and is implemented only because the NT hash and the
+// schemes derived from it are built on it.
+
+const STATE_INIT: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+const SHIFTS: [[u32; 4]; 3] = [[3, 7, 11, 19], [3, 5, 9, 13], [3, 9, 11, 15]];
+
+const ORDER: [[usize; 16]; 3] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3, 7, 11, 15],
+    [0, 8, 4, 12, 2, 10, 6, 14, 1, 9, 5, 13, 3, 11, 7, 15],
+];
+
+fn compress(state: &mut [u32; 4], block: &[u8]) {
+    let mut x = [0u32; 16];
+    for (word, chunk) in x.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    let [mut a, mut b, mut c, mut d] = *state;
+    for (round, (order, shifts)) in ORDER.iter().zip(SHIFTS).enumerate() {
+        for (i, &k) in order.iter().enumerate() {
+            let (f, konst) = match round {
+                0 => ((b & c) | (!b & d), 0),
+                1 => ((b & c) | (b & d) | (c & d), 0x5a827999),
+                _ => (b ^ c ^ d, 0x6ed9eba1),
+            };
+            let t = a
+                .wrapping_add(f)
+                .wrapping_add(x[k])
+                .wrapping_add(konst)
+                .rotate_left(shifts[i % 4]);
+            a = d;
+            d = c;
+            c = b;
+            b = t;
+        }
+    }
+    for (s, v) in state.iter_mut().zip([a, b, c, d]) {
+        *s = s.wrapping_add(v);
+    }
+}
+
+/// Compute the MD4 digest of the input.
+///
+/// MD4 is cryptographically broken. It's provided for interoperability with
+/// the NT hash family, and mustn't be used as a general-purpose digest.
+pub fn md4(data: &[u8]) -> [u8; 16] {
+    let bit_len = (data.len() as u64).wrapping_mul(8);
+    let mut state = STATE_INIT;
+    let mut blocks = data.chunks_exact(64);
+    for block in blocks.by_ref() {
+        compress(&mut state, block);
+    }
+
+    let mut tail = blocks.remainder().to_vec();
+    tail.push(0x80);
+    while tail.len() % 64 != 56 {
+        tail.push(0);
+    }
+    tail.extend_from_slice(&bit_len.to_le_bytes());
+    for block in tail.chunks_exact(64) {
+        compress(&mut state, block);
+    }
+
+    let mut output = [0u8; 16];
+    for (chunk, word) in output.chunks_exact_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    output
+}
+
+/// Compute the NT hash of a password.
+///
+/// The NT hash is the MD4 digest of the password encoded as UTF-16LE.
+pub fn nt_hash(pass: &str) -> [u8; 16] {
+    let pass = pass
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect::<Vec<u8>>();
+    md4(&pass)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encode::hex_encode;
+
+    #[test]
+    fn md4() {
+        for (input, digest) in [
+            ("", "31d6cfe0d16ae931b73c59d7e0c089c0"),
+            ("a", "bde52cb31de33e46245e05fbdbd6fb24"),
+            ("abc", "a448017aaf21d8525fc10ae87aa6729d"),
+            ("message digest", "d9130a8164549fe818874806e1c7014b"),
+            (
+                "abcdefghijklmnopqrstuvwxyz",
+                "d79e1c308aa5bbcdeea8ed63df412da9",
+            ),
+            (
+                "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "043f8582f241db351ce627e153e7f0e4",
+            ),
+            (
+                "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "e33b4ddc9c38f2199c3e7b164fcc0536",
+            ),
+        ] {
+            assert_eq!(hex_encode(&super::md4(input.as_bytes())), digest);
+        }
+    }
+
+    #[test]
+    fn nt_hash() {
+        assert_eq!(
+            hex_encode(&super::nt_hash("password")),
+            "8846f7eaee8fb117ad06bdd830b7586c"
+        );
+    }
+}
@@ -1,5 +1,11 @@
 #[cfg(any(feature = "bsdi", feature = "unix"))]
 pub mod des;
 
+#[cfg(any(feature = "mscash", feature = "primitives", feature = "smbpasswd"))]
+pub mod md4;
+
+#[cfg(feature = "cram_md5")]
+pub mod md5;
+
 #[cfg(feature = "sha2")]
 pub mod sha2;
@@ -15,7 +15,7 @@ use crate::encode::{bcrypt_hash64_decode, md5_sha2_hash64_encode};
 use crate::error::{Error, Result};
 use crate::parse::{self, HashIterator};
 use crate::random;
-use crate::HashSetup;
+use crate::{AlgorithmParams, HashSetup};
 
 /// Minimum rounds.
 pub const MIN_ROUNDS: u32 = 1000;
@@ -29,6 +29,117 @@ pub const DEFAULT_ROUNDS: u32 = 5000;
 /// Maximum (and default) salt length.
 pub const MAX_SALT_LEN: usize = 16;
 
+/// Setup struct for SHA-2 based hashes.
+///
+/// In addition to custom salt and rounds values, the setup controls whether
+/// the __`rounds=`__ field is written into the hash. By default, the field is
+/// present only if the number of rounds was given, which is what glibc does.
+/// Some systems always write the field, and some never write it for the
+/// default number of rounds; the option allows matching their output exactly.
+#[derive(Default)]
+pub struct Sha2Setup<'a> {
+    /// Custom salt.
+    pub salt: Option<&'a str>,
+    /// Number of rounds.
+    pub rounds: Option<u32>,
+    /// Whether the __`rounds=`__ field is written into the hash.
+    ///
+    /// If `Some(true)`, the field is always written, even for the default
+    /// number of rounds. If `Some(false)`, the field is omitted when the
+    /// number of rounds is the default; other values are always written,
+    /// since the hash would be wrong without them.
+    pub emit_rounds: Option<bool>,
+}
+
+impl<'a> Sha2Setup<'a> {
+    /// Configure custom salt for SHA-2 hash
+    pub fn salt(mut self, salt: &'a str) -> Self {
+        self.salt = Some(salt);
+        self
+    }
+    /// Configure custom number of rounds for SHA-2 hash
+    pub fn rounds(mut self, rounds: u32) -> Self {
+        self.rounds = Some(rounds);
+        self
+    }
+    /// Configure whether the `rounds=` field is written into SHA-2 hash
+    pub fn emit_rounds(mut self, emit: bool) -> Self {
+        self.emit_rounds = Some(emit);
+        self
+    }
+}
+
+impl<'a> From<HashSetup<'a>> for Sha2Setup<'a> {
+    fn from(hs: HashSetup<'a>) -> Self {
+        Sha2Setup {
+            salt: hs.salt,
+            rounds: hs.rounds,
+            emit_rounds: None,
+        }
+    }
+}
+
+// Convert a basic setup, whose salt may be given as raw bytes.
+fn from_basic(hs: HashSetup<'_>) -> Result<Sha2Setup<'_>> {
+    Ok(Sha2Setup {
+        salt: hs.salt_str()?,
+        ..hs.into()
+    })
+}
+
+/// A trait for converting a type into a `Sha2Setup` struct.
+pub trait IntoSha2Setup<'a> {
+    /// The conversion function.
+    fn into_sha2_setup(self, f: fn(&'a str) -> Result<HashSetup<'a>>) -> Result<Sha2Setup<'a>>;
+}
+
+impl<'a> IntoSha2Setup<'a> for &'a str {
+    fn into_sha2_setup(self, f: fn(&'a str) -> Result<HashSetup<'a>>) -> Result<Sha2Setup<'a>> {
+        f(self).map(Sha2Setup::from)
+    }
+}
+
+impl<'a> IntoSha2Setup<'a> for &'a String {
+    fn into_sha2_setup(self, f: fn(&'a str) -> Result<HashSetup<'a>>) -> Result<Sha2Setup<'a>> {
+        f(self).map(Sha2Setup::from)
+    }
+}
+
+impl<'a> IntoSha2Setup<'a> for HashSetup<'a> {
+    fn into_sha2_setup(self, _f: fn(&'a str) -> Result<HashSetup<'a>>) -> Result<Sha2Setup<'a>> {
+        from_basic(self)
+    }
+}
+
+impl<'a> IntoSha2Setup<'a> for (&'a str, u32) {
+    fn into_sha2_setup(self, _f: fn(&'a str) -> Result<HashSetup<'a>>) -> Result<Sha2Setup<'a>> {
+        Ok(HashSetup::from(self).into())
+    }
+}
+
+impl<'a> IntoSha2Setup<'a> for Option<&'a str> {
+    fn into_sha2_setup(self, _f: fn(&'a str) -> Result<HashSetup<'a>>) -> Result<Sha2Setup<'a>> {
+        Ok(HashSetup::from(self).into())
+    }
+}
+
+impl<'a> IntoSha2Setup<'a> for Sha2Setup<'a> {
+    fn into_sha2_setup(self, _f: fn(&'a str) -> Result<HashSetup<'a>>) -> Result<Sha2Setup<'a>> {
+        Ok(self)
+    }
+}
+
+impl<'a> IntoSha2Setup<'a> for AlgorithmParams<'a> {
+    fn into_sha2_setup(self, _f: fn(&'a str) -> Result<HashSetup<'a>>) -> Result<Sha2Setup<'a>> {
+        #[allow(unreachable_patterns)]
+        match self {
+            AlgorithmParams::Basic(hs) => from_basic(hs),
+            AlgorithmParams::Sha2(setup) => Ok(setup),
+            _ => Err(Error::InvalidHashString),
+        }
+    }
+}
+
 pub fn sha2_crypt<D: Digest>(
     pass: &[u8],
     salt: &str,
@@ -37,6 +148,7 @@ pub fn sha2_crypt<D: Digest>(
     trn_table: &[u8],
     magic: &str,
 ) -> Result<String> {
+    crate::policy::check_password_len(pass)?;
     let mut dummy_buf = [0u8; 12];
     bcrypt_hash64_decode(salt, &mut dummy_buf)?;
 
@@ -136,21 +248,15 @@ const MAGIC_LEN: usize = 3;
 
 pub fn parse_sha2_hash<'a>(hash: &'a str, magic: &str) -> Result<HashSetup<'a>> {
     let mut hs = parse::HashSlice::new(hash);
-    if hs.take(MAGIC_LEN).unwrap_or("X") != magic {
+    if hs.take(MAGIC_LEN) != Some(magic) {
         return Err(Error::InvalidHashString);
     }
 
     let maybe_rounds = hs.take_until(b'$').ok_or(Error::InvalidHashString)?;
-    let (rounds, salt) = match maybe_rounds.starts_with("rounds=") {
-        false => (None, maybe_rounds),
-        true => {
-            let mut rhs = parse::HashSlice::new(maybe_rounds);
-            rhs.take_until(b'=');
-            let rounds = rhs
-                .take_until(b'$')
-                .unwrap()
-                .parse::<u32>()
-                .map_err(|_e| Error::InvalidRounds)?;
+    let (rounds, salt) = match maybe_rounds.strip_prefix("rounds=") {
+        None => (None, maybe_rounds),
+        Some(rounds) => {
+            let rounds = rounds.parse::<u32>().map_err(Error::InvalidNumber)?;
             let salt = hs.take_until(b'$').ok_or(Error::InvalidHashString)?;
             (Some(rounds), salt)
         }
@@ -159,15 +265,23 @@ pub fn parse_sha2_hash<'a>(hash: &'a str, magic: &str) -> Result<HashSetup<'a>>
     Ok(HashSetup {
         salt: Some(salt),
         rounds,
+        salt_bytes: None,
     })
 }
 
 pub fn sha2_hash_with(
-    param: HashSetup,
+    param: Sha2Setup,
     pass: &[u8],
     hf: fn(&[u8], &str, Option<u32>) -> Result<String>,
 ) -> Result<String> {
-    let rounds = param.rounds.map(|r| r.clamp(MIN_ROUNDS, MAX_ROUNDS));
+    let rounds = match (
+        param.rounds.map(|r| r.clamp(MIN_ROUNDS, MAX_ROUNDS)),
+        param.emit_rounds,
+    ) {
+        (None, Some(true)) => Some(DEFAULT_ROUNDS),
+        (Some(DEFAULT_ROUNDS), Some(false)) => None,
+        (rounds, _) => rounds,
+    };
     let salt = match param.salt {
         None => &random::gen_salt_str(MAX_SALT_LEN),
         Some(salt) => (salt.len() <= MAX_SALT_LEN)
@@ -0,0 +1,208 @@
+//! Key derivation functions built on the crate's hashing primitives.
+//!
+//! These functions derive raw key material rather than password hashes,
+//! and therefore don't produce a hash string. They share their internals
+//! with the corresponding hashing algorithms, so that downstream tools don't
+//! need a second implementation.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::kdf;
+//!
+//! let mut key = [0u8; 32];
+//! kdf::bcrypt_pbkdf("password", b"salt", 4, &mut key).unwrap();
+//! assert_eq!(key[..4], [0x5b, 0xbf, 0x0c, 0xc2]);
+//! ```
+
+#[cfg(feature = "bcrypt_pbkdf")]
+use blowfish::Blowfish;
+#[cfg(feature = "bcrypt_pbkdf")]
+use sha2::{Digest, Sha512};
+
+use crate::{
+    crypt::bcrypt::{BcryptVariant, MAX_COST, MIN_COST, bcrypt_output},
+    error::{Error, Result},
+};
+
+/// Length of the [`bcrypt_raw`] output, in bytes.
+pub const BCRYPT_RAW_LEN: usize = 24;
+
+#[cfg(feature = "bcrypt_pbkdf")]
+const BHASH_WORDS: usize = 8;
+#[cfg(feature = "bcrypt_pbkdf")]
+const BHASH_OUTPUT_LEN: usize = BHASH_WORDS * 4;
+#[cfg(feature = "bcrypt_pbkdf")]
+const BHASH_MAGIC: &[u8; BHASH_OUTPUT_LEN] = b"OxychromaticBlowfishSwatDynamite";
+
+/// Maximum output length of [`bcrypt_pbkdf`], in bytes.
+#[cfg(feature = "bcrypt_pbkdf")]
+pub const BCRYPT_PBKDF_MAX_LEN: usize = BHASH_OUTPUT_LEN * BHASH_OUTPUT_LEN;
+
+#[cfg(feature = "bcrypt_pbkdf")]
+fn bcrypt_hash(sha2pass: &[u8], sha2salt: &[u8], output: &mut [u8; BHASH_OUTPUT_LEN]) {
+    let mut state = Blowfish::bc_init_state();
+    state.salted_expand_key(sha2salt, sha2pass);
+    for _ in 0..64 {
+        state.bc_expand_key(sha2salt);
+        state.bc_expand_key(sha2pass);
+    }
+
+    let mut cdata = [0u32; BHASH_WORDS];
+    for (word, chunk) in cdata.iter_mut().zip(BHASH_MAGIC.chunks(4)) {
+        *word = u32::from_be_bytes(chunk.try_into().unwrap());
+    }
+    for _ in 0..64 {
+        for block in cdata.chunks_mut(2) {
+            let [l, r] = state.bc_encrypt([block[0], block[1]]);
+            block[0] = l;
+            block[1] = r;
+        }
+    }
+    for (chunk, word) in output.chunks_mut(4).zip(cdata) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+}
+
+/// Compute the raw output of bcrypt.
+///
+/// The password is prepared like for __2b__ hashes: a terminating NUL is
+/// appended, and the result is truncated to 72 bytes. The first 23 bytes of
+/// the output are the checksum of the corresponding `$2b$` hash; the last
+/// byte is dropped by the hash format. An error is returned if the cost is
+/// outside the range accepted by bcrypt.
+pub fn bcrypt_raw<B: AsRef<[u8]>>(
+    pass: B,
+    salt: &[u8; 16],
+    cost: u32,
+) -> Result<[u8; BCRYPT_RAW_LEN]> {
+    if !(MIN_COST..=MAX_COST).contains(&cost) {
+        return Err(Error::BadRounds {
+            value: cost,
+            allowed: MIN_COST..=MAX_COST,
+        });
+    }
+    bcrypt_output(pass.as_ref(), salt, cost, &BcryptVariant::V2b)
+}
+
+/// Derive a key with the bcrypt_pbkdf function used by OpenSSH and signify.
+///
+/// The output buffer is filled with the derived key. An error is returned if
+/// the number of rounds is zero, if the password, salt, or output buffer is
+/// empty, or if the output buffer is longer than [`BCRYPT_PBKDF_MAX_LEN`].
+#[cfg(feature = "bcrypt_pbkdf")]
+pub fn bcrypt_pbkdf<B: AsRef<[u8]>>(
+    pass: B,
+    salt: &[u8],
+    rounds: u32,
+    output: &mut [u8],
+) -> Result<()> {
+    let pass = pass.as_ref();
+    if rounds < 1 {
+        return Err(Error::BadRounds {
+            value: rounds,
+            allowed: 1..=u32::MAX,
+        });
+    }
+    if pass.is_empty()
+        || salt.is_empty()
+        || output.is_empty()
+        || output.len() > BCRYPT_PBKDF_MAX_LEN
+    {
+        return Err(Error::InsufficientLength);
+    }
+
+    let stride = output.len().div_ceil(BHASH_OUTPUT_LEN);
+    let amt = output.len().div_ceil(stride);
+    let sha2pass = Sha512::digest(pass);
+
+    let mut tmp = [0u8; BHASH_OUTPUT_LEN];
+    let mut out = [0u8; BHASH_OUTPUT_LEN];
+    for block in 0..stride {
+        let mut dgst = Sha512::new();
+        dgst.update(salt);
+        dgst.update((block as u32 + 1).to_be_bytes());
+        bcrypt_hash(&sha2pass, &dgst.finalize(), &mut tmp);
+        out = tmp;
+        for _ in 1..rounds {
+            bcrypt_hash(&sha2pass, &Sha512::digest(tmp), &mut tmp);
+            out.iter_mut().zip(tmp).for_each(|(o, t)| *o ^= t);
+        }
+
+        for (i, &b) in out.iter().take(amt).enumerate() {
+            match output.get_mut(i * stride + block) {
+                Some(dest) => *dest = b,
+                None => break,
+            }
+        }
+    }
+    tmp.fill(0u8);
+    out.fill(0u8);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn bcrypt_raw() {
+        let salt = [
+            0x77, 0x12, 0x88, 0xf0, 0xfa, 0x24, 0x7f, 0xfd, 0xce, 0xc3, 0xf4, 0xde, 0xa2, 0x86,
+            0x1b, 0x85,
+        ];
+        let output = super::bcrypt_raw("password", &salt, 5).unwrap();
+        assert_eq!(
+            output[..23],
+            [
+                0xec, 0x92, 0xca, 0x38, 0x45, 0x7e, 0xb6, 0x43, 0xd8, 0xda, 0x31, 0xab, 0xfa, 0x1f,
+                0x01, 0x1f, 0x00, 0xb8, 0x06, 0x82, 0x5b, 0x1e, 0xb8
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "value: BadRounds")]
+    fn bcrypt_raw_low_cost() {
+        let _ = super::bcrypt_raw("password", &[0u8; 16], 3).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "bcrypt_pbkdf")]
+    fn bcrypt_pbkdf() {
+        let mut key = [0u8; 32];
+        super::bcrypt_pbkdf("password", b"salt", 4, &mut key).unwrap();
+        assert_eq!(
+            key,
+            [
+                0x5b, 0xbf, 0x0c, 0xc2, 0x93, 0x58, 0x7f, 0x1c, 0x36, 0x35, 0x55, 0x5c, 0x27, 0x79,
+                0x65, 0x98, 0xd4, 0x7e, 0x57, 0x90, 0x71, 0xbf, 0x42, 0x7e, 0x9d, 0x8f, 0xbe, 0x84,
+                0x2a, 0xba, 0x34, 0xd9
+            ]
+        );
+        let mut key = [0u8; 16];
+        super::bcrypt_pbkdf("pass\0word", b"sa\0lt", 4, &mut key).unwrap();
+        assert_eq!(
+            key,
+            [
+                0x4b, 0xa4, 0xac, 0x39, 0x25, 0xc0, 0xe8, 0xd7, 0xf0, 0xcd, 0xb6, 0xbb, 0x16, 0x84,
+                0xa5, 0x6f
+            ]
+        );
+        let mut key = [0u8; 64];
+        super::bcrypt_pbkdf("password", b"salt", 8, &mut key).unwrap();
+        assert_eq!(
+            key[32..],
+            [
+                0x62, 0x27, 0xea, 0x66, 0x08, 0x8b, 0x84, 0x9b, 0x20, 0xab, 0x7a, 0xa4, 0x78, 0x01,
+                0x02, 0x46, 0xe7, 0x4b, 0xba, 0x51, 0x72, 0x3f, 0xef, 0xa9, 0xf9, 0x47, 0x4d, 0x65,
+                0x08, 0x84, 0x5e, 0x8d
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "bcrypt_pbkdf")]
+    #[should_panic(expected = "value: BadRounds")]
+    fn zero_rounds() {
+        super::bcrypt_pbkdf("password", b"salt", 0, &mut [0u8; 32]).unwrap();
+    }
+}
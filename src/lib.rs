@@ -30,15 +30,30 @@
 //!
 //! # Summary
 //!
-//! Currently, there are implementations of seven algorithms, which should
+//! Currently, there are implementations of ten algorithms, which should
 //! cover anything one might find as a system-wide hash on a free Unix-like
 //! OS: [APR1-MD5](crypt::apr1), [bcrypt](crypt::bcrypt), [SHA-512](crypt::sha512),
 //! [SHA-256](crypt::sha256), [HMAC-SHA1](crypt::sha1), [MD5](crypt::md5),
-//! [BSDi crypt](crypt::bsdi), and [DES crypt](crypt::unix). The list is ordered
+//! [BSDi crypt](crypt::bsdi), [bigcrypt](crypt::bigcrypt), [crypt16](crypt::crypt16),
+//! and [DES crypt](crypt::unix). The list is ordered
 //! roughly by security, with the most secure algorithms first. Of the available
 //! options, [bcrypt](crypt::bcrypt) and [SHA-512](crypt::sha512) are recommended
 //! for new passwords.
 //!
+//! Hash formats used by applications, databases, and network devices rather
+//! than operating systems are also supported: [AIX](crypt::aix),
+//! [Apache htdigest](crypt::htdigest), [Cisco IOS](crypt::cisco),
+//! [CRAM-MD5](crypt::cram_md5), [Django](crypt::django),
+//! [Dovecot](crypt::dovecot), [GRUB 2](crypt::grub),
+//! [Invision Power Board](crypt::ipb), [LDAP](crypt::ldap),
+//! [macOS](crypt::macos), [MediaWiki](crypt::mediawiki),
+//! [Microsoft SQL Server](crypt::mssql), [MySQL 4.1+](crypt::mysql41),
+//! [Passlib bcrypt-SHA256](crypt::bcrypt_sha256),
+//! [Passlib PBKDF2](crypt::pbkdf2), [PostgreSQL MD5](crypt::pg_md5),
+//! [PostgreSQL SCRAM-SHA-256](crypt::scram),
+//! [Samba smbpasswd](crypt::smbpasswd), [vBulletin](crypt::vbulletin), and
+//! [Windows cached credentials](crypt::mscash).
+//!
 //! Each algorithm is implemented in its own module, and offers three ways of
 //! using it:
 //!
@@ -55,32 +70,120 @@
 //! parameters (in the appropriate hash format) to `hash_with`. All algorithms
 //! except DES crypt accept a `HashSetup` struct as a means of customization,
 //! while bcrypt also has its own setup structure (see the module documenation.)
+//! A [Salt] checks a salt against an algorithm's requirements before it's
+//! used in a setup, and the [salt] module generates salts ahead of time.
+//! Random salts are drawn from the operating system's generator, unless
+//! another [SaltSource] is installed with [with_salt_source].
 //!
 //! The [unix] module provides a __crypt__(3)-compatible function and a
 //! `verify` which uses it to automatically recognize the algorithm of the
-//! provided hash.
+//! provided hash. The [phc] module parses and writes the PHC string format
+//! shared by scrypt, balloon hashing, and other modern algorithms.
+//! Applications can add their own hash formats to the recognition by
+//! implementing a [Scheme] and registering it with [register_scheme].
+//!
+//! A [Hash](enum@Hash) recognized from a string reports its [Algorithm], so that policy
+//! code can decide which stored hashes are acceptable without parsing them
+//! itself. A [Policy] collects such decisions, and [Hash::needs_rehash]
+//! checks a hash against it. [Hash::strength] rates a hash without a policy.
+//! A [Verifier] only verifies hashes made with the algorithms it allows, so
+//! that legacy hashes left in a database can't be used to log in.
+//!
+//! A [Crypter] gathers the algorithm, the cost, the pepper, and the
+//! normalization of passwords in one object, which hashes and verifies
+//! passwords and applies the corresponding policy. The top-level [hash()]
+//! function hashes a password with a process-wide crypter, set with
+//! [set_default_crypter].
+//!
+//! Stored and calculated hashes are compared in constant time. The comparison
+//! is available as [ct_eq] for other secrets, like API keys.
+//!
+//! To bound the work done for a single password, passwords longer than
+//! [DEFAULT_MAX_PASSWORD_LEN] bytes are rejected, unless the limit is changed
+//! with [set_max_password_len].
+//!
+//! No function panics on malformed input. A hash string, a setting, or a
+//! salt which can't be used results in an [Error](error::Error), whose
+//! variant tells which component was rejected, or in a failed verification.
+//! A panic would indicate a bug in the crate, and should be reported.
 
 #![warn(missing_docs)]
 
+mod algorithm;
+mod crypter;
+mod ct;
 mod encode;
 mod hash;
 mod internal;
+#[cfg(feature = "unicode")]
+mod normalize;
 mod parse;
+mod policy;
 mod random;
+mod scheme;
+#[cfg(feature = "secrecy")]
+mod secret;
+mod strength;
+#[cfg(feature = "test_util")]
+mod test_util;
 mod traits;
 
+#[cfg(feature = "async")]
+pub mod asynch;
 pub mod crypt;
+pub mod encoding;
 pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod htpasswd;
+#[cfg(feature = "bcrypt")]
+pub mod kdf;
+pub mod passwd;
+pub mod phc;
+#[cfg(feature = "prompt")]
+pub mod prompt;
+pub mod salt;
+pub mod shadow;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-pub use hash::Hash;
-pub use traits::{FindNul, IntoHashSetup};
+#[cfg(feature = "primitives")]
+pub mod primitives {
+    //! Low-level primitives shared by the hash implementations.
+    //!
+    //! These are exposed for applications which need to interoperate with
+    //! the same building blocks, e.g. NTLM authentication. They are not
+    //! password hashes, and offer no protection against brute-force attacks.
+    pub use crate::internal::md4::{md4, nt_hash};
+}
+
+pub use algorithm::{Algorithm, ParamInfo};
+pub use crypter::{Crypter, CrypterBuilder, default_crypter, hash, set_default_crypter};
+pub use ct::{ct_eq, ct_eq_ignore_ascii_case};
+pub use hash::{Hash, ParseMode, ParsedHash};
+#[cfg(feature = "unicode")]
+pub use normalize::Normalized;
+pub use policy::{
+    DEFAULT_MAX_PASSWORD_LEN, Policy, Verifier, max_password_len, set_max_password_len,
+};
+pub use random::{
+    DEFAULT_ROUNDS_VARIATION, SaltSource, rounds_variation, set_rounds_variation, vary_rounds,
+    vary_rounds_by, with_salt_source,
+};
+#[cfg(feature = "deterministic")]
+pub use random::{clear_salt_seed, set_salt_seed};
+pub use salt::Salt;
+pub use scheme::{Scheme, register_scheme, unregister_scheme};
+#[cfg(feature = "secrecy")]
+pub use secret::Secret;
+pub use strength::Strength;
+#[cfg(feature = "test_util")]
+pub use test_util::InsecureFastHasher;
+pub use traits::{FindNul, IntoHashSetup, PasswordHasher};
 
 #[inline]
 pub(crate) fn consteq(hash: &str, calchash: error::Result<Hash>) -> bool {
-    calchash
-        .ok()
-        .map(|hstr| hash == hstr.as_str())
-        .unwrap_or_default()
+    calchash.is_ok_and(|hstr| ct::ct_eq(hash, hstr.as_str()))
 }
 
 /// Setup struct for basic hashing customization.
@@ -90,16 +193,33 @@ pub(crate) fn consteq(hash: &str, calchash: error::Result<Hash>) -> bool {
 /// algorithm; visit the algorithm's module-level documentation for details.
 /// It's always safe to initialize `rounds` to `None`, in which case the suitable
 /// default value will be used.
+///
+/// The salt can also be given as raw bytes, for salts which aren't valid
+/// UTF-8 or contain characters outside an algorithm's alphabet, e.g. those
+/// of historical DES-based hashes. The DES-based algorithms accept any bytes,
+/// and algorithms which hash the salt as binary data use them unchanged; for
+/// the others, the bytes must be valid UTF-8, and are checked like a string
+/// salt. If both salts are set, the string is used.
+///
+/// A setup can also be created from a `(salt, rounds)` tuple, or from an
+/// optional salt. The `hash_with` functions which take a `HashSetup` accept
+/// these directly, as do those of SHA-256, SHA-512, and bcrypt; the others
+/// accept the converted setup. [HashSetupBuf] is a version which owns its
+/// salt.
 #[derive(Default)]
 pub struct HashSetup<'a> {
     /// Custom salt.
     pub salt: Option<&'a str>,
     /// Number of rounds.
     pub rounds: Option<u32>,
+    /// Custom salt, given as raw bytes.
+    pub salt_bytes: Option<&'a [u8]>,
 }
 
 impl<'a> HashSetup<'a> {
     /// Configure custom salt for hash algorithm
+    ///
+    /// A [Salt] can be passed by reference, since it dereferences to a `&str`.
     pub fn salt(mut self, salt: &'a str) -> Self {
         self.salt = Some(salt);
         self
@@ -109,6 +229,237 @@ impl<'a> HashSetup<'a> {
         self.rounds = Some(rounds);
         self
     }
+    /// Configure custom salt for hash algorithm, given as raw bytes
+    pub fn salt_bytes(mut self, salt: &'a [u8]) -> Self {
+        self.salt_bytes = Some(salt);
+        self
+    }
+}
+
+impl<'a> From<(&'a str, u32)> for HashSetup<'a> {
+    fn from((salt, rounds): (&'a str, u32)) -> Self {
+        HashSetup {
+            salt: Some(salt),
+            rounds: Some(rounds),
+            salt_bytes: None,
+        }
+    }
+}
+
+impl<'a> From<Option<&'a str>> for HashSetup<'a> {
+    fn from(salt: Option<&'a str>) -> Self {
+        HashSetup {
+            salt,
+            rounds: None,
+            salt_bytes: None,
+        }
+    }
+}
+
+/// Owned version of [HashSetup].
+///
+/// A `HashSetup` borrows its salt, which ties it to the lifetime of the
+/// string. This struct owns the salt, so that a configuration can be built
+/// in one place, stored, and used later or from other threads. A reference
+/// to it is accepted wherever a [HashSetup] is, e.g. by
+/// [`Algorithm::hash_with`]; for other setup structs, pass the
+/// [borrowed setup](HashSetupBuf::as_setup).
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::{Algorithm, HashSetupBuf};
+///
+/// let setup = HashSetupBuf::default().salt("saltsalt".to_string()).rounds(1000);
+/// let h = std::thread::spawn(move || Algorithm::Sha512.hash_with(&setup, "password"))
+///     .join()
+///     .unwrap()
+///     .unwrap();
+/// assert!(h.starts_with("$6$rounds=1000$saltsalt$"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HashSetupBuf {
+    /// Custom salt.
+    pub salt: Option<String>,
+    /// Number of rounds.
+    pub rounds: Option<u32>,
+    /// Custom salt, given as raw bytes.
+    pub salt_bytes: Option<Vec<u8>>,
+}
+
+impl HashSetupBuf {
+    /// Configure custom salt for hash algorithm
+    pub fn salt<S: Into<String>>(mut self, salt: S) -> Self {
+        self.salt = Some(salt.into());
+        self
+    }
+    /// Configure custom number of rounds for hash algorithm
+    pub fn rounds(mut self, rounds: u32) -> Self {
+        self.rounds = Some(rounds);
+        self
+    }
+    /// Configure custom salt for hash algorithm, given as raw bytes
+    pub fn salt_bytes<S: Into<Vec<u8>>>(mut self, salt: S) -> Self {
+        self.salt_bytes = Some(salt.into());
+        self
+    }
+
+    /// Borrow the setup as a [HashSetup].
+    pub fn as_setup(&self) -> HashSetup<'_> {
+        HashSetup {
+            salt: self.salt.as_deref(),
+            rounds: self.rounds,
+            salt_bytes: self.salt_bytes.as_deref(),
+        }
+    }
+}
+
+impl From<HashSetup<'_>> for HashSetupBuf {
+    fn from(setup: HashSetup<'_>) -> Self {
+        HashSetupBuf {
+            salt: setup.salt.map(str::to_string),
+            rounds: setup.rounds,
+            salt_bytes: setup.salt_bytes.map(<[u8]>::to_vec),
+        }
+    }
+}
+
+impl<'a> From<&'a HashSetupBuf> for HashSetup<'a> {
+    fn from(setup: &'a HashSetupBuf) -> Self {
+        setup.as_setup()
+    }
+}
+
+/// Hashing parameters for any algorithm.
+///
+/// Most algorithms are customized with a [HashSetup], but some have setup
+/// structs of their own, with additional fields. This enum holds either, so
+/// that generic code can pass the parameters around without knowing the
+/// algorithm, e.g. to [`Algorithm::hash_with`]. It's accepted by the
+/// `hash_with` function of every module which takes a setup struct.
+///
+/// The basic setup is converted into the algorithm's own setup as if it was
+/// passed directly. An algorithm-specific setup is accepted only by its own
+/// algorithm, and by the algorithms which take the same setup struct; for
+/// the others, an error is returned.
+#[non_exhaustive]
+pub enum AlgorithmParams<'a> {
+    /// Basic parameters, accepted by all algorithms.
+    Basic(HashSetup<'a>),
+    /// Parameters for [`crypt::aix`].
+    #[cfg(feature = "aix")]
+    Aix(crypt::aix::AixSetup<'a>),
+    /// Parameters for [`crypt::balloon`].
+    #[cfg(feature = "balloon")]
+    Balloon(crypt::balloon::BalloonSetup<'a>),
+    /// Parameters for [`crypt::bcrypt`].
+    #[cfg(feature = "bcrypt")]
+    Bcrypt(crypt::bcrypt::BcryptSetup<'a>),
+    /// Parameters for [`crypt::django`].
+    #[cfg(feature = "django")]
+    Django(crypt::django::DjangoSetup<'a>),
+    /// Parameters for [`crypt::ldap`].
+    #[cfg(feature = "ldap")]
+    Ldap(crypt::ldap::LdapSetup<'a>),
+    /// Parameters for [`crypt::mssql`].
+    #[cfg(feature = "mssql")]
+    Mssql(crypt::mssql::MssqlSetup<'a>),
+    /// Parameters for [`crypt::pbkdf2`].
+    #[cfg(feature = "pbkdf2")]
+    Pbkdf2(crypt::pbkdf2::Pbkdf2Setup<'a>),
+    /// Parameters for [`crypt::scrypt`].
+    #[cfg(feature = "scrypt")]
+    Scrypt(crypt::scrypt::ScryptSetup<'a>),
+    /// Parameters for [`crypt::sha256`] and [`crypt::sha512`].
+    #[cfg(feature = "sha2")]
+    Sha2(crypt::sha512::Sha2Setup<'a>),
+}
+
+impl<'a> From<HashSetup<'a>> for AlgorithmParams<'a> {
+    fn from(setup: HashSetup<'a>) -> Self {
+        AlgorithmParams::Basic(setup)
+    }
+}
+
+impl<'a> From<&'a HashSetupBuf> for AlgorithmParams<'a> {
+    fn from(setup: &'a HashSetupBuf) -> Self {
+        AlgorithmParams::Basic(setup.as_setup())
+    }
+}
+
+impl<'a> From<(&'a str, u32)> for AlgorithmParams<'a> {
+    fn from(setup: (&'a str, u32)) -> Self {
+        AlgorithmParams::Basic(setup.into())
+    }
+}
+
+impl<'a> From<Option<&'a str>> for AlgorithmParams<'a> {
+    fn from(salt: Option<&'a str>) -> Self {
+        AlgorithmParams::Basic(salt.into())
+    }
+}
+
+#[cfg(feature = "aix")]
+impl<'a> From<crypt::aix::AixSetup<'a>> for AlgorithmParams<'a> {
+    fn from(setup: crypt::aix::AixSetup<'a>) -> Self {
+        AlgorithmParams::Aix(setup)
+    }
+}
+
+#[cfg(feature = "balloon")]
+impl<'a> From<crypt::balloon::BalloonSetup<'a>> for AlgorithmParams<'a> {
+    fn from(setup: crypt::balloon::BalloonSetup<'a>) -> Self {
+        AlgorithmParams::Balloon(setup)
+    }
+}
+
+#[cfg(feature = "bcrypt")]
+impl<'a> From<crypt::bcrypt::BcryptSetup<'a>> for AlgorithmParams<'a> {
+    fn from(setup: crypt::bcrypt::BcryptSetup<'a>) -> Self {
+        AlgorithmParams::Bcrypt(setup)
+    }
+}
+
+#[cfg(feature = "django")]
+impl<'a> From<crypt::django::DjangoSetup<'a>> for AlgorithmParams<'a> {
+    fn from(setup: crypt::django::DjangoSetup<'a>) -> Self {
+        AlgorithmParams::Django(setup)
+    }
+}
+
+#[cfg(feature = "ldap")]
+impl<'a> From<crypt::ldap::LdapSetup<'a>> for AlgorithmParams<'a> {
+    fn from(setup: crypt::ldap::LdapSetup<'a>) -> Self {
+        AlgorithmParams::Ldap(setup)
+    }
+}
+
+#[cfg(feature = "mssql")]
+impl<'a> From<crypt::mssql::MssqlSetup<'a>> for AlgorithmParams<'a> {
+    fn from(setup: crypt::mssql::MssqlSetup<'a>) -> Self {
+        AlgorithmParams::Mssql(setup)
+    }
+}
+
+#[cfg(feature = "pbkdf2")]
+impl<'a> From<crypt::pbkdf2::Pbkdf2Setup<'a>> for AlgorithmParams<'a> {
+    fn from(setup: crypt::pbkdf2::Pbkdf2Setup<'a>) -> Self {
+        AlgorithmParams::Pbkdf2(setup)
+    }
+}
+
+#[cfg(feature = "scrypt")]
+impl<'a> From<crypt::scrypt::ScryptSetup<'a>> for AlgorithmParams<'a> {
+    fn from(setup: crypt::scrypt::ScryptSetup<'a>) -> Self {
+        AlgorithmParams::Scrypt(setup)
+    }
+}
+
+#[cfg(feature = "sha2")]
+impl<'a> From<crypt::sha512::Sha2Setup<'a>> for AlgorithmParams<'a> {
+    fn from(setup: crypt::sha512::Sha2Setup<'a>) -> Self {
+        AlgorithmParams::Sha2(setup)
+    }
 }
 
 pub mod unix {
@@ -116,8 +467,16 @@ pub mod unix {
     //!
     //! If it's known that a hash is in one of the supported modular hash formats,
     //! the functions in this module can be used to verify or re-calculate the
-    //! hash.
-    use crate::{Hash, consteq, error::Result};
+    //! hash. [gensalt] creates the settings string for a new hash,
+    //! [checksalt] tells whether a settings string is fit for one, and
+    //! [preferred_method] names the best scheme for it, like the functions of
+    //! the same names in libxcrypt.
+    #[allow(unused_imports)]
+    use crate::{
+        Algorithm, Hash, HashSetup, ParseMode, Policy, Strength, consteq, crypt,
+        error::{Error, Result},
+        random,
+    };
 
     /// A Unix __crypt__(3) work-alike.
     #[inline]
@@ -130,6 +489,423 @@ pub mod unix {
         consteq(hash, crypt(pass, hash))
     }
 
+    /// Verify that the hash corresponds to a password, reporting unusable
+    /// hashes.
+    ///
+    /// Unlike [verify], which returns `false` for any failure, an error is
+    /// returned if the hash isn't in a recognized format, or can't be
+    /// recalculated with its parameters. `Ok(false)` means that the hash is
+    /// valid, but doesn't correspond to the password.
+    pub fn verify_result<B: AsRef<[u8]>>(pass: B, hash: &str) -> Result<bool> {
+        let calchash = crypt(pass, hash)?;
+        Ok(consteq(hash, Ok(calchash)))
+    }
+
+    /// Spend the time of verifying a password against a hash, and fail.
+    ///
+    /// The password is hashed with the parameters of the given hash, and the
+    /// result is discarded. When a login names an account which doesn't
+    /// exist, calling this function with a fixed hash of the same algorithm
+    /// and cost as the stored ones makes the failure take as long as a failed
+    /// verification of an existing account, so that the response time doesn't
+    /// tell which accounts exist. A hash which can't be recalculated, like a
+    /// truncated one, takes no time.
+    ///
+    /// The result is always `false`, even if the hash corresponds to the
+    /// password.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crypt3_rs::unix;
+    ///
+    /// const DUMMY: &str = "$2b$10$saltsaltsaltsaltsaltsOF8gOe7Q2djsDdlP9U528dfcxNNK.BXC";
+    /// assert!(!unix::dummy_verify("password", DUMMY));
+    /// ```
+    pub fn dummy_verify<B: AsRef<[u8]>>(pass: B, hash: &str) -> bool {
+        let _ = std::hint::black_box(crypt(pass, hash));
+        false
+    }
+
+    /// Verify a password, and compute a replacement hash if the stored one
+    /// is outdated.
+    ///
+    /// The first value of the result tells whether the hash corresponds to
+    /// the password. If it does, and the hash [needs rehashing](Hash::needs_rehash)
+    /// according to the policy, the second value is a new hash of the password
+    /// made with the policy's algorithm, which should be stored in place of
+    /// the old one. The new hash uses the default parameters of the algorithm,
+    /// or the policy's minimal rounds if the defaults don't satisfy it. If the
+    /// new hash can't be computed, the password is still reported as verified,
+    /// but there is no replacement. A password longer than the policy's
+    /// maximum is never verified.
+    pub fn verify_and_rehash<B: AsRef<[u8]>>(
+        pass: B,
+        hash: &str,
+        policy: &Policy,
+    ) -> (bool, Option<Hash>) {
+        let pass = pass.as_ref();
+        if pass.len() > policy.max_password_len {
+            return (false, None);
+        }
+        let Ok(stored) = Hash::try_from(hash) else {
+            return (false, None);
+        };
+        if !consteq(hash, stored.hash_with(pass)) {
+            return (false, None);
+        }
+        if !stored.needs_rehash(policy) {
+            return (true, None);
+        }
+        let algorithm = policy.algorithm;
+        let rehash = algorithm.hash(pass).ok().and_then(|new| {
+            if !new.needs_rehash(policy) {
+                return Some(new);
+            }
+            let (_, rounds) = policy
+                .min_rounds
+                .iter()
+                .find(|&&(alg, _)| alg == algorithm)?;
+            algorithm
+                .hash_with(HashSetup::default().rounds(*rounds), pass)
+                .ok()
+        });
+        (true, rehash)
+    }
+
+    /// State of an account, as recorded in its password field.
+    ///
+    /// Besides a hash, the password field of a shadow file can contain
+    /// markers which prevent password logins, or allow them without a
+    /// password. See [classify].
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
+    pub enum HashState {
+        /// The field contains a recognized hash.
+        Active(Hash),
+        /// The field starts with `!`; the account is locked. If the rest of
+        /// the field is a recognized hash, it's the hash which will be active
+        /// after unlocking. A field consisting only of `!` or `!!` means that
+        /// a password was never set.
+        Locked(Option<Hash>),
+        /// The field starts with `*`, e.g. `*`, `*LK*`, or `*NP*`; logging in
+        /// with a password is disabled.
+        Disabled,
+        /// The field is empty; no password is required.
+        NoPassword,
+    }
+
+    /// Classify the password field of a shadow file entry.
+    ///
+    /// Locked, disabled, and passwordless entries are reported as such,
+    /// instead of being rejected as invalid hashes. An error is returned if
+    /// the field is none of these, and isn't a recognized hash.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crypt3_rs::unix::{self, HashState};
+    ///
+    /// assert!(matches!(unix::classify("!!").unwrap(), HashState::Locked(None)));
+    /// assert!(matches!(unix::classify("*").unwrap(), HashState::Disabled));
+    /// ```
+    pub fn classify(hash: &str) -> Result<HashState> {
+        if hash.is_empty() {
+            return Ok(HashState::NoPassword);
+        }
+        if hash.starts_with('*') {
+            return Ok(HashState::Disabled);
+        }
+        match hash.strip_prefix('!') {
+            Some(locked) => {
+                let inner = locked.trim_start_matches('!');
+                Ok(HashState::Locked(Hash::try_from(inner).ok()))
+            }
+            None => Hash::try_from(hash).map(HashState::Active),
+        }
+    }
+
+    /// A trait for selecting the __crypt__(3) scheme of a settings string.
+    pub trait SettingPrefix {
+        /// Get the prefix of the scheme's hashes.
+        ///
+        /// For an [Algorithm] without a __crypt__(3) settings string,
+        /// [`Error::NoSettingString`] is returned.
+        fn prefix(&self) -> Result<&str>;
+    }
+
+    impl SettingPrefix for &str {
+        fn prefix(&self) -> Result<&str> {
+            Ok(self)
+        }
+    }
+
+    impl SettingPrefix for Algorithm {
+        fn prefix(&self) -> Result<&str> {
+            #[allow(unreachable_patterns)]
+            match self {
+                #[cfg(feature = "apr1")]
+                Algorithm::Apr1 => Ok("$apr1$"),
+                #[cfg(feature = "bcrypt")]
+                Algorithm::Bcrypt => Ok("$2b$"),
+                #[cfg(feature = "bsdi")]
+                Algorithm::Bsdi => Ok("_"),
+                #[cfg(feature = "md5")]
+                Algorithm::Md5 => Ok("$1$"),
+                #[cfg(feature = "sha1")]
+                Algorithm::Sha1 => Ok("$sha1$"),
+                #[cfg(feature = "sha2")]
+                Algorithm::Sha256 => Ok("$5$"),
+                #[cfg(feature = "sha2")]
+                Algorithm::Sha512 => Ok("$6$"),
+                #[cfg(feature = "unix")]
+                Algorithm::Unix => Ok(""),
+                _ => Err(Error::NoSettingString { algorithm: *self }),
+            }
+        }
+    }
+
+    /// Create a __crypt__(3) settings string for a new hash.
+    ///
+    /// Only the __crypt__(3) schemes have settings strings. The scheme is
+    /// selected either by an [Algorithm], or by the prefix of
+    /// its hashes as accepted by libxcrypt's __crypt_gensalt__(3): `""` for
+    /// DES, `"_"` for BSDi, `"$1$"`, `"$apr1$"`, `"$2a$"`, `"$2b$"`, `"$2x$"`,
+    /// `"$2y$"`, `"$sha1$"`, `"$5$"`, or `"$6$"`. The settings string contains
+    /// the prefix, the number of rounds or cost, and a random salt, and can
+    /// be passed to the `hash_with` function of the scheme's module. If
+    /// `rounds` is `None`, the scheme's default is used.
+    ///
+    /// The other algorithms, like scrypt or the LDAP and Django schemes, give
+    /// [`Error::NoSettingString`]. They are hashed with their parameters
+    /// given directly: get the ranges from [`Algorithm::params`], a salt
+    /// from [`Salt::random_for`](crate::Salt::random_for), and pass them to
+    /// [`Algorithm::hash_with`]. For an unknown prefix,
+    /// [`Error::UnsupportedScheme`] is returned with the prefix.
+    ///
+    /// An error is also returned if rounds are given for a scheme with a
+    /// fixed number of rounds, or if the number of rounds is out of range for
+    /// BSDi, bcrypt, or SHA-1. Like in the `hash_with` functions, the rounds
+    /// of SHA-256 and SHA-512 are coerced into the allowed range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crypt3_rs::{crypt::sha512, unix};
+    ///
+    /// let setting = unix::gensalt("$6$", Some(10000)).unwrap();
+    /// assert!(setting.starts_with("$6$rounds=10000$"));
+    /// assert!(sha512::verify("password", &sha512::hash_with(setting.as_str(), "password").unwrap()));
+    /// ```
+    pub fn gensalt<P: SettingPrefix>(prefix: P, rounds: Option<u32>) -> Result<String> {
+        let prefix = prefix.prefix()?;
+        #[allow(unused_variables)]
+        let fixed = |setting: String| match rounds {
+            Some(_) => Err(Error::InvalidRounds),
+            None => Ok(setting),
+        };
+        match prefix {
+            #[cfg(feature = "unix")]
+            "" => fixed(random::gen_salt_str(crypt::unix::SALT_LEN)),
+            #[cfg(feature = "bsdi")]
+            "_" => {
+                use crypt::bsdi::{DEFAULT_ROUNDS, MAX_ROUNDS, MIN_ROUNDS, SALT_LEN};
+
+                let rounds = rounds.unwrap_or(DEFAULT_ROUNDS);
+                if !(MIN_ROUNDS..=MAX_ROUNDS).contains(&rounds) {
+                    return Err(Error::BadRounds {
+                        value: rounds,
+                        allowed: MIN_ROUNDS..=MAX_ROUNDS,
+                    });
+                }
+                Ok(format!(
+                    "_{}{}",
+                    crate::encode::encode_val(rounds, SALT_LEN),
+                    random::gen_salt_str(SALT_LEN)
+                ))
+            }
+            #[cfg(feature = "md5")]
+            "$1$" => fixed(format!(
+                "$1${}",
+                random::gen_salt_str(crypt::md5::MAX_SALT_LEN)
+            )),
+            #[cfg(feature = "apr1")]
+            "$apr1$" => fixed(format!(
+                "$apr1${}",
+                random::gen_salt_str(crypt::apr1::MAX_SALT_LEN)
+            )),
+            #[cfg(feature = "bcrypt")]
+            "$2a$" | "$2b$" | "$2x$" | "$2y$" => {
+                use crypt::bcrypt::{DEFAULT_COST, MAX_COST, MIN_COST};
+
+                let cost = rounds.unwrap_or(DEFAULT_COST);
+                if !(MIN_COST..=MAX_COST).contains(&cost) {
+                    return Err(Error::BadRounds {
+                        value: cost,
+                        allowed: MIN_COST..=MAX_COST,
+                    });
+                }
+                let mut salt_buf = [0u8; 16];
+                random::gen_salt_bytes(&mut salt_buf);
+                Ok(format!(
+                    "{prefix}{cost:02}${}",
+                    crate::encode::bcrypt_hash64_encode(&salt_buf)
+                ))
+            }
+            #[cfg(feature = "sha1")]
+            "$sha1$" => {
+                use crypt::sha1::{DEFAULT_ROUNDS, DEFAULT_SALT_LEN, MIN_ROUNDS};
+
+                let rounds = rounds.unwrap_or_else(|| random::vary_rounds(DEFAULT_ROUNDS));
+                if rounds < MIN_ROUNDS {
+                    return Err(Error::BadRounds {
+                        value: rounds,
+                        allowed: MIN_ROUNDS..=u32::MAX,
+                    });
+                }
+                Ok(format!(
+                    "$sha1${rounds}${}",
+                    random::gen_salt_str(DEFAULT_SALT_LEN)
+                ))
+            }
+            #[cfg(feature = "sha2")]
+            "$5$" | "$6$" => {
+                use crypt::sha512::{MAX_ROUNDS, MAX_SALT_LEN, MIN_ROUNDS};
+
+                let salt = random::gen_salt_str(MAX_SALT_LEN);
+                Ok(match rounds {
+                    Some(rounds) => format!(
+                        "{prefix}rounds={}${salt}",
+                        rounds.clamp(MIN_ROUNDS, MAX_ROUNDS)
+                    ),
+                    None => format!("{prefix}{salt}"),
+                })
+            }
+            _ => Err(Error::UnsupportedScheme {
+                prefix: prefix.to_string(),
+            }),
+        }
+    }
+
+    // Prefixes of the schemes of [gensalt], from the strongest to the
+    // weakest, and whether they're in the build.
+    const PREFERENCE: &[(bool, &str)] = &[
+        (cfg!(feature = "bcrypt"), "$2b$"),
+        (cfg!(feature = "sha2"), "$6$"),
+        (cfg!(feature = "sha1"), "$sha1$"),
+        (cfg!(feature = "md5"), "$1$"),
+        (cfg!(feature = "bsdi"), "_"),
+        (cfg!(feature = "unix"), ""),
+    ];
+
+    /// Get the prefix of the strongest scheme of [gensalt] in the build,
+    /// like libxcrypt's __crypt_preferred_method__(3).
+    ///
+    /// The prefix can be passed to [gensalt] to make a settings string for
+    /// the best scheme available. `None` is returned if no such scheme is
+    /// in the build.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crypt3_rs::unix;
+    ///
+    /// let prefix = unix::preferred_method().unwrap();
+    /// assert_eq!(prefix, "$2b$");
+    /// let setting = unix::gensalt(prefix, None).unwrap();
+    /// assert_eq!(unix::checksalt(&setting), unix::SaltCheck::Ok);
+    /// ```
+    pub fn preferred_method() -> Option<&'static str> {
+        PREFERENCE
+            .iter()
+            .find(|&&(enabled, _)| enabled)
+            .map(|&(_, prefix)| prefix)
+    }
+
+    /// Classification of a settings string by [checksalt].
+    ///
+    /// The variants correspond to the `CRYPT_SALT_*` values of libxcrypt.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[non_exhaustive]
+    pub enum SaltCheck {
+        /// The setting is fit for new hashes.
+        Ok,
+        /// The setting isn't a valid setting or hash of a known scheme.
+        Invalid,
+        /// The scheme is one of libxcrypt's, but it isn't in this build.
+        MethodDisabled,
+        /// The scheme is outdated, and shouldn't be used for new hashes.
+        MethodLegacy,
+        /// The scheme is sound, but the setting's cost is too low.
+        TooCheap,
+    }
+
+    // Prefixes of the schemes of libxcrypt.
+    const LIBXCRYPT_PREFIXES: &[&str] = &[
+        "_", "$1$", "$2a$", "$2b$", "$2x$", "$2y$", "$3$", "$5$", "$6$", "$7$", "$apr1$",
+        "$gy$", "$md5", "$sha1$", "$y$",
+    ];
+
+    // Complete a settings string made by [gensalt], which lacks a checksum,
+    // with a placeholder one, so that it parses as a hash.
+    fn complete_setting(setting: &str) -> Option<String> {
+        let sep = match setting.ends_with('$') {
+            true => "",
+            false => "$",
+        };
+        let (sep, len) = match setting {
+            _ if setting.starts_with("$1$") || setting.starts_with("$apr1$") => (sep, 22),
+            _ if setting.starts_with("$2") && setting.len() == 29 => ("", 31),
+            _ if setting.starts_with("$sha1$") => (sep, 28),
+            _ if setting.starts_with("$5$") => (sep, 43),
+            _ if setting.starts_with("$6$") => (sep, 86),
+            _ if setting.starts_with('_') && setting.len() == 9 => ("", 11),
+            _ if setting.len() == 2 => ("", 11),
+            _ => return None,
+        };
+        Some(format!("{setting}{sep}{}", ".".repeat(len)))
+    }
+
+    /// Check whether a settings string is fit for new hashes, like
+    /// libxcrypt's __crypt_checksalt__(3).
+    ///
+    /// The setting can be one made by [gensalt], or a complete hash. It's
+    /// [invalid](SaltCheck::Invalid) if it doesn't parse in
+    /// [strict](ParseMode::Strict) mode, [legacy](SaltCheck::MethodLegacy) if
+    /// its algorithm is deprecated or its [strength](Hash::strength) is
+    /// weak, and [too cheap](SaltCheck::TooCheap) if its strength is below
+    /// acceptable.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crypt3_rs::unix::{self, SaltCheck};
+    ///
+    /// assert_eq!(unix::checksalt("$2b$12$saltsaltsaltsaltsaltsO"), SaltCheck::Ok);
+    /// assert_eq!(unix::checksalt("$2b$04$saltsaltsaltsaltsaltsO"), SaltCheck::TooCheap);
+    /// assert_eq!(unix::checksalt("$1$saltsalt"), SaltCheck::MethodLegacy);
+    /// assert_eq!(unix::checksalt("$y$j9T$salt"), SaltCheck::MethodDisabled);
+    /// assert_eq!(unix::checksalt("$2b$12$salt"), SaltCheck::Invalid);
+    /// ```
+    pub fn checksalt(setting: &str) -> SaltCheck {
+        let hash = Hash::parse(setting, ParseMode::Strict).or_else(|err| {
+            Hash::parse(&complete_setting(setting).ok_or(err)?, ParseMode::Strict)
+        });
+        match hash {
+            Ok(hash) if hash.is_deprecated() || hash.strength() == Strength::Weak => {
+                SaltCheck::MethodLegacy
+            }
+            Ok(hash) if hash.strength() < Strength::Acceptable => SaltCheck::TooCheap,
+            Ok(_) => SaltCheck::Ok,
+            Err(Error::UnsupportedScheme { .. })
+                if LIBXCRYPT_PREFIXES.iter().any(|prefix| setting.starts_with(prefix)) =>
+            {
+                SaltCheck::MethodDisabled
+            }
+            Err(_) => SaltCheck::Invalid,
+        }
+    }
+
     #[cfg(test)]
     mod tests {
         #[test]
@@ -143,5 +919,199 @@ pub mod unix {
                 "aZGJuE6EXrjEE"
             );
         }
+
+        #[test]
+        #[cfg(feature = "md5")]
+        fn classify() {
+            use super::HashState;
+
+            let h = "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0";
+            assert!(matches!(super::classify(h).unwrap(), HashState::Active(_)));
+            assert!(matches!(
+                super::classify(&format!("!{h}")).unwrap(),
+                HashState::Locked(Some(inner)) if inner.as_str() == h
+            ));
+            assert!(matches!(
+                super::classify("!").unwrap(),
+                HashState::Locked(None)
+            ));
+            assert!(matches!(
+                super::classify("*LK*").unwrap(),
+                HashState::Disabled
+            ));
+            assert!(matches!(
+                super::classify("").unwrap(),
+                HashState::NoPassword
+            ));
+            assert!(super::classify("x").is_err());
+        }
+
+        #[test]
+        #[allow(deprecated)]
+        #[cfg(all(
+            feature = "bcrypt",
+            feature = "bsdi",
+            feature = "md5",
+            feature = "sha2",
+            feature = "unix"
+        ))]
+        fn checksalt() {
+            use super::SaltCheck;
+            use crate::Algorithm;
+
+            for (prefix, check) in [
+                ("$6$", SaltCheck::Ok),
+                ("$5$", SaltCheck::MethodLegacy),
+                ("$2b$", SaltCheck::Ok),
+                ("$1$", SaltCheck::MethodLegacy),
+                ("_", SaltCheck::MethodLegacy),
+                ("", SaltCheck::MethodLegacy),
+            ] {
+                let setting = super::gensalt(prefix, None).unwrap();
+                assert_eq!(super::checksalt(&setting), check, "{setting}");
+            }
+            let setting = super::gensalt(Algorithm::Sha512, Some(1000)).unwrap();
+            assert_eq!(super::checksalt(&setting), SaltCheck::TooCheap);
+            let h = "$6$rounds=1000$saltsalt$Z/J9iYO1iE9xnr8JPQL57ZWsVRtVjrUv3CiWc/wKWseqXgSqn3HFYJ/Ng7YXa8XlLj.wpdAwHOJJzuGFqBBRa0";
+            assert_eq!(super::checksalt(h), SaltCheck::TooCheap);
+            assert_eq!(super::checksalt("$6$saltsalt$"), SaltCheck::Ok);
+            assert_eq!(super::checksalt("$7$CU..../....salt"), SaltCheck::MethodDisabled);
+            for setting in ["", "$6", "$x$salt", "a!", "_J9..sal", "$2b$10$salt$"] {
+                assert_eq!(super::checksalt(setting), SaltCheck::Invalid, "{setting}");
+            }
+        }
+
+        #[test]
+        fn preferred_method() {
+            let prefix = super::preferred_method();
+            assert_eq!(prefix.is_some(), super::PREFERENCE.iter().any(|&(enabled, _)| enabled));
+            if let Some(prefix) = prefix {
+                assert!(super::gensalt(prefix, None).is_ok());
+            }
+        }
+
+        #[test]
+        #[cfg(feature = "md5")]
+        fn verify_result() {
+            let h = "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0";
+            assert!(super::verify_result("password", h).unwrap());
+            assert!(!super::verify_result("passwore", h).unwrap());
+            assert!(matches!(
+                super::verify_result("password", "$1$5pZSV9va$"),
+                Err(crate::error::Error::InsufficientLength)
+            ));
+        }
+
+        #[test]
+        #[should_panic(expected = "value: UnsupportedScheme")]
+        fn verify_result_unknown() {
+            let _ = super::verify_result("password", "$0$5pZSV9va$azfrPr6af3Fc7dLblQXVa0").unwrap();
+        }
+
+        #[test]
+        #[cfg(feature = "md5")]
+        fn dummy_verify() {
+            use crate::Algorithm;
+
+            assert!(!super::dummy_verify(
+                "password",
+                "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0"
+            ));
+            assert!(!super::dummy_verify("password", "$1$5pZSV9va"));
+            assert!(!super::dummy_verify("password", "$0$"));
+            #[allow(deprecated)]
+            let md5 = Algorithm::Md5;
+            assert!(!md5.dummy_verify("password"));
+        }
+
+        #[test]
+        #[cfg(all(feature = "bcrypt", feature = "md5"))]
+        fn verify_and_rehash() {
+            use crate::{Algorithm, Policy};
+
+            let policy = Policy::new(Algorithm::Bcrypt).min_rounds(Algorithm::Bcrypt, 5);
+            let (ok, new) =
+                super::verify_and_rehash("password", "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0", &policy);
+            assert!(ok);
+            let new = new.unwrap();
+            assert_eq!(new.algorithm(), Algorithm::Bcrypt);
+            assert!(new.verify("password"));
+
+            let h = "$2y$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe";
+            assert!(matches!(
+                super::verify_and_rehash("password", h, &policy),
+                (true, None)
+            ));
+            assert!(matches!(
+                super::verify_and_rehash("passwore", h, &policy),
+                (false, None)
+            ));
+        }
+
+        #[test]
+        #[allow(deprecated)]
+        #[cfg(all(
+            feature = "bcrypt",
+            feature = "bsdi",
+            feature = "md5",
+            feature = "sha2"
+        ))]
+        fn gensalt() {
+            use crate::{Algorithm, crypt};
+
+            let setting = super::gensalt(Algorithm::Bcrypt, Some(5)).unwrap();
+            assert!(setting.starts_with("$2b$05$") && setting.len() == 29);
+            let h = crypt::bcrypt::hash_with(setting.as_str(), "password").unwrap();
+            assert!(h.starts_with(&setting) && h.verify("password"));
+
+            let setting = super::gensalt("$2y$", None).unwrap();
+            assert!(setting.starts_with("$2y$10$"));
+
+            let setting = super::gensalt("_", Some(725)).unwrap();
+            assert!(setting.starts_with("_J9..") && setting.len() == 9);
+            let h = crypt::bsdi::hash_with(setting.as_str(), "password").unwrap();
+            assert!(h.starts_with(&setting) && h.verify("password"));
+
+            let setting = super::gensalt("$1$", None).unwrap();
+            let h = crypt::md5::hash_with(setting.as_str(), "password").unwrap();
+            assert!(h.starts_with(&setting) && h.verify("password"));
+
+            let setting = super::gensalt(Algorithm::Sha256, None).unwrap();
+            assert_eq!(setting.len(), 19);
+            let h = crypt::sha256::hash_with(setting.as_str(), "password").unwrap();
+            assert!(h.starts_with(&setting) && h.verify("password"));
+
+            assert!(
+                super::gensalt("$6$", Some(1))
+                    .unwrap()
+                    .starts_with("$6$rounds=1000$")
+            );
+            assert!(super::gensalt("$2b$", Some(3)).is_err());
+            assert!(super::gensalt("$1$", Some(1000)).is_err());
+            assert!(super::gensalt("$7$", None).is_err());
+        }
+
+        #[test]
+        #[cfg(all(feature = "django", feature = "scrypt"))]
+        fn gensalt_unsupported() {
+            use crate::{Algorithm, error::Error};
+
+            assert!(matches!(
+                super::gensalt(Algorithm::Scrypt, None),
+                Err(Error::NoSettingString {
+                    algorithm: Algorithm::Scrypt
+                })
+            ));
+            assert!(matches!(
+                super::gensalt(Algorithm::Django, Some(1000)),
+                Err(Error::NoSettingString {
+                    algorithm: Algorithm::Django
+                })
+            ));
+            assert!(matches!(
+                super::gensalt("pbkdf2_sha256", None),
+                Err(Error::UnsupportedScheme { prefix }) if prefix == "pbkdf2_sha256"
+            ));
+        }
     }
 }
@@ -0,0 +1,68 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// A password converted to Unicode normalization form NFKC.
+///
+/// The same password can be encoded as different byte sequences, e.g.
+/// when an accented letter is typed as a single precomposed character on
+/// one system and as a letter followed by a combining accent on another.
+/// The hashes of such passwords differ, unless the password is normalized
+/// before it's hashed. Since existing hashes were made without
+/// normalization, it's opt-in: wrap the password in this type both when
+/// hashing and when verifying it, and the conversion is applied
+/// consistently. Normalization is only defined for text, so the password
+/// must be a string.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::{Normalized, crypt::sha512};
+///
+/// let h = sha512::hash(Normalized::from("p\u{e4}ssword")).unwrap();
+/// assert!(h.verify(Normalized::from("pa\u{308}ssword")));
+/// assert!(!h.verify("pa\u{308}ssword"));
+/// ```
+pub struct Normalized(String);
+
+impl From<&str> for Normalized {
+    #[inline]
+    fn from(pass: &str) -> Self {
+        Normalized(pass.nfkc().collect())
+    }
+}
+
+impl AsRef<[u8]> for Normalized {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl std::fmt::Debug for Normalized {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Normalized([REDACTED])")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Normalized;
+
+    #[test]
+    fn nfkc() {
+        let composed = Normalized::from("p\u{e4}ssword");
+        assert_eq!(composed.as_ref(), "p\u{e4}ssword".as_bytes());
+        assert_eq!(
+            Normalized::from("pa\u{308}ssword").as_ref(),
+            composed.as_ref()
+        );
+        assert_eq!(Normalized::from("\u{fb01}").as_ref(), b"fi");
+        assert_eq!(format!("{composed:?}"), "Normalized([REDACTED])");
+    }
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn verify() {
+        let h = crate::crypt::sha512::hash(Normalized::from("pa\u{308}ssword")).unwrap();
+        assert!(crate::unix::verify(Normalized::from("p\u{e4}ssword"), &h));
+    }
+}
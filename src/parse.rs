@@ -83,7 +83,7 @@ impl<'a> HashIterator for HashSlice<'a> {
     }
 
     fn at_end(&mut self) -> bool {
-        self.take(0).unwrap_or("X") == "X"
+        self.take(0).is_none()
     }
 }
 
@@ -107,18 +107,18 @@ mod tests {
             hs.take_until(b'$').unwrap(),
             "5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe"
         );
-        assert_eq!(hs.at_end(), true);
+        assert!(hs.at_end());
         assert_eq!(hs1.take(31).unwrap(), "5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe");
-        assert_eq!(hs1.at_end(), true);
+        assert!(hs1.at_end());
     }
 
     #[test]
     fn empty_string() {
         let mut hs = HashSlice::new("");
         assert_eq!(hs.take_until(b'$').unwrap(), "");
-        assert_eq!(hs.at_end(), true);
+        assert!(hs.at_end());
         let mut hs = HashSlice::new("");
-        assert_eq!(hs.at_end(), false);
+        assert!(!hs.at_end());
     }
 
     #[test]
@@ -126,7 +126,7 @@ mod tests {
         let mut hs = HashSlice::new("$");
         assert_eq!(hs.take_until(b'$').unwrap(), "");
         assert_eq!(hs.take_until(b'$').unwrap(), "");
-        assert_eq!(hs.at_end(), true);
+        assert!(hs.at_end());
     }
 
     #[test]
@@ -134,6 +134,6 @@ mod tests {
         let mut hs = HashSlice::new("$");
         let _ = hs.take_until(b'$').unwrap();
         assert_eq!(hs.take_until(b'$').unwrap(), "");
-        assert_eq!(hs.at_end(), true);
+        assert!(hs.at_end());
     }
 }
@@ -0,0 +1,254 @@
+//! Password file entries, and accounts joined with their shadow entries.
+//!
+//! A line of a __passwd__(5) file has seven fields separated by colons: the
+//! login name, the password field, the user and group IDs, the GECOS field
+//! with the user's name, the home directory, and the login shell. On
+//! systems with shadow passwords, the password field is `x`, and the hash is
+//! in the [shadow](crate::shadow) file.
+//!
+//! [`PasswdEntry`] parses such a line with [`FromStr`], and writes it back
+//! with its [`Display`](fmt::Display) implementation. [`Account`] combines
+//! an entry with the shadow entry of the same name, and finds the hash
+//! wherever the system would.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::{passwd, shadow};
+//!
+//! let passwd = "root:x:0:0:root:/root:/bin/bash\n\
+//!               alice:x:1000:1000:Alice,,,:/home/alice:/bin/sh\n";
+//! let shadow = "root:*:19000:0:99999:7:::\n\
+//!               alice:$2b$10$saltsaltsaltsaltsaltsOF8gOe7Q2djsDdlP9U528dfcxNNK.BXC:19000:0:99999:7:::\n";
+//! let accounts = passwd::accounts(
+//!     passwd::entries(passwd).map(Result::unwrap),
+//!     shadow::entries(shadow).map(Result::unwrap),
+//! );
+//! assert_eq!(accounts[1].passwd.uid, 1000);
+//! assert!(accounts[1].hash().unwrap().starts_with("$2b$"));
+//! assert!(!accounts[0].verify("password"));
+//! ```
+
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use crate::{
+    error::{Error, Result},
+    shadow::{ShadowEntry, split_fields},
+    unix::{self, HashState},
+};
+
+const FIELDS: usize = 7;
+
+/// Kind of the password field of a password file entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PasswordField {
+    /// The field is `x`; the hash is in the shadow file.
+    Shadowed,
+    /// The field starts with `*`; logging in with a password is disabled.
+    Disabled,
+    /// The field is empty; no password is required.
+    NoPassword,
+    /// The field holds the hash itself, as on systems without shadow
+    /// passwords.
+    Inline,
+}
+
+/// An entry of a password file.
+///
+/// The fields must not contain colons or line breaks, which no parsed entry
+/// does, for the entry to be written back as a valid line.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PasswdEntry {
+    /// Login name.
+    pub name: String,
+    /// Password field: `x` if the hash is in the shadow file.
+    pub password: String,
+    /// User ID.
+    pub uid: u32,
+    /// Group ID.
+    pub gid: u32,
+    /// User information, usually the full name followed by comma-separated
+    /// contact details.
+    pub gecos: String,
+    /// Home directory.
+    pub home: String,
+    /// Login shell.
+    pub shell: String,
+}
+
+impl PasswdEntry {
+    /// Get the kind of the password field.
+    pub fn password_field(&self) -> PasswordField {
+        match self.password.as_str() {
+            "x" => PasswordField::Shadowed,
+            "" => PasswordField::NoPassword,
+            password if password.starts_with('*') => PasswordField::Disabled,
+            _ => PasswordField::Inline,
+        }
+    }
+}
+
+impl FromStr for PasswdEntry {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Self> {
+        let [name, password, uid, gid, gecos, home, shell] = split_fields::<FIELDS>(line)?;
+        Ok(PasswdEntry {
+            name: name.to_string(),
+            password: password.to_string(),
+            uid: uid.parse().map_err(Error::InvalidNumber)?,
+            gid: gid.parse().map_err(Error::InvalidNumber)?,
+            gecos: gecos.to_string(),
+            home: home.to_string(),
+            shell: shell.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for PasswdEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}:{}:{}:{}:{}",
+            self.name, self.password, self.uid, self.gid, self.gecos, self.home, self.shell
+        )
+    }
+}
+
+/// Parse the entries of a password file, skipping empty lines.
+pub fn entries(text: &str) -> impl Iterator<Item = Result<PasswdEntry>> + '_ {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::parse)
+}
+
+/// An account: a password file entry, with its shadow entry if there's one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Account {
+    /// Password file entry.
+    pub passwd: PasswdEntry,
+    /// Shadow file entry of the same name.
+    pub shadow: Option<ShadowEntry>,
+}
+
+impl Account {
+    /// Get the password field which applies to the account.
+    ///
+    /// It's the field of the shadow entry if the password field of the
+    /// password file entry is `x`, and the latter field otherwise. `None`
+    /// is returned if the password is shadowed, but the account has no
+    /// shadow entry.
+    pub fn hash(&self) -> Option<&str> {
+        match self.passwd.password_field() {
+            PasswordField::Shadowed => self.shadow.as_ref().map(|shadow| shadow.hash.as_str()),
+            _ => Some(&self.passwd.password),
+        }
+    }
+
+    /// Verify a password against the password field of the account.
+    ///
+    /// Locked, disabled, and passwordless accounts, and accounts whose
+    /// shadow entry is missing, never verify.
+    pub fn verify<B: AsRef<[u8]>>(&self, pass: B) -> bool {
+        self.hash().is_some_and(|hash| unix::verify(pass, hash))
+    }
+
+    /// Classify the password field of the account with [`unix::classify`].
+    ///
+    /// An account whose shadow entry is missing gives
+    /// [`Error::InvalidHashString`].
+    pub fn state(&self) -> Result<HashState> {
+        unix::classify(self.hash().ok_or(Error::InvalidHashString)?)
+    }
+}
+
+/// Join password file entries with the shadow entries of the same names.
+///
+/// The accounts are in the order of the password file entries. Shadow
+/// entries without a password file entry are left out.
+pub fn accounts<P, S>(passwd: P, shadow: S) -> Vec<Account>
+where
+    P: IntoIterator<Item = PasswdEntry>,
+    S: IntoIterator<Item = ShadowEntry>,
+{
+    let mut shadow: HashMap<String, ShadowEntry> = shadow
+        .into_iter()
+        .map(|entry| (entry.name.clone(), entry))
+        .collect();
+    passwd
+        .into_iter()
+        .map(|passwd| Account {
+            shadow: shadow.remove(&passwd.name),
+            passwd,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Account, PasswdEntry, PasswordField, accounts};
+    use crate::{error::Error, shadow::ShadowEntry, unix::HashState};
+
+    #[test]
+    fn parse() {
+        let line = "nobody:*:65534:65534:nobody:/nonexistent:/usr/sbin/nologin";
+        let entry: PasswdEntry = line.parse().unwrap();
+        assert_eq!(entry.uid, 65534);
+        assert_eq!(entry.shell, "/usr/sbin/nologin");
+        assert_eq!(entry.password_field(), PasswordField::Disabled);
+        assert_eq!(entry.to_string(), line);
+
+        let entry: PasswdEntry = "guest::1001:1001:::\n".parse().unwrap();
+        assert_eq!(entry.password_field(), PasswordField::NoPassword);
+        assert!(matches!(
+            "guest::1001:1001::".parse::<PasswdEntry>(),
+            Err(Error::InvalidEntry {
+                expected: 7,
+                found: 6
+            })
+        ));
+        assert!(matches!(
+            "guest:::1001:::".parse::<PasswdEntry>(),
+            Err(Error::InvalidNumber(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn join() {
+        const SHA512: &str = "$6$saltsalt$qFmFH.bQmmtXzyBY0s9v7Oicd2z4XSIecDzlB5KiA2/jctKu9YterLp8wwnSq.qc.eoxqOmSuNp2xS0ktL3nh/";
+
+        let passwd = |line: &str| line.parse::<PasswdEntry>().unwrap();
+        let shadow = |line: &str| line.parse::<ShadowEntry>().unwrap();
+        let accounts: Vec<Account> = accounts(
+            [
+                passwd("alice:x:1000:1000::/home/alice:/bin/sh"),
+                passwd("bob:x:1001:1001::/home/bob:/bin/sh"),
+                passwd(&format!("carol:{SHA512}:1002:1002::/home/carol:/bin/sh")),
+            ],
+            [
+                shadow("bob:!:19000::::::"),
+                shadow(&format!("alice:{SHA512}:19000::::::")),
+                shadow("dave:*:19000::::::"),
+            ],
+        );
+        assert_eq!(accounts.len(), 3);
+        assert!(accounts[0].verify("password"));
+        assert!(!accounts[0].verify("passwort"));
+        assert!(matches!(
+            accounts[1].state().unwrap(),
+            HashState::Locked(None)
+        ));
+        assert_eq!(accounts[2].shadow, None);
+        assert!(accounts[2].verify("password"));
+
+        let orphan = Account {
+            passwd: passwd("eve:x:1003:1003::/home/eve:/bin/sh"),
+            shadow: None,
+        };
+        assert_eq!(orphan.hash(), None);
+        assert!(!orphan.verify(""));
+        assert!(matches!(orphan.state(), Err(Error::InvalidHashString)));
+    }
+}
@@ -0,0 +1,256 @@
+//! PHC string format.
+//!
+//! The [PHC string format] is the encoding defined by the Password Hashing
+//! Competition, and used by scrypt, Argon2, balloon hashing, and other
+//! modern algorithms. A PHC string has the form
+//! __`$`__*`{id}`*\[__`$v=`__*`{version}`*\]\[__`$`__*`{param}`*__`=`__*`{value}`*\[__`,`__*`{param}`*__`=`__*`{value}`*...\]\]\[__`$`__*`{salt}`*\[__`$`__*`{hash}`*\]\],
+//! where the salt and the hash are usually encoded in standard Base64
+//! without padding.
+//!
+//! [`PhcString`] splits such a string into its components without
+//! allocating for anything but the parameter list, and writes it back with
+//! its [`Display`](std::fmt::Display) implementation. It can also be built
+//! field by field, to produce the hashes of algorithms this crate doesn't
+//! implement.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::phc::PhcString;
+//!
+//! let h = "$scrypt$ln=10,r=8,p=1$c2FsdHNhbHRzYWx0c2FsdA$\
+//!          BVMRKqdiVYikKAaPR1wucsKUKvw4TuPLkdEYtoSHas4";
+//! let phc = PhcString::parse(h).unwrap();
+//! assert_eq!(phc.id, "scrypt");
+//! assert_eq!(phc.param_u32("ln").unwrap(), Some(10));
+//! assert_eq!(phc.salt_bytes().unwrap().unwrap(), b"saltsaltsaltsalt");
+//! assert_eq!(phc.to_string(), h);
+//! ```
+//!
+//! [PHC string format]: https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md
+
+use std::fmt;
+
+use crate::{
+    encode::base64_nopad_decode,
+    error::{Error, Result},
+};
+
+/// Maximum length of the identifier and the parameter names.
+const MAX_NAME_LEN: usize = 32;
+
+fn is_name(name: &str) -> bool {
+    (1..=MAX_NAME_LEN).contains(&name.len())
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+}
+
+fn is_value(value: &str) -> bool {
+    value
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'/' | b'+' | b'.' | b'-'))
+}
+
+/// Components of a PHC string.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PhcString<'a> {
+    /// Identifier of the algorithm.
+    pub id: &'a str,
+    /// Version of the algorithm, from the `v=` segment.
+    pub version: Option<u32>,
+    /// Parameters, in order of appearance.
+    pub params: Vec<(&'a str, &'a str)>,
+    /// Encoded salt.
+    pub salt: Option<&'a str>,
+    /// Encoded hash. It's only present if there is a salt.
+    pub hash: Option<&'a str>,
+}
+
+impl<'a> PhcString<'a> {
+    /// Create a PHC string with the identifier of the algorithm
+    pub fn new(id: &'a str) -> Self {
+        PhcString {
+            id,
+            ..Default::default()
+        }
+    }
+    /// Configure the version
+    pub fn version(mut self, version: u32) -> Self {
+        self.version = Some(version);
+        self
+    }
+    /// Add a parameter
+    pub fn param(mut self, name: &'a str, value: &'a str) -> Self {
+        self.params.push((name, value));
+        self
+    }
+    /// Configure the encoded salt
+    pub fn salt(mut self, salt: &'a str) -> Self {
+        self.salt = Some(salt);
+        self
+    }
+    /// Configure the encoded hash
+    pub fn hash(mut self, hash: &'a str) -> Self {
+        self.hash = Some(hash);
+        self
+    }
+
+    /// Split a PHC string into its components.
+    ///
+    /// The identifier and the parameter names must consist of 1 to 32
+    /// lowercase letters, digits, and dashes, and the values, the salt, and
+    /// the hash of letters, digits, and the characters __`/+.-`__. A segment
+    /// which doesn't fit the format results in an `InvalidHashString` error,
+    /// and a version which isn't a decimal number in an `InvalidNumber`
+    /// error. The salt and the hash aren't decoded.
+    pub fn parse(s: &'a str) -> Result<Self> {
+        let mut segments = s
+            .strip_prefix('$')
+            .ok_or(Error::InvalidHashString)?
+            .split('$');
+        let id = segments
+            .next()
+            .filter(|id| is_name(id))
+            .ok_or(Error::InvalidHashString)?;
+        let mut phc = PhcString::new(id);
+
+        let mut segment = segments.next();
+        if let Some(version) = segment.and_then(|seg| seg.strip_prefix("v=")) {
+            let version = version.parse::<u32>().map_err(Error::InvalidNumber)?;
+            phc.version = Some(version);
+            segment = segments.next();
+        }
+        if let Some(params) = segment.filter(|seg| seg.contains('=')) {
+            for param in params.split(',') {
+                let (name, value) = param.split_once('=').ok_or(Error::InvalidHashString)?;
+                if !is_name(name) || !is_value(value) || phc.get(name).is_some() {
+                    return Err(Error::InvalidHashString);
+                }
+                phc.params.push((name, value));
+            }
+            segment = segments.next();
+        }
+        if let Some(salt) = segment {
+            phc.salt = Some(salt);
+            phc.hash = segments.next();
+        }
+
+        let mut encoded = phc.salt.into_iter().chain(phc.hash);
+        if segments.next().is_some() || !encoded.all(|enc| !enc.is_empty() && is_value(enc)) {
+            return Err(Error::InvalidHashString);
+        }
+        Ok(phc)
+    }
+
+    /// Get the value of a parameter.
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.params
+            .iter()
+            .find(|&&(param, _)| param == name)
+            .map(|&(_, value)| value)
+    }
+
+    /// Get the value of a numeric parameter.
+    ///
+    /// A value which isn't a decimal number results in an `InvalidNumber`
+    /// error.
+    pub fn param_u32(&self, name: &str) -> Result<Option<u32>> {
+        self.get(name)
+            .map(|value| value.parse::<u32>().map_err(Error::InvalidNumber))
+            .transpose()
+    }
+
+    /// Decode the salt from Base64 without padding.
+    pub fn salt_bytes(&self) -> Result<Option<Vec<u8>>> {
+        self.salt.map(base64_nopad_decode).transpose()
+    }
+
+    /// Decode the hash from Base64 without padding.
+    pub fn hash_bytes(&self) -> Result<Option<Vec<u8>>> {
+        self.hash.map(base64_nopad_decode).transpose()
+    }
+}
+
+impl fmt::Display for PhcString<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "${}", self.id)?;
+        if let Some(version) = self.version {
+            write!(f, "$v={version}")?;
+        }
+        for (i, (name, value)) in self.params.iter().enumerate() {
+            let sep = if i == 0 { '$' } else { ',' };
+            write!(f, "{sep}{name}={value}")?;
+        }
+        if let Some(salt) = self.salt {
+            write!(f, "${salt}")?;
+            if let Some(hash) = self.hash {
+                write!(f, "${hash}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PhcString;
+
+    #[test]
+    fn parse() {
+        let h = "$balloon$v=1$s=3,t=3,p=1$c2FsdA$XwL4IG+c0hJIXGvfhVJ7aYlWcBrQhSEG+UuU7pRXc3g";
+        let phc = PhcString::parse(h).unwrap();
+        assert_eq!(phc.id, "balloon");
+        assert_eq!(phc.version, Some(1));
+        assert_eq!(phc.params, [("s", "3"), ("t", "3"), ("p", "1")]);
+        assert_eq!(phc.param_u32("t").unwrap(), Some(3));
+        assert_eq!(phc.param_u32("x").unwrap(), None);
+        assert_eq!(phc.salt, Some("c2FsdA"));
+        assert_eq!(phc.hash_bytes().unwrap().unwrap().len(), 32);
+        assert_eq!(phc.to_string(), h);
+
+        let phc = PhcString::parse("$argon2id$v=19$c2FsdA").unwrap();
+        assert!(phc.params.is_empty());
+        assert_eq!(phc.salt_bytes().unwrap().unwrap(), b"salt");
+        assert_eq!(phc.hash, None);
+        assert_eq!(PhcString::parse("$x").unwrap(), PhcString::new("x"));
+    }
+
+    #[test]
+    fn build() {
+        let phc = PhcString::new("argon2id")
+            .version(19)
+            .param("m", "65536")
+            .param("t", "2")
+            .salt("c2FsdA")
+            .hash("aGFzaA");
+        assert_eq!(phc.to_string(), "$argon2id$v=19$m=65536,t=2$c2FsdA$aGFzaA");
+        assert_eq!(PhcString::parse(&phc.to_string()).unwrap(), phc);
+    }
+
+    #[test]
+    fn invalid() {
+        for h in [
+            "scrypt$ln=10",
+            "$",
+            "$SCRYPT",
+            "$scrypt$ln=10,ln=11",
+            "$scrypt$ln=10,r",
+            "$scrypt$ln=10$salt$hash$extra",
+            "$scrypt$ln=10$$hash",
+            "$scrypt$ln=10$sa_lt",
+        ] {
+            assert!(
+                matches!(
+                    PhcString::parse(h),
+                    Err(crate::error::Error::InvalidHashString)
+                ),
+                "{h}"
+            );
+        }
+        assert!(matches!(
+            PhcString::parse("$argon2id$v=x"),
+            Err(crate::error::Error::InvalidNumber(_))
+        ));
+    }
+}
@@ -0,0 +1,298 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{
+    Algorithm, consteq,
+    error::{Error, Result},
+    hash::Hash,
+};
+
+/// Default maximum password length, in bytes.
+pub const DEFAULT_MAX_PASSWORD_LEN: usize = 4096;
+
+static MAX_PASSWORD_LEN: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_PASSWORD_LEN);
+
+/// Set the maximum password length, in bytes, for the whole process.
+///
+/// The work done by some algorithms, notably SHA-256 and SHA-512, grows with
+/// the length of the password, so that an attacker who can submit
+/// multi-megabyte passwords can tie up a server. Longer passwords are
+/// rejected with [`Error::PasswordTooLong`] by those algorithms, and by the
+/// generic entry points: [`Algorithm::hash`], [`Algorithm::hash_with`],
+/// [`Hash::hash_with`], [`Hash::verify`], and the [`unix`](crate::unix)
+/// functions; verification of a longer password fails. The default is
+/// [`DEFAULT_MAX_PASSWORD_LEN`]; use `usize::MAX` to remove the limit.
+pub fn set_max_password_len(len: usize) {
+    MAX_PASSWORD_LEN.store(len, Ordering::Relaxed);
+}
+
+/// Get the maximum password length, in bytes.
+pub fn max_password_len() -> usize {
+    MAX_PASSWORD_LEN.load(Ordering::Relaxed)
+}
+
+pub(crate) fn check_password_len(pass: &[u8]) -> Result<()> {
+    if pass.len() > max_password_len() {
+        return Err(Error::PasswordTooLong);
+    }
+    Ok(())
+}
+
+/// Requirements for stored hashes.
+///
+/// A policy names the algorithm which should be used for new hashes, and
+/// the minimal parameters an existing hash must have to remain acceptable.
+/// It's used by [`Hash::needs_rehash`] to decide whether a hash should be
+/// replaced, typically after a successful login.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::{Algorithm, Hash, Policy};
+///
+/// let policy = Policy::new(Algorithm::Bcrypt).min_rounds(Algorithm::Bcrypt, 10);
+/// let h = Hash::try_from("$2y$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe").unwrap();
+/// assert!(h.needs_rehash(&policy));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Policy {
+    /// Algorithm for new hashes.
+    pub algorithm: Algorithm,
+    /// Whether hashes made with other algorithms should be replaced.
+    pub require_algorithm: bool,
+    /// Minimal number of rounds or cost for individual algorithms.
+    pub min_rounds: Vec<(Algorithm, u32)>,
+    /// Minimal length of the encoded salt, in characters.
+    pub min_salt_len: usize,
+    /// Maximum password length, in bytes, accepted by
+    /// [`unix::verify_and_rehash`](crate::unix::verify_and_rehash).
+    pub max_password_len: usize,
+}
+
+impl Policy {
+    /// Create a policy with the given algorithm for new hashes.
+    ///
+    /// Hashes made with other algorithms are acceptable unless the algorithm
+    /// is deprecated, and there are no requirements for their parameters.
+    /// The maximum password length is the process-wide one.
+    pub fn new(algorithm: Algorithm) -> Self {
+        Policy {
+            algorithm,
+            require_algorithm: false,
+            min_rounds: Vec::new(),
+            min_salt_len: 0,
+            max_password_len: max_password_len(),
+        }
+    }
+    /// Configure whether hashes made with other algorithms should be replaced
+    pub fn require_algorithm(mut self, require: bool) -> Self {
+        self.require_algorithm = require;
+        self
+    }
+    /// Configure the minimal number of rounds or cost for an algorithm
+    pub fn min_rounds(mut self, algorithm: Algorithm, rounds: u32) -> Self {
+        self.min_rounds.retain(|&(alg, _)| alg != algorithm);
+        self.min_rounds.push((algorithm, rounds));
+        self
+    }
+    /// Configure the minimal length of the encoded salt
+    pub fn min_salt_len(mut self, len: usize) -> Self {
+        self.min_salt_len = len;
+        self
+    }
+    /// Configure the maximum password length
+    pub fn max_password_len(mut self, len: usize) -> Self {
+        self.max_password_len = len;
+        self
+    }
+}
+
+/// Verification restricted to a set of algorithms.
+///
+/// A verifier recognizes the format of a hash like
+/// [`unix::verify`](crate::unix::verify), but fails closed for hashes made
+/// with an algorithm which isn't on its allow-list: they never verify, and
+/// [`Verifier::verify_result`] reports them with
+/// [`Error::DisallowedAlgorithm`]. The password isn't hashed in that case.
+/// A new verifier allows no algorithm.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::{Algorithm, Verifier};
+///
+/// let verifier = Verifier::new().allow(Algorithm::Bcrypt).allow(Algorithm::Sha512);
+/// let h = "$2y$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe";
+/// assert!(verifier.verify("password", h));
+/// assert!(!verifier.verify("password", "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Verifier {
+    /// Algorithms whose hashes can be verified.
+    pub allowed: Vec<Algorithm>,
+}
+
+impl Verifier {
+    /// Create a verifier which allows no algorithm.
+    pub fn new() -> Self {
+        Verifier::default()
+    }
+    /// Allow an algorithm
+    pub fn allow(mut self, algorithm: Algorithm) -> Self {
+        if !self.allowed.contains(&algorithm) {
+            self.allowed.push(algorithm);
+        }
+        self
+    }
+
+    /// Check whether hashes made with the algorithm can be verified.
+    pub fn is_allowed(&self, algorithm: Algorithm) -> bool {
+        self.allowed.contains(&algorithm)
+    }
+
+    /// Verify that the hash corresponds to a password.
+    ///
+    /// Any failure, including a disallowed algorithm, results in `false`.
+    pub fn verify<B: AsRef<[u8]>>(&self, pass: B, hash: &str) -> bool {
+        self.verify_result(pass, hash).unwrap_or_default()
+    }
+
+    /// Verify that the hash corresponds to a password, reporting unusable
+    /// and disallowed hashes.
+    ///
+    /// Like [`unix::verify_result`](crate::unix::verify_result), but a hash
+    /// made with an algorithm which isn't allowed results in a
+    /// `DisallowedAlgorithm` error.
+    pub fn verify_result<B: AsRef<[u8]>>(&self, pass: B, hash: &str) -> Result<bool> {
+        let stored = Hash::try_from(hash)?;
+        let algorithm = stored.algorithm();
+        if !self.is_allowed(algorithm) {
+            return Err(Error::DisallowedAlgorithm { algorithm });
+        }
+        let calchash = stored.hash_with(pass)?;
+        Ok(consteq(hash, Ok(calchash)))
+    }
+}
+
+impl Hash {
+    /// Check whether the hash should be replaced according to the policy.
+    ///
+    /// The hash should be replaced if its algorithm is deprecated, if it's
+    /// not the policy's algorithm and the policy requires it, if its number
+    /// of rounds or cost is below the minimum set for the algorithm, or if
+    /// its salt is shorter than required. The implicit number of rounds of
+    /// SHA-256 and SHA-512 hashes is taken to be 5000; the rounds of other
+    /// algorithms which don't encode them aren't checked, and neither is the
+    /// salt of algorithms which encode it together with the checksum. A hash
+    /// which can't be split into its components should always be replaced.
+    pub fn needs_rehash(&self, policy: &Policy) -> bool {
+        let algorithm = self.algorithm();
+        if algorithm.is_deprecated() || (policy.require_algorithm && algorithm != policy.algorithm)
+        {
+            return true;
+        }
+        let Ok(parts) = self.parts() else {
+            return true;
+        };
+        #[allow(unreachable_patterns)]
+        let rounds = parts.rounds.or(match algorithm {
+            #[cfg(feature = "sha2")]
+            Algorithm::Sha256 | Algorithm::Sha512 => Some(5000),
+            _ => None,
+        });
+        let weak_rounds = policy
+            .min_rounds
+            .iter()
+            .find(|&&(alg, _)| alg == algorithm)
+            .zip(rounds)
+            .is_some_and(|(&(_, min), rounds)| rounds < min);
+        let short_salt = parts
+            .salt
+            .is_some_and(|salt| salt.len() < policy.min_salt_len);
+        weak_rounds || short_salt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Policy, Verifier, check_password_len};
+    use crate::{Algorithm, Hash};
+
+    #[test]
+    #[cfg(all(feature = "bcrypt", feature = "md5", feature = "sha2"))]
+    fn needs_rehash() {
+        let policy = Policy::new(Algorithm::Sha512)
+            .min_rounds(Algorithm::Sha512, 10000)
+            .min_salt_len(16);
+        let h = Hash::try_from(
+            "$6$rounds=11531$G/gkPn17kHYo0gTF$Kq.uZBHlSBXyzsOJXtxJruOOH4yc0Is13\
+             uY7yK0PvAvXxbvc1w8DO1RzREMhKsc82K/Jh8OquV8FZUlreYPJk1",
+        )
+        .unwrap();
+        assert!(!h.needs_rehash(&policy));
+        assert!(!h.needs_rehash(&policy.clone().min_rounds(Algorithm::Sha512, 11531)));
+        assert!(h.needs_rehash(&policy.clone().min_rounds(Algorithm::Sha512, 11532)));
+        assert!(h.needs_rehash(&policy.clone().min_salt_len(17)));
+
+        let h = Hash::try_from(
+            "$6$G/gkPn17kHYo0gTF$QB/incPokwe8ZXtcnr/6T5SimYnNqLB3OooPxZixHcEk5/cp\
+             QvJkB/eRQZVPskF8gKpgV/MxD5xB0vvLAvRxB1",
+        )
+        .unwrap();
+        assert!(h.needs_rehash(&policy));
+
+        let h =
+            Hash::try_from("$2y$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe").unwrap();
+        assert!(!h.needs_rehash(&policy));
+        assert!(h.needs_rehash(&policy.clone().require_algorithm(true)));
+
+        let h = Hash::try_from("$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0").unwrap();
+        assert!(h.needs_rehash(&Policy::new(Algorithm::Md5)));
+    }
+
+    #[test]
+    fn password_len() {
+        assert!(check_password_len(&[b'x'; super::DEFAULT_MAX_PASSWORD_LEN]).is_ok());
+        assert!(matches!(
+            check_password_len(&[b'x'; super::DEFAULT_MAX_PASSWORD_LEN + 1]),
+            Err(crate::error::Error::PasswordTooLong)
+        ));
+        assert_eq!(Policy::new(Algorithm::Bcrypt).max_password_len, 4096);
+    }
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn long_password() {
+        let long = "x".repeat(super::DEFAULT_MAX_PASSWORD_LEN + 1);
+        assert!(matches!(
+            crate::crypt::sha512::hash(&long),
+            Err(crate::error::Error::PasswordTooLong)
+        ));
+        let h = crate::crypt::sha512::hash("password").unwrap();
+        assert!(!h.verify(&long));
+        let policy = Policy::new(Algorithm::Sha512).max_password_len(4);
+        assert!(!crate::unix::verify_and_rehash("password", &h, &policy).0);
+    }
+
+    #[test]
+    #[cfg(all(feature = "bcrypt", feature = "md5"))]
+    fn verifier() {
+        let md5 = "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0";
+        let bcrypt = "$2y$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe";
+        let verifier = Verifier::new().allow(Algorithm::Bcrypt);
+        assert!(verifier.verify("password", bcrypt));
+        assert!(!verifier.verify("passwore", bcrypt));
+        assert!(!verifier.verify("password", md5));
+        assert!(matches!(
+            verifier.verify_result("password", md5),
+            Err(crate::error::Error::DisallowedAlgorithm {
+                algorithm: Algorithm::Md5
+            })
+        ));
+        assert!(matches!(
+            verifier.verify_result("password", "$0$x"),
+            Err(crate::error::Error::UnsupportedScheme { .. })
+        ));
+        assert!(!Verifier::new().verify("password", bcrypt));
+        assert!(verifier.allow(Algorithm::Md5).verify("password", md5));
+    }
+}
@@ -0,0 +1,37 @@
+//! Interactive password prompts.
+//!
+//! The functions here read a password from the terminal without echoing
+//! it, so that it appears neither on the screen nor in the shell history
+//! or the argument list of a process. The prompt is written to, and the
+//! password read from, the controlling terminal, even if the standard
+//! streams are redirected.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use crypt3_rs::{Algorithm, prompt};
+//!
+//! let pass = prompt::prompt_new_password("New password: ").unwrap();
+//! println!("{}", Algorithm::Bcrypt.hash(pass).unwrap());
+//! ```
+
+use crate::error::{Error, Result};
+
+/// Read a password from the terminal, without echo.
+pub fn prompt_password(prompt: &str) -> Result<String> {
+    rpassword::prompt_password(prompt).map_err(Error::Io)
+}
+
+/// Read a new password from the terminal, without echo, and ask for it a
+/// second time for confirmation.
+///
+/// If the second password differs from the first one,
+/// [`Error::PasswordMismatch`] is returned.
+pub fn prompt_new_password(prompt: &str) -> Result<String> {
+    let pass = prompt_password(prompt)?;
+    let confirmation = prompt_password("Retype password: ")?;
+    match crate::ct_eq(&pass, &confirmation) {
+        true => Ok(pass),
+        false => Err(Error::PasswordMismatch),
+    }
+}
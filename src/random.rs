@@ -1,13 +1,108 @@
-use rand::{distr::StandardUniform, random, Rng};
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use rand::{Rng, RngCore};
+#[cfg(feature = "deterministic")]
+use rand::{SeedableRng, rngs::StdRng};
 
 use super::encode::bcrypt_hash64_encode;
 
+/// A source of random bytes for salts and rounds variation.
+///
+/// By default, the hashing functions draw their random values from the
+/// thread-local generator of the `rand` crate, which is seeded from the
+/// operating system. Another source can be installed for the duration of a
+/// closure with [`with_salt_source`], e.g. a hardware generator, or a
+/// seeded generator for reproducible tests. Every `rand` generator is a
+/// source.
+pub trait SaltSource {
+    /// Fill the buffer with random bytes.
+    fn fill_bytes(&mut self, bytes: &mut [u8]);
+}
+
+impl<R: RngCore> SaltSource for R {
+    fn fill_bytes(&mut self, bytes: &mut [u8]) {
+        RngCore::fill_bytes(self, bytes);
+    }
+}
+
+thread_local! {
+    static SOURCE: RefCell<Option<Box<dyn SaltSource>>> = const { RefCell::new(None) };
+}
+
+/// Run a closure with a custom source of random values.
+///
+/// All salts and rounds variations generated by the current thread while
+/// the closure runs are drawn from `source`. The previous source is restored
+/// when the closure returns or panics, so calls can be nested.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::{crypt::sha512, with_salt_source};
+///
+/// struct Zeroes;
+///
+/// impl crypt3_rs::SaltSource for Zeroes {
+///     fn fill_bytes(&mut self, bytes: &mut [u8]) {
+///         bytes.fill(0);
+///     }
+/// }
+///
+/// let h = with_salt_source(Zeroes, || sha512::hash("password")).unwrap();
+/// assert!(h.starts_with("$6$................$"));
+/// ```
+pub fn with_salt_source<S, F, T>(source: S, f: F) -> T
+where
+    S: SaltSource + 'static,
+    F: FnOnce() -> T,
+{
+    struct Restore(Option<Box<dyn SaltSource>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            SOURCE.with(|current| *current.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(SOURCE.with(|current| current.borrow_mut().replace(Box::new(source))));
+    f()
+}
+
+/// Make the random values of the current thread reproducible.
+///
+/// A generator seeded with `seed` replaces the current thread's source, so
+/// that the same sequence of hashing calls produces the same salts and
+/// rounds, e.g. for golden hashes in snapshot tests. The sequence may change
+/// between versions of the crate. If called in a [`with_salt_source`]
+/// closure, the seeded generator is replaced when the closure returns.
+///
+/// Salts generated in this mode are predictable; the function is only
+/// available with the `deterministic` feature, which should never be enabled
+/// in production builds.
+#[cfg(feature = "deterministic")]
+pub fn set_salt_seed(seed: u64) {
+    let source: Box<dyn SaltSource> = Box::new(StdRng::seed_from_u64(seed));
+    SOURCE.with(|current| *current.borrow_mut() = Some(source));
+}
+
+/// Restore the default source of random values for the current thread.
+#[cfg(feature = "deterministic")]
+pub fn clear_salt_seed() {
+    SOURCE.with(|current| *current.borrow_mut() = None);
+}
+
+fn fill(bytes: &mut [u8]) {
+    SOURCE.with(|current| match current.borrow_mut().as_mut() {
+        Some(source) => source.fill_bytes(bytes),
+        None => rand::rng().fill(bytes),
+    })
+}
+
 pub fn gen_salt_str(chars: usize) -> String {
-    let bytes = chars.div_ceil(4) * 3;
-    let rv = rand::rng()
-        .sample_iter(&StandardUniform)
-        .take(bytes)
-        .collect::<Vec<u8>>();
+    let mut rv = vec![0u8; chars.div_ceil(4) * 3];
+    fill(&mut rv);
 
     let mut sstr = bcrypt_hash64_encode(&rv);
     while sstr.len() > chars {
@@ -18,10 +113,132 @@ pub fn gen_salt_str(chars: usize) -> String {
 
 #[inline]
 pub fn gen_salt_bytes(bytes: &mut [u8]) {
-    rand::rng().fill(bytes);
+    fill(bytes);
 }
 
-#[inline]
-pub fn vary_rounds(ceil: u32) -> u32 {
-    ceil - (random::<u32>() % (ceil / 4))
+/// Default variation of the default rounds, in percent.
+pub const DEFAULT_ROUNDS_VARIATION: u32 = 25;
+
+static ROUNDS_VARIATION: AtomicU32 = AtomicU32::new(DEFAULT_ROUNDS_VARIATION);
+
+/// Set the variation of the default rounds, in percent, for the whole
+/// process.
+///
+/// Like on NetBSD, the default number of rounds of SHA-1 crypt is lowered by
+/// a random amount for every new hash, so that hashes made with the defaults
+/// don't all share the same parameters. The reduction is below the given
+/// percentage of the default, [`DEFAULT_ROUNDS_VARIATION`] unless changed;
+/// 0 turns the variation off, and values above 100 are taken as 100. Rounds
+/// given explicitly are never varied.
+///
+/// This is only the default: a [`Crypter`](crate::Crypter) can be
+/// configured with its own
+/// [variation](crate::CrypterBuilder::rounds_variation), and
+/// [`vary_rounds_by`] takes one per call, e.g. to compute the rounds passed
+/// to a `hash_with` function.
+pub fn set_rounds_variation(percent: u32) {
+    ROUNDS_VARIATION.store(percent.min(100), Ordering::Relaxed);
+}
+
+/// Get the variation of the default rounds, in percent.
+pub fn rounds_variation() -> u32 {
+    ROUNDS_VARIATION.load(Ordering::Relaxed)
+}
+
+/// Vary a number of rounds as done for the default rounds.
+///
+/// The result is `rounds` lowered by a random amount below the
+/// [rounds variation](set_rounds_variation) percentage of `rounds`, drawn
+/// from the current [`SaltSource`]. With the default variation of 25%,
+/// 24680 rounds become 18511 to 24680. If the variation is off, or the
+/// percentage of `rounds` is below 1, `rounds` is returned unchanged.
+pub fn vary_rounds(rounds: u32) -> u32 {
+    vary_rounds_by(rounds, rounds_variation())
+}
+
+/// Vary a number of rounds by the given percentage, instead of the
+/// process-wide [rounds variation](set_rounds_variation).
+///
+/// The result is like for [`vary_rounds`]; values of `percent` above 100
+/// are taken as 100.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::{HashSetup, crypt::sha1, vary_rounds_by};
+///
+/// let rounds = vary_rounds_by(sha1::DEFAULT_ROUNDS, 10);
+/// assert!(rounds > sha1::DEFAULT_ROUNDS * 9 / 10);
+/// let h = sha1::hash_with(HashSetup::default().rounds(rounds), "password").unwrap();
+/// assert!(h.starts_with(&format!("$sha1${rounds}$")));
+/// ```
+pub fn vary_rounds_by(rounds: u32, percent: u32) -> u32 {
+    let percent = percent.min(100);
+    let span = (u64::from(rounds) * u64::from(percent) / 100) as u32;
+    if span == 0 {
+        return rounds;
+    }
+    let mut buf = [0u8; 4];
+    fill(&mut buf);
+    rounds - (u32::from_ne_bytes(buf) % span)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SaltSource, gen_salt_str, vary_rounds, with_salt_source};
+
+    struct Counter(u8);
+
+    impl SaltSource for Counter {
+        fn fill_bytes(&mut self, bytes: &mut [u8]) {
+            for b in bytes {
+                *b = self.0;
+                self.0 = self.0.wrapping_add(1);
+            }
+        }
+    }
+
+    #[test]
+    fn custom_source() {
+        let (first, second) = with_salt_source(Counter(0), || {
+            let first = gen_salt_str(4);
+            (first, with_salt_source(Counter(0), || gen_salt_str(4)))
+        });
+        assert_eq!(first, "..CA");
+        assert_eq!(first, second);
+        assert_ne!(gen_salt_str(16), gen_salt_str(16));
+    }
+
+    #[test]
+    fn rounds_variation() {
+        use super::{DEFAULT_ROUNDS_VARIATION, rounds_variation, vary_rounds_by};
+
+        assert_eq!(rounds_variation(), DEFAULT_ROUNDS_VARIATION);
+        with_salt_source(Counter(0), || {
+            assert_eq!(
+                vary_rounds(10000),
+                10000 - u32::from_ne_bytes([0, 1, 2, 3]) % 2500
+            );
+            assert_eq!(vary_rounds(3), 3);
+            assert_eq!(vary_rounds_by(10000, 0), 10000);
+            assert_eq!(
+                vary_rounds_by(10000, 100),
+                10000 - u32::from_ne_bytes([4, 5, 6, 7]) % 10000
+            );
+            assert!(vary_rounds_by(10000, 1000) > 0);
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "deterministic")]
+    fn seeded() {
+        use super::{clear_salt_seed, set_salt_seed};
+
+        set_salt_seed(42);
+        let first = (gen_salt_str(16), vary_rounds(10000));
+        set_salt_seed(42);
+        assert_eq!((gen_salt_str(16), vary_rounds(10000)), first);
+        clear_salt_seed();
+        assert_ne!(gen_salt_str(16), first.0);
+    }
 }
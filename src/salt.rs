@@ -0,0 +1,927 @@
+//! Salt generation and validation.
+//!
+//! The hashing functions generate a random salt when none is given. The
+//! functions in this module generate salts separately, e.g. to prepare
+//! settings strings ahead of time: [`gen_salt_str`] and [`gen_salt_bytes`]
+//! give salts of any length, and [`Salt::random_for`] and
+//! [`Salt::random_with_len`] salts of the right length and encoding for an
+//! algorithm. Random values are drawn from the current
+//! [`SaltSource`](crate::SaltSource).
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::{crypt::sha512, salt};
+//!
+//! let setting = format!("$6${}", salt::gen_salt_str(16));
+//! let h = sha512::hash_with(setting.as_str(), "password").unwrap();
+//! assert!(h.starts_with(&setting));
+//! ```
+
+use std::{
+    fmt,
+    ops::{Deref, RangeInclusive},
+};
+
+#[cfg(any(
+    feature = "aix",
+    feature = "apr1",
+    feature = "balloon",
+    feature = "bcrypt",
+    feature = "bcrypt_sha256",
+    feature = "bigcrypt",
+    feature = "bsdi",
+    feature = "cisco",
+    feature = "django",
+    feature = "grub",
+    feature = "ldap",
+    feature = "md5",
+    feature = "mediawiki",
+    feature = "mssql",
+    feature = "oracle11g",
+    feature = "pbkdf2",
+    feature = "scram",
+    feature = "scrypt",
+    feature = "sha1",
+    feature = "sha2",
+    feature = "unix"
+))]
+use crate::crypt;
+#[cfg(feature = "pbkdf2")]
+use crate::encode::{ab64_decode, ab64_encode};
+#[cfg(feature = "scram")]
+use crate::encode::{base64_decode, base64_encode};
+#[cfg(any(feature = "balloon", feature = "scrypt"))]
+use crate::encode::{base64_nopad_decode, base64_nopad_encode};
+#[cfg(any(feature = "bcrypt", feature = "bcrypt_sha256"))]
+use crate::encode::{bcrypt_hash64_decode, bcrypt_hash64_encode};
+#[cfg(any(feature = "grub", feature = "mssql", feature = "oracle11g"))]
+use crate::encode::{hex_decode, hex_encode};
+use crate::{
+    Algorithm, HashSetup,
+    error::{Error, Result},
+    random,
+};
+
+#[cfg(any(
+    feature = "aix",
+    feature = "apr1",
+    feature = "bcrypt",
+    feature = "bcrypt_sha256",
+    feature = "bigcrypt",
+    feature = "bsdi",
+    feature = "md5",
+    feature = "sha1",
+    feature = "sha2",
+    feature = "unix"
+))]
+const HASH64_CHARS: &str = "./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+// Encodings of salts which are random bytes.
+#[cfg(any(
+    feature = "balloon",
+    feature = "bcrypt",
+    feature = "bcrypt_sha256",
+    feature = "grub",
+    feature = "mssql",
+    feature = "oracle11g",
+    feature = "pbkdf2",
+    feature = "scram",
+    feature = "scrypt"
+))]
+#[derive(Clone, Copy)]
+enum Encoding {
+    #[cfg(feature = "pbkdf2")]
+    Ab64,
+    #[cfg(feature = "scram")]
+    Base64,
+    #[cfg(any(feature = "balloon", feature = "scrypt"))]
+    Base64NoPad,
+    #[cfg(any(feature = "bcrypt", feature = "bcrypt_sha256"))]
+    Bcrypt64,
+    #[cfg(any(feature = "grub", feature = "mssql", feature = "oracle11g"))]
+    Hex,
+}
+
+#[cfg(any(
+    feature = "balloon",
+    feature = "bcrypt",
+    feature = "bcrypt_sha256",
+    feature = "grub",
+    feature = "mssql",
+    feature = "oracle11g",
+    feature = "pbkdf2",
+    feature = "scram",
+    feature = "scrypt"
+))]
+impl Encoding {
+    fn encode(self, bytes: &[u8]) -> String {
+        match self {
+            #[cfg(feature = "pbkdf2")]
+            Encoding::Ab64 => ab64_encode(bytes),
+            #[cfg(feature = "scram")]
+            Encoding::Base64 => base64_encode(bytes),
+            #[cfg(any(feature = "balloon", feature = "scrypt"))]
+            Encoding::Base64NoPad => base64_nopad_encode(bytes),
+            #[cfg(any(feature = "bcrypt", feature = "bcrypt_sha256"))]
+            Encoding::Bcrypt64 => bcrypt_hash64_encode(bytes),
+            #[cfg(any(feature = "grub", feature = "mssql", feature = "oracle11g"))]
+            Encoding::Hex => hex_encode(bytes),
+        }
+    }
+
+    // Get the number of decoded bytes.
+    fn decoded_len(self, enc: &str) -> Result<usize> {
+        match self {
+            #[cfg(feature = "pbkdf2")]
+            Encoding::Ab64 => ab64_decode(enc).map(|bytes| bytes.len()),
+            #[cfg(feature = "scram")]
+            Encoding::Base64 => base64_decode(enc).map(|bytes| bytes.len()),
+            #[cfg(any(feature = "balloon", feature = "scrypt"))]
+            Encoding::Base64NoPad => base64_nopad_decode(enc).map(|bytes| bytes.len()),
+            #[cfg(any(feature = "bcrypt", feature = "bcrypt_sha256"))]
+            Encoding::Bcrypt64 => {
+                check_hash64_chars(enc)?;
+                let mut buf = [0u8; 16];
+                bcrypt_hash64_decode(enc, &mut buf)?;
+                Ok(enc.len() * 3 / 4)
+            }
+            #[cfg(any(feature = "grub", feature = "mssql", feature = "oracle11g"))]
+            Encoding::Hex => hex_decode(enc).map(|bytes| bytes.len()),
+        }
+    }
+}
+
+// Salt requirements of an algorithm.
+enum Spec {
+    // The algorithm doesn't use a salt.
+    None,
+    // Characters of the crypt Base64 alphabet; lengths are in characters.
+    #[cfg(any(
+        feature = "aix",
+        feature = "apr1",
+        feature = "bigcrypt",
+        feature = "bsdi",
+        feature = "md5",
+        feature = "sha1",
+        feature = "sha2",
+        feature = "unix"
+    ))]
+    Hash64 {
+        min: usize,
+        max: usize,
+        default: usize,
+    },
+    // Encoded bytes; lengths are in bytes.
+    #[cfg(any(
+        feature = "balloon",
+        feature = "bcrypt",
+        feature = "bcrypt_sha256",
+        feature = "grub",
+        feature = "mssql",
+        feature = "oracle11g",
+        feature = "pbkdf2",
+        feature = "scram",
+        feature = "scrypt"
+    ))]
+    Bytes {
+        encoding: Encoding,
+        min: usize,
+        max: usize,
+        default: usize,
+    },
+    // Arbitrary text, except for a separator; the length is in characters.
+    #[cfg(any(
+        feature = "cisco",
+        feature = "django",
+        feature = "ldap",
+        feature = "mediawiki"
+    ))]
+    Text {
+        forbidden: Option<char>,
+        default: usize,
+    },
+}
+
+fn spec(algorithm: Algorithm) -> Spec {
+    match algorithm {
+        #[cfg(feature = "aix")]
+        Algorithm::Aix => Spec::Hash64 {
+            min: 0,
+            max: crypt::aix::MAX_SALT_LEN,
+            default: crypt::aix::DEFAULT_SALT_LEN,
+        },
+        #[cfg(feature = "apr1")]
+        Algorithm::Apr1 => Spec::Hash64 {
+            min: 0,
+            max: crypt::apr1::MAX_SALT_LEN,
+            default: crypt::apr1::MAX_SALT_LEN,
+        },
+        #[cfg(feature = "balloon")]
+        Algorithm::Balloon => Spec::Bytes {
+            encoding: Encoding::Base64NoPad,
+            min: 0,
+            max: usize::MAX,
+            default: crypt::balloon::DEFAULT_SALT_LEN,
+        },
+        #[cfg(feature = "bcrypt")]
+        Algorithm::Bcrypt => Spec::Bytes {
+            encoding: Encoding::Bcrypt64,
+            min: 16,
+            max: 16,
+            default: 16,
+        },
+        #[cfg(feature = "bcrypt_sha256")]
+        Algorithm::BcryptSha256 => Spec::Bytes {
+            encoding: Encoding::Bcrypt64,
+            min: 16,
+            max: 16,
+            default: 16,
+        },
+        #[cfg(feature = "bigcrypt")]
+        Algorithm::Bigcrypt => Spec::Hash64 {
+            min: crypt::bigcrypt::SALT_LEN,
+            max: crypt::bigcrypt::SALT_LEN,
+            default: crypt::bigcrypt::SALT_LEN,
+        },
+        #[cfg(feature = "bsdi")]
+        Algorithm::Bsdi => Spec::Hash64 {
+            min: crypt::bsdi::SALT_LEN,
+            max: crypt::bsdi::SALT_LEN,
+            default: crypt::bsdi::SALT_LEN,
+        },
+        #[cfg(feature = "cisco")]
+        Algorithm::CiscoType8 => Spec::Text {
+            forbidden: Some('$'),
+            default: crypt::cisco::type8::DEFAULT_SALT_LEN,
+        },
+        #[cfg(feature = "cisco")]
+        Algorithm::CiscoType9 => Spec::Text {
+            forbidden: Some('$'),
+            default: crypt::cisco::type9::DEFAULT_SALT_LEN,
+        },
+        Algorithm::Custom(_) => Spec::None,
+        #[cfg(feature = "django")]
+        Algorithm::Django => Spec::Text {
+            forbidden: Some('$'),
+            default: crypt::django::DEFAULT_SALT_LEN,
+        },
+        #[cfg(feature = "grub")]
+        Algorithm::Grub => Spec::Bytes {
+            encoding: Encoding::Hex,
+            min: 0,
+            max: usize::MAX,
+            default: crypt::grub::DEFAULT_SALT_LEN,
+        },
+        #[cfg(feature = "ldap")]
+        Algorithm::Ldap => Spec::Text {
+            forbidden: None,
+            default: crypt::ldap::DEFAULT_SALT_LEN,
+        },
+        #[cfg(feature = "md5")]
+        Algorithm::Md5 => Spec::Hash64 {
+            min: 0,
+            max: crypt::md5::MAX_SALT_LEN,
+            default: crypt::md5::MAX_SALT_LEN,
+        },
+        #[cfg(feature = "mediawiki")]
+        Algorithm::Mediawiki => Spec::Text {
+            forbidden: Some(':'),
+            default: 2 * crypt::mediawiki::DEFAULT_SALT_LEN,
+        },
+        #[cfg(feature = "mssql")]
+        Algorithm::Mssql => Spec::Bytes {
+            encoding: Encoding::Hex,
+            min: crypt::mssql::SALT_LEN,
+            max: crypt::mssql::SALT_LEN,
+            default: crypt::mssql::SALT_LEN,
+        },
+        #[cfg(feature = "mysql323")]
+        Algorithm::Mysql323 => Spec::None,
+        #[cfg(feature = "mysql41")]
+        Algorithm::Mysql41 => Spec::None,
+        #[cfg(feature = "oracle11g")]
+        Algorithm::Oracle11g => Spec::Bytes {
+            encoding: Encoding::Hex,
+            min: crypt::oracle11g::SALT_LEN,
+            max: crypt::oracle11g::SALT_LEN,
+            default: crypt::oracle11g::SALT_LEN,
+        },
+        #[cfg(feature = "pbkdf2")]
+        Algorithm::Pbkdf2 => Spec::Bytes {
+            encoding: Encoding::Ab64,
+            min: 0,
+            max: crypt::pbkdf2::MAX_SALT_LEN,
+            default: crypt::pbkdf2::DEFAULT_SALT_LEN,
+        },
+        #[cfg(feature = "pepper")]
+        Algorithm::Peppered => Spec::None,
+        #[cfg(feature = "scram")]
+        Algorithm::Scram => Spec::Bytes {
+            encoding: Encoding::Base64,
+            min: 0,
+            max: usize::MAX,
+            default: crypt::scram::DEFAULT_SALT_LEN,
+        },
+        #[cfg(feature = "scrypt")]
+        Algorithm::Scrypt => Spec::Bytes {
+            encoding: Encoding::Base64NoPad,
+            min: 0,
+            max: usize::MAX,
+            default: crypt::scrypt::DEFAULT_SALT_LEN,
+        },
+        #[cfg(feature = "sha1")]
+        Algorithm::Sha1 => Spec::Hash64 {
+            min: 0,
+            max: crypt::sha1::MAX_SALT_LEN,
+            default: crypt::sha1::DEFAULT_SALT_LEN,
+        },
+        #[cfg(feature = "sha2")]
+        Algorithm::Sha256 => Spec::Hash64 {
+            min: 0,
+            max: crypt::sha256::MAX_SALT_LEN,
+            default: crypt::sha256::MAX_SALT_LEN,
+        },
+        #[cfg(feature = "sha2")]
+        Algorithm::Sha512 => Spec::Hash64 {
+            min: 0,
+            max: crypt::sha512::MAX_SALT_LEN,
+            default: crypt::sha512::MAX_SALT_LEN,
+        },
+        #[cfg(feature = "unix")]
+        Algorithm::Unix => Spec::Hash64 {
+            min: crypt::unix::SALT_LEN,
+            max: crypt::unix::SALT_LEN,
+            default: crypt::unix::SALT_LEN,
+        },
+        #[cfg(feature = "wrapped")]
+        Algorithm::Wrapped => Spec::None,
+    }
+}
+
+// Range of the number of rounds or cost accepted by an algorithm, or `None`
+// if the algorithm doesn't take them.
+#[allow(unreachable_patterns)]
+pub(crate) fn rounds_range(algorithm: Algorithm) -> Option<RangeInclusive<u32>> {
+    match algorithm {
+        #[cfg(feature = "aix")]
+        Algorithm::Aix => Some(crypt::aix::MIN_COST..=crypt::aix::MAX_COST),
+        #[cfg(feature = "balloon")]
+        Algorithm::Balloon => Some(1..=u32::MAX),
+        #[cfg(feature = "bcrypt")]
+        Algorithm::Bcrypt => Some(crypt::bcrypt::MIN_COST..=crypt::bcrypt::MAX_COST),
+        #[cfg(feature = "bcrypt_sha256")]
+        Algorithm::BcryptSha256 => Some(crypt::bcrypt::MIN_COST..=crypt::bcrypt::MAX_COST),
+        #[cfg(feature = "bsdi")]
+        Algorithm::Bsdi => Some(crypt::bsdi::MIN_ROUNDS..=crypt::bsdi::MAX_ROUNDS),
+        #[cfg(feature = "django")]
+        Algorithm::Django => Some(1..=u32::MAX),
+        #[cfg(feature = "grub")]
+        Algorithm::Grub => Some(1..=u32::MAX),
+        #[cfg(feature = "pbkdf2")]
+        Algorithm::Pbkdf2 => Some(1..=u32::MAX),
+        #[cfg(feature = "scram")]
+        Algorithm::Scram => Some(1..=u32::MAX),
+        // the memory size must fit in 64 bits with the default block size
+        #[cfg(feature = "scrypt")]
+        Algorithm::Scrypt => Some(0..=53),
+        #[cfg(feature = "sha1")]
+        Algorithm::Sha1 => Some(crypt::sha1::MIN_ROUNDS..=u32::MAX),
+        #[cfg(feature = "sha2")]
+        Algorithm::Sha256 | Algorithm::Sha512 => {
+            Some(crypt::sha512::MIN_ROUNDS..=crypt::sha512::MAX_ROUNDS)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(any(
+    feature = "aix",
+    feature = "apr1",
+    feature = "bcrypt",
+    feature = "bcrypt_sha256",
+    feature = "bigcrypt",
+    feature = "bsdi",
+    feature = "md5",
+    feature = "sha1",
+    feature = "sha2",
+    feature = "unix"
+))]
+fn check_hash64_chars(salt: &str) -> Result<()> {
+    match salt.find(|c| !HASH64_CHARS.contains(c)) {
+        Some(pos) => Err(Error::BadSaltChar { pos }),
+        None => Ok(()),
+    }
+}
+
+// Range and default of the salt length of an algorithm, as reported by
+// `Algorithm::params`.
+pub(crate) fn salt_len(algorithm: Algorithm) -> Option<(RangeInclusive<usize>, usize)> {
+    match spec(algorithm) {
+        Spec::None => None,
+        #[cfg(any(
+            feature = "aix",
+            feature = "apr1",
+            feature = "bigcrypt",
+            feature = "bsdi",
+            feature = "md5",
+            feature = "sha1",
+            feature = "sha2",
+            feature = "unix"
+        ))]
+        Spec::Hash64 { min, max, default } => Some((min..=max, default)),
+        #[cfg(any(
+            feature = "balloon",
+            feature = "bcrypt",
+            feature = "bcrypt_sha256",
+            feature = "grub",
+            feature = "mssql",
+            feature = "oracle11g",
+            feature = "pbkdf2",
+            feature = "scram",
+            feature = "scrypt"
+        ))]
+        Spec::Bytes {
+            min, max, default, ..
+        } => Some((min..=max, default)),
+        #[cfg(any(
+            feature = "cisco",
+            feature = "django",
+            feature = "ldap",
+            feature = "mediawiki"
+        ))]
+        Spec::Text { default, .. } => Some((0..=usize::MAX, default)),
+    }
+}
+
+#[cfg(any(
+    feature = "aix",
+    feature = "apr1",
+    feature = "balloon",
+    feature = "bcrypt",
+    feature = "bcrypt_sha256",
+    feature = "bigcrypt",
+    feature = "bsdi",
+    feature = "grub",
+    feature = "md5",
+    feature = "mssql",
+    feature = "oracle11g",
+    feature = "pbkdf2",
+    feature = "scram",
+    feature = "scrypt",
+    feature = "sha1",
+    feature = "sha2",
+    feature = "unix"
+))]
+fn check_len(len: usize, min: usize, max: usize) -> Result<()> {
+    match (min..=max).contains(&len) {
+        true => Ok(()),
+        false => Err(Error::BadSaltLen {
+            len,
+            allowed: min..=max,
+        }),
+    }
+}
+
+// Error for an algorithm which doesn't use a salt.
+fn no_salt(algorithm: Algorithm) -> Error {
+    Error::UnsupportedScheme {
+        prefix: algorithm.name().to_string(),
+    }
+}
+
+// Whether the algorithm accepts any bytes in a raw salt, like the historical
+// DES-based implementations.
+#[cfg(any(
+    feature = "aix",
+    feature = "apr1",
+    feature = "bigcrypt",
+    feature = "bsdi",
+    feature = "md5",
+    feature = "sha1",
+    feature = "sha2",
+    feature = "unix"
+))]
+#[allow(unreachable_patterns)]
+fn takes_legacy_bytes(algorithm: Algorithm) -> bool {
+    match algorithm {
+        #[cfg(feature = "bigcrypt")]
+        Algorithm::Bigcrypt => true,
+        #[cfg(feature = "bsdi")]
+        Algorithm::Bsdi => true,
+        #[cfg(feature = "unix")]
+        Algorithm::Unix => true,
+        _ => false,
+    }
+}
+
+// Check a salt given as raw bytes.
+fn check_salt_bytes(salt: &[u8], algorithm: Algorithm) -> Result<()> {
+    match spec(algorithm) {
+        #[cfg(any(
+            feature = "aix",
+            feature = "apr1",
+            feature = "bigcrypt",
+            feature = "bsdi",
+            feature = "md5",
+            feature = "sha1",
+            feature = "sha2",
+            feature = "unix"
+        ))]
+        Spec::Hash64 { min, max, .. } if takes_legacy_bytes(algorithm) => {
+            check_len(salt.len(), min, max)
+        }
+        #[cfg(any(
+            feature = "cisco",
+            feature = "django",
+            feature = "ldap",
+            feature = "mediawiki"
+        ))]
+        Spec::Text {
+            forbidden: None, ..
+        } => Ok(()),
+        _ => {
+            let salt = std::str::from_utf8(salt).map_err(Error::InvalidUtf8)?;
+            Salt::from_str_checked(salt, algorithm).map(|_salt| ())
+        }
+    }
+}
+
+/// Generate a random salt of `len` characters.
+///
+/// The characters are drawn from the alphabet __`./A-Za-z0-9`__ shared by
+/// the __crypt__(3) algorithms.
+pub fn gen_salt_str(len: usize) -> String {
+    random::gen_salt_str(len)
+}
+
+/// Fill a buffer with random salt bytes.
+pub fn gen_salt_bytes(bytes: &mut [u8]) {
+    random::gen_salt_bytes(bytes);
+}
+
+/// A salt which is valid for a particular algorithm.
+///
+/// Most algorithms accept a salt in a [`HashSetup`] or
+/// their own setup struct, and check it only when hashing, sometimes
+/// truncating it silently. A `Salt` is checked when it's created, so that
+/// an invalid salt can be rejected up front, e.g. when it's read from a
+/// configuration file. It dereferences to a `&str`, and can be passed to
+/// any setup struct's `salt` method.
+///
+/// The salt is in the form used in the algorithm's setup struct: for the
+/// algorithms whose salt is a byte string, that's the encoding described in
+/// the algorithm's module documentation.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::{Algorithm, HashSetup, Salt};
+///
+/// let salt = Salt::from_str_checked("saltsalt", Algorithm::Sha512).unwrap();
+/// let h = Algorithm::Sha512
+///     .hash_with(HashSetup::default().salt(&salt), "password")
+///     .unwrap();
+/// assert!(h.starts_with("$6$saltsalt$"));
+/// assert!(Salt::from_str_checked("salt_salt", Algorithm::Sha512).is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Salt(String);
+
+impl Salt {
+    /// Generate a random salt with the algorithm's default length.
+    ///
+    /// [`Error::UnsupportedScheme`], with the name of the algorithm, is
+    /// returned if the algorithm doesn't use a salt.
+    pub fn random_for(algorithm: Algorithm) -> Result<Salt> {
+        match salt_len(algorithm) {
+            Some((_, default)) => Salt::random_with_len(algorithm, default),
+            None => Err(no_salt(algorithm)),
+        }
+    }
+
+    /// Generate a random salt of the given length for the algorithm.
+    ///
+    /// For the algorithms whose salt is a byte string, the length is the
+    /// number of bytes, which are then encoded as in the algorithm's setup
+    /// struct; for the others, it's the number of characters.
+    /// [`Error::UnsupportedScheme`] is returned if the algorithm doesn't use
+    /// a salt, and [`Error::BadSaltLen`] if the length is out of range for
+    /// the algorithm.
+    pub fn random_with_len(algorithm: Algorithm, len: usize) -> Result<Salt> {
+        let (allowed, _) = salt_len(algorithm).ok_or_else(|| no_salt(algorithm))?;
+        if !allowed.contains(&len) {
+            return Err(Error::BadSaltLen { len, allowed });
+        }
+        match spec(algorithm) {
+            #[cfg(any(
+                feature = "balloon",
+                feature = "bcrypt",
+                feature = "bcrypt_sha256",
+                feature = "grub",
+                feature = "mssql",
+                feature = "oracle11g",
+                feature = "pbkdf2",
+                feature = "scram",
+                feature = "scrypt"
+            ))]
+            Spec::Bytes { encoding, .. } => {
+                let mut salt_buf = vec![0u8; len];
+                random::gen_salt_bytes(&mut salt_buf);
+                Ok(Salt(encoding.encode(&salt_buf)))
+            }
+            _ => Ok(Salt(random::gen_salt_str(len))),
+        }
+    }
+
+    /// Create a salt from a string, checking it against the algorithm's
+    /// requirements.
+    ///
+    /// [`Error::UnsupportedScheme`] is returned if the algorithm doesn't use
+    /// a salt, [`Error::BadSaltChar`] if the salt contains a character which
+    /// isn't valid for the algorithm, and [`Error::BadSaltLen`] if its
+    /// length, or the length of the bytes it encodes, is out of range.
+    pub fn from_str_checked(salt: &str, algorithm: Algorithm) -> Result<Salt> {
+        match spec(algorithm) {
+            Spec::None => Err(no_salt(algorithm)),
+            #[cfg(any(
+                feature = "aix",
+                feature = "apr1",
+                feature = "bigcrypt",
+                feature = "bsdi",
+                feature = "md5",
+                feature = "sha1",
+                feature = "sha2",
+                feature = "unix"
+            ))]
+            Spec::Hash64 { min, max, .. } => {
+                check_hash64_chars(salt)?;
+                check_len(salt.len(), min, max)
+            }
+            #[cfg(any(
+                feature = "balloon",
+                feature = "bcrypt",
+                feature = "bcrypt_sha256",
+                feature = "grub",
+                feature = "mssql",
+                feature = "oracle11g",
+                feature = "pbkdf2",
+                feature = "scram",
+                feature = "scrypt"
+            ))]
+            Spec::Bytes {
+                encoding, min, max, ..
+            } => check_len(encoding.decoded_len(salt)?, min, max),
+            #[cfg(any(
+                feature = "cisco",
+                feature = "django",
+                feature = "ldap",
+                feature = "mediawiki"
+            ))]
+            Spec::Text { forbidden, .. } => match forbidden.and_then(|c| salt.find(c)) {
+                Some(pos) => Err(Error::BadSaltChar { pos }),
+                None => Ok(()),
+            },
+        }?;
+        Ok(Salt(salt.to_string()))
+    }
+
+    /// Get the salt as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'a> HashSetup<'a> {
+    /// Get the salt as a string, converting the raw bytes if necessary.
+    #[cfg(any(
+        feature = "aix",
+        feature = "apr1",
+        feature = "balloon",
+        feature = "bcrypt",
+        feature = "bcrypt_sha256",
+        feature = "cisco",
+        feature = "django",
+        feature = "grub",
+        feature = "md5",
+        feature = "mediawiki",
+        feature = "mssql",
+        feature = "oracle11g",
+        feature = "pbkdf2",
+        feature = "scram",
+        feature = "scrypt",
+        feature = "sha1",
+        feature = "sha2"
+    ))]
+    pub(crate) fn salt_str(&self) -> Result<Option<&'a str>> {
+        match (self.salt, self.salt_bytes) {
+            (None, Some(salt)) => std::str::from_utf8(salt)
+                .map(Some)
+                .map_err(Error::InvalidUtf8),
+            (salt, _) => Ok(salt),
+        }
+    }
+
+    /// Get the salt as raw bytes.
+    #[cfg(feature = "ldap")]
+    pub(crate) fn salt_raw(&self) -> Option<&'a [u8]> {
+        self.salt.map(str::as_bytes).or(self.salt_bytes)
+    }
+
+    /// Check the parameters against an algorithm's requirements, without
+    /// hashing.
+    ///
+    /// The salt is checked like with [`Salt::from_str_checked`]; a salt given
+    /// as raw bytes is checked as described for [`HashSetup`]. If the number
+    /// of rounds is set, [`Error::BadRounds`] is returned if it's out of the
+    /// algorithm's range, including the values which the algorithm would
+    /// silently clamp, and [`Error::InvalidRounds`] if the algorithm doesn't
+    /// take a number of rounds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crypt3_rs::{Algorithm, HashSetup};
+    ///
+    /// let setup = HashSetup::default().salt("saltsalt").rounds(500);
+    /// assert!(setup.validate_for(Algorithm::Bcrypt).is_err());
+    /// assert!(setup.validate_for(Algorithm::Sha512).is_err());
+    /// let setup = HashSetup::default().salt("saltsalt").rounds(5000);
+    /// assert!(setup.validate_for(Algorithm::Sha512).is_ok());
+    /// ```
+    pub fn validate_for(&self, algorithm: Algorithm) -> Result<()> {
+        match (self.salt, self.salt_bytes) {
+            (Some(salt), _) => {
+                Salt::from_str_checked(salt, algorithm)?;
+            }
+            (None, Some(salt)) => check_salt_bytes(salt, algorithm)?,
+            (None, None) => (),
+        }
+        match (self.rounds, rounds_range(algorithm)) {
+            (Some(rounds), Some(range)) if !range.contains(&rounds) => Err(Error::BadRounds {
+                value: rounds,
+                allowed: range,
+            }),
+            (Some(_), None) => Err(Error::InvalidRounds),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Deref for Salt {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Salt {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Salt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Salt;
+    use crate::{Algorithm, error::Error};
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn hash64() {
+        let salt = Salt::random_for(Algorithm::Sha512).unwrap();
+        assert_eq!(salt.len(), 16);
+        assert_eq!(
+            Salt::from_str_checked(&salt, Algorithm::Sha512).unwrap(),
+            salt
+        );
+        assert!(Salt::from_str_checked("", Algorithm::Sha256).is_ok());
+        assert!(Salt::from_str_checked("saltsaltsaltsalts", Algorithm::Sha256).is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "bcrypt", feature = "sha2"))]
+    fn random_with_len() {
+        let salt = Salt::random_with_len(Algorithm::Sha512, 8).unwrap();
+        assert_eq!(salt.len(), 8);
+        assert!(matches!(
+            Salt::random_with_len(Algorithm::Sha512, 17),
+            Err(Error::BadSaltLen { len: 17, allowed }) if allowed == (0..=16)
+        ));
+        #[cfg(feature = "mysql41")]
+        assert!(matches!(
+            Salt::random_for(Algorithm::Mysql41),
+            Err(Error::UnsupportedScheme { prefix }) if prefix == "mysql41"
+        ));
+        assert_eq!(
+            Salt::random_with_len(Algorithm::Bcrypt, 16).unwrap().len(),
+            22
+        );
+        assert!(Salt::random_with_len(Algorithm::Bcrypt, 15).is_err());
+
+        let salt = super::gen_salt_str(5);
+        assert_eq!(salt.len(), 5);
+        assert!(super::check_hash64_chars(&salt).is_ok());
+        let mut buf = [0u8; 64];
+        super::gen_salt_bytes(&mut buf);
+        assert_ne!(buf, [0u8; 64]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "bcrypt", feature = "mssql"))]
+    fn bytes() {
+        let salt = Salt::random_for(Algorithm::Bcrypt).unwrap();
+        assert_eq!(salt.len(), 22);
+        assert!(Salt::from_str_checked(&salt, Algorithm::Bcrypt).is_ok());
+        assert!(Salt::from_str_checked("bvIG6Nmid91Mu9RcmmWZf", Algorithm::Bcrypt).is_err());
+        assert_eq!(Salt::random_for(Algorithm::Mssql).unwrap().len(), 8);
+        assert!(Salt::from_str_checked("0A0B0C0D", Algorithm::Mssql).is_ok());
+        assert!(Salt::from_str_checked("0A0B0C", Algorithm::Mssql).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "unix")]
+    #[should_panic(expected = "value: BadSaltChar { pos: 1 }")]
+    fn bad_chars() {
+        let _ = Salt::from_str_checked("a$", Algorithm::Unix).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "django")]
+    #[should_panic(expected = "value: BadSaltChar { pos: 4 }")]
+    fn forbidden_char() {
+        let _ = Salt::from_str_checked("salt$", Algorithm::Django).unwrap();
+    }
+
+    #[test]
+    #[cfg(all(feature = "ldap", feature = "sha2", feature = "unix"))]
+    fn salt_bytes() {
+        use crate::{HashSetup, crypt::sha512};
+
+        let setup = || HashSetup::default().salt_bytes(b"saltsalt");
+        assert_eq!(
+            sha512::hash_with(setup(), "password").unwrap(),
+            sha512::hash_with(Some("saltsalt"), "password").unwrap()
+        );
+        let setup = || HashSetup::default().salt_bytes(b"salt\xff");
+        assert!(matches!(
+            sha512::hash_with(setup(), "password"),
+            Err(Error::InvalidUtf8(_))
+        ));
+        assert!(matches!(
+            setup().validate_for(Algorithm::Sha512),
+            Err(Error::InvalidUtf8(_))
+        ));
+        assert!(setup().validate_for(Algorithm::Ldap).is_ok());
+        assert!(Algorithm::Ldap.hash_with(setup(), "password").is_ok());
+
+        let setup = HashSetup::default().salt_bytes(b"\xff!");
+        assert!(setup.validate_for(Algorithm::Unix).is_ok());
+        assert!(matches!(
+            HashSetup::default()
+                .salt_bytes(b"\xff")
+                .validate_for(Algorithm::Unix),
+            Err(Error::BadSaltLen { len: 1, .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(all(feature = "bcrypt", feature = "md5", feature = "sha2"))]
+    fn validate_for() {
+        use crate::HashSetup;
+
+        let setup = HashSetup::default()
+            .salt("bvIG6Nmid91Mu9RcmmWZfO")
+            .rounds(5);
+        assert!(setup.validate_for(Algorithm::Bcrypt).is_ok());
+        assert!(matches!(
+            HashSetup::default()
+                .rounds(32)
+                .validate_for(Algorithm::Bcrypt),
+            Err(Error::BadRounds { value: 32, .. })
+        ));
+        assert!(matches!(
+            setup.validate_for(Algorithm::Sha512),
+            Err(Error::BadSaltLen { len: 22, .. })
+        ));
+        assert!(
+            HashSetup::default()
+                .salt("saltsalt")
+                .validate_for(Algorithm::Md5)
+                .is_ok()
+        );
+        assert!(
+            HashSetup::default()
+                .rounds(1000)
+                .validate_for(Algorithm::Md5)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::{ct::ct_eq, error::Result};
+
+/// A hash format implemented by the application.
+///
+/// A scheme registered with [`register_scheme`] is recognized by
+/// [`Hash::try_from`](crate::Hash::try_from) for strings which don't belong
+/// to any built-in algorithm, so that [`unix::verify`](crate::unix::verify)
+/// and the other generic entry points can handle in-house formats. Hashes
+/// of the scheme report [`Algorithm::Custom`](crate::Algorithm::Custom) with
+/// its name.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::{Algorithm, Hash, Scheme, error::Result, register_scheme, unix};
+///
+/// // Not a password hash; only for illustration.
+/// struct HexScheme;
+///
+/// impl Scheme for HexScheme {
+///     fn name(&self) -> &'static str {
+///         "hex"
+///     }
+///
+///     fn identify(&self, hash: &str) -> bool {
+///         hash.starts_with("$hex$")
+///     }
+///
+///     fn hash_with(&self, _setting: &str, pass: &[u8]) -> Result<String> {
+///         let hex: String = pass.iter().map(|b| format!("{b:02x}")).collect();
+///         Ok(format!("$hex${hex}"))
+///     }
+/// }
+///
+/// register_scheme(HexScheme);
+/// assert!(unix::verify("password", "$hex$70617373776f7264"));
+/// let h = Hash::try_from("$hex$70617373776f7264").unwrap();
+/// assert_eq!(h.algorithm(), Algorithm::Custom("hex"));
+/// ```
+pub trait Scheme: Send + Sync {
+    /// Get the name of the scheme.
+    fn name(&self) -> &'static str;
+
+    /// Check whether a string is a hash or a setting of the scheme.
+    fn identify(&self, hash: &str) -> bool;
+
+    /// Hash a password with the parameters of a setting or an existing hash.
+    ///
+    /// An empty setting asks for a new hash with the default parameters,
+    /// as done by [`Algorithm::hash`](crate::Algorithm::hash). The result
+    /// must be identified as a hash of the scheme.
+    fn hash_with(&self, setting: &str, pass: &[u8]) -> Result<String>;
+
+    /// Verify that the hash corresponds to a password.
+    ///
+    /// The default implementation hashes the password with the parameters of
+    /// the hash, and compares the result with the hash in constant time.
+    fn verify(&self, pass: &[u8], hash: &str) -> bool {
+        self.hash_with(hash, pass)
+            .is_ok_and(|calc| ct_eq(calc, hash))
+    }
+}
+
+static SCHEMES: RwLock<Vec<Arc<dyn Scheme>>> = RwLock::new(Vec::new());
+
+/// Register a custom scheme for the whole process.
+///
+/// Registered schemes are consulted in order of registration, after the
+/// built-in algorithms. A scheme replaces the one registered under the same
+/// name.
+pub fn register_scheme<S: Scheme + 'static>(scheme: S) {
+    let mut schemes = SCHEMES.write().unwrap_or_else(PoisonError::into_inner);
+    schemes.retain(|registered| registered.name() != scheme.name());
+    schemes.push(Arc::new(scheme));
+}
+
+/// Remove a custom scheme, returning whether it was registered.
+///
+/// Existing [`Hash`](crate::Hash) values of the scheme remain usable.
+pub fn unregister_scheme(name: &str) -> bool {
+    let mut schemes = SCHEMES.write().unwrap_or_else(PoisonError::into_inner);
+    let len = schemes.len();
+    schemes.retain(|registered| registered.name() != name);
+    schemes.len() != len
+}
+
+/// Find the registered scheme which identifies a hash.
+pub(crate) fn identify(hash: &str) -> Option<Arc<dyn Scheme>> {
+    let schemes = SCHEMES.read().unwrap_or_else(PoisonError::into_inner);
+    schemes.iter().find(|scheme| scheme.identify(hash)).cloned()
+}
+
+/// Find a registered scheme by name.
+pub(crate) fn by_name(name: &str) -> Option<Arc<dyn Scheme>> {
+    let schemes = SCHEMES.read().unwrap_or_else(PoisonError::into_inner);
+    schemes.iter().find(|scheme| scheme.name() == name).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Scheme, register_scheme, unregister_scheme};
+    use crate::{
+        Algorithm, Hash, HashSetup,
+        error::{Error, Result},
+        unix,
+    };
+
+    // Reversed password, with the length of the password as a parameter.
+    struct Reversed;
+
+    impl Scheme for Reversed {
+        fn name(&self) -> &'static str {
+            "test_reversed"
+        }
+
+        fn identify(&self, hash: &str) -> bool {
+            hash.starts_with("$test-rev$")
+        }
+
+        fn hash_with(&self, _setting: &str, pass: &[u8]) -> Result<String> {
+            let mut rev = pass.to_vec();
+            rev.reverse();
+            Ok(format!(
+                "$test-rev${}${}",
+                pass.len(),
+                String::from_utf8_lossy(&rev)
+            ))
+        }
+    }
+
+    #[test]
+    fn custom() {
+        register_scheme(Reversed);
+        let h = Hash::try_from("$test-rev$8$drowssap").unwrap();
+        assert_eq!(h.algorithm(), Algorithm::Custom("test_reversed"));
+        assert_eq!(h.algorithm().name(), "test_reversed");
+        assert!(h.verify("password"));
+        assert!(!h.verify("passwort"));
+        assert!(unix::verify("password", "$test-rev$8$drowssap"));
+        assert_eq!(
+            "test_reversed"
+                .parse::<Algorithm>()
+                .unwrap()
+                .hash("abc")
+                .unwrap(),
+            "$test-rev$3$cba"
+        );
+        let alg = Algorithm::Custom("test_reversed");
+        assert_eq!(
+            alg.hash_with(HashSetup::default().salt("$test-rev$"), "abc")
+                .unwrap(),
+            "$test-rev$3$cba"
+        );
+        assert!(matches!(
+            alg.hash_with(HashSetup::default().rounds(10), "abc"),
+            Err(Error::InvalidRounds)
+        ));
+
+        assert!(unregister_scheme("test_reversed"));
+        assert!(!unregister_scheme("test_reversed"));
+        assert!(h.verify("password"));
+        assert!(matches!(
+            Hash::try_from("$test-rev$8$drowssap"),
+            Err(Error::UnsupportedScheme { .. })
+        ));
+        assert!(matches!(
+            alg.hash_with(HashSetup::default(), "abc"),
+            Err(Error::UnsupportedScheme { prefix }) if prefix == "test_reversed"
+        ));
+    }
+}
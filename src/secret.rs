@@ -0,0 +1,75 @@
+use secrecy::{ExposeSecret, SecretSlice, SecretString};
+
+/// A borrowed password kept in a [`secrecy`] container.
+///
+/// The hashing functions accept any password which implements
+/// `AsRef<[u8]>`. The `secrecy` containers deliberately don't, and the
+/// coherence rules don't allow this crate to implement it for them, so a
+/// reference to the container is wrapped in this type instead. The secret
+/// isn't copied: the hashing function reads it in place, and the wrapper
+/// can't be used to obtain it in any other way.
+///
+/// [`Zeroizing`](secrecy::zeroize::Zeroizing) containers of strings and
+/// byte vectors need no wrapping, since they implement `AsRef<[u8]>`
+/// themselves.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::{Secret, unix};
+/// use secrecy::SecretString;
+///
+/// let pass = SecretString::from("password");
+/// let h = "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0";
+/// assert!(unix::verify(Secret::from(&pass), h));
+/// ```
+pub struct Secret<'a>(&'a [u8]);
+
+impl<'a> From<&'a SecretString> for Secret<'a> {
+    #[inline]
+    fn from(secret: &'a SecretString) -> Self {
+        Secret(secret.expose_secret().as_bytes())
+    }
+}
+
+impl<'a> From<&'a SecretSlice<u8>> for Secret<'a> {
+    #[inline]
+    fn from(secret: &'a SecretSlice<u8>) -> Self {
+        Secret(secret.expose_secret())
+    }
+}
+
+impl AsRef<[u8]> for Secret<'_> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.0
+    }
+}
+
+impl std::fmt::Debug for Secret<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::{SecretSlice, SecretString, zeroize::Zeroizing};
+
+    use super::Secret;
+
+    #[test]
+    #[cfg(feature = "md5")]
+    fn secret() {
+        let h = "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0";
+        let pass = SecretString::from("password");
+        assert!(crate::unix::verify(Secret::from(&pass), h));
+        let pass = SecretSlice::from(b"password".to_vec());
+        assert!(crate::crypt::md5::verify(Secret::from(&pass), h));
+        assert_eq!(format!("{:?}", Secret::from(&pass)), "Secret([REDACTED])");
+        let pass = Zeroizing::new(String::from("password"));
+        assert!(crate::unix::verify(&pass, h));
+        let pass = Zeroizing::new(b"passwore".to_vec());
+        assert!(!crate::unix::verify(&pass, h));
+    }
+}
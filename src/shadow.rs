@@ -0,0 +1,209 @@
+//! Shadow password file entries.
+//!
+//! A line of a __shadow__(5) file has nine fields separated by colons: the
+//! login name, the password field, the date of the last password change,
+//! the minimum and maximum password ages, the warning and inactivity
+//! periods, the expiration date of the account, and a reserved field. Dates
+//! are days since 1970-01-01, and periods are days; empty fields mean that
+//! the feature is disabled.
+//!
+//! [`ShadowEntry`] parses such a line with [`FromStr`], and writes it back
+//! with its [`Display`](fmt::Display) implementation.
+//!
+//! # Example
+//!
+//! ```
+//! use crypt3_rs::shadow::ShadowEntry;
+//!
+//! let line = "alice:$2b$10$saltsaltsaltsaltsaltsOF8gOe7Q2djsDdlP9U528dfcxNNK.BXC:19000:0:99999:7:::";
+//! let entry: ShadowEntry = line.parse().unwrap();
+//! assert_eq!(entry.name, "alice");
+//! assert_eq!(entry.max, Some(99999));
+//! assert_eq!(entry.inactive, None);
+//! assert_eq!(entry.to_string(), line);
+//! ```
+
+use std::{fmt, str::FromStr};
+
+use crate::{
+    error::{Error, Result},
+    unix::{self, HashState},
+};
+
+const FIELDS: usize = 9;
+
+/// An entry of a shadow password file.
+///
+/// The fields must not contain colons or line breaks, which no parsed entry
+/// does, for the entry to be written back as a valid line.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShadowEntry {
+    /// Login name.
+    pub name: String,
+    /// Password field: a hash, or a marker such as `!` or `*`; see
+    /// [`unix::classify`].
+    pub hash: String,
+    /// Date of the last password change. Zero means that the password must
+    /// be changed at the next login.
+    pub last_change: Option<i64>,
+    /// Minimum password age.
+    pub min: Option<i64>,
+    /// Maximum password age.
+    pub max: Option<i64>,
+    /// Password warning period.
+    pub warn: Option<i64>,
+    /// Password inactivity period.
+    pub inactive: Option<i64>,
+    /// Account expiration date.
+    pub expire: Option<i64>,
+    /// Reserved field, kept as it is.
+    pub reserved: String,
+}
+
+impl ShadowEntry {
+    /// Verify a password against the password field of the entry.
+    ///
+    /// The password is verified with [`unix::verify`], so that locked,
+    /// disabled, and passwordless entries, whose fields aren't hashes, never
+    /// verify.
+    pub fn verify<B: AsRef<[u8]>>(&self, pass: B) -> bool {
+        unix::verify(pass, &self.hash)
+    }
+
+    /// Classify the password field of the entry with [`unix::classify`].
+    pub fn state(&self) -> Result<HashState> {
+        unix::classify(&self.hash)
+    }
+}
+
+// Parse an optional numeric field.
+fn parse_num<T: FromStr<Err = std::num::ParseIntError>>(
+    field: &str,
+) -> Result<Option<T>> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+    field.parse().map(Some).map_err(Error::InvalidNumber)
+}
+
+// Split a line into exactly `N` colon-separated fields.
+pub(crate) fn split_fields<const N: usize>(line: &str) -> Result<[&str; N]> {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    let fields: Vec<&str> = line.split(':').collect();
+    fields
+        .try_into()
+        .map_err(|fields: Vec<&str>| Error::InvalidEntry {
+            expected: N,
+            found: fields.len(),
+        })
+}
+
+impl FromStr for ShadowEntry {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Self> {
+        let [
+            name,
+            hash,
+            last_change,
+            min,
+            max,
+            warn,
+            inactive,
+            expire,
+            reserved,
+        ] = split_fields::<FIELDS>(line)?;
+        Ok(ShadowEntry {
+            name: name.to_string(),
+            hash: hash.to_string(),
+            last_change: parse_num(last_change)?,
+            min: parse_num(min)?,
+            max: parse_num(max)?,
+            warn: parse_num(warn)?,
+            inactive: parse_num(inactive)?,
+            expire: parse_num(expire)?,
+            reserved: reserved.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for ShadowEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let num = |val: Option<i64>| val.map(|val| val.to_string()).unwrap_or_default();
+        write!(
+            f,
+            "{}:{}:{}:{}:{}:{}:{}:{}:{}",
+            self.name,
+            self.hash,
+            num(self.last_change),
+            num(self.min),
+            num(self.max),
+            num(self.warn),
+            num(self.inactive),
+            num(self.expire),
+            self.reserved
+        )
+    }
+}
+
+/// Parse the entries of a shadow file, skipping empty lines.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::shadow;
+///
+/// let text = "root:*:19000:0:99999:7:::\ndaemon:*:19000:0:99999:7:::\n";
+/// let names: Vec<String> = shadow::entries(text)
+///     .map(|entry| entry.unwrap().name)
+///     .collect();
+/// assert_eq!(names, ["root", "daemon"]);
+/// ```
+pub fn entries(text: &str) -> impl Iterator<Item = Result<ShadowEntry>> + '_ {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::parse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ShadowEntry, entries};
+    use crate::{error::Error, unix::HashState};
+
+    #[test]
+    fn parse() {
+        let line = "bob:!:0::::::";
+        let entry: ShadowEntry = line.parse().unwrap();
+        assert_eq!(entry.last_change, Some(0));
+        assert_eq!(entry.min, None);
+        assert!(matches!(entry.state().unwrap(), HashState::Locked(None)));
+        assert!(!entry.verify(""));
+        assert_eq!(entry.to_string(), line);
+
+        let entry: ShadowEntry = "carol:*:19000:0:99999:7::-1:\r\n".parse().unwrap();
+        assert_eq!(entry.expire, Some(-1));
+        assert!(matches!(
+            "carol:*:19000:0:99999:7::".parse::<ShadowEntry>(),
+            Err(Error::InvalidEntry {
+                expected: 9,
+                found: 8
+            })
+        ));
+        assert!(matches!(
+            "carol:*:x:0:99999:7:::".parse::<ShadowEntry>(),
+            Err(Error::InvalidNumber(_))
+        ));
+        assert_eq!(entries("\nroot:*::::::: \n\n").count(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn verify() {
+        let line = "alice:$6$saltsalt$qFmFH.bQmmtXzyBY0s9v7Oicd2z4XSIecDzlB5KiA2/jctKu9YterLp8wwnSq.qc.eoxqOmSuNp2xS0ktL3nh/:19000:0:99999:7:::";
+        let entry: ShadowEntry = line.parse().unwrap();
+        assert!(entry.verify("password"));
+        assert!(!entry.verify("passwort"));
+        assert!(matches!(entry.state().unwrap(), HashState::Active(_)));
+    }
+}
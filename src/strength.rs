@@ -0,0 +1,196 @@
+#[allow(unused_imports)]
+use crate::{Algorithm, hash::Hash};
+
+/// Resistance of a hash to brute-force attacks.
+///
+/// The rating is derived from the algorithm and its work factor, as encoded
+/// in the hash, so that auditing tools and login flows can tell strong
+/// hashes from weak ones without knowing the details of each scheme. The
+/// variants are ordered from the weakest to the strongest.
+///
+/// The thresholds are a judgment call, and may be raised in later versions
+/// as hardware gets faster:
+///
+/// * bcrypt: cost 10 is acceptable, 12 or more is strong.
+///
+/// * SHA-crypt: 5000 rounds are acceptable, 100000 or more are strong.
+///   SHA-256 is never rated above acceptable.
+///
+/// * PBKDF2 and other iterated HMAC schemes: 10000 iterations are
+///   acceptable, 100000 or more are strong.
+///
+/// * scrypt: N = 2<sup>14</sup> is acceptable, 2<sup>16</sup> or more is
+///   strong. Balloon hashing is strong with a time cost of 3 or more.
+///
+/// * MD5-crypt and BSDi crypt are legacy regardless of their parameters;
+///   DES-based schemes and single-iteration digests are weak.
+///
+/// Peppered hashes are rated by their inner hash, wrapped hashes by their
+/// outer one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Strength {
+    /// Can be brute-forced cheaply, e.g. DES crypt or an unsalted digest.
+    Weak,
+    /// An outdated algorithm or a work factor well below current
+    /// recommendations.
+    Legacy,
+    /// A sound algorithm with a reasonable work factor.
+    Acceptable,
+    /// A sound algorithm with a high work factor.
+    Strong,
+}
+
+// Rate a work factor against the acceptable and strong thresholds.
+#[allow(dead_code)]
+fn rate(work: u32, acceptable: u32, strong: u32) -> Strength {
+    if work >= strong {
+        Strength::Strong
+    } else if work >= acceptable {
+        Strength::Acceptable
+    } else {
+        Strength::Legacy
+    }
+}
+
+impl Hash {
+    /// Rate the hash's resistance to brute-force attacks.
+    ///
+    /// A hash whose parameters can't be parsed is rated weak.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crypt3_rs::{Hash, Strength};
+    ///
+    /// let h = Hash::try_from("$2y$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe").unwrap();
+    /// assert_eq!(h.strength(), Strength::Legacy);
+    /// assert!(h.strength() < Strength::Acceptable);
+    /// ```
+    pub fn strength(&self) -> Strength {
+        let Ok(parts) = self.parts() else {
+            return Strength::Weak;
+        };
+        #[allow(unused_variables)]
+        let rounds = parts.rounds;
+        #[allow(unreachable_patterns)]
+        match self.algorithm() {
+            #[cfg(feature = "aix")]
+            Algorithm::Aix => match rounds {
+                Some(cost) => rate(1u32.checked_shl(cost).unwrap_or(u32::MAX), 10_000, 100_000),
+                None => Strength::Legacy,
+            },
+            #[cfg(feature = "balloon")]
+            Algorithm::Balloon => rate(rounds.unwrap_or(0), 1, 3),
+            #[cfg(feature = "bcrypt")]
+            Algorithm::Bcrypt => rate(rounds.unwrap_or(0), 10, 12),
+            #[cfg(feature = "bcrypt_sha256")]
+            Algorithm::BcryptSha256 => rate(rounds.unwrap_or(0), 10, 12),
+            // PBKDF2 with 20000 iterations, and scrypt with N = 2^14
+            #[cfg(feature = "cisco")]
+            Algorithm::CiscoType8 | Algorithm::CiscoType9 => Strength::Acceptable,
+            #[cfg(feature = "django")]
+            Algorithm::Django => match rounds {
+                Some(rounds) => rate(rounds, 10_000, 100_000),
+                None => Strength::Weak,
+            },
+            #[cfg(feature = "grub")]
+            Algorithm::Grub => rate(rounds.unwrap_or(0), 10_000, 100_000),
+            #[cfg(feature = "ldap")]
+            Algorithm::Ldap => self
+                .as_str()
+                .split_once('}')
+                .filter(|_| {
+                    parts
+                        .ident
+                        .is_some_and(|id| id.eq_ignore_ascii_case("CRYPT"))
+                })
+                .and_then(|(_, inner)| Hash::try_from(inner).ok())
+                .map_or(Strength::Weak, |inner| inner.strength()),
+            #[cfg(feature = "pbkdf2")]
+            Algorithm::Pbkdf2 => rate(rounds.unwrap_or(0), 10_000, 100_000),
+            #[cfg(feature = "pepper")]
+            Algorithm::Peppered => crate::crypt::pepper::inner_hash(self)
+                .and_then(|inner| Hash::try_from(inner).ok())
+                .map_or(Strength::Weak, |inner| inner.strength()),
+            #[cfg(feature = "scram")]
+            Algorithm::Scram => rate(rounds.unwrap_or(0), 10_000, 100_000),
+            #[cfg(feature = "scrypt")]
+            Algorithm::Scrypt => rate(rounds.unwrap_or(0), 14, 16),
+            #[cfg(feature = "sha1")]
+            Algorithm::Sha1 => rate(rounds.unwrap_or(0), 10_000, 100_000),
+            #[cfg(feature = "sha2")]
+            Algorithm::Sha256 => {
+                rate(rounds.unwrap_or(5000), 5000, 100_000).min(Strength::Acceptable)
+            }
+            #[cfg(feature = "sha2")]
+            Algorithm::Sha512 => rate(rounds.unwrap_or(5000), 5000, 100_000),
+            #[cfg(feature = "wrapped")]
+            Algorithm::Wrapped => self
+                .as_str()
+                .strip_prefix("$wrapped$")
+                .and_then(|rest| rest.split_once('$').map(|(ident, _)| &rest[ident.len()..]))
+                .and_then(|outer| Hash::try_from(outer).ok())
+                .map_or(Strength::Weak, |outer| outer.strength()),
+            #[cfg(feature = "apr1")]
+            Algorithm::Apr1 => Strength::Legacy,
+            #[cfg(feature = "bsdi")]
+            Algorithm::Bsdi => Strength::Legacy,
+            #[cfg(feature = "md5")]
+            Algorithm::Md5 => Strength::Legacy,
+            _ => Strength::Weak,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Strength;
+    use crate::Hash;
+
+    fn strength(hash: &str) -> Strength {
+        Hash::try_from(hash).unwrap().strength()
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "bcrypt",
+        feature = "md5",
+        feature = "sha2",
+        feature = "unix"
+    ))]
+    fn strength_of() {
+        assert_eq!(strength("aZGJuE6EXrjEE"), Strength::Weak);
+        assert_eq!(
+            strength("$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0"),
+            Strength::Legacy
+        );
+        assert_eq!(
+            strength("$2y$05$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe"),
+            Strength::Legacy
+        );
+        assert_eq!(
+            strength("$2y$12$bvIG6Nmid91Mu9RcmmWZfO5HJIMCT8riNW0hEp8f6/FuA2/mHZFpe"),
+            Strength::Strong
+        );
+        assert_eq!(
+            strength(
+                "$6$G/gkPn17kHYo0gTF$xhDFU0QYExdMH2ghOWKrrVtu1BuTpNMSJURCXk43.\
+                 EYekmK8iwV6RNqftUUC8mqDel1J7m3JEbUkbu4YyqSyv/"
+            ),
+            Strength::Acceptable
+        );
+        assert_eq!(
+            strength(
+                "$6$rounds=100000$G/gkPn17kHYo0gTF$xhDFU0QYExdMH2ghOWKrrVtu1BuTpNMSJURCXk43.\
+                 EYekmK8iwV6RNqftUUC8mqDel1J7m3JEbUkbu4YyqSyv/"
+            ),
+            Strength::Strong
+        );
+        assert_eq!(
+            strength(
+                "$5$rounds=100000$WH1ABM5sKhxbkgCK$aTQsjPkz0rBsH3lQlJxw9HDTDXPKBxC0LlVeV69P.t1"
+            ),
+            Strength::Acceptable
+        );
+    }
+}
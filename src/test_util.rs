@@ -0,0 +1,57 @@
+use crate::{PasswordHasher, ct::ct_eq, encode::hex_encode, error::Result};
+
+const INSECURE_MAGIC: &str = "$insecure$";
+
+/// A [`PasswordHasher`] for tests, which does no work.
+///
+/// Its "hashes" are the hexadecimal encoding of the password after the
+/// `$insecure$` prefix, so that they are instantaneous to compute and easy
+/// to recognize in test output. It must never be used outside of tests. The
+/// type is only available with the `test_util` feature.
+///
+/// Hashes which don't have the prefix never verify, and need rehashing.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::{InsecureFastHasher, PasswordHasher};
+///
+/// let hasher = InsecureFastHasher;
+/// let h = hasher.hash(b"password").unwrap();
+/// assert_eq!(h, "$insecure$70617373776f7264");
+/// assert!(hasher.verify(b"password", &h));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct InsecureFastHasher;
+
+impl PasswordHasher for InsecureFastHasher {
+    fn hash(&self, pass: &[u8]) -> Result<String> {
+        Ok(format!("{INSECURE_MAGIC}{}", hex_encode(pass)))
+    }
+
+    fn verify(&self, pass: &[u8], hash: &str) -> bool {
+        self.hash(pass).is_ok_and(|calc| ct_eq(calc, hash))
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        !hash.starts_with(INSECURE_MAGIC)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InsecureFastHasher;
+    use crate::PasswordHasher;
+
+    #[test]
+    fn insecure() {
+        let hasher: &dyn PasswordHasher = &InsecureFastHasher;
+        let h = hasher.hash(b"\xffpass").unwrap();
+        assert_eq!(h, "$insecure$ff70617373");
+        assert!(hasher.verify(b"\xffpass", &h));
+        assert!(!hasher.verify(b"pass", &h));
+        assert!(!hasher.needs_rehash(&h));
+        assert!(!hasher.verify(b"password", "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0"));
+        assert!(hasher.needs_rehash("$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0"));
+    }
+}
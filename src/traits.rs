@@ -1,6 +1,14 @@
-use crate::{error::Result, HashSetup};
+use crate::{
+    AlgorithmParams, HashSetup, HashSetupBuf,
+    error::{Error, Result},
+};
 
 /// A trait for converting a type into a `HashSetup` struct.
+///
+/// Besides a `HashSetup` or a reference to a `HashSetupBuf`, a hash string,
+/// given as a `&str` or a `&String`, can be converted to reuse its
+/// parameters; so can a `(salt, rounds)` tuple, and an optional salt with
+/// the default rounds.
 pub trait IntoHashSetup<'a> {
     /// The conversion function.
     fn into_hash_setup(self, f: fn(&'a str) -> Result<HashSetup<'a>>) -> Result<HashSetup<'a>>;
@@ -12,12 +20,78 @@ impl<'a> IntoHashSetup<'a> for &'a str {
     }
 }
 
+impl<'a> IntoHashSetup<'a> for &'a String {
+    fn into_hash_setup(self, f: fn(&'a str) -> Result<HashSetup<'a>>) -> Result<HashSetup<'a>> {
+        f(self)
+    }
+}
+
 impl<'a> IntoHashSetup<'a> for HashSetup<'a> {
     fn into_hash_setup(self, _f: fn(&'a str) -> Result<HashSetup<'a>>) -> Result<HashSetup<'a>> {
         Ok(self)
     }
 }
 
+impl<'a> IntoHashSetup<'a> for &'a HashSetupBuf {
+    fn into_hash_setup(self, _f: fn(&'a str) -> Result<HashSetup<'a>>) -> Result<HashSetup<'a>> {
+        Ok(self.as_setup())
+    }
+}
+
+impl<'a> IntoHashSetup<'a> for (&'a str, u32) {
+    fn into_hash_setup(self, _f: fn(&'a str) -> Result<HashSetup<'a>>) -> Result<HashSetup<'a>> {
+        Ok(self.into())
+    }
+}
+
+impl<'a> IntoHashSetup<'a> for Option<&'a str> {
+    fn into_hash_setup(self, _f: fn(&'a str) -> Result<HashSetup<'a>>) -> Result<HashSetup<'a>> {
+        Ok(self.into())
+    }
+}
+
+impl<'a> IntoHashSetup<'a> for AlgorithmParams<'a> {
+    fn into_hash_setup(self, _f: fn(&'a str) -> Result<HashSetup<'a>>) -> Result<HashSetup<'a>> {
+        #[allow(unreachable_patterns)]
+        match self {
+            AlgorithmParams::Basic(hs) => Ok(hs),
+            _ => Err(Error::InvalidHashString),
+        }
+    }
+}
+
+/// An object-safe interface to password hashing.
+///
+/// Code which hashes and verifies passwords can depend on a
+/// `&dyn PasswordHasher` or a `Box<dyn PasswordHasher>` instead of a
+/// concrete configuration, so that tests can substitute a cheap
+/// implementation. It's implemented by [`Crypter`](crate::Crypter), and, with
+/// the `test_util` feature, by `InsecureFastHasher`.
+///
+/// # Example
+///
+/// ```
+/// use crypt3_rs::{Algorithm, Crypter, PasswordHasher};
+///
+/// fn login(hasher: &dyn PasswordHasher, pass: &str, stored: &str) -> bool {
+///     hasher.verify(pass.as_bytes(), stored)
+/// }
+///
+/// let crypter = Crypter::builder().algorithm(Algorithm::Bcrypt).cost(5).build().unwrap();
+/// let h = PasswordHasher::hash(&crypter, b"password").unwrap();
+/// assert!(login(&crypter, "password", &h));
+/// ```
+pub trait PasswordHasher: Send + Sync {
+    /// Hash a password.
+    fn hash(&self, pass: &[u8]) -> Result<String>;
+
+    /// Verify that the hash corresponds to a password.
+    fn verify(&self, pass: &[u8], hash: &str) -> bool;
+
+    /// Check whether the hash should be replaced with a new one.
+    fn needs_rehash(&self, hash: &str) -> bool;
+}
+
 /// A trait for extracting a NUL-terminated subslice from a slice.
 ///
 /// The original Unix hashing functions expect passwords to be NUL-terminated C strings. This
@@ -0,0 +1,67 @@
+//! JavaScript bindings for WebAssembly.
+//!
+//! With the `wasm` feature, [`hash`], [`verify`], and [`identify`] are
+//! exported to JavaScript with `wasm-bindgen`, so that passwords can be
+//! hashed and verified in a browser, or in a worker of a JavaScript runtime.
+//! On `wasm32-unknown-unknown`, salts are drawn from the
+//! `crypto.getRandomValues` function of the JavaScript environment.
+//!
+//! ```js
+//! import { hash, verify, identify } from "crypt3_rs";
+//!
+//! const h = hash("password", "sha512_crypt");
+//! verify("password", h); // true
+//! identify(h); // "sha512_crypt"
+//! ```
+//!
+//! Errors are thrown as JavaScript `Error`s with the message of the
+//! [`Error`](crate::error::Error).
+
+use wasm_bindgen::prelude::*;
+
+use crate::{Algorithm, Hash, default_crypter, unix};
+
+/// Hash a password with an algorithm, given by its [name](Algorithm::name),
+/// or with the [default crypter](crate::default_crypter) if it's
+/// `undefined`.
+#[wasm_bindgen]
+pub fn hash(password: &str, algorithm: Option<String>) -> Result<String, JsError> {
+    let hash = match algorithm {
+        Some(name) => name.parse::<Algorithm>()?.hash(password)?,
+        None => default_crypter()?.hash(password)?,
+    };
+    Ok(hash.into())
+}
+
+/// Verify a password against a hash, recognizing its algorithm.
+///
+/// Unrecognized and malformed hashes never verify.
+#[wasm_bindgen]
+pub fn verify(password: &str, hash: &str) -> bool {
+    unix::verify(password, hash)
+}
+
+/// Get the [name](Algorithm::name) of the algorithm of a hash.
+#[wasm_bindgen]
+pub fn identify(hash: &str) -> Result<String, JsError> {
+    Ok(Hash::try_from(hash)?.algorithm().name().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    // Errors can't be tested on other targets than WebAssembly, where
+    // `JsError` is a JavaScript object.
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn hash_verify() {
+        let h = super::hash("password", Some("sha512_crypt".to_string())).unwrap();
+        assert!(super::verify("password", &h));
+        assert!(!super::verify("passwort", &h));
+        assert!(!super::verify("password", "$6$"));
+        assert_eq!(super::identify(&h).unwrap(), "sha512_crypt");
+
+        let h = super::hash("password", None).unwrap();
+        assert!(super::verify("password", &h));
+    }
+}